@@ -0,0 +1,44 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "wayland")]
+    wayland::generate_protocols();
+}
+
+#[cfg(feature = "wayland")]
+mod wayland {
+    use std::env;
+    use std::path::Path;
+
+    use wayland_scanner::Side;
+
+    /// Protocols, or protocol versions, that the `wayland-protocols` we depend on doesn't provide
+    /// yet.
+    const PROTOCOLS: &[&str] = &[
+        "fractional-scale-v1",
+        "text-input-unstable-v2",
+        "pointer-gestures-unstable-v1",
+    ];
+
+    const PROTOCOLS_DIR: &str = "src/platform_impl/linux/wayland/protocols";
+
+    pub fn generate_protocols() {
+        // The build script is compiled for the host, so check the target at runtime.
+        let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+        if !matches!(
+            target_os.as_str(),
+            "linux" | "dragonfly" | "freebsd" | "openbsd" | "netbsd"
+        ) {
+            return;
+        }
+
+        let out_dir = env::var("OUT_DIR").unwrap();
+        for name in PROTOCOLS {
+            let protocol = Path::new(PROTOCOLS_DIR).join(format!("{}.xml", name));
+            println!("cargo:rerun-if-changed={}", protocol.display());
+
+            let target = Path::new(&out_dir).join(format!("{}_client_api.rs", name));
+            wayland_scanner::generate_code(&protocol, target, Side::Client);
+        }
+    }
+}
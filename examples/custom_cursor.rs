@@ -0,0 +1,90 @@
+#![allow(clippy::single_match)]
+
+use std::time::Duration;
+
+use simple_logger::SimpleLogger;
+use winit::{
+    event::{ElementState, Event, KeyboardInput, WindowEvent},
+    event_loop::EventLoop,
+    window::{CursorIcon, CustomCursor, WindowBuilder},
+};
+
+fn main() {
+    SimpleLogger::new().init().unwrap();
+    let event_loop = EventLoop::new();
+
+    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    window.set_title("Press any key to change the cursor");
+
+    // Cursors are created once, and can then be set as often as needed.
+    let circle = circle_cursor(24, 12, [255, 64, 64]);
+    let frames: Vec<CustomCursor> = (4..=12)
+        .chain((5..12).rev())
+        .map(|radius| circle_cursor(24, radius, [64, 128, 255]))
+        .collect();
+    let pulse = CustomCursor::from_animation(Duration::from_millis(50), &frames).unwrap();
+
+    let mut cursor_idx = 0;
+
+    event_loop.run(move |event, _, control_flow| {
+        control_flow.set_wait();
+
+        match event {
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                cursor_idx = (cursor_idx + 1) % 3;
+                match cursor_idx {
+                    0 => {
+                        println!("Setting cursor to the default icon");
+                        window.set_cursor_icon(CursorIcon::Default);
+                    }
+                    1 => {
+                        println!("Setting cursor to a circle");
+                        window.set_custom_cursor(&circle);
+                    }
+                    _ => {
+                        println!("Setting cursor to a pulsing circle");
+                        window.set_custom_cursor(&pulse);
+                    }
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                control_flow.set_exit();
+            }
+            _ => (),
+        }
+    });
+}
+
+/// A `size`x`size` cursor showing a circle, with the hotspot at its center.
+fn circle_cursor(size: u32, radius: u32, [r, g, b]: [u8; 3]) -> CustomCursor {
+    let center = size as f32 / 2.0;
+    let mut rgba = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f32 + 0.5 - center;
+            let dy = y as f32 + 0.5 - center;
+            let alpha = if (dx * dx + dy * dy).sqrt() <= radius as f32 {
+                255
+            } else {
+                0
+            };
+            rgba.extend_from_slice(&[r, g, b, alpha]);
+        }
+    }
+
+    CustomCursor::from_rgba(rgba, size, size, size / 2, size / 2).unwrap()
+}
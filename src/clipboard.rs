@@ -0,0 +1,85 @@
+//! Types used to exchange data with the system clipboard.
+//!
+//! See [`Window::set_clipboard`] and [`Window::clipboard`] for how to use them.
+//!
+//! [`Window::set_clipboard`]: crate::window::Window::set_clipboard
+//! [`Window::clipboard`]: crate::window::Window::clipboard
+
+/// The MIME type used for UTF-8 encoded plain text.
+///
+/// Backends translate this type to and from the platform's native text formats (e.g.
+/// `UTF8_STRING` on X11), so it should be used for any textual payload.
+pub const TEXT_MIME_TYPE: &str = "text/plain;charset=utf-8";
+
+/// The selection a clipboard operation is targeting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ClipboardKind {
+    /// The regular clipboard, used by copy and paste actions.
+    Clipboard,
+    /// The primary selection, which holds the most recently selected text and is usually
+    /// pasted with the middle mouse button.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the `zwp_primary_selection_device_manager_v1` protocol.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    Primary,
+}
+
+/// Data offered on a clipboard, in one or more representations.
+///
+/// Each representation is identified by its MIME type. Readers pick whichever representation
+/// they understand best, so offering several of them (e.g. `text/html` alongside plain text)
+/// makes the data usable by more applications.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClipboardContents {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl ClipboardContents {
+    /// Creates empty contents.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates contents holding only `text`, offered as [`TEXT_MIME_TYPE`].
+    pub fn from_text<S: Into<String>>(text: S) -> Self {
+        Self::new().with_data(TEXT_MIME_TYPE, text.into().into_bytes())
+    }
+
+    /// Adds a representation of the data, replacing any previous one with the same MIME type.
+    pub fn with_data<S: Into<String>, D: Into<Vec<u8>>>(mut self, mime_type: S, data: D) -> Self {
+        self.insert(mime_type, data);
+        self
+    }
+
+    /// Adds a representation of the data, replacing any previous one with the same MIME type.
+    pub fn insert<S: Into<String>, D: Into<Vec<u8>>>(&mut self, mime_type: S, data: D) {
+        let mime_type = mime_type.into();
+        let data = data.into();
+        match self.entries.iter_mut().find(|(mime, _)| *mime == mime_type) {
+            Some(entry) => entry.1 = data,
+            None => self.entries.push((mime_type, data)),
+        }
+    }
+
+    /// Returns the data stored for `mime_type`, if any.
+    pub fn get(&self, mime_type: &str) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|(mime, _)| mime == mime_type)
+            .map(|(_, data)| data.as_slice())
+    }
+
+    /// Returns the offered MIME types, in insertion order.
+    pub fn mime_types(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(mime, _)| mime.as_str())
+    }
+
+    /// Returns `true` if no representation was added.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
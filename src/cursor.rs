@@ -0,0 +1,217 @@
+use std::{error::Error, fmt, hash, sync::Arc, time::Duration};
+
+use crate::icon::PIXEL_SIZE;
+
+/// The largest width and height of a [`CustomCursor`].
+pub const MAX_CURSOR_SIZE: u32 = i16::MAX as u32;
+
+#[derive(Debug)]
+/// An error produced when using [`CustomCursor::from_rgba`] or [`CustomCursor::from_animation`]
+/// with invalid arguments.
+pub enum BadImage {
+    /// Produced when the length of the `rgba` argument isn't divisible by 4, thus `rgba` can't be
+    /// safely interpreted as 32bpp RGBA pixels.
+    ByteCountNotDivisibleBy4 { byte_count: usize },
+    /// Produced when the number of pixels (`rgba.len() / 4`) isn't equal to `width * height`.
+    /// At least one of your arguments is incorrect.
+    DimensionsVsPixelCount {
+        width: u32,
+        height: u32,
+        width_x_height: usize,
+        pixel_count: usize,
+    },
+    /// Produced when the width or the height is larger than [`MAX_CURSOR_SIZE`].
+    TooLarge { width: u32, height: u32 },
+    /// Produced when the hotspot isn't inside the image.
+    HotspotOutOfBounds {
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    },
+    /// Produced when an animation has no frames.
+    NoFrames,
+}
+
+impl fmt::Display for BadImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BadImage::ByteCountNotDivisibleBy4 { byte_count } => write!(f,
+                "The length of the `rgba` argument ({:?}) isn't divisible by 4, making it impossible to interpret as 32bpp RGBA pixels.",
+                byte_count,
+            ),
+            BadImage::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            } => write!(f,
+                "The specified dimensions ({:?}x{:?}) don't match the number of pixels supplied by the `rgba` argument ({:?}). For those dimensions, the expected pixel count is {:?}.",
+                width, height, pixel_count, width_x_height,
+            ),
+            BadImage::TooLarge { width, height } => write!(f,
+                "The specified dimensions ({:?}x{:?}) are too large. The maximum is {:?}x{:?}.",
+                width, height, MAX_CURSOR_SIZE, MAX_CURSOR_SIZE,
+            ),
+            BadImage::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot_x,
+                hotspot_y,
+            } => write!(f,
+                "The specified hotspot ({:?}, {:?}) is outside the image bounds ({:?}x{:?}).",
+                hotspot_x, hotspot_y, width, height,
+            ),
+            BadImage::NoFrames => write!(f, "The animation doesn't have any frames."),
+        }
+    }
+}
+
+impl Error for BadImage {}
+
+/// A frame of a custom cursor.
+#[allow(dead_code)] // These are not used on every platform
+#[derive(Debug)]
+pub struct CursorImage {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) hotspot_x: u32,
+    pub(crate) hotspot_y: u32,
+    /// Premultiplied ARGB pixels, the format used by both Xcursor and `wl_shm`.
+    pub(crate) argb: Vec<u32>,
+    /// How long the frame is shown in an animation.
+    pub(crate) duration: Duration,
+}
+
+/// A cursor image, which can be shown with [`Window::set_custom_cursor`].
+///
+/// Cloning a cursor is cheap, and the platform resources backing it are created the first time
+/// it's shown and shared between the clones.
+///
+/// [`Window::set_custom_cursor`]: crate::window::Window::set_custom_cursor
+#[derive(Clone)]
+pub struct CustomCursor {
+    pub(crate) images: Arc<Vec<CursorImage>>,
+}
+
+impl fmt::Debug for CustomCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let image = &self.images[0];
+        f.debug_struct("CustomCursor")
+            .field("width", &image.width)
+            .field("height", &image.height)
+            .field("frames", &self.images.len())
+            .finish()
+    }
+}
+
+impl PartialEq for CustomCursor {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.images, &other.images)
+    }
+}
+
+impl Eq for CustomCursor {}
+
+impl hash::Hash for CustomCursor {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.images).hash(state);
+    }
+}
+
+impl CustomCursor {
+    /// Creates a cursor from 32bpp RGBA data, with the pointer position at the
+    /// `(hotspot_x, hotspot_y)` pixel.
+    ///
+    /// The length of `rgba` must be divisible by 4, and `width * height` must equal
+    /// `rgba.len() / 4`. The dimensions can't exceed [`MAX_CURSOR_SIZE`] and the hotspot must be
+    /// inside the image. Otherwise, this will return a `BadImage` error.
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot_x: u32,
+        hotspot_y: u32,
+    ) -> Result<Self, BadImage> {
+        if rgba.len() % PIXEL_SIZE != 0 {
+            return Err(BadImage::ByteCountNotDivisibleBy4 {
+                byte_count: rgba.len(),
+            });
+        }
+        if width > MAX_CURSOR_SIZE || height > MAX_CURSOR_SIZE {
+            return Err(BadImage::TooLarge { width, height });
+        }
+        let pixel_count = rgba.len() / PIXEL_SIZE;
+        let width_x_height = width as usize * height as usize;
+        if pixel_count != width_x_height {
+            return Err(BadImage::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            });
+        }
+        if hotspot_x >= width || hotspot_y >= height {
+            return Err(BadImage::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot_x,
+                hotspot_y,
+            });
+        }
+
+        let argb = rgba
+            .chunks_exact(PIXEL_SIZE)
+            .map(|pixel| {
+                let alpha = pixel[3] as u32;
+                let premultiply = |channel: u8| (channel as u32 * alpha + 127) / 255;
+                (alpha << 24)
+                    | (premultiply(pixel[0]) << 16)
+                    | (premultiply(pixel[1]) << 8)
+                    | premultiply(pixel[2])
+            })
+            .collect();
+
+        let image = CursorImage {
+            width,
+            height,
+            hotspot_x,
+            hotspot_y,
+            argb,
+            duration: Duration::from_secs(0),
+        };
+        Ok(CustomCursor {
+            images: Arc::new(vec![image]),
+        })
+    }
+
+    /// Creates an animated cursor, showing each of `frames` for `frame_duration` in a loop.
+    ///
+    /// The frames of animated cursors in `frames` are all included. This returns a `BadImage`
+    /// error if `frames` is empty.
+    pub fn from_animation(
+        frame_duration: Duration,
+        frames: &[CustomCursor],
+    ) -> Result<Self, BadImage> {
+        let images: Vec<CursorImage> = frames
+            .iter()
+            .flat_map(|frame| frame.images.iter())
+            .map(|image| CursorImage {
+                width: image.width,
+                height: image.height,
+                hotspot_x: image.hotspot_x,
+                hotspot_y: image.hotspot_y,
+                argb: image.argb.clone(),
+                duration: frame_duration,
+            })
+            .collect();
+
+        if images.is_empty() {
+            return Err(BadImage::NoFrames);
+        }
+
+        Ok(CustomCursor {
+            images: Arc::new(images),
+        })
+    }
+}
@@ -0,0 +1,64 @@
+//! Types used for drag and drop between applications.
+//!
+//! See [`Window::start_drag`] for how to start a drag.
+//!
+//! [`Window::start_drag`]: crate::window::Window::start_drag
+
+use crate::clipboard::ClipboardContents;
+
+/// What happens to the dragged data once it is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DndAction {
+    /// The target makes a copy of the data.
+    Copy,
+    /// The target takes the data, so the source should delete it after a successful drop.
+    Move,
+    /// The target creates a reference to the data.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Unsupported, a drag offering only this action is copied instead.
+    Link,
+}
+
+/// Data dragged out of a window with [`Window::start_drag`].
+///
+/// [`Window::start_drag`]: crate::window::Window::start_drag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DragData {
+    contents: ClipboardContents,
+    actions: Vec<DndAction>,
+}
+
+impl DragData {
+    /// Creates a drag offering `contents`, which can only be copied.
+    pub fn new(contents: ClipboardContents) -> Self {
+        Self {
+            contents,
+            actions: vec![DndAction::Copy],
+        }
+    }
+
+    /// Sets the actions that the drop target can perform, the preferred one first.
+    ///
+    /// An empty list is replaced with [`DndAction::Copy`].
+    pub fn with_actions(mut self, actions: &[DndAction]) -> Self {
+        self.actions = if actions.is_empty() {
+            vec![DndAction::Copy]
+        } else {
+            actions.to_vec()
+        };
+        self
+    }
+
+    /// Returns the dragged data.
+    pub fn contents(&self) -> &ClipboardContents {
+        &self.contents
+    }
+
+    /// Returns the allowed actions, the preferred one first.
+    pub fn actions(&self) -> &[DndAction] {
+        &self.actions
+    }
+}
@@ -40,15 +40,19 @@ use std::path::PathBuf;
 #[cfg(doc)]
 use crate::window::Window;
 use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dnd::DndAction,
     dpi::{PhysicalPosition, PhysicalSize},
+    keyboard::{KeyEvent, KeyboardLayout},
     platform_impl,
-    window::{Theme, WindowId},
+    window::{DecorationMode, Theme, WindowId},
 };
 
 /// Describes a generic event.
 ///
 /// See the module-level docs for more information on the event loop manages each event.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Event<'a, T: 'static> {
     /// Emitted when new events arrive from the OS to be processed.
     ///
@@ -227,12 +231,14 @@ pub enum Event<'a, T: 'static> {
 
 /// Describes an event from some specific platform.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PlatformSpecific {
     MacOS(MacOS),
 }
 
 /// Describes an event that only happens in `MacOS`.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MacOS {
     ReceivedUrl(String),
 }
@@ -303,7 +309,11 @@ impl<'a, T> Event<'a, T> {
 }
 
 /// Describes the reason the event loop is resuming.
+///
+/// When serialized, instants are stored relative to a point in time picked once per process, so
+/// only the durations between them are preserved when deserializing in another process.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum StartCause {
     /// Sent if the time specified by [`ControlFlow::WaitUntil`] has been reached. Contains the
     /// moment the timeout was requested and the requested resume time. The actual resume time is
@@ -311,14 +321,18 @@ pub enum StartCause {
     ///
     /// [`ControlFlow::WaitUntil`]: crate::event_loop::ControlFlow::WaitUntil
     ResumeTimeReached {
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
         start: Instant,
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
         requested_resume: Instant,
     },
 
     /// Sent if the OS has new events to send to the window, after a wait was requested. Contains
     /// the moment the wait was requested and the resume time, if requested.
     WaitCancelled {
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
         start: Instant,
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde::option"))]
         requested_resume: Option<Instant>,
     },
 
@@ -333,7 +347,28 @@ pub enum StartCause {
 }
 
 /// Describes an event from a [`Window`].
+///
+/// ## Timestamps
+///
+/// The input events [`KeyboardInput`], [`CursorMoved`], [`MouseWheel`], [`MouseInput`] and
+/// [`Touch`] carry the time at which the system generated them. It uses the same clock as
+/// [`Instant::now()`], so it can be compared with the current time to measure input latency,
+/// and with the timestamps of other events for double-click or gesture detection.
+///
+/// ## Platform-specific
+///
+/// - **X11 / Wayland:** Taken from the millisecond timestamp of the event. If the server's clock
+///   isn't the local monotonic clock, which can happen with a remote X server, the time at which
+///   winit received the event is used instead.
+/// - **Windows / macOS / iOS / Android / Web:** The time at which winit received the event.
+///
+/// [`KeyboardInput`]: Self::KeyboardInput
+/// [`CursorMoved`]: Self::CursorMoved
+/// [`MouseWheel`]: Self::MouseWheel
+/// [`MouseInput`]: Self::MouseInput
+/// [`Touch`]: Self::Touch
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WindowEvent<'a> {
     /// The size of the window has changed. Contains the client area's new dimensions.
     Resized(PhysicalSize<u32>),
@@ -367,7 +402,8 @@ pub enum WindowEvent<'a> {
 
     /// The window received a unicode character.
     ///
-    /// See also the [`Ime`](Self::Ime) event for more complex character sequences.
+    /// See also the [`Ime`](Self::Ime) event for more complex character sequences, and
+    /// [`KeyEvent::text`] to know which key typed the character.
     ReceivedCharacter(char),
 
     /// The window gained or lost focus.
@@ -378,7 +414,12 @@ pub enum WindowEvent<'a> {
     /// An event from the keyboard has been received.
     KeyboardInput {
         device_id: DeviceId,
+        /// The scancode and virtual keycode of the key, kept for compatibility.
+        ///
+        /// Prefer `event`, which doesn't depend on the platform and also gives the text typed.
         input: KeyboardInput,
+        /// The key that was pressed or released, and the text it typed.
+        event: KeyEvent,
         /// If `true`, the event was generated synthetically by winit
         /// in one of the following circumstances:
         ///
@@ -389,6 +430,9 @@ pub enum WindowEvent<'a> {
         ///
         /// Otherwise, this value is always `false`.
         is_synthetic: bool,
+        /// When the system generated the event, see [`WindowEvent`] for details.
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+        timestamp: Instant,
     },
 
     /// The keyboard modifiers have changed.
@@ -399,6 +443,18 @@ pub enum WindowEvent<'a> {
     ///   issue, and it should get fixed - but it's the current state of the API.
     ModifiersChanged(ModifiersState),
 
+    /// The active keyboard layout changed, or the layouts were reconfigured.
+    ///
+    /// Only sent to the window with keyboard focus. Use [`Window::key_label`] to get the labels
+    /// of the keys in the new layout.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`Window::key_label`]: crate::window::Window::key_label
+    KeyboardLayoutChanged(KeyboardLayout),
+
     /// An event from an input method.
     ///
     /// **Note:** You have to explicitly enable this event using [`Window::set_ime_allowed`].
@@ -416,6 +472,9 @@ pub enum WindowEvent<'a> {
         /// limited by the display area and it may have been transformed by the OS to implement effects such as cursor
         /// acceleration, it should not be used to implement non-cursor-like interactions such as 3D camera control.
         position: PhysicalPosition<f64>,
+        /// When the system generated the event, see [`WindowEvent`] for details.
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+        timestamp: Instant,
         #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
         modifiers: ModifiersState,
     },
@@ -431,6 +490,9 @@ pub enum WindowEvent<'a> {
         device_id: DeviceId,
         delta: MouseScrollDelta,
         phase: TouchPhase,
+        /// When the system generated the event, see [`WindowEvent`] for details.
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+        timestamp: Instant,
         #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
         modifiers: ModifiersState,
     },
@@ -440,6 +502,9 @@ pub enum WindowEvent<'a> {
         device_id: DeviceId,
         state: ElementState,
         button: MouseButton,
+        /// When the system generated the event, see [`WindowEvent`] for details.
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+        timestamp: Instant,
         #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
         modifiers: ModifiersState,
     },
@@ -455,6 +520,84 @@ pub enum WindowEvent<'a> {
         stage: i64,
     },
 
+    /// Two or more fingers moved closer together or further apart on a touchpad, to zoom.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the `zwp_pointer_gestures_v1` protocol.
+    /// - **X11:** Requires XInput 2.4.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    PinchGesture {
+        device_id: DeviceId,
+        /// The change of the distance between the fingers, as a fraction of the distance they
+        /// started at. Positive values zoom in, and the delta is `0.0` when the phase is
+        /// [`TouchPhase::Started`].
+        delta: f64,
+        phase: TouchPhase,
+        /// When the system generated the event, see [`WindowEvent`] for details.
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+        timestamp: Instant,
+    },
+
+    /// Two or more fingers turned around each other on a touchpad.
+    ///
+    /// Sent along with [`WindowEvent::PinchGesture`], since both come from the same gesture.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the `zwp_pointer_gestures_v1` protocol.
+    /// - **X11:** Requires XInput 2.4.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    RotationGesture {
+        device_id: DeviceId,
+        /// The change of the angle, in degrees. Positive values are counterclockwise.
+        delta: f32,
+        phase: TouchPhase,
+        /// When the system generated the event, see [`WindowEvent`] for details.
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+        timestamp: Instant,
+    },
+
+    /// Three or more fingers moved together across a touchpad.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the `zwp_pointer_gestures_v1` protocol.
+    /// - **X11:** Requires XInput 2.4.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    SwipeGesture {
+        device_id: DeviceId,
+        /// How far the fingers moved since the last event, in pixels.
+        delta: PhysicalPosition<f64>,
+        /// The number of fingers on the touchpad.
+        fingers: u32,
+        phase: TouchPhase,
+        /// When the system generated the event, see [`WindowEvent`] for details.
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+        timestamp: Instant,
+    },
+
+    /// One or more fingers rested on a touchpad without moving.
+    ///
+    /// The gesture ends with [`TouchPhase::Cancelled`] when the fingers start to move, for
+    /// example when it turns into a swipe or a pinch. Applications commonly use it to stop kinetic
+    /// scrolling.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires version 3 of the `zwp_pointer_gestures_v1` protocol.
+    /// - **X11:** Unsupported, XInput 2.4 only has pinch and swipe gestures.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    HoldGesture {
+        device_id: DeviceId,
+        /// The number of fingers on the touchpad.
+        fingers: u32,
+        phase: TouchPhase,
+        /// When the system generated the event, see [`WindowEvent`] for details.
+        #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+        timestamp: Instant,
+    },
+
     /// Motion on some analog axis. May report data redundant to other, more specific events.
     AxisMotion {
         device_id: DeviceId,
@@ -478,6 +621,9 @@ pub enum WindowEvent<'a> {
     /// by the OS, but it can be changed to any value.
     ///
     /// For more information about DPI in general, see the [`dpi`](crate::dpi) module.
+    ///
+    /// This event can be serialized, but not deserialized since it borrows the new inner size.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
     ScaleFactorChanged {
         scale_factor: f64,
         new_inner_size: &'a mut PhysicalSize<u32>,
@@ -490,9 +636,37 @@ pub enum WindowEvent<'a> {
     ///
     /// ## Platform-specific
     ///
-    /// At the moment this is only supported on Windows.
+    /// - **X11 / Wayland:** Sent to all the windows when the `color-scheme` setting of the XDG
+    ///   desktop portal changes, and on X11 also when the GTK theme of XSETTINGS does.
+    /// - **macOS / iOS / Android:** Unsupported.
     ThemeChanged(Theme),
 
+    /// The compositor picked who draws the decorations of the window.
+    ///
+    /// Sent when the compositor first configures the window and whenever the mode changes. The
+    /// mode is either [`DecorationMode::Client`] or [`DecorationMode::Server`]. Applications
+    /// which prefer to draw their own title bar can disable the decorations when the mode is
+    /// [`DecorationMode::Client`]. The current mode is also available from
+    /// `WindowExtUnix::wayland_decoration_mode`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11 / Windows / macOS / iOS / Android / Web:** Unsupported.
+    DecorationModeChanged(DecorationMode),
+
+    /// The system shortcuts started or stopped being sent to the window as regular key events,
+    /// after they were requested with [`Window::set_shortcuts_inhibited`].
+    ///
+    /// `false` is also sent when the system refused the request, and while the window doesn't
+    /// have keyboard focus.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    ///
+    /// [`Window::set_shortcuts_inhibited`]: crate::window::Window::set_shortcuts_inhibited
+    ShortcutsInhibited(bool),
+
     /// The window has been occluded (completely hidden from view).
     ///
     /// This is different to window visibility as it depends on whether the window is closed,
@@ -501,6 +675,62 @@ pub enum WindowEvent<'a> {
     /// Platform-specific behavior:
     /// - **iOS / Android / Web / Wayland / Windows:** Unsupported.
     Occluded(bool),
+
+    /// The contents of a clipboard changed.
+    ///
+    /// This is sent to every window of the application, including when the application itself
+    /// set the new contents. Use [`Window::clipboard`] to read them.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Changes made by other clients are only reported if the XFixes extension is
+    ///   available.
+    /// - **Wayland:** Only reported while one of the application's windows has keyboard focus,
+    ///   and may be emitted again when the focus is gained even if the contents didn't change.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported.
+    ClipboardChanged(ClipboardKind),
+
+    /// A drag started with [`Window::start_drag`] on this window ended.
+    ///
+    /// Contains the action performed by the drop target, or `None` if the drag was cancelled or
+    /// rejected. After a [`DndAction::Move`], the application should delete the dragged data.
+    ///
+    /// [`Window::start_drag`]: crate::window::Window::start_drag
+    DragFinished(Option<DndAction>),
+
+    /// A drag entered the window.
+    ///
+    /// Contains the MIME types the drag offers, and the position of the pointer relative to the
+    /// top-left corner of the window. The drag is rejected until the application accepts it with
+    /// [`Window::accept_drag`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported, only [`DroppedFile`] and
+    ///   [`HoveredFile`] are reported.
+    ///
+    /// [`DroppedFile`]: WindowEvent::DroppedFile
+    /// [`HoveredFile`]: WindowEvent::HoveredFile
+    DragEnter {
+        mime_types: Vec<String>,
+        position: PhysicalPosition<f64>,
+    },
+
+    /// The drag over the window moved.
+    ///
+    /// The application can change its answer with [`Window::accept_drag`] and
+    /// [`Window::reject_drag`] depending on the position.
+    DragOver { position: PhysicalPosition<f64> },
+
+    /// The drag over the window was dropped, with the data of the type accepted by the
+    /// application.
+    ///
+    /// Only sent if the application accepted the drag and the data could be transferred,
+    /// otherwise [`WindowEvent::DragLeave`] is sent.
+    DragDrop { data: ClipboardContents },
+
+    /// The drag left the window without being dropped.
+    DragLeave,
 }
 
 impl Clone for WindowEvent<'static> {
@@ -519,22 +749,29 @@ impl Clone for WindowEvent<'static> {
             KeyboardInput {
                 device_id,
                 input,
+                event,
                 is_synthetic,
+                timestamp,
             } => KeyboardInput {
                 device_id: *device_id,
                 input: *input,
+                event: event.clone(),
                 is_synthetic: *is_synthetic,
+                timestamp: *timestamp,
             },
             Ime(preedit_state) => Ime(preedit_state.clone()),
             ModifiersChanged(modifiers) => ModifiersChanged(*modifiers),
+            KeyboardLayoutChanged(layout) => KeyboardLayoutChanged(layout.clone()),
             #[allow(deprecated)]
             CursorMoved {
                 device_id,
                 position,
+                timestamp,
                 modifiers,
             } => CursorMoved {
                 device_id: *device_id,
                 position: *position,
+                timestamp: *timestamp,
                 modifiers: *modifiers,
             },
             CursorEntered { device_id } => CursorEntered {
@@ -548,11 +785,13 @@ impl Clone for WindowEvent<'static> {
                 device_id,
                 delta,
                 phase,
+                timestamp,
                 modifiers,
             } => MouseWheel {
                 device_id: *device_id,
                 delta: *delta,
                 phase: *phase,
+                timestamp: *timestamp,
                 modifiers: *modifiers,
             },
             #[allow(deprecated)]
@@ -560,11 +799,13 @@ impl Clone for WindowEvent<'static> {
                 device_id,
                 state,
                 button,
+                timestamp,
                 modifiers,
             } => MouseInput {
                 device_id: *device_id,
                 state: *state,
                 button: *button,
+                timestamp: *timestamp,
                 modifiers: *modifiers,
             },
             TouchpadPressure {
@@ -576,6 +817,52 @@ impl Clone for WindowEvent<'static> {
                 pressure: *pressure,
                 stage: *stage,
             },
+            PinchGesture {
+                device_id,
+                delta,
+                phase,
+                timestamp,
+            } => PinchGesture {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+                timestamp: *timestamp,
+            },
+            RotationGesture {
+                device_id,
+                delta,
+                phase,
+                timestamp,
+            } => RotationGesture {
+                device_id: *device_id,
+                delta: *delta,
+                phase: *phase,
+                timestamp: *timestamp,
+            },
+            SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+                timestamp,
+            } => SwipeGesture {
+                device_id: *device_id,
+                delta: *delta,
+                fingers: *fingers,
+                phase: *phase,
+                timestamp: *timestamp,
+            },
+            HoldGesture {
+                device_id,
+                fingers,
+                phase,
+                timestamp,
+            } => HoldGesture {
+                device_id: *device_id,
+                fingers: *fingers,
+                phase: *phase,
+                timestamp: *timestamp,
+            },
             AxisMotion {
                 device_id,
                 axis,
@@ -587,10 +874,26 @@ impl Clone for WindowEvent<'static> {
             },
             Touch(touch) => Touch(*touch),
             ThemeChanged(theme) => ThemeChanged(*theme),
+            DecorationModeChanged(mode) => DecorationModeChanged(*mode),
+            ShortcutsInhibited(inhibited) => ShortcutsInhibited(*inhibited),
             ScaleFactorChanged { .. } => {
                 unreachable!("Static event can't be about scale factor changing")
             }
             Occluded(occluded) => Occluded(*occluded),
+            ClipboardChanged(kind) => ClipboardChanged(*kind),
+            DragFinished(action) => DragFinished(*action),
+            DragEnter {
+                mime_types,
+                position,
+            } => DragEnter {
+                mime_types: mime_types.clone(),
+                position: *position,
+            },
+            DragOver { position } => DragOver {
+                position: *position,
+            },
+            DragDrop { data } => DragDrop { data: data.clone() },
+            DragLeave => DragLeave,
         };
     }
 }
@@ -611,22 +914,29 @@ impl<'a> WindowEvent<'a> {
             KeyboardInput {
                 device_id,
                 input,
+                event,
                 is_synthetic,
+                timestamp,
             } => Some(KeyboardInput {
                 device_id,
                 input,
+                event,
                 is_synthetic,
+                timestamp,
             }),
             ModifiersChanged(modifiers) => Some(ModifiersChanged(modifiers)),
+            KeyboardLayoutChanged(layout) => Some(KeyboardLayoutChanged(layout)),
             Ime(event) => Some(Ime(event)),
             #[allow(deprecated)]
             CursorMoved {
                 device_id,
                 position,
+                timestamp,
                 modifiers,
             } => Some(CursorMoved {
                 device_id,
                 position,
+                timestamp,
                 modifiers,
             }),
             CursorEntered { device_id } => Some(CursorEntered { device_id }),
@@ -636,11 +946,13 @@ impl<'a> WindowEvent<'a> {
                 device_id,
                 delta,
                 phase,
+                timestamp,
                 modifiers,
             } => Some(MouseWheel {
                 device_id,
                 delta,
                 phase,
+                timestamp,
                 modifiers,
             }),
             #[allow(deprecated)]
@@ -648,11 +960,13 @@ impl<'a> WindowEvent<'a> {
                 device_id,
                 state,
                 button,
+                timestamp,
                 modifiers,
             } => Some(MouseInput {
                 device_id,
                 state,
                 button,
+                timestamp,
                 modifiers,
             }),
             TouchpadPressure {
@@ -664,6 +978,52 @@ impl<'a> WindowEvent<'a> {
                 pressure,
                 stage,
             }),
+            PinchGesture {
+                device_id,
+                delta,
+                phase,
+                timestamp,
+            } => Some(PinchGesture {
+                device_id,
+                delta,
+                phase,
+                timestamp,
+            }),
+            RotationGesture {
+                device_id,
+                delta,
+                phase,
+                timestamp,
+            } => Some(RotationGesture {
+                device_id,
+                delta,
+                phase,
+                timestamp,
+            }),
+            SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+                timestamp,
+            } => Some(SwipeGesture {
+                device_id,
+                delta,
+                fingers,
+                phase,
+                timestamp,
+            }),
+            HoldGesture {
+                device_id,
+                fingers,
+                phase,
+                timestamp,
+            } => Some(HoldGesture {
+                device_id,
+                fingers,
+                phase,
+                timestamp,
+            }),
             AxisMotion {
                 device_id,
                 axis,
@@ -675,8 +1035,22 @@ impl<'a> WindowEvent<'a> {
             }),
             Touch(touch) => Some(Touch(touch)),
             ThemeChanged(theme) => Some(ThemeChanged(theme)),
+            DecorationModeChanged(mode) => Some(DecorationModeChanged(mode)),
+            ShortcutsInhibited(inhibited) => Some(ShortcutsInhibited(inhibited)),
             ScaleFactorChanged { .. } => None,
             Occluded(occluded) => Some(Occluded(occluded)),
+            ClipboardChanged(kind) => Some(ClipboardChanged(kind)),
+            DragFinished(action) => Some(DragFinished(action)),
+            DragEnter {
+                mime_types,
+                position,
+            } => Some(DragEnter {
+                mime_types,
+                position,
+            }),
+            DragOver { position } => Some(DragOver { position }),
+            DragDrop { data } => Some(DragDrop { data }),
+            DragLeave => Some(DragLeave),
         }
     }
 }
@@ -686,6 +1060,9 @@ impl<'a> WindowEvent<'a> {
 /// Whenever you receive an event arising from a particular input device, this event contains a `DeviceId` which
 /// identifies its origin. Note that devices may be virtual (representing an on-screen cursor and keyboard focus) or
 /// physical. Virtual devices typically aggregate inputs from multiple physical devices.
+///
+/// When serialized, the id is stored as an opaque number. Deserialized ids can be compared with
+/// each other, but must not be passed to winit functions.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DeviceId(pub(crate) platform_impl::DeviceId);
 
@@ -713,6 +1090,7 @@ impl DeviceId {
 ///
 /// Note that these events are delivered regardless of input focus.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DeviceEvent {
     Added,
     Removed,
@@ -829,13 +1207,39 @@ pub enum Ime {
     /// position. When it's `None`, the cursor should be hidden.
     ///
     /// The cursor position is byte-wise indexed.
-    Preedit(String, Option<(usize, usize)>),
+    ///
+    /// The spans give the style of the parts of the string that the IME wants drawn differently,
+    /// like the clause being converted, and are sorted. The text outside of them is drawn
+    /// normally.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11 / Windows:** The styles come from the IME.
+    /// - **Wayland:** The styles come from the IME with text input v1 and v2, the spans are empty
+    ///   with text input v3, which doesn't have styles.
+    /// - **macOS:** The spans are always empty.
+    Preedit(String, Option<(usize, usize)>, Vec<PreeditSpan>),
 
     /// Notifies when text should be inserted into the editor widget.
     ///
     /// Any pending [`Preedit`](Self::Preedit) must be cleared.
     Commit(String),
 
+    /// Notifies when text around the cursor should be deleted.
+    ///
+    /// The lengths are in bytes, counted from the cursor, or from the edges of the selection
+    /// when there is one, in the text last given to [`Window::set_ime_surrounding_text`]. This
+    /// comes before the [`Commit`](Self::Commit) and [`Preedit`](Self::Preedit) events of the
+    /// same IME update, and any pending preedit must be cleared before deleting.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only sent on **Wayland**.
+    DeleteSurrounding {
+        before_length: usize,
+        after_length: usize,
+    },
+
     /// Notifies when the IME was disabled.
     ///
     /// After receiving this event you won't get any more [`Preedit`](Self::Preedit) or
@@ -845,6 +1249,27 @@ pub enum Ime {
     Disabled,
 }
 
+/// A styled part of the preedit text of [`Ime::Preedit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PreeditSpan {
+    /// The byte offset of the start of the span.
+    pub start: usize,
+    /// The byte offset of the end of the span, exclusive.
+    pub end: usize,
+    pub style: PreeditStyle,
+}
+
+/// How a part of the preedit text should be drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PreeditStyle {
+    /// Underlined, for text that's being composed.
+    Underline,
+    /// Highlighted, usually like selected text, for the clause that's being converted.
+    Highlight,
+}
+
 /// Describes touch-screen input state.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -872,6 +1297,7 @@ pub enum TouchPhase {
 /// touch, such as when the window loses focus, or on iOS if the user moves the
 /// device against their face.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Touch {
     pub device_id: DeviceId,
     pub phase: TouchPhase,
@@ -885,10 +1311,14 @@ pub struct Touch {
     pub force: Option<Force>,
     /// Unique identifier of a finger.
     pub id: u64,
+    /// When the system generated the event, see [`WindowEvent`] for details.
+    #[cfg_attr(feature = "serde", serde(with = "instant_serde"))]
+    pub timestamp: Instant,
 }
 
 /// Describes the force of a touch event
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Force {
     /// On iOS, the force is calibrated so that the same number corresponds to
     /// roughly the same amount of pressure on the screen regardless of the
@@ -1297,3 +1727,96 @@ mod modifiers_serde {
         }
     }
 }
+
+#[cfg(feature = "serde")]
+mod device_id_serde {
+    use super::DeviceId;
+    use crate::platform_impl;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    impl Serialize for DeviceId {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            self.0.into_raw().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for DeviceId {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let raw = u64::deserialize(deserializer)?;
+            Ok(DeviceId(platform_impl::DeviceId::from_raw(raw)))
+        }
+    }
+}
+
+/// An `Instant` means nothing outside of the process that created it, so it is stored as the
+/// signed number of nanoseconds since a reference point, picked the first time an instant is
+/// serialized or deserialized.
+#[cfg(feature = "serde")]
+mod instant_serde {
+    use instant::Instant;
+    use once_cell::sync::Lazy;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::{convert::TryFrom, time::Duration};
+
+    static REFERENCE: Lazy<Instant> = Lazy::new(Instant::now);
+
+    fn to_nanos(instant: Instant) -> i64 {
+        let reference = *REFERENCE;
+        if instant >= reference {
+            i64::try_from((instant - reference).as_nanos()).unwrap_or(i64::MAX)
+        } else {
+            i64::try_from((reference - instant).as_nanos()).map_or(i64::MIN, |nanos| -nanos)
+        }
+    }
+
+    fn from_nanos(nanos: i64) -> Instant {
+        let reference = *REFERENCE;
+        let offset = Duration::from_nanos(nanos.unsigned_abs());
+        if nanos >= 0 {
+            reference + offset
+        } else {
+            // Instants can't go arbitrarily far back on some platforms.
+            reference.checked_sub(offset).unwrap_or(reference)
+        }
+    }
+
+    pub fn serialize<S>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        to_nanos(*instant).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Instant, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        i64::deserialize(deserializer).map(from_nanos)
+    }
+
+    pub mod option {
+        use super::{from_nanos, to_nanos};
+        use instant::Instant;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<S>(instant: &Option<Instant>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            instant.map(to_nanos).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Instant>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Option::<i64>::deserialize(deserializer).map(|nanos| nanos.map(from_nanos))
+        }
+    }
+}
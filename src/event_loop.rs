@@ -94,7 +94,7 @@ impl<T> EventLoopBuilder<T> {
     /// ## Platform-specific
     ///
     /// - **Linux:** Backend type can be controlled using an environment variable
-    ///   `WINIT_UNIX_BACKEND`. Legal values are `x11` and `wayland`.
+    ///   `WINIT_UNIX_BACKEND`. Legal values are `x11`, `wayland` and `headless`.
     ///   If it is not set, winit will try to connect to a Wayland connection, and if that fails,
     ///   will fall back on X11. If this variable is set with any other value, winit will panic.
     ///
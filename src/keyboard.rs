@@ -0,0 +1,732 @@
+//! Types describing key presses, carried by [`WindowEvent::KeyboardInput`].
+//!
+//! A [`KeyEvent`] describes a key in two ways: its [`KeyCode`] identifies the physical key and
+//! ignores the keyboard layout, which suits key bindings based on the position of the keys, such
+//! as movement in a game. Its [`Key`] is what the key means in the current layout with the
+//! modifiers applied, which suits shortcuts like <kbd>Ctrl</kbd>+<kbd>Z</kbd>. The text typed by
+//! the key is given separately, since a key can type several characters or none.
+//!
+//! [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+
+use crate::event::ElementState;
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+use crate::event::{KeyboardInput, VirtualKeyCode};
+
+/// A key press or release.
+///
+/// ## Platform-specific
+///
+/// - **Windows / macOS / Web:** `logical_key` is derived from
+///   [`KeyboardInput::virtual_keycode`], so punctuation and dead keys may not follow the layout.
+/// - **Android:** `physical_key` and `logical_key` are both derived from
+///   [`KeyboardInput::virtual_keycode`], and `text` is always `None`. The text is only delivered
+///   by [`WindowEvent::ReceivedCharacter`].
+/// - **iOS:** Unsupported.
+///
+/// [`KeyboardInput::virtual_keycode`]: crate::event::KeyboardInput::virtual_keycode
+/// [`WindowEvent::ReceivedCharacter`]: crate::event::WindowEvent::ReceivedCharacter
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyEvent {
+    /// The physical key, which doesn't depend on the keyboard layout.
+    pub physical_key: KeyCode,
+
+    /// What the key means in the current keyboard layout, with the modifiers applied.
+    ///
+    /// Pressing <kbd>Shift</kbd>+<kbd>A</kbd> on a US layout gives `Key::Character("A")`, and
+    /// the same key without modifiers on a French AZERTY layout gives `Key::Character("q")`.
+    pub logical_key: Key,
+
+    /// The text typed by the key, if any.
+    ///
+    /// This is `None` for releases and for keys that don't type anything, like the arrows or a
+    /// dead key starting a compose sequence. With <kbd>Ctrl</kbd> held, it can be a control
+    /// character. Text typed through an input method is delivered by [`WindowEvent::Ime`]
+    /// instead.
+    ///
+    /// [`WindowEvent::Ime`]: crate::event::WindowEvent::Ime
+    pub text: Option<String>,
+
+    /// Which of several keys with the same meaning was used, like the left or right
+    /// <kbd>Shift</kbd>.
+    pub location: KeyLocation,
+
+    /// Whether the key was pressed or released.
+    pub state: ElementState,
+
+    /// Whether the press was generated by the key being held down.
+    ///
+    /// Releases are never repeated.
+    pub repeat: bool,
+}
+
+impl KeyEvent {
+    /// Builds a key event from the information of the legacy [`KeyboardInput`], for the
+    /// platforms that don't provide anything more accurate.
+    #[cfg(target_os = "android")]
+    pub(crate) fn from_keyboard_input(input: &KeyboardInput, repeat: bool) -> Self {
+        let physical_key = match input.virtual_keycode {
+            Some(virtual_keycode) => from_virtual_keycode(virtual_keycode, input.scancode).0,
+            None => KeyCode::Unidentified(input.scancode),
+        };
+        Self::from_native_key(input, physical_key, None, repeat)
+    }
+
+    /// Builds a key event from the physical key and the text the platform reported, taking the
+    /// logical key from the legacy [`KeyboardInput`].
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    pub(crate) fn from_native_key(
+        input: &KeyboardInput,
+        physical_key: KeyCode,
+        text: Option<String>,
+        repeat: bool,
+    ) -> Self {
+        let logical_key = match input.virtual_keycode {
+            Some(virtual_keycode) => from_virtual_keycode(virtual_keycode, input.scancode).1,
+            None => Key::Unidentified,
+        };
+        KeyEvent {
+            location: physical_key.location(),
+            physical_key,
+            logical_key,
+            text: text.filter(|text| input.state == ElementState::Pressed && !text.is_empty()),
+            state: input.state,
+            repeat,
+        }
+    }
+}
+
+/// A keyboard layout among the ones the user configured, reported by
+/// [`WindowEvent::KeyboardLayoutChanged`] and [`Window::keyboard_layout`].
+///
+/// [`WindowEvent::KeyboardLayoutChanged`]: crate::event::WindowEvent::KeyboardLayoutChanged
+/// [`Window::keyboard_layout`]: crate::window::Window::keyboard_layout
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyboardLayout {
+    /// The position of the layout in the list of configured layouts, starting at 0.
+    pub index: u32,
+
+    /// The human-readable name of the layout, like `English (US)`, or an empty string if the
+    /// keymap doesn't name it.
+    pub name: String,
+}
+
+/// Where a key is on the keyboard, for the keys that exist several times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyLocation {
+    /// The key is the only one of its kind, or its location isn't known.
+    Standard,
+    /// The key on the left side of the keyboard, like the left <kbd>Shift</kbd>.
+    Left,
+    /// The key on the right side of the keyboard, like the right <kbd>Shift</kbd>.
+    Right,
+    /// The key on the numeric keypad, like the keypad <kbd>1</kbd>.
+    Numpad,
+}
+
+/// The meaning of a key in the current keyboard layout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Key {
+    /// A key that doesn't type a character, like <kbd>Enter</kbd> or the arrows.
+    Named(NamedKey),
+
+    /// A key that types characters, given here as they are typed with the current modifiers.
+    ///
+    /// This is usually a single character, but can be more, or differ from
+    /// [`KeyEvent::text`] with modifiers like <kbd>Ctrl</kbd>.
+    Character(String),
+
+    /// A dead key, which modifies the next character typed instead of typing one itself.
+    ///
+    /// Contains the character for the accent it adds, if known, like `´` for an acute accent.
+    Dead(Option<char>),
+
+    /// A key whose meaning isn't known.
+    Unidentified,
+}
+
+/// A key that doesn't type a character.
+///
+/// The names follow the `key` values of the [UI Events specification][spec].
+///
+/// [spec]: https://www.w3.org/TR/uievents-key/
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NamedKey {
+    /// The <kbd>Alt</kbd> key, or <kbd>Option</kbd> on Apple keyboards.
+    Alt,
+    /// The <kbd>AltGr</kbd> key, which gives access to a third character on some keys.
+    AltGraph,
+    CapsLock,
+    Control,
+    Fn,
+    NumLock,
+    ScrollLock,
+    Shift,
+    /// The <kbd>Windows</kbd> key, or <kbd>Command</kbd> on Apple keyboards.
+    Super,
+    /// The <kbd>Hyper</kbd> modifier found in some X11 layouts.
+    Hyper,
+    /// The <kbd>Meta</kbd> modifier found in some X11 layouts.
+    Meta,
+
+    Enter,
+    Tab,
+    Space,
+
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+    End,
+    Home,
+    PageDown,
+    PageUp,
+
+    Backspace,
+    Clear,
+    Copy,
+    Cut,
+    Delete,
+    Insert,
+    Paste,
+    Redo,
+    Undo,
+
+    Cancel,
+    /// The key opening the context menu, usually next to the right <kbd>Ctrl</kbd>.
+    ContextMenu,
+    Escape,
+    Execute,
+    Find,
+    Help,
+    Pause,
+    Select,
+    PrintScreen,
+
+    /// The <kbd>Compose</kbd> key, starting a compose sequence.
+    Compose,
+    Convert,
+    NonConvert,
+    /// Switches to the next character group of the layout.
+    ModeChange,
+    KanaMode,
+    KanjiMode,
+    Hiragana,
+    Katakana,
+    HiraganaKatakana,
+    ZenkakuHankaku,
+    Eisu,
+    HangulMode,
+    HanjaMode,
+
+    BrightnessDown,
+    BrightnessUp,
+    Eject,
+    Power,
+    Standby,
+    WakeUp,
+
+    MediaFastForward,
+    MediaPause,
+    MediaPlay,
+    MediaPlayPause,
+    MediaRecord,
+    MediaRewind,
+    MediaStop,
+    MediaTrackNext,
+    MediaTrackPrevious,
+    AudioVolumeDown,
+    AudioVolumeMute,
+    AudioVolumeUp,
+
+    BrowserBack,
+    BrowserFavorites,
+    BrowserForward,
+    BrowserHome,
+    BrowserRefresh,
+    BrowserSearch,
+    BrowserStop,
+    LaunchMail,
+    LaunchMediaPlayer,
+    /// Usually opens the file manager, or "My Computer" on Windows.
+    LaunchApplication1,
+    /// Usually opens the calculator.
+    LaunchApplication2,
+
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+}
+
+/// A physical key, named after the key at the same position on a US keyboard.
+///
+/// The names follow the `code` values of the [UI Events specification][spec], which are based on
+/// the USB HID usages, so the <kbd>Q</kbd> key of an AZERTY keyboard is [`KeyCode::KeyA`].
+///
+/// [spec]: https://www.w3.org/TR/uievents-code/
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyCode {
+    /// <kbd>`</kbd> on a US keyboard, <kbd>半角/全角</kbd> on a Japanese keyboard.
+    Backquote,
+    /// <kbd>\\</kbd> on a US keyboard, <kbd>#</kbd> on a UK keyboard.
+    Backslash,
+    BracketLeft,
+    BracketRight,
+    Comma,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    Equal,
+    /// The extra key next to the left <kbd>Shift</kbd> on ISO keyboards, <kbd>\\</kbd> on a UK
+    /// keyboard.
+    IntlBackslash,
+    /// <kbd>\\</kbd> next to the right <kbd>Shift</kbd> on a Japanese keyboard.
+    IntlRo,
+    /// <kbd>¥</kbd> on a Japanese keyboard.
+    IntlYen,
+    KeyA,
+    KeyB,
+    KeyC,
+    KeyD,
+    KeyE,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyI,
+    KeyJ,
+    KeyK,
+    KeyL,
+    KeyM,
+    KeyN,
+    KeyO,
+    KeyP,
+    KeyQ,
+    KeyR,
+    KeyS,
+    KeyT,
+    KeyU,
+    KeyV,
+    KeyW,
+    KeyX,
+    KeyY,
+    KeyZ,
+    Minus,
+    Period,
+    Quote,
+    Semicolon,
+    Slash,
+
+    AltLeft,
+    /// Often labeled <kbd>AltGr</kbd>.
+    AltRight,
+    Backspace,
+    CapsLock,
+    ContextMenu,
+    ControlLeft,
+    ControlRight,
+    Enter,
+    /// The left <kbd>Windows</kbd> key, or <kbd>Command</kbd> on Apple keyboards.
+    SuperLeft,
+    /// The right <kbd>Windows</kbd> key, or <kbd>Command</kbd> on Apple keyboards.
+    SuperRight,
+    ShiftLeft,
+    ShiftRight,
+    Space,
+    Tab,
+    /// <kbd>変換</kbd> on a Japanese keyboard.
+    Convert,
+    /// <kbd>カタカナ/ひらがな/ローマ字</kbd> on a Japanese keyboard.
+    KanaMode,
+    /// <kbd>한/영</kbd> on a Korean keyboard.
+    Lang1,
+    /// <kbd>한자</kbd> on a Korean keyboard.
+    Lang2,
+    /// <kbd>カタカナ</kbd> on a Japanese keyboard.
+    Lang3,
+    /// <kbd>ひらがな</kbd> on a Japanese keyboard.
+    Lang4,
+    /// <kbd>半角/全角</kbd> on a Japanese keyboard.
+    Lang5,
+    /// <kbd>無変換</kbd> on a Japanese keyboard.
+    NonConvert,
+
+    Delete,
+    End,
+    Help,
+    Home,
+    Insert,
+    PageDown,
+    PageUp,
+
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    ArrowUp,
+
+    NumLock,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadComma,
+    NumpadDecimal,
+    NumpadDivide,
+    NumpadEnter,
+    NumpadEqual,
+    NumpadMultiply,
+    NumpadParenLeft,
+    NumpadParenRight,
+    NumpadSubtract,
+
+    Escape,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    Fn,
+    PrintScreen,
+    ScrollLock,
+    Pause,
+
+    BrowserBack,
+    BrowserFavorites,
+    BrowserForward,
+    BrowserHome,
+    BrowserRefresh,
+    BrowserSearch,
+    BrowserStop,
+    Eject,
+    LaunchApp1,
+    LaunchApp2,
+    LaunchMail,
+    MediaPlayPause,
+    MediaSelect,
+    MediaStop,
+    MediaTrackNext,
+    MediaTrackPrevious,
+    Power,
+    Sleep,
+    AudioVolumeDown,
+    AudioVolumeMute,
+    AudioVolumeUp,
+    WakeUp,
+
+    Again,
+    Copy,
+    Cut,
+    Find,
+    Open,
+    Paste,
+    Props,
+    Select,
+    Undo,
+
+    /// A key without a name, with its platform-specific scancode.
+    Unidentified(u32),
+}
+
+impl KeyCode {
+    /// The location of the key, which only depends on the physical key.
+    pub(crate) fn location(self) -> KeyLocation {
+        use KeyCode::*;
+        match self {
+            AltLeft | ControlLeft | ShiftLeft | SuperLeft => KeyLocation::Left,
+            AltRight | ControlRight | ShiftRight | SuperRight => KeyLocation::Right,
+            Numpad0 | Numpad1 | Numpad2 | Numpad3 | Numpad4 | Numpad5 | Numpad6 | Numpad7
+            | Numpad8 | Numpad9 | NumpadAdd | NumpadComma | NumpadDecimal | NumpadDivide
+            | NumpadEnter | NumpadEqual | NumpadMultiply | NumpadParenLeft | NumpadParenRight
+            | NumpadSubtract => KeyLocation::Numpad,
+            _ => KeyLocation::Standard,
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn from_virtual_keycode(virtual_keycode: VirtualKeyCode, scancode: u32) -> (KeyCode, Key) {
+    use VirtualKeyCode as V;
+
+    fn character(s: &str) -> Key {
+        Key::Character(s.to_owned())
+    }
+
+    match virtual_keycode {
+        V::Key1 => (KeyCode::Digit1, character("1")),
+        V::Key2 => (KeyCode::Digit2, character("2")),
+        V::Key3 => (KeyCode::Digit3, character("3")),
+        V::Key4 => (KeyCode::Digit4, character("4")),
+        V::Key5 => (KeyCode::Digit5, character("5")),
+        V::Key6 => (KeyCode::Digit6, character("6")),
+        V::Key7 => (KeyCode::Digit7, character("7")),
+        V::Key8 => (KeyCode::Digit8, character("8")),
+        V::Key9 => (KeyCode::Digit9, character("9")),
+        V::Key0 => (KeyCode::Digit0, character("0")),
+        V::A => (KeyCode::KeyA, character("a")),
+        V::B => (KeyCode::KeyB, character("b")),
+        V::C => (KeyCode::KeyC, character("c")),
+        V::D => (KeyCode::KeyD, character("d")),
+        V::E => (KeyCode::KeyE, character("e")),
+        V::F => (KeyCode::KeyF, character("f")),
+        V::G => (KeyCode::KeyG, character("g")),
+        V::H => (KeyCode::KeyH, character("h")),
+        V::I => (KeyCode::KeyI, character("i")),
+        V::J => (KeyCode::KeyJ, character("j")),
+        V::K => (KeyCode::KeyK, character("k")),
+        V::L => (KeyCode::KeyL, character("l")),
+        V::M => (KeyCode::KeyM, character("m")),
+        V::N => (KeyCode::KeyN, character("n")),
+        V::O => (KeyCode::KeyO, character("o")),
+        V::P => (KeyCode::KeyP, character("p")),
+        V::Q => (KeyCode::KeyQ, character("q")),
+        V::R => (KeyCode::KeyR, character("r")),
+        V::S => (KeyCode::KeyS, character("s")),
+        V::T => (KeyCode::KeyT, character("t")),
+        V::U => (KeyCode::KeyU, character("u")),
+        V::V => (KeyCode::KeyV, character("v")),
+        V::W => (KeyCode::KeyW, character("w")),
+        V::X => (KeyCode::KeyX, character("x")),
+        V::Y => (KeyCode::KeyY, character("y")),
+        V::Z => (KeyCode::KeyZ, character("z")),
+        V::Escape => (KeyCode::Escape, Key::Named(NamedKey::Escape)),
+        V::F1 => (KeyCode::F1, Key::Named(NamedKey::F1)),
+        V::F2 => (KeyCode::F2, Key::Named(NamedKey::F2)),
+        V::F3 => (KeyCode::F3, Key::Named(NamedKey::F3)),
+        V::F4 => (KeyCode::F4, Key::Named(NamedKey::F4)),
+        V::F5 => (KeyCode::F5, Key::Named(NamedKey::F5)),
+        V::F6 => (KeyCode::F6, Key::Named(NamedKey::F6)),
+        V::F7 => (KeyCode::F7, Key::Named(NamedKey::F7)),
+        V::F8 => (KeyCode::F8, Key::Named(NamedKey::F8)),
+        V::F9 => (KeyCode::F9, Key::Named(NamedKey::F9)),
+        V::F10 => (KeyCode::F10, Key::Named(NamedKey::F10)),
+        V::F11 => (KeyCode::F11, Key::Named(NamedKey::F11)),
+        V::F12 => (KeyCode::F12, Key::Named(NamedKey::F12)),
+        V::F13 => (KeyCode::F13, Key::Named(NamedKey::F13)),
+        V::F14 => (KeyCode::F14, Key::Named(NamedKey::F14)),
+        V::F15 => (KeyCode::F15, Key::Named(NamedKey::F15)),
+        V::F16 => (KeyCode::F16, Key::Named(NamedKey::F16)),
+        V::F17 => (KeyCode::F17, Key::Named(NamedKey::F17)),
+        V::F18 => (KeyCode::F18, Key::Named(NamedKey::F18)),
+        V::F19 => (KeyCode::F19, Key::Named(NamedKey::F19)),
+        V::F20 => (KeyCode::F20, Key::Named(NamedKey::F20)),
+        V::F21 => (KeyCode::F21, Key::Named(NamedKey::F21)),
+        V::F22 => (KeyCode::F22, Key::Named(NamedKey::F22)),
+        V::F23 => (KeyCode::F23, Key::Named(NamedKey::F23)),
+        V::F24 => (KeyCode::F24, Key::Named(NamedKey::F24)),
+        V::Snapshot | V::Sysrq => (KeyCode::PrintScreen, Key::Named(NamedKey::PrintScreen)),
+        V::Scroll => (KeyCode::ScrollLock, Key::Named(NamedKey::ScrollLock)),
+        V::Pause => (KeyCode::Pause, Key::Named(NamedKey::Pause)),
+        V::Insert => (KeyCode::Insert, Key::Named(NamedKey::Insert)),
+        V::Home => (KeyCode::Home, Key::Named(NamedKey::Home)),
+        V::Delete => (KeyCode::Delete, Key::Named(NamedKey::Delete)),
+        V::End => (KeyCode::End, Key::Named(NamedKey::End)),
+        V::PageDown => (KeyCode::PageDown, Key::Named(NamedKey::PageDown)),
+        V::PageUp => (KeyCode::PageUp, Key::Named(NamedKey::PageUp)),
+        V::Left => (KeyCode::ArrowLeft, Key::Named(NamedKey::ArrowLeft)),
+        V::Up => (KeyCode::ArrowUp, Key::Named(NamedKey::ArrowUp)),
+        V::Right => (KeyCode::ArrowRight, Key::Named(NamedKey::ArrowRight)),
+        V::Down => (KeyCode::ArrowDown, Key::Named(NamedKey::ArrowDown)),
+        V::Back => (KeyCode::Backspace, Key::Named(NamedKey::Backspace)),
+        V::Return => (KeyCode::Enter, Key::Named(NamedKey::Enter)),
+        V::Space => (KeyCode::Space, Key::Named(NamedKey::Space)),
+        V::Compose => (KeyCode::ContextMenu, Key::Named(NamedKey::Compose)),
+        V::Caret => (KeyCode::Equal, character("^")),
+        V::Numlock => (KeyCode::NumLock, Key::Named(NamedKey::NumLock)),
+        V::Numpad0 => (KeyCode::Numpad0, character("0")),
+        V::Numpad1 => (KeyCode::Numpad1, character("1")),
+        V::Numpad2 => (KeyCode::Numpad2, character("2")),
+        V::Numpad3 => (KeyCode::Numpad3, character("3")),
+        V::Numpad4 => (KeyCode::Numpad4, character("4")),
+        V::Numpad5 => (KeyCode::Numpad5, character("5")),
+        V::Numpad6 => (KeyCode::Numpad6, character("6")),
+        V::Numpad7 => (KeyCode::Numpad7, character("7")),
+        V::Numpad8 => (KeyCode::Numpad8, character("8")),
+        V::Numpad9 => (KeyCode::Numpad9, character("9")),
+        V::NumpadAdd => (KeyCode::NumpadAdd, character("+")),
+        V::NumpadDivide => (KeyCode::NumpadDivide, character("/")),
+        V::NumpadDecimal => (KeyCode::NumpadDecimal, character(".")),
+        V::NumpadComma => (KeyCode::NumpadComma, character(",")),
+        V::NumpadEnter => (KeyCode::NumpadEnter, Key::Named(NamedKey::Enter)),
+        V::NumpadEquals => (KeyCode::NumpadEqual, character("=")),
+        V::NumpadMultiply => (KeyCode::NumpadMultiply, character("*")),
+        V::NumpadSubtract => (KeyCode::NumpadSubtract, character("-")),
+        V::AbntC1 => (KeyCode::IntlRo, character("/")),
+        V::AbntC2 => (KeyCode::NumpadComma, character(".")),
+        V::Apostrophe => (KeyCode::Quote, character("'")),
+        V::Apps => (KeyCode::ContextMenu, Key::Named(NamedKey::ContextMenu)),
+        V::Asterisk => (KeyCode::Digit8, character("*")),
+        V::At => (KeyCode::Digit2, character("@")),
+        V::Ax => (KeyCode::Unidentified(scancode), Key::Unidentified),
+        V::Backslash => (KeyCode::Backslash, character("\\")),
+        V::Calculator => (
+            KeyCode::LaunchApp2,
+            Key::Named(NamedKey::LaunchApplication2),
+        ),
+        V::Capital => (KeyCode::CapsLock, Key::Named(NamedKey::CapsLock)),
+        V::Colon => (KeyCode::Semicolon, character(":")),
+        V::Comma => (KeyCode::Comma, character(",")),
+        V::Convert => (KeyCode::Convert, Key::Named(NamedKey::Convert)),
+        V::Equals => (KeyCode::Equal, character("=")),
+        V::Grave => (KeyCode::Backquote, character("`")),
+        V::Kana => (KeyCode::KanaMode, Key::Named(NamedKey::KanaMode)),
+        V::Kanji => (KeyCode::Lang2, Key::Named(NamedKey::KanjiMode)),
+        V::LAlt => (KeyCode::AltLeft, Key::Named(NamedKey::Alt)),
+        V::LBracket => (KeyCode::BracketLeft, character("[")),
+        V::LControl => (KeyCode::ControlLeft, Key::Named(NamedKey::Control)),
+        V::LShift => (KeyCode::ShiftLeft, Key::Named(NamedKey::Shift)),
+        V::LWin => (KeyCode::SuperLeft, Key::Named(NamedKey::Super)),
+        V::Mail => (KeyCode::LaunchMail, Key::Named(NamedKey::LaunchMail)),
+        V::MediaSelect => (
+            KeyCode::MediaSelect,
+            Key::Named(NamedKey::LaunchMediaPlayer),
+        ),
+        V::MediaStop => (KeyCode::MediaStop, Key::Named(NamedKey::MediaStop)),
+        V::Minus => (KeyCode::Minus, character("-")),
+        V::Mute => (
+            KeyCode::AudioVolumeMute,
+            Key::Named(NamedKey::AudioVolumeMute),
+        ),
+        V::MyComputer => (
+            KeyCode::LaunchApp1,
+            Key::Named(NamedKey::LaunchApplication1),
+        ),
+        V::NavigateForward => (
+            KeyCode::BrowserForward,
+            Key::Named(NamedKey::BrowserForward),
+        ),
+        V::NavigateBackward => (KeyCode::BrowserBack, Key::Named(NamedKey::BrowserBack)),
+        V::NextTrack => (
+            KeyCode::MediaTrackNext,
+            Key::Named(NamedKey::MediaTrackNext),
+        ),
+        V::NoConvert => (KeyCode::NonConvert, Key::Named(NamedKey::NonConvert)),
+        V::OEM102 => (KeyCode::IntlBackslash, character("\\")),
+        V::Period => (KeyCode::Period, character(".")),
+        V::PlayPause => (
+            KeyCode::MediaPlayPause,
+            Key::Named(NamedKey::MediaPlayPause),
+        ),
+        V::Plus => (KeyCode::Equal, character("+")),
+        V::Power => (KeyCode::Power, Key::Named(NamedKey::Power)),
+        V::PrevTrack => (
+            KeyCode::MediaTrackPrevious,
+            Key::Named(NamedKey::MediaTrackPrevious),
+        ),
+        V::RAlt => (KeyCode::AltRight, Key::Named(NamedKey::Alt)),
+        V::RBracket => (KeyCode::BracketRight, character("]")),
+        V::RControl => (KeyCode::ControlRight, Key::Named(NamedKey::Control)),
+        V::RShift => (KeyCode::ShiftRight, Key::Named(NamedKey::Shift)),
+        V::RWin => (KeyCode::SuperRight, Key::Named(NamedKey::Super)),
+        V::Semicolon => (KeyCode::Semicolon, character(";")),
+        V::Slash => (KeyCode::Slash, character("/")),
+        V::Sleep => (KeyCode::Sleep, Key::Named(NamedKey::Standby)),
+        V::Stop => (KeyCode::MediaStop, Key::Named(NamedKey::MediaStop)),
+        V::Tab => (KeyCode::Tab, Key::Named(NamedKey::Tab)),
+        V::Underline => (KeyCode::Minus, character("_")),
+        V::Unlabeled => (KeyCode::Unidentified(scancode), Key::Unidentified),
+        V::VolumeDown => (
+            KeyCode::AudioVolumeDown,
+            Key::Named(NamedKey::AudioVolumeDown),
+        ),
+        V::VolumeUp => (KeyCode::AudioVolumeUp, Key::Named(NamedKey::AudioVolumeUp)),
+        V::Wake => (KeyCode::WakeUp, Key::Named(NamedKey::WakeUp)),
+        V::WebBack => (KeyCode::BrowserBack, Key::Named(NamedKey::BrowserBack)),
+        V::WebFavorites => (
+            KeyCode::BrowserFavorites,
+            Key::Named(NamedKey::BrowserFavorites),
+        ),
+        V::WebForward => (
+            KeyCode::BrowserForward,
+            Key::Named(NamedKey::BrowserForward),
+        ),
+        V::WebHome => (KeyCode::BrowserHome, Key::Named(NamedKey::BrowserHome)),
+        V::WebRefresh => (
+            KeyCode::BrowserRefresh,
+            Key::Named(NamedKey::BrowserRefresh),
+        ),
+        V::WebSearch => (KeyCode::BrowserSearch, Key::Named(NamedKey::BrowserSearch)),
+        V::WebStop => (KeyCode::BrowserStop, Key::Named(NamedKey::BrowserStop)),
+        V::Yen => (KeyCode::IntlYen, character("¥")),
+        V::Copy => (KeyCode::Copy, Key::Named(NamedKey::Copy)),
+        V::Paste => (KeyCode::Paste, Key::Named(NamedKey::Paste)),
+        V::Cut => (KeyCode::Cut, Key::Named(NamedKey::Cut)),
+    }
+}
@@ -149,14 +149,21 @@ extern crate bitflags;
 #[macro_use]
 extern crate objc;
 
+pub mod clipboard;
+mod cursor;
+pub mod dnd;
 pub mod dpi;
 #[macro_use]
 pub mod error;
 pub mod event;
 pub mod event_loop;
 mod icon;
+pub mod keyboard;
 pub mod monitor;
 mod platform_impl;
+#[cfg(feature = "record")]
+pub mod record;
+mod region;
 pub mod window;
 
 pub mod platform;
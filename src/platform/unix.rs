@@ -8,7 +8,7 @@
 
 use std::os::raw;
 #[cfg(feature = "x11")]
-use std::{ptr, sync::Arc};
+use std::{ptr, sync::Arc, time::Duration};
 
 use crate::{
     event_loop::{EventLoopBuilder, EventLoopWindowTarget},
@@ -16,8 +16,7 @@ use crate::{
     window::{Window, WindowBuilder},
 };
 
-#[cfg(feature = "x11")]
-use crate::dpi::Size;
+use crate::dpi::{Position, Size};
 #[cfg(feature = "x11")]
 use crate::platform_impl::{x11::ffi::XVisualInfo, x11::XConnection, XLIB_ERROR_HOOKS};
 use crate::platform_impl::{
@@ -33,7 +32,9 @@ pub use crate::platform_impl::x11;
 pub use crate::platform_impl::{x11::util::WindowType as XWindowType, XNotSupported};
 
 #[cfg(feature = "wayland")]
-pub use crate::window::Theme;
+pub use crate::window::{DecorationMode, Theme};
+
+pub use crate::platform_impl::headless::HeadlessInjector;
 
 /// The first argument in the provided hook will be the pointer to `XDisplay`
 /// and the second one the pointer to [`XErrorEvent`]. The returned `bool` is an
@@ -60,6 +61,44 @@ pub fn register_xlib_error_hook(hook: XlibErrorHook) {
     }
 }
 
+/// The settings the desktop shares with X11 clients through an XSETTINGS manager, such as the
+/// GNOME or Xfce settings daemons.
+///
+/// The settings which the manager doesn't have, or which have an invalid value, are `None`, as
+/// are all of them when there's no manager.
+#[cfg(feature = "x11")]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct XSettings {
+    /// The resolution of fonts in dots per inch, from `Xft/DPI`.
+    ///
+    /// Unless overridden with `WINIT_X11_SCALE_FACTOR`, it sets the scale factor of the monitors
+    /// as `dpi / 96`.
+    pub dpi: Option<f64>,
+    /// The name of the cursor theme, from `Gtk/CursorThemeName`.
+    pub cursor_theme_name: Option<String>,
+    /// The size of the cursors in pixels, from `Gtk/CursorThemeSize`.
+    pub cursor_theme_size: Option<u32>,
+    /// The longest time between the clicks of a double click, from `Net/DoubleClickTime`.
+    pub double_click_time: Option<Duration>,
+    /// The time of a full blink of the text cursor, from `Net/CursorBlinkTime`.
+    pub cursor_blink_time: Option<Duration>,
+    /// The name of the GTK theme, from `Net/ThemeName`.
+    pub theme_name: Option<String>,
+}
+
+/// A Wayland text input protocol, which IME support relies on.
+#[cfg(feature = "wayland")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextInputProtocol {
+    /// `zwp_text_input_v1`, which has no notion of seats, so winit follows the keyboard focus.
+    V1,
+    /// `zwp_text_input_v2`, used by older KDE and GNOME compositors.
+    V2,
+    /// `zwp_text_input_v3`.
+    V3,
+}
+
 /// Additional methods on [`EventLoopWindowTarget`] that are specific to Unix.
 pub trait EventLoopWindowTargetExtUnix {
     /// True if the [`EventLoopWindowTarget`] uses Wayland.
@@ -70,10 +109,29 @@ pub trait EventLoopWindowTargetExtUnix {
     #[cfg(feature = "x11")]
     fn is_x11(&self) -> bool;
 
+    /// True if the [`EventLoopWindowTarget`] uses the headless backend.
+    fn is_headless(&self) -> bool;
+
+    /// Returns a [`HeadlessInjector`] to simulate input, monitors and the passing of time.
+    ///
+    /// Returns `None` if the [`EventLoopWindowTarget`] doesn't use the headless backend.
+    fn headless_injector(&self) -> Option<HeadlessInjector>;
+
     #[doc(hidden)]
     #[cfg(feature = "x11")]
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>>;
 
+    /// Returns the current [`XSettings`] of the desktop.
+    ///
+    /// A change of the DPI emits [`WindowEvent::ScaleFactorChanged`] to the windows whose scale
+    /// factor changes, but no event is emitted for the other settings.
+    ///
+    /// Returns `None` if the [`EventLoopWindowTarget`] doesn't use X11.
+    ///
+    /// [`WindowEvent::ScaleFactorChanged`]: crate::event::WindowEvent::ScaleFactorChanged
+    #[cfg(feature = "x11")]
+    fn xsettings(&self) -> Option<XSettings>;
+
     /// Returns a pointer to the `wl_display` object of wayland that is used by this
     /// [`EventLoopWindowTarget`].
     ///
@@ -84,6 +142,16 @@ pub trait EventLoopWindowTargetExtUnix {
     /// [`EventLoop`]: crate::event_loop::EventLoop
     #[cfg(feature = "wayland")]
     fn wayland_display(&self) -> Option<*mut raw::c_void>;
+
+    /// Returns the text input protocol used for IME, the newest one the compositor supports.
+    ///
+    /// Returns `None` if the compositor supports none of them, in which case
+    /// [`Window::set_ime_allowed`] has no effect and no [`Ime`] events are sent, or if the
+    /// [`EventLoopWindowTarget`] doesn't use Wayland.
+    ///
+    /// [`Ime`]: crate::event::Ime
+    #[cfg(feature = "wayland")]
+    fn wayland_text_input(&self) -> Option<TextInputProtocol>;
 }
 
 impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
@@ -96,7 +164,21 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     #[inline]
     #[cfg(feature = "x11")]
     fn is_x11(&self) -> bool {
-        !self.p.is_wayland()
+        self.p.is_x11()
+    }
+
+    #[inline]
+    fn is_headless(&self) -> bool {
+        self.p.is_headless()
+    }
+
+    #[inline]
+    fn headless_injector(&self) -> Option<HeadlessInjector> {
+        match self.p {
+            LinuxEventLoopWindowTarget::Headless(ref p) => Some(p.injector()),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
     }
 
     #[inline]
@@ -104,7 +186,15 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>> {
         match self.p {
             LinuxEventLoopWindowTarget::X(ref e) => Some(e.x_connection().clone()),
-            #[cfg(feature = "wayland")]
+            _ => None,
+        }
+    }
+
+    #[inline]
+    #[cfg(feature = "x11")]
+    fn xsettings(&self) -> Option<XSettings> {
+        match self.p {
+            LinuxEventLoopWindowTarget::X(ref e) => Some(e.xsettings()),
             _ => None,
         }
     }
@@ -116,7 +206,15 @@ impl<T> EventLoopWindowTargetExtUnix for EventLoopWindowTarget<T> {
             LinuxEventLoopWindowTarget::Wayland(ref p) => {
                 Some(p.display().get_display_ptr() as *mut _)
             }
-            #[cfg(feature = "x11")]
+            _ => None,
+        }
+    }
+
+    #[inline]
+    #[cfg(feature = "wayland")]
+    fn wayland_text_input(&self) -> Option<TextInputProtocol> {
+        match self.p {
+            LinuxEventLoopWindowTarget::Wayland(ref p) => p.windowing_features.text_input(),
             _ => None,
         }
     }
@@ -132,6 +230,11 @@ pub trait EventLoopBuilderExtUnix {
     #[cfg(feature = "wayland")]
     fn with_wayland(&mut self) -> &mut Self;
 
+    /// Force using the headless backend, which doesn't need a display server.
+    ///
+    /// See [`HeadlessInjector`] for how to drive it.
+    fn with_headless(&mut self) -> &mut Self;
+
     /// Whether to allow the event loop to be created off of the main thread.
     ///
     /// By default, the window is only allowed to be created on the main
@@ -154,6 +257,12 @@ impl<T> EventLoopBuilderExtUnix for EventLoopBuilder<T> {
         self
     }
 
+    #[inline]
+    fn with_headless(&mut self) -> &mut Self {
+        self.platform_specific.forced_backend = Some(Backend::Headless);
+        self
+    }
+
     #[inline]
     fn with_any_thread(&mut self, any_thread: bool) -> &mut Self {
         self.platform_specific.any_thread = any_thread;
@@ -215,6 +324,16 @@ pub trait WindowExtUnix {
     #[cfg(feature = "wayland")]
     fn wayland_set_csd_theme(&self, config: Theme);
 
+    /// Returns who draws the decorations of the window, as picked by the compositor.
+    ///
+    /// Returns `None` if the window doesn't use wayland, isn't a toplevel, or until the compositor
+    /// configured it. The mode is either [`DecorationMode::Client`] or [`DecorationMode::Server`],
+    /// and its changes are reported with [`WindowEvent::DecorationModeChanged`].
+    ///
+    /// [`WindowEvent::DecorationModeChanged`]: crate::event::WindowEvent::DecorationModeChanged
+    #[cfg(feature = "wayland")]
+    fn wayland_decoration_mode(&self) -> Option<DecorationMode>;
+
     /// Check if the window is ready for drawing
     ///
     /// It is a remnant of a previous implementation detail for the
@@ -231,7 +350,6 @@ impl WindowExtUnix for Window {
     fn xlib_window(&self) -> Option<raw::c_ulong> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_window()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -241,7 +359,6 @@ impl WindowExtUnix for Window {
     fn xlib_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_display()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -251,7 +368,6 @@ impl WindowExtUnix for Window {
     fn xlib_screen_id(&self) -> Option<raw::c_int> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_screen_id()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -261,7 +377,6 @@ impl WindowExtUnix for Window {
     fn xlib_xconnection(&self) -> Option<Arc<XConnection>> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xlib_xconnection()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -271,7 +386,6 @@ impl WindowExtUnix for Window {
     fn xcb_connection(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::X(ref w) => Some(w.xcb_connection()),
-            #[cfg(feature = "wayland")]
             _ => None,
         }
     }
@@ -281,7 +395,6 @@ impl WindowExtUnix for Window {
     fn wayland_surface(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.surface().as_ref().c_ptr() as *mut _),
-            #[cfg(feature = "x11")]
             _ => None,
         }
     }
@@ -291,7 +404,6 @@ impl WindowExtUnix for Window {
     fn wayland_display(&self) -> Option<*mut raw::c_void> {
         match self.window {
             LinuxWindow::Wayland(ref w) => Some(w.display().get_display_ptr() as *mut _),
-            #[cfg(feature = "x11")]
             _ => None,
         }
     }
@@ -302,11 +414,19 @@ impl WindowExtUnix for Window {
         #[allow(clippy::single_match)]
         match self.window {
             LinuxWindow::Wayland(ref w) => w.set_csd_theme(theme),
-            #[cfg(feature = "x11")]
             _ => (),
         }
     }
 
+    #[inline]
+    #[cfg(feature = "wayland")]
+    fn wayland_decoration_mode(&self) -> Option<DecorationMode> {
+        match self.window {
+            LinuxWindow::Wayland(ref w) => w.decoration_mode(),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn is_ready(&self) -> bool {
         true
@@ -348,10 +468,23 @@ pub trait WindowBuilderExtUnix {
     /// Build window with certain decoration [`Theme`]
     ///
     /// You can also use `WINIT_WAYLAND_CSD_THEME` env variable to set the theme.
-    /// Possible values for env variable are: "dark" and light"
+    /// Possible values for env variable are: "dark" and light". Otherwise, the decorations follow
+    /// the theme of the system when the window is created.
     #[cfg(feature = "wayland")]
     fn with_wayland_csd_theme(self, theme: Theme) -> Self;
 
+    /// Build window asking the compositor for the given [`DecorationMode`].
+    ///
+    /// By default the compositor picks the mode it prefers. [`DecorationMode::Client`] always
+    /// draws the decorations on the client side, and [`DecorationMode::Server`] leaves the window
+    /// undecorated if the compositor refuses to draw them. The `Prefer*` modes ask for a mode but
+    /// follow the compositor when it picks the other one. The mode in use is reported with
+    /// [`WindowEvent::DecorationModeChanged`].
+    ///
+    /// [`WindowEvent::DecorationModeChanged`]: crate::event::WindowEvent::DecorationModeChanged
+    #[cfg(feature = "wayland")]
+    fn with_wayland_decoration_mode(self, mode: DecorationMode) -> Self;
+
     /// Build window with resize increment hint. Only implemented on X11.
     ///
     /// ```
@@ -381,6 +514,36 @@ pub trait WindowBuilderExtUnix {
     /// ```
     #[cfg(feature = "x11")]
     fn with_base_size<S: Into<Size>>(self, base_size: S) -> Self;
+
+    /// Build a popup, like a context menu or a tooltip, placed against its parent as described by
+    /// the given [`Popup`].
+    ///
+    /// The parent is set with [`WindowBuilder::with_parent`], without which the popup is a normal
+    /// window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The popup is an override-redirect window, which isn't managed by the window
+    ///   manager, with the [`XWindowType::PopupMenu`] type unless another type is set. It's kept
+    ///   inside the monitor it opens on.
+    /// - **Wayland:** The popup is an `xdg_popup`, placed by the compositor, which reports its
+    ///   position relative to the parent with `Window::inner_position`. The parent must be a
+    ///   toplevel window of `xdg_shell`, a popup or a layer surface. The grab needs a pointer over
+    ///   the parent, and [`WindowEvent::CloseRequested`] is also sent when the compositor
+    ///   dismisses the popup.
+    ///
+    /// [`WindowEvent::CloseRequested`]: crate::event::WindowEvent::CloseRequested
+    fn with_popup(self, popup: Popup) -> Self;
+
+    /// Build a layer surface, like a panel, a dock or an overlay, instead of a normal window.
+    ///
+    /// The window is stacked in a layer of the desktop and placed by the compositor according to
+    /// the given [`LayerShell`]. It doesn't have decorations, nor a parent, and most of the other
+    /// window attributes don't apply to it.
+    ///
+    /// The compositor must support `zwlr_layer_shell_v1`, otherwise building the window fails.
+    #[cfg(feature = "wayland")]
+    fn with_wayland_layer_shell(self, layer_shell: LayerShell) -> Self;
 }
 
 impl WindowBuilderExtUnix for WindowBuilder {
@@ -435,6 +598,13 @@ impl WindowBuilderExtUnix for WindowBuilder {
         self
     }
 
+    #[inline]
+    #[cfg(feature = "wayland")]
+    fn with_wayland_decoration_mode(mut self, mode: DecorationMode) -> Self {
+        self.platform_specific.decoration_mode = Some(mode);
+        self
+    }
+
     #[inline]
     #[cfg(feature = "x11")]
     fn with_resize_increments<S: Into<Size>>(mut self, increments: S) -> Self {
@@ -448,6 +618,201 @@ impl WindowBuilderExtUnix for WindowBuilder {
         self.platform_specific.base_size = Some(base_size.into());
         self
     }
+
+    #[inline]
+    fn with_popup(mut self, popup: Popup) -> Self {
+        self.platform_specific.popup = Some(popup);
+        self
+    }
+
+    #[inline]
+    #[cfg(feature = "wayland")]
+    fn with_wayland_layer_shell(mut self, layer_shell: LayerShell) -> Self {
+        self.platform_specific.layer_shell = Some(layer_shell);
+        self
+    }
+}
+
+/// The placement of a popup, set with [`WindowBuilderExtUnix::with_popup`].
+///
+/// The popup is attached to a point of the anchor rectangle, which is in the coordinates of the
+/// client area of the parent, and extends from there in the direction of the gravity. When it
+/// doesn't fit on the monitor, it's moved according to the constraint adjustment.
+///
+/// ```
+/// # use winit::dpi::{LogicalPosition, LogicalSize};
+/// # use winit::platform::unix::{ConstraintAdjustment, Popup, PopupAnchor};
+/// // A menu opening below a button, or above it when there's no room below.
+/// let popup = Popup {
+///     anchor: PopupAnchor::BottomLeft,
+///     gravity: PopupAnchor::BottomRight,
+///     constraint_adjustment: ConstraintAdjustment::FLIP_Y | ConstraintAdjustment::SLIDE_X,
+///     grab: true,
+///     ..Popup::new(LogicalPosition::new(10, 10), LogicalSize::new(80, 24))
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Popup {
+    /// The position of the anchor rectangle.
+    pub anchor_position: Position,
+    /// The size of the anchor rectangle.
+    pub anchor_size: Size,
+    /// The point of the anchor rectangle the popup is attached to.
+    pub anchor: PopupAnchor,
+    /// The direction the popup extends to from its anchor point.
+    pub gravity: PopupAnchor,
+    /// How the popup is moved when it doesn't fit on the monitor.
+    pub constraint_adjustment: ConstraintAdjustment,
+    /// Whether the popup grabs the pointer and the keyboard, and is closed with
+    /// [`WindowEvent::CloseRequested`] when clicking outside of it, like a menu.
+    ///
+    /// [`WindowEvent::CloseRequested`]: crate::event::WindowEvent::CloseRequested
+    pub grab: bool,
+}
+
+impl Popup {
+    /// A popup centered on the given anchor rectangle, which isn't moved to fit on the monitor
+    /// and doesn't grab the input.
+    pub fn new<P: Into<Position>, S: Into<Size>>(anchor_position: P, anchor_size: S) -> Self {
+        Self {
+            anchor_position: anchor_position.into(),
+            anchor_size: anchor_size.into(),
+            anchor: PopupAnchor::Center,
+            gravity: PopupAnchor::Center,
+            constraint_adjustment: ConstraintAdjustment::empty(),
+            grab: false,
+        }
+    }
+}
+
+/// A point of a rectangle, or a direction from it when used as a gravity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopupAnchor {
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    BottomLeft,
+    TopRight,
+    BottomRight,
+}
+
+bitflags! {
+    /// How a [`Popup`] is moved when it doesn't fit on the monitor.
+    ///
+    /// The adjustments are tried in the order of flipping, sliding and resizing, on each axis
+    /// separately.
+    #[derive(Default)]
+    pub struct ConstraintAdjustment: u32 {
+        /// Move the popup along the X axis until it fits.
+        const SLIDE_X = 1 << 0;
+        /// Move the popup along the Y axis until it fits.
+        const SLIDE_Y = 1 << 1;
+        /// Mirror the anchor and the gravity along the X axis, when it makes the popup fit.
+        const FLIP_X = 1 << 2;
+        /// Mirror the anchor and the gravity along the Y axis, when it makes the popup fit.
+        const FLIP_Y = 1 << 3;
+        /// Shrink the popup along the X axis to the part which fits.
+        const RESIZE_X = 1 << 4;
+        /// Shrink the popup along the Y axis to the part which fits.
+        const RESIZE_Y = 1 << 5;
+    }
+}
+
+/// The placement of a layer surface, set with [`WindowBuilderExtUnix::with_wayland_layer_shell`].
+///
+/// The size of the window is the one asked with [`WindowBuilder::with_inner_size`], where a
+/// dimension of zero lets the compositor stretch the surface between the edges it's anchored to
+/// on that axis. The size picked by the compositor is reported with `WindowEvent::Resized`.
+///
+/// ```
+/// # use winit::platform::unix::{Layer, LayerAnchor, LayerShell};
+/// // A bar along the top of the monitor, which other windows don't cover.
+/// let bar = LayerShell {
+///     anchor: LayerAnchor::TOP | LayerAnchor::LEFT | LayerAnchor::RIGHT,
+///     exclusive_zone: 32,
+///     ..LayerShell::new(Layer::Top, "status-bar")
+/// };
+/// ```
+#[cfg(feature = "wayland")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerShell {
+    /// The layer the surface is stacked in.
+    pub layer: Layer,
+    /// The edges of the monitor the surface is attached to, it's centered on the other axes.
+    pub anchor: LayerAnchor,
+    /// The logical distance from the anchored edge which other surfaces shouldn't cover.
+    ///
+    /// Zero lets the surface be moved out of the way of the exclusive zones of other surfaces,
+    /// while a negative value places it over them.
+    pub exclusive_zone: i32,
+    /// The logical distances from the anchored edges, in the order top, right, bottom and left.
+    pub margin: (i32, i32, i32, i32),
+    /// When the surface gets the keyboard focus.
+    pub keyboard_interactivity: KeyboardInteractivity,
+    /// The monitor to show the surface on, which the compositor picks when it's `None`.
+    pub output: Option<MonitorHandle>,
+    /// The purpose of the surface, which the compositor may use to apply its rules.
+    pub namespace: String,
+}
+
+#[cfg(feature = "wayland")]
+impl LayerShell {
+    /// A surface in the given layer, centered on a monitor picked by the compositor, without
+    /// exclusive zone and which doesn't get the keyboard focus.
+    pub fn new(layer: Layer, namespace: impl Into<String>) -> Self {
+        Self {
+            layer,
+            anchor: LayerAnchor::empty(),
+            exclusive_zone: 0,
+            margin: (0, 0, 0, 0),
+            keyboard_interactivity: KeyboardInteractivity::None,
+            output: None,
+            namespace: namespace.into(),
+        }
+    }
+}
+
+/// The layers of the desktop, from the bottom to the top.
+///
+/// The normal windows are between [`Layer::Bottom`] and [`Layer::Top`], and fullscreen windows
+/// are usually shown over [`Layer::Top`].
+#[cfg(feature = "wayland")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    Background,
+    Bottom,
+    Top,
+    Overlay,
+}
+
+#[cfg(feature = "wayland")]
+bitflags! {
+    /// The edges of the monitor a [`LayerShell`] surface is attached to.
+    #[derive(Default)]
+    pub struct LayerAnchor: u32 {
+        const TOP = 1 << 0;
+        const BOTTOM = 1 << 1;
+        const LEFT = 1 << 2;
+        const RIGHT = 1 << 3;
+    }
+}
+
+/// When a [`LayerShell`] surface gets the keyboard focus.
+#[cfg(feature = "wayland")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyboardInteractivity {
+    /// The surface never gets the keyboard focus.
+    None,
+    /// The surface gets all the keyboard input while it's in [`Layer::Top`] or
+    /// [`Layer::Overlay`], like a lock screen.
+    Exclusive,
+    /// The surface gets the keyboard focus like a normal window, when clicked for example.
+    ///
+    /// Compositors which don't support it fall back on [`KeyboardInteractivity::None`].
+    OnDemand,
 }
 
 /// Additional methods on `MonitorHandle` that are specific to Linux.
@@ -173,7 +173,8 @@ impl WindowExtWindows for Window {
 
     #[inline]
     fn theme(&self) -> Theme {
-        self.window.theme()
+        // The theme is always known on Windows.
+        self.window.theme().unwrap()
     }
 
     #[inline]
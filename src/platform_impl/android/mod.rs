@@ -19,10 +19,12 @@ use raw_window_handle::{
 };
 
 use crate::{
+    clipboard, dnd,
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error,
     event::{self, VirtualKeyCode},
     event_loop::{self, ControlFlow},
+    keyboard::{self, KeyEvent},
     monitor,
     window::{self, CursorGrabMode},
 };
@@ -450,6 +452,7 @@ impl<T: 'static> EventLoop<T> {
                                                             location,
                                                             id: pointer.pointer_id() as u64,
                                                             force: None,
+                                                            timestamp: Instant::now(),
                                                         },
                                                     ),
                                                 };
@@ -469,19 +472,25 @@ impl<T: 'static> EventLoop<T> {
                                             _ => event::ElementState::Released,
                                         };
                                         #[allow(deprecated)]
+                                        let input = event::KeyboardInput {
+                                            scancode: key.scan_code() as u32,
+                                            state,
+                                            virtual_keycode: ndk_keycode_to_virtualkeycode(
+                                                key.key_code(),
+                                            ),
+                                            modifiers: event::ModifiersState::default(),
+                                        };
                                         let event = event::Event::WindowEvent {
                                             window_id,
                                             event: event::WindowEvent::KeyboardInput {
                                                 device_id,
-                                                input: event::KeyboardInput {
-                                                    scancode: key.scan_code() as u32,
-                                                    state,
-                                                    virtual_keycode: ndk_keycode_to_virtualkeycode(
-                                                        key.key_code(),
-                                                    ),
-                                                    modifiers: event::ModifiersState::default(),
-                                                },
+                                                input,
+                                                event: KeyEvent::from_keyboard_input(
+                                                    &input,
+                                                    key.repeat_count() > 0,
+                                                ),
                                                 is_synthetic: false,
+                                                timestamp: Instant::now(),
                                             },
                                         };
                                         call_event_handler!(
@@ -681,6 +690,16 @@ impl DeviceId {
     pub const fn dummy() -> Self {
         DeviceId
     }
+
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        0
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_raw(_raw: u64) -> Self {
+        DeviceId
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -799,14 +818,55 @@ impl Window {
 
     pub fn set_ime_position(&self, _position: Position) {}
 
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+    pub fn set_ime_surrounding_text(
+        &self,
+        _text: &str,
+        _cursor: usize,
+        _anchor: usize,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_ime_purpose(
+        &self,
+        _purpose: window::ImePurpose,
+        _hints: window::ImeHints,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn set_ime_allowed(&self, _allowed: bool) {}
 
     pub fn focus_window(&self) {}
 
     pub fn request_user_attention(&self, _request_type: Option<window::UserAttentionType>) {}
 
+    pub fn set_idle_inhibit(&self, _inhibit: bool) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_shortcuts_inhibited(&self, _inhibit: bool) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn theme(&self) -> Option<window::Theme> {
+        None
+    }
+
     pub fn set_cursor_icon(&self, _: window::CursorIcon) {}
 
+    pub fn set_custom_cursor(&self, _: &window::CustomCursor) {}
+
     pub fn set_cursor_position(&self, _: Position) -> Result<(), error::ExternalError> {
         Err(error::ExternalError::NotSupported(
             error::NotSupportedError::new(),
@@ -833,6 +893,81 @@ impl Window {
         ))
     }
 
+    pub fn set_input_region(
+        &self,
+        _region: Option<&[window::Rect]>,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_shape(&self, _shape: Option<window::Region>) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_blur(&self, _blur: bool) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn set_clipboard(
+        &self,
+        _kind: clipboard::ClipboardKind,
+        _contents: clipboard::ClipboardContents,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn clipboard(&self, _kind: clipboard::ClipboardKind, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    pub fn clipboard_mime_types(&self, _kind: clipboard::ClipboardKind) -> Vec<String> {
+        Vec::new()
+    }
+
+    pub fn keyboard_layout(&self) -> Option<keyboard::KeyboardLayout> {
+        None
+    }
+
+    pub fn key_label(&self, _physical_key: keyboard::KeyCode) -> Option<keyboard::Key> {
+        None
+    }
+
+    pub fn start_drag(&self, _data: dnd::DragData) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn accept_drag(
+        &self,
+        _mime_type: &str,
+        _action: dnd::DndAction,
+    ) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
+    pub fn reject_drag(&self) -> Result<(), error::ExternalError> {
+        Err(error::ExternalError::NotSupported(
+            error::NotSupportedError::new(),
+        ))
+    }
+
     pub fn raw_window_handle(&self) -> RawWindowHandle {
         if let Some(native_window) = ndk_glue::native_window() {
             native_window.raw_window_handle()
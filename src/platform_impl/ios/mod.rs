@@ -100,6 +100,19 @@ impl DeviceId {
             uiscreen: std::ptr::null_mut(),
         }
     }
+
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        self.uiscreen as usize as u64
+    }
+
+    /// The resulting id is only used for comparisons, the screen is never dereferenced.
+    #[cfg(feature = "serde")]
+    pub fn from_raw(raw: u64) -> Self {
+        DeviceId {
+            uiscreen: raw as usize as ffi::id,
+        }
+    }
 }
 
 unsafe impl Send for DeviceId {}
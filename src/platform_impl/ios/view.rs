@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Instant};
 
 use objc::{
     declare::ClassDecl,
@@ -273,6 +273,7 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
                             location: physical_location,
                             force,
                             phase,
+                            timestamp: Instant::now(),
                         }),
                     }));
                 }
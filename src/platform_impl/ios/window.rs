@@ -7,10 +7,13 @@ use objc::runtime::{Class, Object, BOOL, NO, YES};
 use raw_window_handle::{RawDisplayHandle, RawWindowHandle, UiKitDisplayHandle, UiKitWindowHandle};
 
 use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dnd::{DndAction, DragData},
     dpi::{self, LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     event::{Event, WindowEvent},
     icon::Icon,
+    keyboard::{Key, KeyCode, KeyboardLayout},
     monitor::MonitorHandle as RootMonitorHandle,
     platform::ios::{MonitorHandleExtIOS, ScreenEdge, ValidOrientations},
     platform_impl::platform::{
@@ -23,8 +26,8 @@ use crate::{
         monitor, view, EventLoopWindowTarget, MonitorHandle,
     },
     window::{
-        CursorGrabMode, CursorIcon, Fullscreen, UserAttentionType, WindowAttributes,
-        WindowId as RootWindowId,
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ImeHints, ImePurpose, Rect, Region,
+        Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
     },
 };
 
@@ -181,6 +184,10 @@ impl Inner {
         debug!("`Window::set_cursor_icon` ignored on iOS")
     }
 
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) {
+        debug!("`Window::set_custom_cursor` ignored on iOS")
+    }
+
     pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
@@ -201,6 +208,58 @@ impl Inner {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
+    pub fn set_input_region(&self, _region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_shape(&self, _shape: Option<Region>) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_blur(&self, _blur: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn clipboard(&self, _kind: ClipboardKind, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    pub fn clipboard_mime_types(&self, _kind: ClipboardKind) -> Vec<String> {
+        Vec::new()
+    }
+
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        None
+    }
+
+    pub fn key_label(&self, _physical_key: KeyCode) -> Option<Key> {
+        None
+    }
+
+    pub fn start_drag(&self, _data: DragData) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn accept_drag(&self, _mime_type: &str, _action: DndAction) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn set_minimized(&self, _minimized: bool) {
         warn!("`Window::set_minimized` is ignored on iOS")
     }
@@ -292,6 +351,27 @@ impl Inner {
         warn!("`Window::set_ime_position` is ignored on iOS")
     }
 
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {
+        warn!("`Window::set_ime_cursor_area` is ignored on iOS")
+    }
+
+    pub fn set_ime_surrounding_text(
+        &self,
+        _text: &str,
+        _cursor: usize,
+        _anchor: usize,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_ime_purpose(
+        &self,
+        _purpose: ImePurpose,
+        _hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub fn set_ime_allowed(&self, _allowed: bool) {
         warn!("`Window::set_ime_allowed` is ignored on iOS")
     }
@@ -304,6 +384,18 @@ impl Inner {
         warn!("`Window::request_user_attention` is ignored on iOS")
     }
 
+    pub fn set_idle_inhibit(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn set_shortcuts_inhibited(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    pub fn theme(&self) -> Option<Theme> {
+        None
+    }
+
     // Allow directly accessing the current monitor internally without unwrapping.
     fn current_monitor_inner(&self) -> RootMonitorHandle {
         unsafe {
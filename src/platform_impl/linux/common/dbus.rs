@@ -0,0 +1,836 @@
+//! A minimal client of the D-Bus session bus, enough to call methods with string arguments and
+//! receive signals.
+//!
+//! See <https://dbus.freedesktop.org/doc/dbus-specification.html> for the wire format.
+
+use std::{
+    env,
+    ffi::OsStr,
+    io::{self, Read, Write},
+    mem,
+    os::{
+        raw::c_char,
+        unix::{ffi::OsStrExt, io::FromRawFd, net::UnixStream},
+    },
+    path::{Path, PathBuf},
+};
+
+pub const BUS_NAME: &str = "org.freedesktop.DBus";
+pub const BUS_PATH: &str = "/org/freedesktop/DBus";
+pub const BUS_INTERFACE: &str = "org.freedesktop.DBus";
+
+/// The largest message the specification allows.
+const MAX_MESSAGE_SIZE: usize = 1 << 27;
+
+/// The deepest nesting of containers the specification allows.
+const MAX_DEPTH: usize = 64;
+
+const HEADER_PATH: u8 = 1;
+const HEADER_INTERFACE: u8 = 2;
+const HEADER_MEMBER: u8 = 3;
+const HEADER_REPLY_SERIAL: u8 = 5;
+const HEADER_DESTINATION: u8 = 6;
+const HEADER_SIGNATURE: u8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    MethodCall,
+    MethodReturn,
+    Error,
+    Signal,
+}
+
+/// A value of the body of a message. Integers are widened and all the string types are merged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Double(f64),
+    Str(String),
+    Variant(Box<Value>),
+    Array(Vec<Value>),
+    /// A struct or a dict entry.
+    Struct(Vec<Value>),
+}
+
+impl Value {
+    /// The value inside of any number of variants.
+    pub fn unwrap_variants(&self) -> &Value {
+        let mut value = self;
+        while let Value::Variant(inner) = value {
+            value = inner;
+        }
+        value
+    }
+}
+
+#[derive(Debug)]
+pub struct Message {
+    pub message_type: MessageType,
+    // Only needed to answer calls, which only the tests do.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub serial: u32,
+    /// The serial of the call this message answers.
+    pub reply_serial: Option<u32>,
+    pub interface: Option<String>,
+    pub member: Option<String>,
+    pub body: Vec<Value>,
+}
+
+/// An authenticated connection to the session bus.
+pub struct Connection {
+    stream: UnixStream,
+    /// The serial of the next message we send.
+    serial: u32,
+}
+
+impl Connection {
+    /// Connects to the session bus of the user, from `DBUS_SESSION_BUS_ADDRESS` or else at the
+    /// default path.
+    pub fn session() -> io::Result<Self> {
+        let stream = match env::var("DBUS_SESSION_BUS_ADDRESS") {
+            Ok(addresses) => connect_any(&addresses)?,
+            Err(_) => {
+                let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+                    .ok_or_else(|| invalid_data("no D-Bus session bus address"))?;
+                UnixStream::connect(Path::new(&runtime_dir).join("bus"))?
+            }
+        };
+
+        let mut connection = Connection { stream, serial: 1 };
+        connection.authenticate()?;
+        // Every connection has to say hello before anything else.
+        connection.call(BUS_NAME, BUS_PATH, BUS_INTERFACE, "Hello", &[])?;
+        Ok(connection)
+    }
+
+    /// A connection over a stream which needs no authentication, like one end of a pair.
+    #[cfg(test)]
+    pub fn from_stream(stream: UnixStream) -> Self {
+        Connection { stream, serial: 1 }
+    }
+
+    /// Calls a method with string arguments, returning the serial its reply will answer.
+    pub fn call(
+        &mut self,
+        destination: &str,
+        path: &str,
+        interface: &str,
+        member: &str,
+        args: &[&str],
+    ) -> io::Result<u32> {
+        let mut body = Writer::default();
+        for arg in args {
+            body.string(arg);
+        }
+
+        let serial = self.serial;
+        self.serial = self.serial.wrapping_add(1).max(1);
+
+        let mut message = Writer::default();
+        message.buf.extend_from_slice(&[b'l', 1, 0, 1]);
+        message.u32(body.buf.len() as u32);
+        message.u32(serial);
+
+        // The array of header fields, whose length is patched in afterwards.
+        let fields_length_offset = message.buf.len();
+        message.u32(0);
+        message.align(8);
+        let fields_start = message.buf.len();
+        message.field(HEADER_PATH, "o", |message| message.string(path));
+        message.field(HEADER_DESTINATION, "s", |message| {
+            message.string(destination)
+        });
+        message.field(HEADER_INTERFACE, "s", |message| message.string(interface));
+        message.field(HEADER_MEMBER, "s", |message| message.string(member));
+        if !args.is_empty() {
+            let signature = "s".repeat(args.len());
+            message.field(HEADER_SIGNATURE, "g", |message| {
+                message.signature(&signature)
+            });
+        }
+        let fields_length = (message.buf.len() - fields_start) as u32;
+        message.buf[fields_length_offset..fields_length_offset + 4]
+            .copy_from_slice(&fields_length.to_le_bytes());
+        message.align(8);
+
+        message.buf.extend_from_slice(&body.buf);
+        self.stream.write_all(&message.buf)?;
+        Ok(serial)
+    }
+
+    /// Waits for the next message, which may be a reply or a signal.
+    pub fn read_message(&mut self) -> io::Result<Message> {
+        let mut fixed = [0; 16];
+        self.stream.read_exact(&mut fixed)?;
+        let big_endian = match fixed[0] {
+            b'l' => false,
+            b'B' => true,
+            _ => return Err(invalid_data("unknown byte order")),
+        };
+        let message_type = match fixed[1] {
+            1 => MessageType::MethodCall,
+            2 => MessageType::MethodReturn,
+            3 => MessageType::Error,
+            4 => MessageType::Signal,
+            _ => return Err(invalid_data("unknown message type")),
+        };
+
+        let read_u32 = |bytes: &[u8]| {
+            let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+            if big_endian {
+                u32::from_be_bytes(bytes)
+            } else {
+                u32::from_le_bytes(bytes)
+            }
+        };
+        let body_length = read_u32(&fixed[4..8]) as usize;
+        let fields_length = read_u32(&fixed[12..16]) as usize;
+        let header_length = align_to(16 + fields_length, 8);
+        if header_length + body_length > MAX_MESSAGE_SIZE {
+            return Err(invalid_data("message too large"));
+        }
+
+        let mut data = fixed.to_vec();
+        data.resize(header_length + body_length, 0);
+        self.stream.read_exact(&mut data[16..])?;
+
+        let mut reader = Reader {
+            data: &data[..16 + fields_length],
+            pos: 12,
+            big_endian,
+        };
+        let fields = match reader.read(b"a(yv)", 0)? {
+            Value::Array(fields) => fields,
+            _ => unreachable!(),
+        };
+
+        let mut message = Message {
+            message_type,
+            serial: read_u32(&fixed[8..12]),
+            reply_serial: None,
+            interface: None,
+            member: None,
+            body: Vec::new(),
+        };
+        let mut signature = String::new();
+        for field in fields {
+            let (code, value) = match field {
+                Value::Struct(mut field) if field.len() == 2 => {
+                    let value = field.pop().unwrap();
+                    (field.pop().unwrap(), value)
+                }
+                _ => continue,
+            };
+            match (code, value.unwrap_variants().clone()) {
+                (Value::Uint(code), Value::Uint(serial)) if code == HEADER_REPLY_SERIAL as u64 => {
+                    message.reply_serial = Some(serial as u32);
+                }
+                (Value::Uint(code), Value::Str(interface)) if code == HEADER_INTERFACE as u64 => {
+                    message.interface = Some(interface);
+                }
+                (Value::Uint(code), Value::Str(member)) if code == HEADER_MEMBER as u64 => {
+                    message.member = Some(member);
+                }
+                (Value::Uint(code), Value::Str(value)) if code == HEADER_SIGNATURE as u64 => {
+                    signature = value;
+                }
+                _ => (),
+            }
+        }
+
+        let mut reader = Reader {
+            data: &data,
+            pos: header_length,
+            big_endian,
+        };
+        let mut signature = signature.as_bytes();
+        while !signature.is_empty() {
+            let (single, rest) = split_single_type(signature)?;
+            message.body.push(reader.read(single, 0)?);
+            signature = rest;
+        }
+
+        Ok(message)
+    }
+
+    /// Authenticates with the credentials of the socket, as the bus only trusts local clients.
+    fn authenticate(&mut self) -> io::Result<()> {
+        let uid = unsafe { libc::getuid() }.to_string();
+        let hex_uid: String = uid.bytes().map(|byte| format!("{:02x}", byte)).collect();
+        // The protocol starts with a nul byte, which carries the credentials on some systems.
+        self.stream
+            .write_all(format!("\0AUTH EXTERNAL {}\r\n", hex_uid).as_bytes())?;
+
+        // The server doesn't send anything else before we begin, so there's no need to buffer.
+        let mut line = Vec::new();
+        let mut byte = [0];
+        while !line.ends_with(b"\r\n") {
+            self.stream.read_exact(&mut byte)?;
+            line.push(byte[0]);
+            if line.len() > 512 {
+                return Err(invalid_data("authentication line too long"));
+            }
+        }
+        if !line.starts_with(b"OK ") {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "D-Bus authentication was rejected",
+            ));
+        }
+
+        self.stream.write_all(b"BEGIN\r\n")
+    }
+}
+
+/// Connects to the first of the `;` separated addresses which works.
+fn connect_any(addresses: &str) -> io::Result<UnixStream> {
+    let mut error = invalid_data("no supported D-Bus address");
+    for address in addresses.split(';') {
+        let options = match address.strip_prefix("unix:") {
+            Some(options) => options,
+            None => continue,
+        };
+        for option in options.split(',') {
+            let result = if let Some(path) = option.strip_prefix("path=") {
+                UnixStream::connect(PathBuf::from(OsStr::from_bytes(&unescape(path))))
+            } else if let Some(name) = option.strip_prefix("abstract=") {
+                connect_abstract(&unescape(name))
+            } else {
+                continue;
+            };
+            match result {
+                Ok(stream) => return Ok(stream),
+                Err(err) => error = err,
+            }
+        }
+    }
+    Err(error)
+}
+
+/// Connects to a socket of the abstract namespace, which the standard library can't name.
+fn connect_abstract(name: &[u8]) -> io::Result<UnixStream> {
+    unsafe {
+        let mut address: libc::sockaddr_un = mem::zeroed();
+        address.sun_family = libc::AF_UNIX as libc::sa_family_t;
+        // The name follows a nul byte, instead of being nul terminated.
+        if name.len() + 1 > address.sun_path.len() {
+            return Err(invalid_data("abstract socket name too long"));
+        }
+        for (dst, src) in address.sun_path[1..].iter_mut().zip(name) {
+            *dst = *src as c_char;
+        }
+        let length = mem::size_of::<libc::sa_family_t>() + 1 + name.len();
+
+        let fd = libc::socket(libc::AF_UNIX, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Owning the fd right away closes it on errors.
+        let stream = UnixStream::from_raw_fd(fd);
+        let address_ptr = &address as *const libc::sockaddr_un as *const libc::sockaddr;
+        if libc::connect(fd, address_ptr, length as libc::socklen_t) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(stream)
+    }
+}
+
+/// Decodes the `%xx` escapes of the values of addresses.
+fn unescape(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                unescaped.push(byte);
+                i += 3;
+            }
+            None => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    unescaped
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn align_to(pos: usize, alignment: usize) -> usize {
+    (pos + alignment - 1) / alignment * alignment
+}
+
+/// The alignment of the values of a type, from its first signature character.
+fn alignment(type_code: u8) -> usize {
+    match type_code {
+        b'n' | b'q' => 2,
+        b'b' | b'i' | b'u' | b'h' | b's' | b'o' | b'a' => 4,
+        b'x' | b't' | b'd' | b'(' | b'{' => 8,
+        _ => 1,
+    }
+}
+
+/// Splits the first complete type off a signature.
+fn split_single_type(signature: &[u8]) -> io::Result<(&[u8], &[u8])> {
+    let mut depth = 0;
+    for (i, &code) in signature.iter().enumerate() {
+        match code {
+            b'a' => continue,
+            b'(' | b'{' => depth += 1,
+            b')' | b'}' if depth > 0 => depth -= 1,
+            b')' | b'}' => break,
+            _ => (),
+        }
+        if depth == 0 {
+            return Ok(signature.split_at(i + 1));
+        }
+    }
+    Err(invalid_data("invalid signature"))
+}
+
+/// Marshals values in little endian.
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn align(&mut self, alignment: usize) {
+        let len = align_to(self.buf.len(), alignment);
+        self.buf.resize(len, 0);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.align(4);
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.buf.extend_from_slice(value.as_bytes());
+        self.buf.push(0);
+    }
+
+    fn signature(&mut self, value: &str) {
+        self.buf.push(value.len() as u8);
+        self.buf.extend_from_slice(value.as_bytes());
+        self.buf.push(0);
+    }
+
+    /// Writes a header field, as a struct of its code and a variant.
+    fn field(&mut self, code: u8, signature: &str, value: impl FnOnce(&mut Self)) {
+        self.align(8);
+        self.buf.push(code);
+        self.signature(signature);
+        value(self);
+    }
+}
+
+/// Unmarshals values, aligned relatively to the start of the message.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let data = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| invalid_data("truncated message"))?;
+        self.pos += len;
+        Ok(data)
+    }
+
+    fn take_aligned<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        self.pos = align_to(self.pos, N);
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.take(N)?);
+        if self.big_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        self.take_aligned().map(u32::from_le_bytes)
+    }
+
+    fn string(&mut self, len: usize) -> io::Result<String> {
+        let string = String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| invalid_data("invalid string"))?;
+        // Skip the nul terminator.
+        self.take(1)?;
+        Ok(string)
+    }
+
+    /// Reads a value of the given single complete type.
+    fn read(&mut self, signature: &[u8], depth: usize) -> io::Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(invalid_data("values nested too deeply"));
+        }
+
+        let value = match signature[0] {
+            b'y' => Value::Uint(self.take(1)?[0] as u64),
+            b'b' => Value::Bool(self.u32()? != 0),
+            b'n' => Value::Int(self.take_aligned().map(i16::from_le_bytes)? as i64),
+            b'q' => Value::Uint(self.take_aligned().map(u16::from_le_bytes)? as u64),
+            b'i' => Value::Int(self.take_aligned().map(i32::from_le_bytes)? as i64),
+            b'u' | b'h' => Value::Uint(self.u32()? as u64),
+            b'x' => Value::Int(self.take_aligned().map(i64::from_le_bytes)?),
+            b't' => Value::Uint(self.take_aligned().map(u64::from_le_bytes)?),
+            b'd' => Value::Double(self.take_aligned().map(f64::from_le_bytes)?),
+            b's' | b'o' => {
+                let len = self.u32()? as usize;
+                Value::Str(self.string(len)?)
+            }
+            b'g' => {
+                let len = self.take(1)?[0] as usize;
+                Value::Str(self.string(len)?)
+            }
+            b'v' => {
+                let len = self.take(1)?[0] as usize;
+                let signature = self.take(len)?;
+                self.take(1)?;
+                let (single, rest) = split_single_type(signature)?;
+                if !rest.is_empty() {
+                    return Err(invalid_data("variant with several types"));
+                }
+                Value::Variant(Box::new(self.read(single, depth + 1)?))
+            }
+            b'a' => {
+                let len = self.u32()? as usize;
+                let element = &signature[1..];
+                self.pos = align_to(self.pos, alignment(element[0]));
+                let end = self.pos + len;
+                let mut elements = Vec::new();
+                while self.pos < end {
+                    elements.push(self.read(element, depth + 1)?);
+                }
+                Value::Array(elements)
+            }
+            b'(' | b'{' => {
+                self.pos = align_to(self.pos, 8);
+                let mut fields = Vec::new();
+                let mut signature = &signature[1..signature.len() - 1];
+                while !signature.is_empty() {
+                    let (single, rest) = split_single_type(signature)?;
+                    fields.push(self.read(single, depth + 1)?);
+                    signature = rest;
+                }
+                Value::Struct(fields)
+            }
+            _ => return Err(invalid_data("unknown type")),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::thread;
+
+    use super::*;
+
+    /// Marshals values in either byte order, for the messages `Connection` only reads.
+    pub(crate) struct Marshaller {
+        big_endian: bool,
+        pub(crate) buf: Vec<u8>,
+    }
+
+    impl Marshaller {
+        pub(crate) fn new(big_endian: bool) -> Self {
+            Marshaller {
+                big_endian,
+                buf: Vec::new(),
+            }
+        }
+
+        pub(crate) fn align(&mut self, alignment: usize) {
+            let len = align_to(self.buf.len(), alignment);
+            self.buf.resize(len, 0);
+        }
+
+        pub(crate) fn byte(&mut self, value: u8) {
+            self.buf.push(value);
+        }
+
+        pub(crate) fn u32(&mut self, value: u32) {
+            self.align(4);
+            let bytes = if self.big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            };
+            self.buf.extend_from_slice(&bytes);
+        }
+
+        pub(crate) fn string(&mut self, value: &str) {
+            self.u32(value.len() as u32);
+            self.buf.extend_from_slice(value.as_bytes());
+            self.buf.push(0);
+        }
+
+        pub(crate) fn signature(&mut self, value: &str) {
+            self.buf.push(value.len() as u8);
+            self.buf.extend_from_slice(value.as_bytes());
+            self.buf.push(0);
+        }
+
+        /// Wraps the marshalled body, of the given signature, into a message.
+        pub(crate) fn message(
+            self,
+            message_type: MessageType,
+            serial: u32,
+            reply_serial: Option<u32>,
+            interface_and_member: Option<(&str, &str)>,
+            signature: &str,
+        ) -> Vec<u8> {
+            let big_endian = self.big_endian;
+            // The fields start at an offset of 16, so they're aligned the same from 0.
+            let mut fields = Marshaller::new(big_endian);
+            if let Some(reply_serial) = reply_serial {
+                fields.align(8);
+                fields.byte(HEADER_REPLY_SERIAL);
+                fields.signature("u");
+                fields.u32(reply_serial);
+            }
+            if let Some((interface, member)) = interface_and_member {
+                fields.align(8);
+                fields.byte(HEADER_INTERFACE);
+                fields.signature("s");
+                fields.string(interface);
+                fields.align(8);
+                fields.byte(HEADER_MEMBER);
+                fields.signature("s");
+                fields.string(member);
+            }
+            if !signature.is_empty() {
+                fields.align(8);
+                fields.byte(HEADER_SIGNATURE);
+                fields.signature("g");
+                fields.signature(signature);
+            }
+
+            let message_type = match message_type {
+                MessageType::MethodCall => 1,
+                MessageType::MethodReturn => 2,
+                MessageType::Error => 3,
+                MessageType::Signal => 4,
+            };
+            let mut message = Marshaller::new(big_endian);
+            message.byte(if big_endian { b'B' } else { b'l' });
+            message.byte(message_type);
+            message.byte(0);
+            message.byte(1);
+            message.u32(self.buf.len() as u32);
+            message.u32(serial);
+            message.u32(fields.buf.len() as u32);
+            message.buf.extend_from_slice(&fields.buf);
+            message.align(8);
+            message.buf.extend_from_slice(&self.buf);
+            message.buf
+        }
+    }
+
+    /// Reads the given bytes as a message.
+    fn read(bytes: &[u8]) -> io::Result<Message> {
+        let (mut writer, reader) = UnixStream::pair().unwrap();
+        writer.write_all(bytes).unwrap();
+        drop(writer);
+        Connection::from_stream(reader).read_message()
+    }
+
+    /// A signal with strings, a variant and a dictionary, which need padding.
+    fn signal(big_endian: bool) -> Vec<u8> {
+        let mut body = Marshaller::new(big_endian);
+        body.string("org.freedesktop.appearance");
+        body.byte(7);
+        body.signature("u");
+        body.u32(1);
+        // The length of the array doesn't count the padding before the first entry.
+        body.u32(0);
+        let length_offset = body.buf.len() - 4;
+        body.align(8);
+        let entries_start = body.buf.len();
+        body.string("a");
+        body.signature("b");
+        body.u32(1);
+        body.align(8);
+        body.string("bc");
+        body.signature("s");
+        body.string("d");
+        let length = (body.buf.len() - entries_start) as u32;
+        let length = if big_endian {
+            length.to_be_bytes()
+        } else {
+            length.to_le_bytes()
+        };
+        body.buf[length_offset..length_offset + 4].copy_from_slice(&length);
+        body.message(
+            MessageType::Signal,
+            12,
+            None,
+            Some(("org.example.Interface", "Changed")),
+            "syva{sv}",
+        )
+    }
+
+    fn assert_signal(message: Message) {
+        assert_eq!(message.message_type, MessageType::Signal);
+        assert_eq!(message.serial, 12);
+        assert_eq!(message.reply_serial, None);
+        assert_eq!(message.interface.as_deref(), Some("org.example.Interface"));
+        assert_eq!(message.member.as_deref(), Some("Changed"));
+        assert_eq!(
+            message.body,
+            [
+                Value::Str("org.freedesktop.appearance".to_owned()),
+                Value::Uint(7),
+                Value::Variant(Box::new(Value::Uint(1))),
+                Value::Array(vec![
+                    Value::Struct(vec![
+                        Value::Str("a".to_owned()),
+                        Value::Variant(Box::new(Value::Bool(true))),
+                    ]),
+                    Value::Struct(vec![
+                        Value::Str("bc".to_owned()),
+                        Value::Variant(Box::new(Value::Str("d".to_owned()))),
+                    ]),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_little_endian() {
+        assert_signal(read(&signal(false)).unwrap());
+    }
+
+    #[test]
+    fn read_big_endian() {
+        assert_signal(read(&signal(true)).unwrap());
+    }
+
+    #[test]
+    fn read_reply() {
+        let mut body = Marshaller::new(false);
+        body.signature("v");
+        body.signature("u");
+        body.u32(2);
+        let message =
+            read(&body.message(MessageType::MethodReturn, 3, Some(2), None, "v")).unwrap();
+        assert_eq!(message.message_type, MessageType::MethodReturn);
+        assert_eq!(message.reply_serial, Some(2));
+        assert_eq!(
+            message.body,
+            [Value::Variant(Box::new(Value::Variant(Box::new(
+                Value::Uint(2)
+            ))))]
+        );
+    }
+
+    #[test]
+    fn read_invalid() {
+        let mut message = signal(false);
+        message[0] = b'x';
+        assert_eq!(
+            read(&message).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let mut message = signal(false);
+        message[1] = 9;
+        assert_eq!(
+            read(&message).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let message = signal(false);
+        let error = read(&message[..message.len() - 1]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut message = signal(false);
+        message[4..8].copy_from_slice(&(MAX_MESSAGE_SIZE as u32).to_le_bytes());
+        assert_eq!(
+            read(&message).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // A variant nesting itself more deeply than allowed.
+        let mut body = Marshaller::new(false);
+        for _ in 0..=MAX_DEPTH {
+            body.signature("v");
+        }
+        body.signature("u");
+        body.u32(0);
+        let message = body.message(MessageType::Signal, 1, None, None, "v");
+        assert_eq!(
+            read(&message).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn call_round_trip() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let mut client = Connection::from_stream(client);
+        let serial = client
+            .call(
+                "org.example",
+                "/org/example",
+                "org.example.Interface",
+                "Method",
+                &["a", "bcd"],
+            )
+            .unwrap();
+        let second_serial = client
+            .call(
+                "org.example",
+                "/org/example",
+                "org.example.Interface",
+                "Other",
+                &[],
+            )
+            .unwrap();
+        assert_ne!(serial, second_serial);
+
+        let reader = thread::spawn(move || {
+            let mut server = Connection::from_stream(server);
+            (
+                server.read_message().unwrap(),
+                server.read_message().unwrap(),
+            )
+        });
+        let (message, second_message) = reader.join().unwrap();
+        assert_eq!(message.message_type, MessageType::MethodCall);
+        assert_eq!(message.serial, serial);
+        assert_eq!(message.interface.as_deref(), Some("org.example.Interface"));
+        assert_eq!(message.member.as_deref(), Some("Method"));
+        assert_eq!(
+            message.body,
+            [Value::Str("a".to_owned()), Value::Str("bcd".to_owned())]
+        );
+        assert_eq!(second_message.serial, second_serial);
+        assert_eq!(second_message.member.as_deref(), Some("Other"));
+        assert!(second_message.body.is_empty());
+    }
+
+    #[test]
+    fn unescape_address() {
+        assert_eq!(unescape("/run/user/1000/bus"), b"/run/user/1000/bus");
+        assert_eq!(unescape("a%2cb%3D%"), b"a,b=%");
+        assert_eq!(unescape("%zz"), b"%zz");
+    }
+}
@@ -0,0 +1,731 @@
+//! Conversion of evdev scancodes and X keysyms, which both X11 and Wayland use, to winit keys.
+
+#[cfg(feature = "wayland")]
+use sctk::seat::keyboard::keysyms;
+#[cfg(not(feature = "wayland"))]
+use xkbcommon_dl::keysyms;
+
+use crate::{
+    event::{ElementState, VirtualKeyCode},
+    keyboard::{Key, KeyCode, KeyEvent, NamedKey},
+};
+
+/// Builds the key event for the key with the evdev `scancode`, which produced `keysym` and
+/// `text` with the current layout and modifiers.
+pub fn key_event(
+    scancode: u32,
+    keysym: u32,
+    text: Option<String>,
+    state: ElementState,
+    repeat: bool,
+) -> KeyEvent {
+    let text = text.filter(|text| state == ElementState::Pressed && !text.is_empty());
+    let physical_key = scancode_to_keycode(scancode);
+    KeyEvent {
+        physical_key,
+        logical_key: keysym_to_key(keysym, text.as_deref()),
+        text,
+        location: physical_key.location(),
+        state,
+        repeat,
+    }
+}
+
+/// Converts an evdev scancode, as defined in `linux/input-event-codes.h`, to a physical key.
+pub fn scancode_to_keycode(scancode: u32) -> KeyCode {
+    match scancode {
+        1 => KeyCode::Escape,
+        2 => KeyCode::Digit1,
+        3 => KeyCode::Digit2,
+        4 => KeyCode::Digit3,
+        5 => KeyCode::Digit4,
+        6 => KeyCode::Digit5,
+        7 => KeyCode::Digit6,
+        8 => KeyCode::Digit7,
+        9 => KeyCode::Digit8,
+        10 => KeyCode::Digit9,
+        11 => KeyCode::Digit0,
+        12 => KeyCode::Minus,
+        13 => KeyCode::Equal,
+        14 => KeyCode::Backspace,
+        15 => KeyCode::Tab,
+        16 => KeyCode::KeyQ,
+        17 => KeyCode::KeyW,
+        18 => KeyCode::KeyE,
+        19 => KeyCode::KeyR,
+        20 => KeyCode::KeyT,
+        21 => KeyCode::KeyY,
+        22 => KeyCode::KeyU,
+        23 => KeyCode::KeyI,
+        24 => KeyCode::KeyO,
+        25 => KeyCode::KeyP,
+        26 => KeyCode::BracketLeft,
+        27 => KeyCode::BracketRight,
+        28 => KeyCode::Enter,
+        29 => KeyCode::ControlLeft,
+        30 => KeyCode::KeyA,
+        31 => KeyCode::KeyS,
+        32 => KeyCode::KeyD,
+        33 => KeyCode::KeyF,
+        34 => KeyCode::KeyG,
+        35 => KeyCode::KeyH,
+        36 => KeyCode::KeyJ,
+        37 => KeyCode::KeyK,
+        38 => KeyCode::KeyL,
+        39 => KeyCode::Semicolon,
+        40 => KeyCode::Quote,
+        41 => KeyCode::Backquote,
+        42 => KeyCode::ShiftLeft,
+        43 => KeyCode::Backslash,
+        44 => KeyCode::KeyZ,
+        45 => KeyCode::KeyX,
+        46 => KeyCode::KeyC,
+        47 => KeyCode::KeyV,
+        48 => KeyCode::KeyB,
+        49 => KeyCode::KeyN,
+        50 => KeyCode::KeyM,
+        51 => KeyCode::Comma,
+        52 => KeyCode::Period,
+        53 => KeyCode::Slash,
+        54 => KeyCode::ShiftRight,
+        55 => KeyCode::NumpadMultiply,
+        56 => KeyCode::AltLeft,
+        57 => KeyCode::Space,
+        58 => KeyCode::CapsLock,
+        59 => KeyCode::F1,
+        60 => KeyCode::F2,
+        61 => KeyCode::F3,
+        62 => KeyCode::F4,
+        63 => KeyCode::F5,
+        64 => KeyCode::F6,
+        65 => KeyCode::F7,
+        66 => KeyCode::F8,
+        67 => KeyCode::F9,
+        68 => KeyCode::F10,
+        69 => KeyCode::NumLock,
+        70 => KeyCode::ScrollLock,
+        71 => KeyCode::Numpad7,
+        72 => KeyCode::Numpad8,
+        73 => KeyCode::Numpad9,
+        74 => KeyCode::NumpadSubtract,
+        75 => KeyCode::Numpad4,
+        76 => KeyCode::Numpad5,
+        77 => KeyCode::Numpad6,
+        78 => KeyCode::NumpadAdd,
+        79 => KeyCode::Numpad1,
+        80 => KeyCode::Numpad2,
+        81 => KeyCode::Numpad3,
+        82 => KeyCode::Numpad0,
+        83 => KeyCode::NumpadDecimal,
+        85 => KeyCode::Lang5,
+        86 => KeyCode::IntlBackslash,
+        87 => KeyCode::F11,
+        88 => KeyCode::F12,
+        89 => KeyCode::IntlRo,
+        90 => KeyCode::Lang3,
+        91 => KeyCode::Lang4,
+        92 => KeyCode::Convert,
+        93 => KeyCode::KanaMode,
+        94 => KeyCode::NonConvert,
+        95 => KeyCode::NumpadComma,
+        96 => KeyCode::NumpadEnter,
+        97 => KeyCode::ControlRight,
+        98 => KeyCode::NumpadDivide,
+        99 => KeyCode::PrintScreen,
+        100 => KeyCode::AltRight,
+        102 => KeyCode::Home,
+        103 => KeyCode::ArrowUp,
+        104 => KeyCode::PageUp,
+        105 => KeyCode::ArrowLeft,
+        106 => KeyCode::ArrowRight,
+        107 => KeyCode::End,
+        108 => KeyCode::ArrowDown,
+        109 => KeyCode::PageDown,
+        110 => KeyCode::Insert,
+        111 => KeyCode::Delete,
+        113 => KeyCode::AudioVolumeMute,
+        114 => KeyCode::AudioVolumeDown,
+        115 => KeyCode::AudioVolumeUp,
+        116 => KeyCode::Power,
+        117 => KeyCode::NumpadEqual,
+        119 => KeyCode::Pause,
+        121 => KeyCode::NumpadComma,
+        122 => KeyCode::Lang1,
+        123 => KeyCode::Lang2,
+        124 => KeyCode::IntlYen,
+        125 => KeyCode::SuperLeft,
+        126 => KeyCode::SuperRight,
+        127 => KeyCode::ContextMenu,
+        128 => KeyCode::MediaStop,
+        129 => KeyCode::Again,
+        130 => KeyCode::Props,
+        131 => KeyCode::Undo,
+        132 => KeyCode::Select,
+        133 => KeyCode::Copy,
+        134 => KeyCode::Open,
+        135 => KeyCode::Paste,
+        136 => KeyCode::Find,
+        137 => KeyCode::Cut,
+        138 => KeyCode::Help,
+        140 => KeyCode::LaunchApp2,
+        142 => KeyCode::Sleep,
+        143 => KeyCode::WakeUp,
+        144 => KeyCode::LaunchApp1,
+        155 => KeyCode::LaunchMail,
+        156 => KeyCode::BrowserFavorites,
+        158 => KeyCode::BrowserBack,
+        159 => KeyCode::BrowserForward,
+        161 => KeyCode::Eject,
+        163 => KeyCode::MediaTrackNext,
+        164 => KeyCode::MediaPlayPause,
+        165 => KeyCode::MediaTrackPrevious,
+        166 => KeyCode::MediaStop,
+        172 => KeyCode::BrowserHome,
+        173 => KeyCode::BrowserRefresh,
+        179 => KeyCode::NumpadParenLeft,
+        180 => KeyCode::NumpadParenRight,
+        183 => KeyCode::F13,
+        184 => KeyCode::F14,
+        185 => KeyCode::F15,
+        186 => KeyCode::F16,
+        187 => KeyCode::F17,
+        188 => KeyCode::F18,
+        189 => KeyCode::F19,
+        190 => KeyCode::F20,
+        191 => KeyCode::F21,
+        192 => KeyCode::F22,
+        193 => KeyCode::F23,
+        194 => KeyCode::F24,
+        217 => KeyCode::BrowserSearch,
+        226 => KeyCode::MediaSelect,
+        464 => KeyCode::Fn,
+        _ => KeyCode::Unidentified(scancode),
+    }
+}
+
+/// Converts a keysym to the virtual key code of [`KeyboardInput`](crate::event::KeyboardInput).
+pub fn keysym_to_vkey(keysym: u32) -> Option<VirtualKeyCode> {
+    match keysym {
+        // Numbers.
+        keysyms::XKB_KEY_1 => Some(VirtualKeyCode::Key1),
+        keysyms::XKB_KEY_2 => Some(VirtualKeyCode::Key2),
+        keysyms::XKB_KEY_3 => Some(VirtualKeyCode::Key3),
+        keysyms::XKB_KEY_4 => Some(VirtualKeyCode::Key4),
+        keysyms::XKB_KEY_5 => Some(VirtualKeyCode::Key5),
+        keysyms::XKB_KEY_6 => Some(VirtualKeyCode::Key6),
+        keysyms::XKB_KEY_7 => Some(VirtualKeyCode::Key7),
+        keysyms::XKB_KEY_8 => Some(VirtualKeyCode::Key8),
+        keysyms::XKB_KEY_9 => Some(VirtualKeyCode::Key9),
+        keysyms::XKB_KEY_0 => Some(VirtualKeyCode::Key0),
+        // Letters.
+        keysyms::XKB_KEY_A | keysyms::XKB_KEY_a => Some(VirtualKeyCode::A),
+        keysyms::XKB_KEY_B | keysyms::XKB_KEY_b => Some(VirtualKeyCode::B),
+        keysyms::XKB_KEY_C | keysyms::XKB_KEY_c => Some(VirtualKeyCode::C),
+        keysyms::XKB_KEY_D | keysyms::XKB_KEY_d => Some(VirtualKeyCode::D),
+        keysyms::XKB_KEY_E | keysyms::XKB_KEY_e => Some(VirtualKeyCode::E),
+        keysyms::XKB_KEY_F | keysyms::XKB_KEY_f => Some(VirtualKeyCode::F),
+        keysyms::XKB_KEY_G | keysyms::XKB_KEY_g => Some(VirtualKeyCode::G),
+        keysyms::XKB_KEY_H | keysyms::XKB_KEY_h => Some(VirtualKeyCode::H),
+        keysyms::XKB_KEY_I | keysyms::XKB_KEY_i => Some(VirtualKeyCode::I),
+        keysyms::XKB_KEY_J | keysyms::XKB_KEY_j => Some(VirtualKeyCode::J),
+        keysyms::XKB_KEY_K | keysyms::XKB_KEY_k => Some(VirtualKeyCode::K),
+        keysyms::XKB_KEY_L | keysyms::XKB_KEY_l => Some(VirtualKeyCode::L),
+        keysyms::XKB_KEY_M | keysyms::XKB_KEY_m => Some(VirtualKeyCode::M),
+        keysyms::XKB_KEY_N | keysyms::XKB_KEY_n => Some(VirtualKeyCode::N),
+        keysyms::XKB_KEY_O | keysyms::XKB_KEY_o => Some(VirtualKeyCode::O),
+        keysyms::XKB_KEY_P | keysyms::XKB_KEY_p => Some(VirtualKeyCode::P),
+        keysyms::XKB_KEY_Q | keysyms::XKB_KEY_q => Some(VirtualKeyCode::Q),
+        keysyms::XKB_KEY_R | keysyms::XKB_KEY_r => Some(VirtualKeyCode::R),
+        keysyms::XKB_KEY_S | keysyms::XKB_KEY_s => Some(VirtualKeyCode::S),
+        keysyms::XKB_KEY_T | keysyms::XKB_KEY_t => Some(VirtualKeyCode::T),
+        keysyms::XKB_KEY_U | keysyms::XKB_KEY_u => Some(VirtualKeyCode::U),
+        keysyms::XKB_KEY_V | keysyms::XKB_KEY_v => Some(VirtualKeyCode::V),
+        keysyms::XKB_KEY_W | keysyms::XKB_KEY_w => Some(VirtualKeyCode::W),
+        keysyms::XKB_KEY_X | keysyms::XKB_KEY_x => Some(VirtualKeyCode::X),
+        keysyms::XKB_KEY_Y | keysyms::XKB_KEY_y => Some(VirtualKeyCode::Y),
+        keysyms::XKB_KEY_Z | keysyms::XKB_KEY_z => Some(VirtualKeyCode::Z),
+        // Escape.
+        keysyms::XKB_KEY_Escape => Some(VirtualKeyCode::Escape),
+        // Function keys.
+        keysyms::XKB_KEY_F1 => Some(VirtualKeyCode::F1),
+        keysyms::XKB_KEY_F2 => Some(VirtualKeyCode::F2),
+        keysyms::XKB_KEY_F3 => Some(VirtualKeyCode::F3),
+        keysyms::XKB_KEY_F4 => Some(VirtualKeyCode::F4),
+        keysyms::XKB_KEY_F5 => Some(VirtualKeyCode::F5),
+        keysyms::XKB_KEY_F6 => Some(VirtualKeyCode::F6),
+        keysyms::XKB_KEY_F7 => Some(VirtualKeyCode::F7),
+        keysyms::XKB_KEY_F8 => Some(VirtualKeyCode::F8),
+        keysyms::XKB_KEY_F9 => Some(VirtualKeyCode::F9),
+        keysyms::XKB_KEY_F10 => Some(VirtualKeyCode::F10),
+        keysyms::XKB_KEY_F11 => Some(VirtualKeyCode::F11),
+        keysyms::XKB_KEY_F12 => Some(VirtualKeyCode::F12),
+        keysyms::XKB_KEY_F13 => Some(VirtualKeyCode::F13),
+        keysyms::XKB_KEY_F14 => Some(VirtualKeyCode::F14),
+        keysyms::XKB_KEY_F15 => Some(VirtualKeyCode::F15),
+        keysyms::XKB_KEY_F16 => Some(VirtualKeyCode::F16),
+        keysyms::XKB_KEY_F17 => Some(VirtualKeyCode::F17),
+        keysyms::XKB_KEY_F18 => Some(VirtualKeyCode::F18),
+        keysyms::XKB_KEY_F19 => Some(VirtualKeyCode::F19),
+        keysyms::XKB_KEY_F20 => Some(VirtualKeyCode::F20),
+        keysyms::XKB_KEY_F21 => Some(VirtualKeyCode::F21),
+        keysyms::XKB_KEY_F22 => Some(VirtualKeyCode::F22),
+        keysyms::XKB_KEY_F23 => Some(VirtualKeyCode::F23),
+        keysyms::XKB_KEY_F24 => Some(VirtualKeyCode::F24),
+        // Flow control.
+        keysyms::XKB_KEY_Print => Some(VirtualKeyCode::Snapshot),
+        keysyms::XKB_KEY_Scroll_Lock => Some(VirtualKeyCode::Scroll),
+        keysyms::XKB_KEY_Pause => Some(VirtualKeyCode::Pause),
+        keysyms::XKB_KEY_Insert => Some(VirtualKeyCode::Insert),
+        keysyms::XKB_KEY_Home => Some(VirtualKeyCode::Home),
+        keysyms::XKB_KEY_Delete => Some(VirtualKeyCode::Delete),
+        keysyms::XKB_KEY_End => Some(VirtualKeyCode::End),
+        keysyms::XKB_KEY_Page_Down => Some(VirtualKeyCode::PageDown),
+        keysyms::XKB_KEY_Page_Up => Some(VirtualKeyCode::PageUp),
+        // Arrows.
+        keysyms::XKB_KEY_Left => Some(VirtualKeyCode::Left),
+        keysyms::XKB_KEY_Up => Some(VirtualKeyCode::Up),
+        keysyms::XKB_KEY_Right => Some(VirtualKeyCode::Right),
+        keysyms::XKB_KEY_Down => Some(VirtualKeyCode::Down),
+
+        keysyms::XKB_KEY_BackSpace => Some(VirtualKeyCode::Back),
+        keysyms::XKB_KEY_Return => Some(VirtualKeyCode::Return),
+        keysyms::XKB_KEY_space => Some(VirtualKeyCode::Space),
+
+        keysyms::XKB_KEY_Multi_key => Some(VirtualKeyCode::Compose),
+        keysyms::XKB_KEY_caret => Some(VirtualKeyCode::Caret),
+
+        // Keypad.
+        keysyms::XKB_KEY_Num_Lock => Some(VirtualKeyCode::Numlock),
+        keysyms::XKB_KEY_KP_0 => Some(VirtualKeyCode::Numpad0),
+        keysyms::XKB_KEY_KP_1 => Some(VirtualKeyCode::Numpad1),
+        keysyms::XKB_KEY_KP_2 => Some(VirtualKeyCode::Numpad2),
+        keysyms::XKB_KEY_KP_3 => Some(VirtualKeyCode::Numpad3),
+        keysyms::XKB_KEY_KP_4 => Some(VirtualKeyCode::Numpad4),
+        keysyms::XKB_KEY_KP_5 => Some(VirtualKeyCode::Numpad5),
+        keysyms::XKB_KEY_KP_6 => Some(VirtualKeyCode::Numpad6),
+        keysyms::XKB_KEY_KP_7 => Some(VirtualKeyCode::Numpad7),
+        keysyms::XKB_KEY_KP_8 => Some(VirtualKeyCode::Numpad8),
+        keysyms::XKB_KEY_KP_9 => Some(VirtualKeyCode::Numpad9),
+        // Misc.
+        // => Some(VirtualKeyCode::AbntC1),
+        // => Some(VirtualKeyCode::AbntC2),
+        keysyms::XKB_KEY_plus => Some(VirtualKeyCode::Plus),
+        keysyms::XKB_KEY_apostrophe => Some(VirtualKeyCode::Apostrophe),
+        // => Some(VirtualKeyCode::Apps),
+        keysyms::XKB_KEY_at => Some(VirtualKeyCode::At),
+        // => Some(VirtualKeyCode::Ax),
+        keysyms::XKB_KEY_backslash => Some(VirtualKeyCode::Backslash),
+        keysyms::XKB_KEY_XF86Calculator => Some(VirtualKeyCode::Calculator),
+        keysyms::XKB_KEY_Caps_Lock => Some(VirtualKeyCode::Capital),
+        keysyms::XKB_KEY_colon => Some(VirtualKeyCode::Colon),
+        keysyms::XKB_KEY_comma => Some(VirtualKeyCode::Comma),
+        // => Some(VirtualKeyCode::Convert),
+        keysyms::XKB_KEY_equal => Some(VirtualKeyCode::Equals),
+        keysyms::XKB_KEY_grave => Some(VirtualKeyCode::Grave),
+        // => Some(VirtualKeyCode::Kana),
+        keysyms::XKB_KEY_Kanji => Some(VirtualKeyCode::Kanji),
+        keysyms::XKB_KEY_Alt_L => Some(VirtualKeyCode::LAlt),
+        keysyms::XKB_KEY_bracketleft => Some(VirtualKeyCode::LBracket),
+        keysyms::XKB_KEY_Control_L => Some(VirtualKeyCode::LControl),
+        keysyms::XKB_KEY_Shift_L => Some(VirtualKeyCode::LShift),
+        keysyms::XKB_KEY_Super_L => Some(VirtualKeyCode::LWin),
+        keysyms::XKB_KEY_XF86Mail => Some(VirtualKeyCode::Mail),
+        // => Some(VirtualKeyCode::MediaSelect),
+        // => Some(VirtualKeyCode::MediaStop),
+        keysyms::XKB_KEY_minus => Some(VirtualKeyCode::Minus),
+        keysyms::XKB_KEY_asterisk => Some(VirtualKeyCode::Asterisk),
+        keysyms::XKB_KEY_XF86AudioMute => Some(VirtualKeyCode::Mute),
+        // => Some(VirtualKeyCode::MyComputer),
+        keysyms::XKB_KEY_XF86AudioNext => Some(VirtualKeyCode::NextTrack),
+        // => Some(VirtualKeyCode::NoConvert),
+        keysyms::XKB_KEY_KP_Separator => Some(VirtualKeyCode::NumpadComma),
+        keysyms::XKB_KEY_KP_Enter => Some(VirtualKeyCode::NumpadEnter),
+        keysyms::XKB_KEY_KP_Equal => Some(VirtualKeyCode::NumpadEquals),
+        keysyms::XKB_KEY_KP_Add => Some(VirtualKeyCode::NumpadAdd),
+        keysyms::XKB_KEY_KP_Subtract => Some(VirtualKeyCode::NumpadSubtract),
+        keysyms::XKB_KEY_KP_Multiply => Some(VirtualKeyCode::NumpadMultiply),
+        keysyms::XKB_KEY_KP_Divide => Some(VirtualKeyCode::NumpadDivide),
+        keysyms::XKB_KEY_KP_Decimal => Some(VirtualKeyCode::NumpadDecimal),
+        keysyms::XKB_KEY_KP_Page_Up => Some(VirtualKeyCode::PageUp),
+        keysyms::XKB_KEY_KP_Page_Down => Some(VirtualKeyCode::PageDown),
+        keysyms::XKB_KEY_KP_Home => Some(VirtualKeyCode::Home),
+        keysyms::XKB_KEY_KP_End => Some(VirtualKeyCode::End),
+        keysyms::XKB_KEY_KP_Left => Some(VirtualKeyCode::Left),
+        keysyms::XKB_KEY_KP_Up => Some(VirtualKeyCode::Up),
+        keysyms::XKB_KEY_KP_Right => Some(VirtualKeyCode::Right),
+        keysyms::XKB_KEY_KP_Down => Some(VirtualKeyCode::Down),
+        keysyms::XKB_KEY_KP_Insert => Some(VirtualKeyCode::Insert),
+        keysyms::XKB_KEY_KP_Delete => Some(VirtualKeyCode::Delete),
+        // => Some(VirtualKeyCode::OEM102),
+        keysyms::XKB_KEY_period => Some(VirtualKeyCode::Period),
+        // => Some(VirtualKeyCode::Playpause),
+        keysyms::XKB_KEY_XF86PowerOff => Some(VirtualKeyCode::Power),
+        keysyms::XKB_KEY_XF86AudioPrev => Some(VirtualKeyCode::PrevTrack),
+        keysyms::XKB_KEY_Alt_R => Some(VirtualKeyCode::RAlt),
+        keysyms::XKB_KEY_bracketright => Some(VirtualKeyCode::RBracket),
+        keysyms::XKB_KEY_Control_R => Some(VirtualKeyCode::RControl),
+        keysyms::XKB_KEY_Shift_R => Some(VirtualKeyCode::RShift),
+        keysyms::XKB_KEY_Super_R => Some(VirtualKeyCode::RWin),
+        keysyms::XKB_KEY_semicolon => Some(VirtualKeyCode::Semicolon),
+        keysyms::XKB_KEY_slash => Some(VirtualKeyCode::Slash),
+        keysyms::XKB_KEY_XF86Sleep => Some(VirtualKeyCode::Sleep),
+        // => Some(VirtualKeyCode::Stop),
+        // => Some(VirtualKeyCode::Sysrq),
+        keysyms::XKB_KEY_Tab => Some(VirtualKeyCode::Tab),
+        keysyms::XKB_KEY_ISO_Left_Tab => Some(VirtualKeyCode::Tab),
+        keysyms::XKB_KEY_underscore => Some(VirtualKeyCode::Underline),
+        // => Some(VirtualKeyCode::Unlabeled),
+        keysyms::XKB_KEY_XF86AudioLowerVolume => Some(VirtualKeyCode::VolumeDown),
+        keysyms::XKB_KEY_XF86AudioRaiseVolume => Some(VirtualKeyCode::VolumeUp),
+        // => Some(VirtualKeyCode::Wake),
+        keysyms::XKB_KEY_XF86Back => Some(VirtualKeyCode::NavigateBackward),
+        // => Some(VirtualKeyCode::WebFavorites),
+        keysyms::XKB_KEY_XF86Forward => Some(VirtualKeyCode::NavigateForward),
+        // => Some(VirtualKeyCode::WebHome),
+        // => Some(VirtualKeyCode::WebRefresh),
+        // => Some(VirtualKeyCode::WebSearch),
+        // => Some(VirtualKeyCode::WebStop),
+        keysyms::XKB_KEY_yen => Some(VirtualKeyCode::Yen),
+        keysyms::XKB_KEY_XF86Copy => Some(VirtualKeyCode::Copy),
+        keysyms::XKB_KEY_XF86Paste => Some(VirtualKeyCode::Paste),
+        keysyms::XKB_KEY_XF86Cut => Some(VirtualKeyCode::Cut),
+        // Fallback.
+        _ => None,
+    }
+}
+
+/// Converts a keysym, as defined in `X11/keysymdef.h` and `X11/XF86keysym.h`, to a logical key.
+///
+/// Keysyms of characters outside of Latin-1 and Unicode, like the legacy Cyrillic ones, are
+/// converted through `text` when it's a printable character.
+pub fn keysym_to_key(keysym: u32, text: Option<&str>) -> Key {
+    if let Some(named) = keysym_to_named_key(keysym) {
+        return Key::Named(named);
+    }
+
+    if (0xfe50..=0xfe8f).contains(&keysym) {
+        return Key::Dead(dead_keysym_to_char(keysym));
+    }
+
+    let character = match keysym {
+        // Latin-1 keysyms are the same as the code points.
+        0x20..=0x7e | 0xa0..=0xff => char::from_u32(keysym),
+        // Unicode keysyms.
+        0x0100_0100..=0x0110_ffff => char::from_u32(keysym - 0x0100_0000),
+        0xffaa => Some('*'),
+        0xffab => Some('+'),
+        0xffac => Some(','),
+        0xffad => Some('-'),
+        0xffae => Some('.'),
+        0xffaf => Some('/'),
+        0xffb0..=0xffb9 => char::from_digit(keysym - 0xffb0, 10),
+        0xffbd => Some('='),
+        _ => None,
+    };
+
+    match (character, text) {
+        (Some(character), _) => Key::Character(character.to_string()),
+        (None, Some(text)) if !text.chars().any(char::is_control) => {
+            Key::Character(text.to_owned())
+        }
+        _ => Key::Unidentified,
+    }
+}
+
+fn keysym_to_named_key(keysym: u32) -> Option<NamedKey> {
+    let named = match keysym {
+        0x0020 | 0xff80 => NamedKey::Space,
+        0xff08 => NamedKey::Backspace,
+        0xff09 | 0xff89 | 0xfe20 => NamedKey::Tab,
+        0xff0b => NamedKey::Clear,
+        0xff0d | 0xff8d => NamedKey::Enter,
+        0xff13 => NamedKey::Pause,
+        0xff14 => NamedKey::ScrollLock,
+        0xff15 | 0xff61 => NamedKey::PrintScreen,
+        0xff1b => NamedKey::Escape,
+        0xffff | 0xff9f => NamedKey::Delete,
+
+        0xff20 => NamedKey::Compose,
+        0xff21 => NamedKey::KanjiMode,
+        0xff22 => NamedKey::NonConvert,
+        0xff23 => NamedKey::Convert,
+        0xff25 => NamedKey::Hiragana,
+        0xff26 => NamedKey::Katakana,
+        0xff27 => NamedKey::HiraganaKatakana,
+        0xff2a => NamedKey::ZenkakuHankaku,
+        0xff2d => NamedKey::KanaMode,
+        0xff2f | 0xff30 => NamedKey::Eisu,
+        0xff31 => NamedKey::HangulMode,
+        0xff34 => NamedKey::HanjaMode,
+
+        0xff50 | 0xff95 => NamedKey::Home,
+        0xff51 | 0xff96 => NamedKey::ArrowLeft,
+        0xff52 | 0xff97 => NamedKey::ArrowUp,
+        0xff53 | 0xff98 => NamedKey::ArrowRight,
+        0xff54 | 0xff99 => NamedKey::ArrowDown,
+        0xff55 | 0xff9a => NamedKey::PageUp,
+        0xff56 | 0xff9b => NamedKey::PageDown,
+        0xff57 | 0xff9c => NamedKey::End,
+        0xff58 | 0xff9d => NamedKey::Clear,
+        0xff60 => NamedKey::Select,
+        0xff62 => NamedKey::Execute,
+        0xff63 | 0xff9e => NamedKey::Insert,
+        0xff65 => NamedKey::Undo,
+        0xff66 => NamedKey::Redo,
+        0xff67 => NamedKey::ContextMenu,
+        0xff68 => NamedKey::Find,
+        0xff69 => NamedKey::Cancel,
+        0xff6a => NamedKey::Help,
+        0xff6b => NamedKey::Pause,
+        0xff7e => NamedKey::ModeChange,
+        0xff7f => NamedKey::NumLock,
+
+        0xff91 => NamedKey::F1,
+        0xff92 => NamedKey::F2,
+        0xff93 => NamedKey::F3,
+        0xff94 => NamedKey::F4,
+        0xffbe => NamedKey::F1,
+        0xffbf => NamedKey::F2,
+        0xffc0 => NamedKey::F3,
+        0xffc1 => NamedKey::F4,
+        0xffc2 => NamedKey::F5,
+        0xffc3 => NamedKey::F6,
+        0xffc4 => NamedKey::F7,
+        0xffc5 => NamedKey::F8,
+        0xffc6 => NamedKey::F9,
+        0xffc7 => NamedKey::F10,
+        0xffc8 => NamedKey::F11,
+        0xffc9 => NamedKey::F12,
+        0xffca => NamedKey::F13,
+        0xffcb => NamedKey::F14,
+        0xffcc => NamedKey::F15,
+        0xffcd => NamedKey::F16,
+        0xffce => NamedKey::F17,
+        0xffcf => NamedKey::F18,
+        0xffd0 => NamedKey::F19,
+        0xffd1 => NamedKey::F20,
+        0xffd2 => NamedKey::F21,
+        0xffd3 => NamedKey::F22,
+        0xffd4 => NamedKey::F23,
+        0xffd5 => NamedKey::F24,
+
+        0xffe1 | 0xffe2 => NamedKey::Shift,
+        0xffe3 | 0xffe4 => NamedKey::Control,
+        0xffe5 | 0xffe6 => NamedKey::CapsLock,
+        0xffe7 | 0xffe8 => NamedKey::Meta,
+        0xffe9 | 0xffea => NamedKey::Alt,
+        0xffeb | 0xffec => NamedKey::Super,
+        0xffed | 0xffee => NamedKey::Hyper,
+        0xfe03 => NamedKey::AltGraph,
+
+        0x1008_ff02 => NamedKey::BrightnessUp,
+        0x1008_ff03 => NamedKey::BrightnessDown,
+        0x1008_ff11 => NamedKey::AudioVolumeDown,
+        0x1008_ff12 => NamedKey::AudioVolumeMute,
+        0x1008_ff13 => NamedKey::AudioVolumeUp,
+        0x1008_ff14 => NamedKey::MediaPlay,
+        0x1008_ff15 => NamedKey::MediaStop,
+        0x1008_ff16 => NamedKey::MediaTrackPrevious,
+        0x1008_ff17 => NamedKey::MediaTrackNext,
+        0x1008_ff18 => NamedKey::BrowserHome,
+        0x1008_ff19 => NamedKey::LaunchMail,
+        0x1008_ff1b => NamedKey::BrowserSearch,
+        0x1008_ff1c => NamedKey::MediaRecord,
+        0x1008_ff1d => NamedKey::LaunchApplication2,
+        0x1008_ff26 => NamedKey::BrowserBack,
+        0x1008_ff27 => NamedKey::BrowserForward,
+        0x1008_ff28 => NamedKey::BrowserStop,
+        0x1008_ff29 => NamedKey::BrowserRefresh,
+        0x1008_ff2a => NamedKey::Power,
+        0x1008_ff2b => NamedKey::WakeUp,
+        0x1008_ff2c => NamedKey::Eject,
+        0x1008_ff2f => NamedKey::Standby,
+        0x1008_ff30 => NamedKey::BrowserFavorites,
+        0x1008_ff31 => NamedKey::MediaPause,
+        0x1008_ff32 => NamedKey::LaunchMediaPlayer,
+        0x1008_ff33 => NamedKey::LaunchApplication1,
+        0x1008_ff3e => NamedKey::MediaRewind,
+        0x1008_ff57 => NamedKey::Copy,
+        0x1008_ff58 => NamedKey::Cut,
+        0x1008_ff6d => NamedKey::Paste,
+        0x1008_ff97 => NamedKey::MediaFastForward,
+        _ => return None,
+    };
+    Some(named)
+}
+
+/// The accent added by a dead key, as a spacing character.
+fn dead_keysym_to_char(keysym: u32) -> Option<char> {
+    let character = match keysym {
+        0xfe50 => '`',
+        0xfe51 => '´',
+        0xfe52 => '^',
+        0xfe53 => '~',
+        0xfe54 => '¯',
+        0xfe55 => '˘',
+        0xfe56 => '˙',
+        0xfe57 => '¨',
+        0xfe58 => '˚',
+        0xfe59 => '˝',
+        0xfe5a => 'ˇ',
+        0xfe5b => '¸',
+        0xfe5c => '˛',
+        _ => return None,
+    };
+    Some(character)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::KeyLocation;
+
+    fn character(s: &str) -> Key {
+        Key::Character(s.to_owned())
+    }
+
+    #[test]
+    fn scancodes() {
+        assert_eq!(scancode_to_keycode(1), KeyCode::Escape);
+        assert_eq!(scancode_to_keycode(16), KeyCode::KeyQ);
+        assert_eq!(scancode_to_keycode(30), KeyCode::KeyA);
+        assert_eq!(scancode_to_keycode(86), KeyCode::IntlBackslash);
+        assert_eq!(scancode_to_keycode(125), KeyCode::SuperLeft);
+        assert_eq!(scancode_to_keycode(464), KeyCode::Fn);
+    }
+
+    #[test]
+    fn unknown_scancodes() {
+        assert_eq!(scancode_to_keycode(0), KeyCode::Unidentified(0));
+        assert_eq!(scancode_to_keycode(84), KeyCode::Unidentified(84));
+        assert_eq!(scancode_to_keycode(0x2ff), KeyCode::Unidentified(0x2ff));
+    }
+
+    #[test]
+    fn keysyms() {
+        assert_eq!(keysym_to_key(0x61, Some("a")), character("a"));
+        assert_eq!(keysym_to_key(0x41, Some("A")), character("A"));
+        assert_eq!(keysym_to_key(0xe9, Some("é")), character("é"));
+        // Unicode keysyms, like the Euro sign.
+        assert_eq!(keysym_to_key(0x0100_20ac, Some("€")), character("€"));
+        assert_eq!(keysym_to_key(0x20, Some(" ")), Key::Named(NamedKey::Space));
+        assert_eq!(
+            keysym_to_key(0xff0d, Some("\r")),
+            Key::Named(NamedKey::Enter)
+        );
+        assert_eq!(keysym_to_key(0xffe1, None), Key::Named(NamedKey::Shift));
+        assert_eq!(keysym_to_key(0xfe03, None), Key::Named(NamedKey::AltGraph));
+        assert_eq!(
+            keysym_to_key(0x1008_ff13, None),
+            Key::Named(NamedKey::AudioVolumeUp)
+        );
+    }
+
+    #[test]
+    fn dead_keysyms() {
+        assert_eq!(keysym_to_key(0xfe51, None), Key::Dead(Some('´')));
+        assert_eq!(keysym_to_key(0xfe57, None), Key::Dead(Some('¨')));
+        // Dead keys without a spacing accent.
+        assert_eq!(keysym_to_key(0xfe60, None), Key::Dead(None));
+    }
+
+    #[test]
+    fn legacy_keysyms_use_the_text() {
+        // Cyrillic_a.
+        assert_eq!(keysym_to_key(0x6c1, Some("а")), character("а"));
+        assert_eq!(keysym_to_key(0x6c1, None), Key::Unidentified);
+        // Control characters aren't what the key means.
+        assert_eq!(keysym_to_key(0x6c1, Some("\u{1}")), Key::Unidentified);
+    }
+
+    #[test]
+    fn unknown_keysyms() {
+        assert_eq!(keysym_to_key(0, None), Key::Unidentified);
+        assert_eq!(keysym_to_key(0x1234_5678, None), Key::Unidentified);
+        assert_eq!(keysym_to_vkey(0), None);
+        assert_eq!(keysym_to_vkey(0x1234_5678), None);
+    }
+
+    #[test]
+    fn virtual_keycodes() {
+        assert_eq!(keysym_to_vkey(0x61), Some(VirtualKeyCode::A));
+        assert_eq!(keysym_to_vkey(0x41), Some(VirtualKeyCode::A));
+        assert_eq!(keysym_to_vkey(0x31), Some(VirtualKeyCode::Key1));
+        assert_eq!(keysym_to_vkey(0xffb1), Some(VirtualKeyCode::Numpad1));
+        assert_eq!(keysym_to_vkey(0xff0d), Some(VirtualKeyCode::Return));
+        assert_eq!(keysym_to_vkey(0xff8d), Some(VirtualKeyCode::NumpadEnter));
+    }
+
+    #[test]
+    fn keypad_and_main_row() {
+        let digit = key_event(2, 0x31, Some("1".to_owned()), ElementState::Pressed, false);
+        assert_eq!(digit.physical_key, KeyCode::Digit1);
+        assert_eq!(digit.logical_key, character("1"));
+        assert_eq!(digit.location, KeyLocation::Standard);
+
+        let numpad = key_event(
+            79,
+            0xffb1,
+            Some("1".to_owned()),
+            ElementState::Pressed,
+            false,
+        );
+        assert_eq!(numpad.physical_key, KeyCode::Numpad1);
+        assert_eq!(numpad.logical_key, character("1"));
+        assert_eq!(numpad.location, KeyLocation::Numpad);
+
+        // Without Num Lock, the keypad keys move the cursor.
+        let numpad = key_event(79, 0xff9c, None, ElementState::Pressed, false);
+        assert_eq!(numpad.physical_key, KeyCode::Numpad1);
+        assert_eq!(numpad.logical_key, Key::Named(NamedKey::End));
+        assert_eq!(numpad.location, KeyLocation::Numpad);
+
+        let enter = key_event(
+            28,
+            0xff0d,
+            Some("\r".to_owned()),
+            ElementState::Pressed,
+            false,
+        );
+        let numpad_enter = key_event(
+            96,
+            0xff8d,
+            Some("\r".to_owned()),
+            ElementState::Pressed,
+            false,
+        );
+        assert_eq!(enter.logical_key, numpad_enter.logical_key);
+        assert_eq!(enter.location, KeyLocation::Standard);
+        assert_eq!(numpad_enter.location, KeyLocation::Numpad);
+    }
+
+    #[test]
+    fn left_and_right() {
+        let left = key_event(42, 0xffe1, None, ElementState::Pressed, false);
+        let right = key_event(54, 0xffe2, None, ElementState::Pressed, false);
+        assert_eq!(left.logical_key, Key::Named(NamedKey::Shift));
+        assert_eq!(right.logical_key, Key::Named(NamedKey::Shift));
+        assert_eq!(left.location, KeyLocation::Left);
+        assert_eq!(right.location, KeyLocation::Right);
+    }
+
+    #[test]
+    fn text() {
+        let press = key_event(30, 0x61, Some("a".to_owned()), ElementState::Pressed, true);
+        assert_eq!(press.text.as_deref(), Some("a"));
+        assert!(press.repeat);
+
+        let release = key_event(
+            30,
+            0x61,
+            Some("a".to_owned()),
+            ElementState::Released,
+            false,
+        );
+        assert_eq!(release.text, None);
+        assert_eq!(release.logical_key, character("a"));
+
+        let empty = key_event(1, 0xff1b, Some(String::new()), ElementState::Pressed, false);
+        assert_eq!(empty.text, None);
+    }
+}
@@ -0,0 +1,8 @@
+//! Code shared by the X11 and Wayland backends.
+
+pub mod dbus;
+pub mod keymap;
+#[cfg(feature = "x11")]
+pub mod popup;
+pub mod portal;
+pub mod xkb;
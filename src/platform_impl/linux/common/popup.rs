@@ -0,0 +1,277 @@
+//! Placement of popups, following the rules of `xdg_positioner`.
+
+use crate::platform::unix::{ConstraintAdjustment, Popup, PopupAnchor};
+
+/// A rectangle as its position and size, in either logical or physical pixels.
+pub type Rect = ((i32, i32), (u32, u32));
+
+/// Where a popup is attached on one axis, or which way it extends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Start,
+    Center,
+    End,
+}
+
+impl Side {
+    fn flip(self) -> Self {
+        match self {
+            Side::Start => Side::End,
+            Side::Center => Side::Center,
+            Side::End => Side::Start,
+        }
+    }
+}
+
+fn sides(anchor: PopupAnchor) -> (Side, Side) {
+    match anchor {
+        PopupAnchor::Center => (Side::Center, Side::Center),
+        PopupAnchor::Top => (Side::Center, Side::Start),
+        PopupAnchor::Bottom => (Side::Center, Side::End),
+        PopupAnchor::Left => (Side::Start, Side::Center),
+        PopupAnchor::Right => (Side::End, Side::Center),
+        PopupAnchor::TopLeft => (Side::Start, Side::Start),
+        PopupAnchor::BottomLeft => (Side::Start, Side::End),
+        PopupAnchor::TopRight => (Side::End, Side::Start),
+        PopupAnchor::BottomRight => (Side::End, Side::End),
+    }
+}
+
+/// Places the popup of the given size against the anchor rectangle, both in the same coordinates
+/// as the bounds, and moves it to fit in the bounds when there are some.
+pub fn place(popup: &Popup, anchor_rect: Rect, size: (u32, u32), bounds: Option<Rect>) -> Rect {
+    let ((anchor_x, anchor_y), (anchor_width, anchor_height)) = anchor_rect;
+    let (anchor_x_side, anchor_y_side) = sides(popup.anchor);
+    let (gravity_x_side, gravity_y_side) = sides(popup.gravity);
+    let adjustment = popup.constraint_adjustment;
+
+    let x_bounds = bounds.map(|((x, _), (width, _))| (x, width));
+    let (x, width) = place_axis(
+        (anchor_x, anchor_width),
+        (anchor_x_side, gravity_x_side),
+        size.0,
+        x_bounds,
+        (
+            adjustment.contains(ConstraintAdjustment::FLIP_X),
+            adjustment.contains(ConstraintAdjustment::SLIDE_X),
+            adjustment.contains(ConstraintAdjustment::RESIZE_X),
+        ),
+    );
+
+    let y_bounds = bounds.map(|((_, y), (_, height))| (y, height));
+    let (y, height) = place_axis(
+        (anchor_y, anchor_height),
+        (anchor_y_side, gravity_y_side),
+        size.1,
+        y_bounds,
+        (
+            adjustment.contains(ConstraintAdjustment::FLIP_Y),
+            adjustment.contains(ConstraintAdjustment::SLIDE_Y),
+            adjustment.contains(ConstraintAdjustment::RESIZE_Y),
+        ),
+    );
+
+    ((x, y), (width, height))
+}
+
+fn place_axis(
+    (anchor_start, anchor_length): (i32, u32),
+    (anchor, gravity): (Side, Side),
+    length: u32,
+    bounds: Option<(i32, u32)>,
+    (flip, slide, resize): (bool, bool, bool),
+) -> (i32, u32) {
+    let position = |anchor, gravity| {
+        let point = match anchor {
+            Side::Start => anchor_start,
+            Side::Center => anchor_start + (anchor_length / 2) as i32,
+            Side::End => anchor_start + anchor_length as i32,
+        };
+
+        match gravity {
+            Side::Start => point - length as i32,
+            Side::Center => point - (length / 2) as i32,
+            Side::End => point,
+        }
+    };
+
+    let mut start = position(anchor, gravity);
+    let (bounds_start, bounds_end) = match bounds {
+        Some((bounds_start, bounds_length)) => (bounds_start, bounds_start + bounds_length as i32),
+        None => return (start, length),
+    };
+    let fits =
+        |start: i32, length: u32| start >= bounds_start && start + length as i32 <= bounds_end;
+
+    if fits(start, length) {
+        return (start, length);
+    }
+
+    // The flip is reverted when the popup still doesn't fit.
+    if flip {
+        let flipped = position(anchor.flip(), gravity.flip());
+        if fits(flipped, length) {
+            return (flipped, length);
+        }
+    }
+
+    if slide {
+        // Keep the start visible when the popup is larger than the bounds.
+        start = start.min(bounds_end - length as i32).max(bounds_start);
+        if fits(start, length) {
+            return (start, length);
+        }
+    }
+
+    if resize {
+        let end = (start + length as i32).min(bounds_end);
+        let resized_start = start.max(bounds_start);
+        if end > resized_start {
+            return (resized_start, (end - resized_start) as u32);
+        }
+    }
+
+    (start, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dpi::{LogicalPosition, LogicalSize};
+
+    const ANCHOR: Rect = ((100, 100), (50, 20));
+    const SIZE: (u32, u32) = (80, 40);
+    const BOUNDS: Option<Rect> = Some(((0, 0), (300, 200)));
+
+    fn popup(
+        anchor: PopupAnchor,
+        gravity: PopupAnchor,
+        constraint_adjustment: ConstraintAdjustment,
+    ) -> Popup {
+        Popup {
+            anchor,
+            gravity,
+            constraint_adjustment,
+            ..Popup::new(LogicalPosition::new(0, 0), LogicalSize::new(0, 0))
+        }
+    }
+
+    #[test]
+    fn centered_by_default() {
+        let popup = Popup::new(LogicalPosition::new(0, 0), LogicalSize::new(0, 0));
+        assert_eq!(place(&popup, ANCHOR, SIZE, BOUNDS), ((85, 90), SIZE));
+    }
+
+    #[test]
+    fn anchor_and_gravity() {
+        let empty = ConstraintAdjustment::empty();
+        // A menu below its button, extending to the right.
+        let below = popup(PopupAnchor::BottomLeft, PopupAnchor::BottomRight, empty);
+        assert_eq!(place(&below, ANCHOR, SIZE, BOUNDS), ((100, 120), SIZE));
+        // A submenu next to its item, extending down.
+        let right = popup(PopupAnchor::TopRight, PopupAnchor::BottomRight, empty);
+        assert_eq!(place(&right, ANCHOR, SIZE, BOUNDS), ((150, 100), SIZE));
+        let above = popup(PopupAnchor::TopRight, PopupAnchor::TopLeft, empty);
+        assert_eq!(place(&above, ANCHOR, SIZE, BOUNDS), ((70, 60), SIZE));
+        let centered_above = popup(PopupAnchor::Top, PopupAnchor::Top, empty);
+        assert_eq!(
+            place(&centered_above, ANCHOR, SIZE, BOUNDS),
+            ((85, 60), SIZE)
+        );
+    }
+
+    #[test]
+    fn unconstrained() {
+        // Without adjustments or bounds, the popup stays where it's placed.
+        let popup = popup(
+            PopupAnchor::TopLeft,
+            PopupAnchor::TopLeft,
+            ConstraintAdjustment::all(),
+        );
+        let anchor = ((10, 10), (10, 10));
+        assert_eq!(place(&popup, anchor, SIZE, None), ((-70, -30), SIZE));
+
+        let popup = Popup {
+            constraint_adjustment: ConstraintAdjustment::empty(),
+            ..popup
+        };
+        assert_eq!(place(&popup, anchor, SIZE, BOUNDS), ((-70, -30), SIZE));
+    }
+
+    #[test]
+    fn flip() {
+        let popup = popup(
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            ConstraintAdjustment::FLIP_Y,
+        );
+        // Flipped above the anchor when there's no room below.
+        let anchor = ((100, 180), (50, 20));
+        assert_eq!(place(&popup, anchor, SIZE, BOUNDS), ((100, 140), SIZE));
+        // The flip is reverted when the popup doesn't fit above either.
+        let bounds = Some(((0, 0), (300, 60)));
+        let anchor = ((100, 20), (50, 20));
+        assert_eq!(place(&popup, anchor, SIZE, bounds), ((100, 40), SIZE));
+        // The horizontal axis isn't flipped.
+        let anchor = ((280, 100), (10, 10));
+        assert_eq!(place(&popup, anchor, SIZE, BOUNDS), ((280, 110), SIZE));
+    }
+
+    #[test]
+    fn flip_before_slide() {
+        let popup = popup(
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            ConstraintAdjustment::FLIP_Y | ConstraintAdjustment::SLIDE_Y,
+        );
+        let anchor = ((100, 180), (50, 20));
+        assert_eq!(place(&popup, anchor, SIZE, BOUNDS), ((100, 140), SIZE));
+        // Sliding is the fallback when flipping doesn't help.
+        let bounds = Some(((0, 0), (300, 60)));
+        let anchor = ((100, 20), (50, 20));
+        assert_eq!(place(&popup, anchor, SIZE, bounds), ((100, 20), SIZE));
+    }
+
+    #[test]
+    fn slide() {
+        let popup = popup(
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            ConstraintAdjustment::SLIDE_X,
+        );
+        let anchor = ((280, 100), (10, 10));
+        assert_eq!(place(&popup, anchor, SIZE, BOUNDS), ((220, 110), SIZE));
+        // Bounds of a monitor on the right of another one.
+        let bounds = Some(((1920, 0), (1920, 1080)));
+        let anchor = ((1900, 100), (10, 10));
+        assert_eq!(place(&popup, anchor, SIZE, bounds), ((1920, 110), SIZE));
+        // The start stays visible when the popup is larger than the bounds.
+        let bounds = Some(((0, 0), (50, 200)));
+        assert_eq!(place(&popup, ANCHOR, SIZE, bounds), ((0, 120), SIZE));
+    }
+
+    #[test]
+    fn resize() {
+        let popup = popup(
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            ConstraintAdjustment::RESIZE_X,
+        );
+        let anchor = ((280, 100), (10, 10));
+        assert_eq!(place(&popup, anchor, SIZE, BOUNDS), ((280, 110), (20, 40)));
+        // A popup entirely out of the bounds can't be resized to fit.
+        let anchor = ((400, 100), (10, 10));
+        assert_eq!(place(&popup, anchor, SIZE, BOUNDS), ((400, 110), SIZE));
+    }
+
+    #[test]
+    fn slide_then_resize() {
+        let popup = popup(
+            PopupAnchor::BottomLeft,
+            PopupAnchor::BottomRight,
+            ConstraintAdjustment::SLIDE_X | ConstraintAdjustment::RESIZE_X,
+        );
+        let bounds = Some(((0, 0), (50, 200)));
+        assert_eq!(place(&popup, ANCHOR, SIZE, bounds), ((0, 120), (50, 40)));
+    }
+}
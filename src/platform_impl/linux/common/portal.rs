@@ -0,0 +1,264 @@
+//! The color scheme of the desktop, from the settings of the XDG desktop portal.
+//!
+//! See <https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.Settings.html>.
+
+use std::{io, thread};
+
+use super::dbus::{self, Connection, Message, MessageType, Value};
+use crate::window::Theme;
+
+const PORTAL_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SETTINGS_INTERFACE: &str = "org.freedesktop.portal.Settings";
+
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+/// Reads the color scheme from a thread, then calls `on_change` from there with it and whenever
+/// it changes.
+///
+/// The theme is `None` when the user has no preference. `on_change` isn't called when there's no
+/// portal, since the portal may have to be started, which would delay the creation of the event
+/// loop if it waited for the answer.
+pub fn watch_color_scheme<F>(on_change: F)
+where
+    F: FnMut(Option<Theme>) + Send + 'static,
+{
+    let result = thread::Builder::new()
+        .name("winit settings portal".to_owned())
+        .spawn(move || {
+            let mut connection = match Connection::session() {
+                Ok(connection) => connection,
+                Err(err) => {
+                    debug!("Failed to connect to the D-Bus session bus: {}", err);
+                    return;
+                }
+            };
+            if let Err(err) = follow_color_scheme(&mut connection, on_change) {
+                debug!(
+                    "Stopped following the color scheme of the settings portal: {}",
+                    err
+                );
+            }
+        });
+    if let Err(err) = result {
+        warn!(
+            "Failed to spawn the thread following the color scheme: {}",
+            err
+        );
+    }
+}
+
+/// Reads the color scheme, then passes it to `on_change` whenever it changes, until the connection
+/// fails.
+fn follow_color_scheme<F>(connection: &mut Connection, mut on_change: F) -> io::Result<()>
+where
+    F: FnMut(Option<Theme>),
+{
+    let rule = format!(
+        "type='signal',interface='{}',member='SettingChanged',path='{}',arg0='{}'",
+        SETTINGS_INTERFACE, PORTAL_PATH, APPEARANCE_NAMESPACE,
+    );
+    connection.call(
+        dbus::BUS_NAME,
+        dbus::BUS_PATH,
+        dbus::BUS_INTERFACE,
+        "AddMatch",
+        &[&rule],
+    )?;
+    let read_serial = connection.call(
+        PORTAL_NAME,
+        PORTAL_PATH,
+        SETTINGS_INTERFACE,
+        "Read",
+        &[APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY],
+    )?;
+
+    loop {
+        let message = connection.read_message()?;
+        if let Some(theme) = color_scheme(&message, read_serial) {
+            on_change(theme);
+        }
+    }
+}
+
+/// The theme a message carries, if it's the answer to our read or a change of the color scheme.
+fn color_scheme(message: &Message, read_serial: u32) -> Option<Option<Theme>> {
+    let value = match message.message_type {
+        MessageType::MethodReturn if message.reply_serial == Some(read_serial) => {
+            message.body.first()
+        }
+        // Older portals don't have the setting.
+        MessageType::Error if message.reply_serial == Some(read_serial) => None,
+        MessageType::Signal
+            if message.interface.as_deref() == Some(SETTINGS_INTERFACE)
+                && message.member.as_deref() == Some("SettingChanged") =>
+        {
+            match message.body.as_slice() {
+                [Value::Str(namespace), Value::Str(key), value]
+                    if namespace == APPEARANCE_NAMESPACE && key == COLOR_SCHEME_KEY =>
+                {
+                    Some(value)
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    // `Read` wraps the value in one more variant than the signal.
+    let theme = match value.map(Value::unwrap_variants) {
+        Some(Value::Uint(1)) => Some(Theme::Dark),
+        Some(Value::Uint(2)) => Some(Theme::Light),
+        _ => None,
+    };
+    Some(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    use super::dbus::tests::Marshaller;
+    use super::*;
+
+    fn reply(reply_serial: u32, value: Value) -> Message {
+        Message {
+            message_type: MessageType::MethodReturn,
+            serial: 10,
+            reply_serial: Some(reply_serial),
+            interface: None,
+            member: None,
+            body: vec![Value::Variant(Box::new(Value::Variant(Box::new(value))))],
+        }
+    }
+
+    fn setting_changed(namespace: &str, key: &str, value: Value) -> Message {
+        Message {
+            message_type: MessageType::Signal,
+            serial: 11,
+            reply_serial: None,
+            interface: Some(SETTINGS_INTERFACE.to_owned()),
+            member: Some("SettingChanged".to_owned()),
+            body: vec![
+                Value::Str(namespace.to_owned()),
+                Value::Str(key.to_owned()),
+                Value::Variant(Box::new(value)),
+            ],
+        }
+    }
+
+    #[test]
+    fn color_scheme_values() {
+        assert_eq!(
+            color_scheme(&reply(2, Value::Uint(1)), 2),
+            Some(Some(Theme::Dark))
+        );
+        assert_eq!(
+            color_scheme(&reply(2, Value::Uint(2)), 2),
+            Some(Some(Theme::Light))
+        );
+        // No preference, and values from newer versions of the portal.
+        assert_eq!(color_scheme(&reply(2, Value::Uint(0)), 2), Some(None));
+        assert_eq!(color_scheme(&reply(2, Value::Uint(3)), 2), Some(None));
+        assert_eq!(color_scheme(&reply(2, Value::Int(1)), 2), Some(None));
+
+        let signal = setting_changed(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY, Value::Uint(1));
+        assert_eq!(color_scheme(&signal, 2), Some(Some(Theme::Dark)));
+    }
+
+    #[test]
+    fn color_scheme_unrelated_messages() {
+        // The reply to another call.
+        assert_eq!(color_scheme(&reply(3, Value::Uint(1)), 2), None);
+
+        let signal = setting_changed(APPEARANCE_NAMESPACE, "accent-color", Value::Uint(1));
+        assert_eq!(color_scheme(&signal, 2), None);
+        let signal = setting_changed(
+            "org.gnome.desktop.interface",
+            COLOR_SCHEME_KEY,
+            Value::Uint(1),
+        );
+        assert_eq!(color_scheme(&signal, 2), None);
+
+        let mut signal = setting_changed(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY, Value::Uint(1));
+        signal.member = Some("SettingsChanged".to_owned());
+        assert_eq!(color_scheme(&signal, 2), None);
+    }
+
+    #[test]
+    fn color_scheme_read_error() {
+        let error = Message {
+            message_type: MessageType::Error,
+            serial: 10,
+            reply_serial: Some(2),
+            interface: None,
+            member: None,
+            body: vec![Value::Str("Requested setting not found".to_owned())],
+        };
+        assert_eq!(color_scheme(&error, 2), Some(None));
+        assert_eq!(color_scheme(&error, 3), None);
+    }
+
+    /// Serves the settings portal on one end of a pair, as the bus would forward it.
+    #[test]
+    fn follow_portal() {
+        let (client, mut server) = UnixStream::pair().unwrap();
+        let portal = std::thread::spawn(move || {
+            let mut calls = Connection::from_stream(server.try_clone().unwrap());
+            let add_match = calls.read_message().unwrap();
+            assert_eq!(add_match.member.as_deref(), Some("AddMatch"));
+            let arg0 = format!("arg0='{}'", APPEARANCE_NAMESPACE);
+            match &add_match.body[..] {
+                [Value::Str(rule)] => assert!(rule.contains(&arg0), "{}", rule),
+                body => panic!("unexpected AddMatch arguments: {:?}", body),
+            }
+
+            let read = calls.read_message().unwrap();
+            assert_eq!(read.interface.as_deref(), Some(SETTINGS_INTERFACE));
+            assert_eq!(read.member.as_deref(), Some("Read"));
+            assert_eq!(
+                read.body,
+                [
+                    Value::Str(APPEARANCE_NAMESPACE.to_owned()),
+                    Value::Str(COLOR_SCHEME_KEY.to_owned()),
+                ]
+            );
+
+            // Another setting changes before the answer, then the color scheme changes twice.
+            let changed = |key: &str, value: u32| {
+                let mut body = Marshaller::new(true);
+                body.string(APPEARANCE_NAMESPACE);
+                body.string(key);
+                body.signature("u");
+                body.u32(value);
+                body.message(
+                    MessageType::Signal,
+                    1,
+                    None,
+                    Some((SETTINGS_INTERFACE, "SettingChanged")),
+                    "ssv",
+                )
+            };
+            server.write_all(&changed("contrast", 1)).unwrap();
+            let mut body = Marshaller::new(false);
+            body.signature("v");
+            body.signature("u");
+            body.u32(1);
+            let answer = body.message(MessageType::MethodReturn, 2, Some(read.serial), None, "v");
+            server.write_all(&answer).unwrap();
+            server.write_all(&changed(COLOR_SCHEME_KEY, 2)).unwrap();
+            server.write_all(&changed(COLOR_SCHEME_KEY, 0)).unwrap();
+        });
+
+        let mut themes = Vec::new();
+        let mut connection = Connection::from_stream(client);
+        let error = follow_color_scheme(&mut connection, |theme| themes.push(theme)).unwrap_err();
+        portal.join().unwrap();
+
+        // The portal closing the connection stops the watch.
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(themes, [Some(Theme::Dark), Some(Theme::Light), None]);
+    }
+}
@@ -0,0 +1,438 @@
+//! Keymap handling with xkbcommon, which both X11 and Wayland use for layout-dependent lookups.
+
+#[cfg(feature = "wayland")]
+use std::os::raw::c_int;
+use std::{
+    collections::HashMap,
+    ffi::CStr,
+    mem,
+    os::raw::{c_char, c_void},
+    ptr,
+    sync::{Arc, Mutex},
+};
+
+use xkbcommon_dl::{
+    xkb_context, xkb_context_flags, xkb_keymap, xkb_state, xkb_state_component, XkbCommon,
+};
+#[cfg(feature = "wayland")]
+use xkbcommon_dl::{
+    xkb_keymap_compile_flags, xkb_keymap_format, XKB_MOD_NAME_ALT, XKB_MOD_NAME_CTRL,
+    XKB_MOD_NAME_LOGO, XKB_MOD_NAME_SHIFT,
+};
+
+use super::keymap;
+#[cfg(feature = "wayland")]
+use crate::event::ModifiersState;
+use crate::keyboard::{Key, KeyCode, KeyboardLayout};
+
+/// XKB keycodes are evdev scancodes offset by 8.
+const KEYCODE_OFFSET: u32 = 8;
+
+/// The largest keycode of the keys that have a [`KeyCode`].
+const MAX_KEYCODE: u32 = 255;
+
+/// A keymap and the state of its modifiers and layout.
+pub struct XkbKeymap {
+    xkb: XkbCommon,
+    functions: KeymapFunctions,
+    context: *mut xkb_context,
+    keymap: *mut xkb_keymap,
+    state: *mut xkb_state,
+    /// The names of the layouts, by index.
+    layout_names: Vec<String>,
+    /// The index of the active layout.
+    layout: u32,
+    shared_layout: SharedLayout,
+}
+
+impl XkbKeymap {
+    /// Creates an empty keymap, which reports its layout to `shared_layout` once set.
+    ///
+    /// Returns `None` if `libxkbcommon` isn't available.
+    pub fn new(shared_layout: SharedLayout) -> Option<Self> {
+        let xkb = XkbCommon::open("libxkbcommon.so.0")
+            .or_else(|_| XkbCommon::open("libxkbcommon.so"))
+            .ok()?;
+        let functions = KeymapFunctions::open()?;
+
+        let context = unsafe { (xkb.xkb_context_new)(xkb_context_flags::XKB_CONTEXT_NO_FLAGS) };
+        if context.is_null() {
+            return None;
+        }
+
+        Some(Self {
+            xkb,
+            functions,
+            context,
+            keymap: ptr::null_mut(),
+            state: ptr::null_mut(),
+            layout_names: Vec::new(),
+            layout: 0,
+            shared_layout,
+        })
+    }
+
+    /// The context to create keymaps with.
+    pub fn context(&self) -> *mut xkb_context {
+        self.context
+    }
+
+    /// Replaces the keymap, taking ownership of `keymap` and `state`.
+    ///
+    /// A new state is created if `state` is null, with the active layout kept if the new keymap
+    /// has it. Returns whether the keymap was set.
+    ///
+    /// # Safety
+    ///
+    /// `keymap` and `state` must be null or valid, and created with [`XkbKeymap::context`].
+    pub unsafe fn set_keymap(&mut self, keymap: *mut xkb_keymap, state: *mut xkb_state) -> bool {
+        if keymap.is_null() {
+            return false;
+        }
+
+        let new_state = if state.is_null() {
+            (self.xkb.xkb_state_new)(keymap)
+        } else {
+            state
+        };
+        if new_state.is_null() {
+            (self.xkb.xkb_keymap_unref)(keymap);
+            return false;
+        }
+
+        self.unref_keymap();
+        self.keymap = keymap;
+        self.state = new_state;
+        self.layout_names = self.read_layout_names();
+        if self.layout as usize >= self.layout_names.len() {
+            self.layout = 0;
+        }
+        // Until the next state update, reloading the keymap shouldn't look like a layout change.
+        if state.is_null() {
+            (self.xkb.xkb_state_update_mask)(new_state, 0, 0, 0, 0, 0, self.layout);
+        }
+        self.update_shared_layout();
+        true
+    }
+
+    /// Replaces the keymap with the one described by `text`, in the XKB text format.
+    ///
+    /// # Safety
+    ///
+    /// `text` must point to a nul-terminated string.
+    #[cfg(feature = "wayland")]
+    pub unsafe fn set_keymap_from_string(&mut self, text: *const c_char) -> bool {
+        let keymap = (self.xkb.xkb_keymap_new_from_string)(
+            self.context,
+            text,
+            xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1,
+            xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+        );
+        self.set_keymap(keymap, ptr::null_mut())
+    }
+
+    /// Updates the state of the modifiers and of the layout.
+    ///
+    /// Returns whether the active layout changed.
+    pub fn update_mask(
+        &mut self,
+        depressed_mods: u32,
+        latched_mods: u32,
+        locked_mods: u32,
+        depressed_layout: u32,
+        latched_layout: u32,
+        locked_layout: u32,
+    ) -> bool {
+        if self.state.is_null() {
+            return false;
+        }
+
+        unsafe {
+            (self.xkb.xkb_state_update_mask)(
+                self.state,
+                depressed_mods,
+                latched_mods,
+                locked_mods,
+                depressed_layout,
+                latched_layout,
+                locked_layout,
+            );
+        }
+
+        // The layouts are signed offsets, which the keymap decides how to bring back in range.
+        let layout = unsafe {
+            (self.functions.serialize_layout)(
+                self.state,
+                xkb_state_component::XKB_STATE_LAYOUT_EFFECTIVE.bits(),
+            )
+        };
+        if layout == self.layout {
+            return false;
+        }
+
+        self.layout = layout;
+        self.update_shared_layout();
+        true
+    }
+
+    /// The index of the active layout and the names of all the layouts, which tell whether a new
+    /// keymap or state changed the layouts.
+    pub fn layouts(&self) -> (u32, Vec<String>) {
+        (self.layout, self.layout_names.clone())
+    }
+
+    /// The active layout, if a keymap is set.
+    pub fn layout(&self) -> Option<KeyboardLayout> {
+        if self.keymap.is_null() {
+            return None;
+        }
+
+        Some(KeyboardLayout {
+            index: self.layout,
+            name: self
+                .layout_names
+                .get(self.layout as usize)
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// The keysym produced by the key with the XKB `keycode`, with the current modifiers and
+    /// layout.
+    pub fn keysym(&self, keycode: u32) -> u32 {
+        if self.state.is_null() {
+            return 0;
+        }
+
+        unsafe { (self.xkb.xkb_state_key_get_one_sym)(self.state, keycode) }
+    }
+
+    /// The text produced by the key with the XKB `keycode`, with the current modifiers and
+    /// layout.
+    pub fn text(&self, keycode: u32) -> Option<String> {
+        if self.state.is_null() {
+            return None;
+        }
+
+        unsafe { self.state_text(self.state, keycode) }
+    }
+
+    /// Whether the key with the XKB `keycode` repeats when held down.
+    #[cfg(feature = "wayland")]
+    pub fn key_repeats(&self, keycode: u32) -> bool {
+        if self.keymap.is_null() {
+            return false;
+        }
+
+        unsafe { (self.functions.key_repeats)(self.keymap, keycode) == 1 }
+    }
+
+    /// The active modifiers.
+    #[cfg(feature = "wayland")]
+    pub fn modifiers(&self) -> ModifiersState {
+        let mut modifiers = ModifiersState::empty();
+        if self.state.is_null() {
+            return modifiers;
+        }
+
+        let is_active = |name: &[u8]| unsafe {
+            (self.xkb.xkb_state_mod_name_is_active)(
+                self.state,
+                name.as_ptr() as *const c_char,
+                xkb_state_component::XKB_STATE_MODS_EFFECTIVE,
+            ) > 0
+        };
+        modifiers.set(ModifiersState::SHIFT, is_active(XKB_MOD_NAME_SHIFT));
+        modifiers.set(ModifiersState::CTRL, is_active(XKB_MOD_NAME_CTRL));
+        modifiers.set(ModifiersState::ALT, is_active(XKB_MOD_NAME_ALT));
+        modifiers.set(ModifiersState::LOGO, is_active(XKB_MOD_NAME_LOGO));
+        modifiers
+    }
+
+    unsafe fn state_text(&self, state: *mut xkb_state, keycode: u32) -> Option<String> {
+        let size = (self.xkb.xkb_state_key_get_utf8)(state, keycode, ptr::null_mut(), 0);
+        if size <= 0 {
+            return None;
+        }
+
+        let mut buffer = vec![0u8; size as usize + 1];
+        (self.xkb.xkb_state_key_get_utf8)(
+            state,
+            keycode,
+            buffer.as_mut_ptr() as *mut c_char,
+            buffer.len(),
+        );
+        buffer.truncate(size as usize);
+        String::from_utf8(buffer).ok()
+    }
+
+    /// Reads the names of the layouts, by index.
+    fn read_layout_names(&self) -> Vec<String> {
+        unsafe {
+            let count = (self.functions.num_layouts)(self.keymap);
+            (0..count)
+                .map(|index| {
+                    let name = (self.functions.layout_get_name)(self.keymap, index);
+                    if name.is_null() {
+                        String::new()
+                    } else {
+                        CStr::from_ptr(name).to_string_lossy().into_owned()
+                    }
+                })
+                .collect()
+        }
+    }
+
+    /// Publishes the active layout and the labels of the keys in it.
+    fn update_shared_layout(&self) {
+        let mut labels = HashMap::new();
+        unsafe {
+            // The labels are the keys without modifiers, so they're looked up in a separate
+            // state that only has the layout set.
+            let state = (self.xkb.xkb_state_new)(self.keymap);
+            if !state.is_null() {
+                (self.xkb.xkb_state_update_mask)(state, 0, 0, 0, 0, 0, self.layout);
+                for keycode in KEYCODE_OFFSET..=MAX_KEYCODE {
+                    let keysym = (self.xkb.xkb_state_key_get_one_sym)(state, keycode);
+                    if keysym == 0 {
+                        continue;
+                    }
+
+                    let text = self.state_text(state, keycode);
+                    let key = keymap::keysym_to_key(keysym, text.as_deref());
+                    if key != Key::Unidentified {
+                        let physical_key = keymap::scancode_to_keycode(keycode - KEYCODE_OFFSET);
+                        labels.insert(physical_key, key);
+                    }
+                }
+                (self.xkb.xkb_state_unref)(state);
+            }
+        }
+
+        let mut shared = self.shared_layout.inner.lock().unwrap();
+        shared.layout = self.layout();
+        shared.labels = labels;
+    }
+
+    fn unref_keymap(&mut self) {
+        unsafe {
+            if !self.state.is_null() {
+                (self.xkb.xkb_state_unref)(self.state);
+            }
+            if !self.keymap.is_null() {
+                (self.xkb.xkb_keymap_unref)(self.keymap);
+            }
+        }
+    }
+}
+
+impl Drop for XkbKeymap {
+    fn drop(&mut self) {
+        self.unref_keymap();
+        unsafe { (self.xkb.xkb_context_unref)(self.context) };
+    }
+}
+
+/// Functions of `libxkbcommon` which `xkbcommon-dl` doesn't load.
+struct KeymapFunctions {
+    library: *mut c_void,
+    num_layouts: NumLayouts,
+    layout_get_name: LayoutGetName,
+    #[cfg(feature = "wayland")]
+    key_repeats: KeyRepeats,
+    serialize_layout: SerializeLayout,
+}
+
+type NumLayouts = unsafe extern "C" fn(*mut xkb_keymap) -> u32;
+type LayoutGetName = unsafe extern "C" fn(*mut xkb_keymap, u32) -> *const c_char;
+#[cfg(feature = "wayland")]
+type KeyRepeats = unsafe extern "C" fn(*mut xkb_keymap, u32) -> c_int;
+/// Takes the bits of an `xkb_state_component`, whose bitflags type isn't FFI-safe.
+type SerializeLayout = unsafe extern "C" fn(*mut xkb_state, u32) -> u32;
+
+impl KeymapFunctions {
+    fn open() -> Option<Self> {
+        unsafe {
+            let mut library = libc::dlopen(
+                b"libxkbcommon.so.0\0".as_ptr() as *const c_char,
+                libc::RTLD_LAZY,
+            );
+            if library.is_null() {
+                library = libc::dlopen(
+                    b"libxkbcommon.so\0".as_ptr() as *const c_char,
+                    libc::RTLD_LAZY,
+                );
+            }
+            if library.is_null() {
+                return None;
+            }
+
+            let functions = Self::load(library);
+            if functions.is_none() {
+                libc::dlclose(library);
+            }
+            functions
+        }
+    }
+
+    unsafe fn load(library: *mut c_void) -> Option<Self> {
+        let symbol = |name: &[u8]| {
+            let symbol = libc::dlsym(library, name.as_ptr() as *const c_char);
+            if symbol.is_null() {
+                None
+            } else {
+                Some(symbol)
+            }
+        };
+        Some(Self {
+            library,
+            num_layouts: mem::transmute::<*mut c_void, NumLayouts>(symbol(
+                b"xkb_keymap_num_layouts\0",
+            )?),
+            layout_get_name: mem::transmute::<*mut c_void, LayoutGetName>(symbol(
+                b"xkb_keymap_layout_get_name\0",
+            )?),
+            #[cfg(feature = "wayland")]
+            key_repeats: mem::transmute::<*mut c_void, KeyRepeats>(symbol(
+                b"xkb_keymap_key_repeats\0",
+            )?),
+            serialize_layout: mem::transmute::<*mut c_void, SerializeLayout>(symbol(
+                b"xkb_state_serialize_layout\0",
+            )?),
+        })
+    }
+}
+
+impl Drop for KeymapFunctions {
+    fn drop(&mut self) {
+        unsafe { libc::dlclose(self.library) };
+    }
+}
+
+/// The active keyboard layout and the labels of the keys in it, which the event loop updates
+/// and the windows read.
+#[derive(Clone, Default)]
+pub struct SharedLayout {
+    inner: Arc<Mutex<LayoutState>>,
+}
+
+#[derive(Default)]
+struct LayoutState {
+    layout: Option<KeyboardLayout>,
+    labels: HashMap<KeyCode, Key>,
+}
+
+impl SharedLayout {
+    pub fn layout(&self) -> Option<KeyboardLayout> {
+        self.inner.lock().unwrap().layout.clone()
+    }
+
+    pub fn key_label(&self, physical_key: KeyCode) -> Option<Key> {
+        self.inner
+            .lock()
+            .unwrap()
+            .labels
+            .get(&physical_key)
+            .cloned()
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    mem,
+    sync::{
+        mpsc::{self, Receiver, SendError, Sender},
+        Arc,
+    },
+};
+
+use raw_window_handle::{DrmDisplayHandle, RawDisplayHandle};
+
+use crate::{
+    event::{Event, StartCause, WindowEvent},
+    event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
+    monitor::MonitorHandle as RootMonitorHandle,
+    window::WindowId as RootWindowId,
+};
+
+use super::{
+    super::{sticky_exit_callback, DeviceId as PlatformDeviceId, WindowId},
+    HeadlessInjector, MonitorHandle, PendingEvent, PlatformMonitorHandle, Shared,
+};
+
+pub struct EventLoopWindowTarget<T> {
+    pub(crate) shared: Arc<Shared>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> EventLoopWindowTarget<T> {
+    #[inline]
+    pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
+        self.shared.lock().monitors.iter().cloned().collect()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<RootMonitorHandle> {
+        let monitor = self.shared.lock().primary_monitor()?.clone();
+        Some(RootMonitorHandle {
+            inner: PlatformMonitorHandle::Headless(monitor),
+        })
+    }
+
+    #[inline]
+    pub fn injector(&self) -> HeadlessInjector {
+        HeadlessInjector {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// There's no display to render to, so the handle is empty.
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Drm(DrmDisplayHandle::empty())
+    }
+}
+
+pub struct EventLoopProxy<T: 'static> {
+    user_sender: Sender<T>,
+    shared: Arc<Shared>,
+}
+
+impl<T: 'static> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        EventLoopProxy {
+            user_sender: self.user_sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T: 'static> EventLoopProxy<T> {
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.user_sender
+            .send(event)
+            .map_err(|SendError(event)| EventLoopClosed(event))?;
+
+        let mut state = self.shared.lock();
+        self.shared.wake(&mut state);
+        Ok(())
+    }
+}
+
+pub struct EventLoop<T: 'static> {
+    shared: Arc<Shared>,
+    user_sender: Sender<T>,
+    user_receiver: Receiver<T>,
+    window_target: RootELW<T>,
+}
+
+impl<T: 'static> EventLoop<T> {
+    pub fn new() -> EventLoop<T> {
+        let shared = Arc::new(Shared::new());
+        let (user_sender, user_receiver) = mpsc::channel();
+
+        let window_target = RootELW {
+            p: super::super::EventLoopWindowTarget::Headless(EventLoopWindowTarget {
+                shared: shared.clone(),
+                _marker: PhantomData,
+            }),
+            _marker: PhantomData,
+        };
+
+        EventLoop {
+            shared,
+            user_sender,
+            user_receiver,
+            window_target,
+        }
+    }
+
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            user_sender: self.user_sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+
+    pub fn window_target(&self) -> &RootELW<T> {
+        &self.window_target
+    }
+
+    pub fn run<F>(mut self, callback: F) -> !
+    where
+        F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let exit_code = self.run_return(callback);
+        ::std::process::exit(exit_code);
+    }
+
+    pub fn run_return<F>(&mut self, mut callback: F) -> i32
+    where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let mut control_flow = ControlFlow::default();
+        let mut cause = StartCause::Init;
+
+        let exit_code = loop {
+            sticky_exit_callback(
+                Event::NewEvents(cause),
+                &self.window_target,
+                &mut control_flow,
+                &mut callback,
+            );
+
+            // NB: For consistency all platforms must emit a 'resumed' event even though headless
+            // applications don't have a suspend/resume lifecycle.
+            if cause == StartCause::Init {
+                sticky_exit_callback(
+                    Event::Resumed,
+                    &self.window_target,
+                    &mut control_flow,
+                    &mut callback,
+                );
+            }
+
+            // Only deliver the events pending at this point, the ones queued while they're
+            // delivered are for the next iteration.
+            let events = {
+                let mut state = self.shared.lock();
+                state.woken = false;
+                mem::take(&mut state.events)
+            };
+            for event in events {
+                self.dispatch_event(event, &mut control_flow, &mut callback);
+            }
+
+            while let Ok(event) = self.user_receiver.try_recv() {
+                sticky_exit_callback(
+                    Event::UserEvent(event),
+                    &self.window_target,
+                    &mut control_flow,
+                    &mut callback,
+                );
+            }
+
+            sticky_exit_callback(
+                Event::MainEventsCleared,
+                &self.window_target,
+                &mut control_flow,
+                &mut callback,
+            );
+
+            let redraws = mem::take(&mut self.shared.lock().redraws);
+            for window_id in redraws {
+                sticky_exit_callback(
+                    Event::RedrawRequested(RootWindowId(window_id)),
+                    &self.window_target,
+                    &mut control_flow,
+                    &mut callback,
+                );
+            }
+
+            sticky_exit_callback(
+                Event::RedrawEventsCleared,
+                &self.window_target,
+                &mut control_flow,
+                &mut callback,
+            );
+
+            let start = self.shared.lock().now;
+            cause = match control_flow {
+                ControlFlow::ExitWithCode(code) => break code,
+                ControlFlow::Poll => StartCause::Poll,
+                ControlFlow::Wait => {
+                    self.shared.wait();
+                    StartCause::WaitCancelled {
+                        start,
+                        requested_resume: None,
+                    }
+                }
+                ControlFlow::WaitUntil(deadline) => {
+                    let mut state = self.shared.lock();
+                    if state.now < deadline && state.has_pending() {
+                        StartCause::WaitCancelled {
+                            start,
+                            requested_resume: Some(deadline),
+                        }
+                    } else {
+                        // Nothing else can happen before the deadline, so skip to it.
+                        state.now = state.now.max(deadline);
+                        StartCause::ResumeTimeReached {
+                            start,
+                            requested_resume: deadline,
+                        }
+                    }
+                }
+            };
+        };
+
+        callback(Event::LoopDestroyed, &self.window_target, &mut control_flow);
+        exit_code
+    }
+
+    fn dispatch_event<F>(
+        &self,
+        event: PendingEvent,
+        control_flow: &mut ControlFlow,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let event = match event {
+            PendingEvent::Window(window_id, event) => Event::WindowEvent {
+                window_id: RootWindowId(window_id),
+                event,
+            },
+            PendingEvent::Device(event) => Event::DeviceEvent {
+                device_id: crate::event::DeviceId(PlatformDeviceId::Headless(super::DeviceId)),
+                event,
+            },
+            PendingEvent::ScaleFactorChanged(window_id, scale_factor) => {
+                self.scale_factor_changed(window_id, scale_factor, control_flow, callback);
+                return;
+            }
+        };
+
+        sticky_exit_callback(event, &self.window_target, control_flow, callback);
+    }
+
+    fn scale_factor_changed<F>(
+        &self,
+        window_id: WindowId,
+        scale_factor: f64,
+        control_flow: &mut ControlFlow,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
+    {
+        let (old_size, mut new_inner_size) = {
+            let mut state = self.shared.lock();
+            let window = match state.windows.get_mut(&window_id) {
+                Some(window) => window,
+                None => return,
+            };
+            let old_size = window.size;
+            let new_inner_size = old_size
+                .to_logical::<f64>(window.scale_factor)
+                .to_physical(scale_factor);
+            window.scale_factor = scale_factor;
+            (old_size, new_inner_size)
+        };
+
+        sticky_exit_callback(
+            Event::WindowEvent {
+                window_id: RootWindowId(window_id),
+                event: WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    new_inner_size: &mut new_inner_size,
+                },
+            },
+            &self.window_target,
+            control_flow,
+            callback,
+        );
+
+        if let Some(window) = self.shared.lock().windows.get_mut(&window_id) {
+            window.size = new_inner_size;
+        }
+
+        if new_inner_size != old_size {
+            sticky_exit_callback(
+                Event::WindowEvent {
+                    window_id: RootWindowId(window_id),
+                    event: WindowEvent::Resized(new_inner_size),
+                },
+                &self.window_target,
+                control_flow,
+                callback,
+            );
+        }
+    }
+}
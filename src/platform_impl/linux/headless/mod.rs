@@ -0,0 +1,295 @@
+//! A backend without any display server, for testing applications.
+//!
+//! Windows and monitors only exist in memory. Input is simulated with a [`HeadlessInjector`],
+//! which also drives the virtual clock used for [`ControlFlow::WaitUntil`].
+//!
+//! [`ControlFlow::WaitUntil`]: crate::event_loop::ControlFlow::WaitUntil
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dpi::{PhysicalPosition, PhysicalSize, Size},
+    event::{DeviceEvent, WindowEvent},
+    monitor::MonitorHandle as RootMonitorHandle,
+    window::{Fullscreen, WindowId as RootWindowId},
+};
+
+pub use self::event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
+pub use self::monitor::{MonitorHandle, VideoMode};
+pub use self::window::Window;
+
+use super::{MonitorHandle as PlatformMonitorHandle, WindowId};
+
+mod event_loop;
+mod monitor;
+mod window;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeviceId;
+
+impl DeviceId {
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        0
+    }
+}
+
+/// Handle used to drive a headless event loop, obtained with
+/// [`EventLoopWindowTargetExtUnix::headless_injector`].
+///
+/// Injected events are delivered by the event loop in the order they were injected. The
+/// injector can be cloned and used from any thread, and the event loop wakes up when something
+/// is injected.
+///
+/// [`EventLoopWindowTargetExtUnix::headless_injector`]: crate::platform::unix::EventLoopWindowTargetExtUnix::headless_injector
+#[derive(Clone)]
+pub struct HeadlessInjector {
+    shared: Arc<Shared>,
+}
+
+impl HeadlessInjector {
+    /// The current time of the virtual clock.
+    ///
+    /// The clock only moves when advanced with [`HeadlessInjector::advance_time`], or when the
+    /// event loop has nothing to do until a [`ControlFlow::WaitUntil`] deadline, in which case
+    /// it jumps to the deadline instead of sleeping.
+    ///
+    /// [`ControlFlow::WaitUntil`]: crate::event_loop::ControlFlow::WaitUntil
+    pub fn now(&self) -> Instant {
+        self.shared.lock().now
+    }
+
+    /// Moves the virtual clock forward.
+    pub fn advance_time(&self, duration: Duration) {
+        let mut state = self.shared.lock();
+        state.now += duration;
+        self.shared.wake(&mut state);
+    }
+
+    /// The id of the simulated input device, for the events that need one.
+    pub fn device_id(&self) -> crate::event::DeviceId {
+        crate::event::DeviceId(super::DeviceId::Headless(DeviceId))
+    }
+
+    /// Delivers `event` to the window.
+    ///
+    /// `Resized` and `Moved` events also update the size and position reported by the window,
+    /// like a window manager would. Use [`HeadlessInjector::set_scale_factor`] to simulate
+    /// scale factor changes.
+    pub fn inject_window_event(&self, window_id: RootWindowId, event: WindowEvent<'static>) {
+        let mut state = self.shared.lock();
+        if let Some(window) = state.windows.get_mut(&window_id.0) {
+            match event {
+                WindowEvent::Resized(size) => window.size = size,
+                WindowEvent::Moved(position) => window.position = position,
+                _ => (),
+            }
+        }
+        state
+            .events
+            .push_back(PendingEvent::Window(window_id.0, event));
+        self.shared.wake(&mut state);
+    }
+
+    /// Delivers a device event from the simulated input device.
+    pub fn inject_device_event(&self, event: DeviceEvent) {
+        let mut state = self.shared.lock();
+        state.events.push_back(PendingEvent::Device(event));
+        self.shared.wake(&mut state);
+    }
+
+    /// Connects a new monitor.
+    ///
+    /// The event loop starts with a single 1920x1080 monitor at a scale factor of 1, which is
+    /// the primary monitor.
+    pub fn add_monitor(
+        &self,
+        name: impl Into<String>,
+        size: PhysicalSize<u32>,
+        position: PhysicalPosition<i32>,
+        scale_factor: f64,
+    ) -> RootMonitorHandle {
+        let mut state = self.shared.lock();
+        let monitor = state.add_monitor(name.into(), size, position, scale_factor);
+        RootMonitorHandle {
+            inner: PlatformMonitorHandle::Headless(monitor),
+        }
+    }
+
+    /// Changes the scale factor of a monitor.
+    ///
+    /// Windows on the monitor receive a [`WindowEvent::ScaleFactorChanged`], followed by a
+    /// [`WindowEvent::Resized`] if their size changed. Monitor handles obtained before the change
+    /// keep reporting the old scale factor.
+    pub fn set_scale_factor(&self, monitor: &RootMonitorHandle, scale_factor: f64) {
+        let monitor_id = match monitor.inner {
+            PlatformMonitorHandle::Headless(ref monitor) => monitor.id,
+            _ => return,
+        };
+
+        let mut state = self.shared.lock();
+        let state = &mut *state;
+        for monitor in state.monitors.iter_mut().filter(|m| m.id == monitor_id) {
+            monitor.scale_factor = scale_factor;
+        }
+        for (window_id, window) in state.windows.iter() {
+            if window.monitor == monitor_id && window.scale_factor != scale_factor {
+                state
+                    .events
+                    .push_back(PendingEvent::ScaleFactorChanged(*window_id, scale_factor));
+            }
+        }
+        self.shared.wake(state);
+    }
+}
+
+/// State shared by the event loop, its windows and injectors.
+pub(crate) struct Shared {
+    state: Mutex<State>,
+    wakeup: Condvar,
+}
+
+impl Shared {
+    fn new() -> Self {
+        let mut state = State {
+            now: Instant::now(),
+            events: VecDeque::new(),
+            redraws: Vec::new(),
+            woken: false,
+            monitors: Vec::new(),
+            next_monitor_id: 0,
+            windows: HashMap::new(),
+            next_window_id: 1,
+            clipboards: HashMap::new(),
+        };
+        state.add_monitor(
+            "HEADLESS-1".to_owned(),
+            PhysicalSize::new(1920, 1080),
+            PhysicalPosition::new(0, 0),
+            1.0,
+        );
+
+        Self {
+            state: Mutex::new(state),
+            wakeup: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    /// Wakes up the event loop, which must be done while holding the lock.
+    fn wake(&self, state: &mut State) {
+        state.woken = true;
+        self.wakeup.notify_all();
+    }
+
+    /// Blocks until the event loop has something to do.
+    fn wait(&self) {
+        let mut state = self.lock();
+        while !state.has_pending() {
+            state = self.wakeup.wait(state).unwrap();
+        }
+    }
+}
+
+pub(crate) struct State {
+    /// The virtual clock.
+    now: Instant,
+    events: VecDeque<PendingEvent>,
+    redraws: Vec<WindowId>,
+    /// Whether something happened that the event loop should wake up for.
+    woken: bool,
+    monitors: Vec<MonitorHandle>,
+    next_monitor_id: u32,
+    windows: HashMap<WindowId, WindowState>,
+    next_window_id: u64,
+    clipboards: HashMap<ClipboardKind, ClipboardContents>,
+}
+
+impl State {
+    fn has_pending(&self) -> bool {
+        self.woken || !self.events.is_empty() || !self.redraws.is_empty()
+    }
+
+    fn add_monitor(
+        &mut self,
+        name: String,
+        size: PhysicalSize<u32>,
+        position: PhysicalPosition<i32>,
+        scale_factor: f64,
+    ) -> MonitorHandle {
+        let monitor = MonitorHandle {
+            id: self.next_monitor_id,
+            name,
+            size,
+            position,
+            scale_factor,
+        };
+        self.next_monitor_id += 1;
+        self.monitors.push(monitor.clone());
+        monitor
+    }
+
+    fn monitor(&self, id: u32) -> Option<&MonitorHandle> {
+        self.monitors.iter().find(|monitor| monitor.id == id)
+    }
+
+    fn primary_monitor(&self) -> Option<&MonitorHandle> {
+        self.monitors.first()
+    }
+
+    fn request_redraw(&mut self, window_id: WindowId) {
+        if !self.redraws.contains(&window_id) {
+            self.redraws.push(window_id);
+        }
+    }
+}
+
+/// An event waiting to be delivered by the event loop.
+enum PendingEvent {
+    Window(WindowId, WindowEvent<'static>),
+    Device(DeviceEvent),
+    ScaleFactorChanged(WindowId, f64),
+}
+
+/// What a window manager would know about a window.
+struct WindowState {
+    visible: bool,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    min_size: Option<Size>,
+    max_size: Option<Size>,
+    resizable: bool,
+    maximized: bool,
+    decorated: bool,
+    fullscreen: Option<Fullscreen>,
+    /// The geometry to restore when leaving fullscreen.
+    windowed: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    /// The monitor the window is on.
+    monitor: u32,
+    scale_factor: f64,
+}
+
+impl WindowState {
+    fn clamp_size(&self, size: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let mut size = size;
+        if let Some(min_size) = self.min_size {
+            let min_size: PhysicalSize<u32> = min_size.to_physical(self.scale_factor);
+            size.width = size.width.max(min_size.width);
+            size.height = size.height.max(min_size.height);
+        }
+        if let Some(max_size) = self.max_size {
+            let max_size: PhysicalSize<u32> = max_size.to_physical(self.scale_factor);
+            size.width = size.width.min(max_size.width);
+            size.height = size.height.min(max_size.height);
+        }
+        size
+    }
+}
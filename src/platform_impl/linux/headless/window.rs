@@ -0,0 +1,515 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use raw_window_handle::{DrmDisplayHandle, DrmWindowHandle, RawDisplayHandle, RawWindowHandle};
+
+use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dnd::{DndAction, DragData},
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
+    error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    event::WindowEvent,
+    keyboard::{Key, KeyCode, KeyboardLayout},
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, Icon, ImeHints, ImePurpose, Rect,
+        Region, Theme, UserAttentionType, WindowAttributes,
+    },
+};
+
+use super::{
+    super::{PlatformSpecificWindowBuilderAttributes, VideoMode as PlatformVideoMode, WindowId},
+    EventLoopWindowTarget, MonitorHandle, PendingEvent, PlatformMonitorHandle, Shared, State,
+    WindowState,
+};
+
+pub struct Window {
+    window_id: WindowId,
+    shared: Arc<Shared>,
+}
+
+impl Window {
+    pub(crate) fn new<T>(
+        event_loop_window_target: &EventLoopWindowTarget<T>,
+        attributes: WindowAttributes,
+        _pl_attribs: PlatformSpecificWindowBuilderAttributes,
+    ) -> Result<Self, RootOsError> {
+        let shared = event_loop_window_target.shared.clone();
+        let mut state = shared.lock();
+
+        let window_id = WindowId(state.next_window_id);
+        state.next_window_id += 1;
+
+        // Unwrap is safe, since monitors can't be removed.
+        let monitor = state.primary_monitor().unwrap().clone();
+        let scale_factor = monitor.scale_factor;
+        let size = attributes
+            .inner_size
+            .unwrap_or_else(|| LogicalSize::new(800., 600.).into())
+            .to_physical(scale_factor);
+        let position = attributes
+            .position
+            .map(|position| position.to_physical(scale_factor))
+            .unwrap_or(monitor.position);
+
+        let mut window = WindowState {
+            visible: attributes.visible,
+            position,
+            size,
+            min_size: attributes.min_inner_size,
+            max_size: attributes.max_inner_size,
+            resizable: attributes.resizable,
+            maximized: attributes.maximized,
+            decorated: attributes.decorations,
+            fullscreen: None,
+            windowed: None,
+            monitor: monitor.id,
+            scale_factor,
+        };
+        window.size = window.clamp_size(window.size);
+        state.windows.insert(window_id, window);
+
+        if let Some(fullscreen) = attributes.fullscreen {
+            set_fullscreen(&mut state, window_id, Some(fullscreen), false);
+        }
+
+        // Like a window manager asking for the window contents when it's mapped.
+        if attributes.visible {
+            state.request_redraw(window_id);
+            shared.wake(&mut state);
+        }
+
+        drop(state);
+
+        Ok(Window { window_id, shared })
+    }
+
+    /// Runs `f` on the state of the window.
+    fn with_state<U>(&self, f: impl FnOnce(&mut WindowState) -> U) -> U {
+        // Unwrap is safe, since the state is only removed when the window is dropped.
+        f(self.shared.lock().windows.get_mut(&self.window_id).unwrap())
+    }
+
+    #[inline]
+    pub fn id(&self) -> WindowId {
+        self.window_id
+    }
+
+    #[inline]
+    pub fn set_title(&self, _title: &str) {}
+
+    #[inline]
+    pub fn set_visible(&self, visible: bool) {
+        let mut state = self.shared.lock();
+        let window = state.windows.get_mut(&self.window_id).unwrap();
+        let was_visible = window.visible;
+        window.visible = visible;
+        if visible && !was_visible {
+            state.request_redraw(self.window_id);
+            self.shared.wake(&mut state);
+        }
+    }
+
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        Some(self.with_state(|window| window.visible))
+    }
+
+    #[inline]
+    pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.with_state(|window| window.position))
+    }
+
+    #[inline]
+    pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        self.outer_position()
+    }
+
+    #[inline]
+    pub fn set_outer_position(&self, position: Position) {
+        let mut state = self.shared.lock();
+        let window = state.windows.get_mut(&self.window_id).unwrap();
+        let position = position.to_physical(window.scale_factor);
+        move_window(&mut state, self.window_id, position);
+        self.shared.wake(&mut state);
+    }
+
+    #[inline]
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.with_state(|window| window.size)
+    }
+
+    #[inline]
+    pub fn outer_size(&self) -> PhysicalSize<u32> {
+        self.inner_size()
+    }
+
+    #[inline]
+    pub fn set_inner_size(&self, size: Size) {
+        let mut state = self.shared.lock();
+        let window = state.windows.get_mut(&self.window_id).unwrap();
+        let size = window.clamp_size(size.to_physical(window.scale_factor));
+        resize_window(&mut state, self.window_id, size);
+        self.shared.wake(&mut state);
+    }
+
+    #[inline]
+    pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
+        let mut state = self.shared.lock();
+        let window = state.windows.get_mut(&self.window_id).unwrap();
+        window.min_size = dimensions;
+        let size = window.clamp_size(window.size);
+        resize_window(&mut state, self.window_id, size);
+        self.shared.wake(&mut state);
+    }
+
+    #[inline]
+    pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
+        let mut state = self.shared.lock();
+        let window = state.windows.get_mut(&self.window_id).unwrap();
+        window.max_size = dimensions;
+        let size = window.clamp_size(window.size);
+        resize_window(&mut state, self.window_id, size);
+        self.shared.wake(&mut state);
+    }
+
+    #[inline]
+    pub fn set_resizable(&self, resizable: bool) {
+        self.with_state(|window| window.resizable = resizable)
+    }
+
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.with_state(|window| window.resizable)
+    }
+
+    #[inline]
+    pub fn set_cursor_icon(&self, _cursor: CursorIcon) {}
+
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) {}
+
+    #[inline]
+    pub fn set_cursor_grab(&self, _mode: CursorGrabMode) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_visible(&self, _visible: bool) {}
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, _hittest: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_input_region(&self, _region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_shape(&self, _shape: Option<Region>) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        self.shared.lock().clipboards.insert(kind, contents);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn clipboard(&self, kind: ClipboardKind, mime_type: &str) -> Option<Vec<u8>> {
+        let state = self.shared.lock();
+        state
+            .clipboards
+            .get(&kind)
+            .and_then(|contents| contents.get(mime_type))
+            .map(<[u8]>::to_vec)
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Vec<String> {
+        let state = self.shared.lock();
+        state
+            .clipboards
+            .get(&kind)
+            .map(|contents| contents.mime_types().map(str::to_owned).collect())
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        None
+    }
+
+    #[inline]
+    pub fn key_label(&self, _physical_key: KeyCode) -> Option<Key> {
+        None
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _data: DragData) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn accept_drag(&self, _mime_type: &str, _action: DndAction) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.with_state(|window| window.scale_factor)
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        self.with_state(|window| window.maximized = maximized)
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.with_state(|window| window.maximized)
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, _minimized: bool) {}
+
+    #[inline]
+    pub fn fullscreen(&self) -> Option<Fullscreen> {
+        self.with_state(|window| window.fullscreen.clone())
+    }
+
+    #[inline]
+    pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        let mut state = self.shared.lock();
+        set_fullscreen(&mut state, self.window_id, fullscreen, true);
+        self.shared.wake(&mut state);
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorate: bool) {
+        self.with_state(|window| window.decorated = decorate)
+    }
+
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.with_state(|window| window.decorated)
+    }
+
+    #[inline]
+    pub fn set_always_on_top(&self, _always_on_top: bool) {}
+
+    #[inline]
+    pub fn set_window_icon(&self, _window_icon: Option<Icon>) {}
+
+    #[inline]
+    pub fn set_ime_position(&self, _position: Position) {}
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+    #[inline]
+    pub fn set_ime_surrounding_text(
+        &self,
+        _text: &str,
+        _cursor: usize,
+        _anchor: usize,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(
+        &self,
+        _purpose: ImePurpose,
+        _hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_ime_allowed(&self, _allowed: bool) {}
+
+    #[inline]
+    pub fn focus_window(&self) {}
+
+    #[inline]
+    pub fn request_user_attention(&self, _request_type: Option<UserAttentionType>) {}
+
+    #[inline]
+    pub fn set_idle_inhibit(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_shortcuts_inhibited(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        None
+    }
+
+    #[inline]
+    pub fn request_redraw(&self) {
+        let mut state = self.shared.lock();
+        state.request_redraw(self.window_id);
+        self.shared.wake(&mut state);
+    }
+
+    #[inline]
+    pub fn current_monitor(&self) -> Option<MonitorHandle> {
+        let state = self.shared.lock();
+        let monitor = state.windows.get(&self.window_id)?.monitor;
+        state.monitor(monitor).cloned()
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
+        self.shared.lock().monitors.iter().cloned().collect()
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        self.shared.lock().primary_monitor().cloned()
+    }
+
+    /// There's no surface to render to, so the handle is empty.
+    #[inline]
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Drm(DrmWindowHandle::empty())
+    }
+
+    #[inline]
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Drm(DrmDisplayHandle::empty())
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.windows.remove(&self.window_id);
+        state
+            .redraws
+            .retain(|window_id| *window_id != self.window_id);
+        state
+            .events
+            .push_back(PendingEvent::Window(self.window_id, WindowEvent::Destroyed));
+        self.shared.wake(&mut state);
+    }
+}
+
+/// Moves the window, queuing a `Moved` event if its position changed.
+fn move_window(state: &mut State, window_id: WindowId, position: PhysicalPosition<i32>) {
+    let window = state.windows.get_mut(&window_id).unwrap();
+    if window.position != position {
+        window.position = position;
+        state.events.push_back(PendingEvent::Window(
+            window_id,
+            WindowEvent::Moved(position),
+        ));
+    }
+}
+
+/// Resizes the window, queuing a `Resized` event if its size changed.
+fn resize_window(state: &mut State, window_id: WindowId, size: PhysicalSize<u32>) {
+    let window = state.windows.get_mut(&window_id).unwrap();
+    if window.size != size {
+        window.size = size;
+        state
+            .events
+            .push_back(PendingEvent::Window(window_id, WindowEvent::Resized(size)));
+    }
+}
+
+/// Covers the monitor with the window, or restores its previous geometry.
+///
+/// Events are only queued when `notify` is set, a new window is created with the final
+/// geometry instead.
+fn set_fullscreen(
+    state: &mut State,
+    window_id: WindowId,
+    fullscreen: Option<Fullscreen>,
+    notify: bool,
+) {
+    let window = state.windows.get(&window_id).unwrap();
+    let monitor_id = match fullscreen {
+        Some(Fullscreen::Exclusive(ref video_mode)) => match video_mode.video_mode {
+            PlatformVideoMode::Headless(ref video_mode) => video_mode.monitor.id,
+            _ => return,
+        },
+        Some(Fullscreen::Borderless(Some(ref monitor))) => match monitor.inner {
+            PlatformMonitorHandle::Headless(ref monitor) => monitor.id,
+            _ => return,
+        },
+        Some(Fullscreen::Borderless(None)) | None => window.monitor,
+    };
+    let monitor = match state.monitor(monitor_id) {
+        Some(monitor) => monitor,
+        None => return,
+    };
+
+    let (position, size) = match fullscreen {
+        Some(Fullscreen::Exclusive(ref video_mode)) => (monitor.position, video_mode.size()),
+        Some(Fullscreen::Borderless(_)) => (monitor.position, monitor.size),
+        None => match window.windowed {
+            Some(geometry) => geometry,
+            None => return,
+        },
+    };
+    let scale_factor = monitor.scale_factor;
+
+    let window = state.windows.get_mut(&window_id).unwrap();
+    window.windowed = match fullscreen {
+        Some(_) => window.windowed.or(Some((window.position, window.size))),
+        None => None,
+    };
+    window.fullscreen = fullscreen;
+    window.monitor = monitor_id;
+
+    if !notify {
+        window.position = position;
+        window.size = size;
+        window.scale_factor = scale_factor;
+        return;
+    }
+
+    if window.scale_factor != scale_factor {
+        state
+            .events
+            .push_back(PendingEvent::ScaleFactorChanged(window_id, scale_factor));
+    }
+    move_window(state, window_id, position);
+    resize_window(state, window_id, size);
+}
@@ -12,7 +12,11 @@ compile_error!("Please select a feature to build for unix: `x11`, `wayland`");
 #[cfg(feature = "wayland")]
 use std::error::Error;
 
-use std::{collections::VecDeque, env, fmt};
+use std::{
+    collections::VecDeque,
+    env, fmt,
+    time::{Duration, Instant},
+};
 #[cfg(feature = "x11")]
 use std::{ffi::CStr, mem::MaybeUninit, os::raw::*, sync::Arc};
 
@@ -26,11 +30,16 @@ use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 pub use self::x11::XNotSupported;
 #[cfg(feature = "x11")]
 use self::x11::{ffi::XVisualInfo, util::WindowType as XWindowType, XConnection, XError};
+#[cfg(feature = "wayland")]
+use crate::platform::unix::LayerShell;
+use crate::platform::unix::Popup;
 #[cfg(feature = "x11")]
 use crate::platform::unix::XlibErrorHook;
 #[cfg(feature = "wayland")]
-use crate::window::Theme;
+use crate::window::DecorationMode;
 use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dnd::{DndAction, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     event::Event,
@@ -38,12 +47,19 @@ use crate::{
         ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW,
     },
     icon::Icon,
+    keyboard::{Key, KeyCode, KeyboardLayout},
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
-    window::{CursorGrabMode, CursorIcon, Fullscreen, UserAttentionType, WindowAttributes},
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ImeHints, ImePurpose, Rect, Region,
+        Theme, UserAttentionType, WindowAttributes,
+    },
 };
 
 pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
 
+#[cfg(any(feature = "x11", feature = "wayland"))]
+mod common;
+pub mod headless;
 #[cfg(feature = "wayland")]
 pub mod wayland;
 #[cfg(feature = "x11")]
@@ -51,9 +67,9 @@ pub mod x11;
 
 /// Environment variable specifying which backend should be used on unix platform.
 ///
-/// Legal values are x11 and wayland. If this variable is set only the named backend
+/// Legal values are x11, wayland and headless. If this variable is set only the named backend
 /// will be tried by winit. If it is not set, winit will try to connect to a wayland connection,
-/// and if it fails will fallback on x11.
+/// and if it fails will fallback on x11. The headless backend is never picked automatically.
 ///
 /// If this variable is set with any other value, winit will panic.
 const BACKEND_PREFERENCE_ENV_VAR: &str = "WINIT_UNIX_BACKEND";
@@ -64,6 +80,7 @@ pub(crate) enum Backend {
     X,
     #[cfg(feature = "wayland")]
     Wayland,
+    Headless,
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
@@ -103,6 +120,11 @@ pub struct PlatformSpecificWindowBuilderAttributes {
     pub gtk_theme_variant: Option<String>,
     #[cfg(feature = "wayland")]
     pub csd_theme: Option<Theme>,
+    #[cfg(feature = "wayland")]
+    pub decoration_mode: Option<DecorationMode>,
+    pub popup: Option<Popup>,
+    #[cfg(feature = "wayland")]
+    pub layer_shell: Option<LayerShell>,
 }
 
 impl Default for PlatformSpecificWindowBuilderAttributes {
@@ -125,6 +147,11 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
             gtk_theme_variant: None,
             #[cfg(feature = "wayland")]
             csd_theme: None,
+            #[cfg(feature = "wayland")]
+            decoration_mode: None,
+            popup: None,
+            #[cfg(feature = "wayland")]
+            layer_shell: None,
         }
     }
 }
@@ -161,6 +188,7 @@ pub enum Window {
     X(x11::Window),
     #[cfg(feature = "wayland")]
     Wayland(wayland::Window),
+    Headless(headless::Window),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -190,6 +218,7 @@ pub enum DeviceId {
     X(x11::DeviceId),
     #[cfg(feature = "wayland")]
     Wayland(wayland::DeviceId),
+    Headless(headless::DeviceId),
 }
 
 impl DeviceId {
@@ -199,6 +228,29 @@ impl DeviceId {
         #[cfg(all(not(feature = "wayland"), feature = "x11"))]
         return DeviceId::X(x11::DeviceId::dummy());
     }
+
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        match self {
+            #[cfg(feature = "x11")]
+            DeviceId::X(id) => id.into_raw(),
+            #[cfg(feature = "wayland")]
+            DeviceId::Wayland(id) => id.into_raw(),
+            DeviceId::Headless(id) => id.into_raw(),
+        }
+    }
+
+    /// Only X11 distinguishes devices, so the X11 variant is used when it's available.
+    #[cfg(feature = "serde")]
+    pub fn from_raw(raw: u64) -> Self {
+        #[cfg(feature = "x11")]
+        return DeviceId::X(x11::DeviceId::from_raw(raw));
+        #[cfg(not(feature = "x11"))]
+        {
+            let _ = raw;
+            unsafe { DeviceId::dummy() }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -207,6 +259,7 @@ pub enum MonitorHandle {
     X(x11::MonitorHandle),
     #[cfg(feature = "wayland")]
     Wayland(wayland::MonitorHandle),
+    Headless(headless::MonitorHandle),
 }
 
 /// `x11_or_wayland!(match expr; Enum(foo) => foo.something())`
@@ -215,6 +268,7 @@ pub enum MonitorHandle {
 /// match self {
 ///    Enum::X(foo) => foo.something(),
 ///    Enum::Wayland(foo) => foo.something(),
+///    Enum::Headless(foo) => foo.something(),
 /// }
 /// ```
 /// The result can be converted to another enum by adding `; as AnotherEnum`
@@ -225,6 +279,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $enum2::X($x),
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $enum2::Wayland($x),
+            $enum::Headless($($c1)*) => $enum2::Headless($x),
         }
     };
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr) => {
@@ -233,6 +288,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $x,
             #[cfg(feature = "wayland")]
             $enum::Wayland($($c1)*) => $x,
+            $enum::Headless($($c1)*) => $x,
         }
     };
 }
@@ -280,6 +336,7 @@ pub enum VideoMode {
     X(x11::VideoMode),
     #[cfg(feature = "wayland")]
     Wayland(wayland::VideoMode),
+    Headless(headless::VideoMode),
 }
 
 impl VideoMode {
@@ -320,6 +377,9 @@ impl Window {
             EventLoopWindowTarget::X(ref window_target) => {
                 x11::Window::new(window_target, attribs, pl_attribs).map(Window::X)
             }
+            EventLoopWindowTarget::Headless(ref window_target) => {
+                headless::Window::new(window_target, attribs, pl_attribs).map(Window::Headless)
+            }
         }
     }
 
@@ -330,6 +390,7 @@ impl Window {
             Self::Wayland(window) => window.id(),
             #[cfg(feature = "x11")]
             Self::X(window) => window.id(),
+            Self::Headless(window) => window.id(),
         }
     }
 
@@ -423,9 +484,78 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_cursor_hittest(hittest))
     }
 
+    #[inline]
+    pub fn set_input_region(&self, region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_input_region(region))
+    }
+
+    #[inline]
+    pub fn set_shape(&self, shape: Option<Region>) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_shape(shape))
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_opacity(opacity))
+    }
+
+    #[inline]
+    pub fn set_blur(&self, blur: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_blur(blur))
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_clipboard(kind, contents))
+    }
+
+    #[inline]
+    pub fn clipboard(&self, kind: ClipboardKind, mime_type: &str) -> Option<Vec<u8>> {
+        x11_or_wayland!(match self; Window(w) => w.clipboard(kind, mime_type))
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Vec<String> {
+        x11_or_wayland!(match self; Window(w) => w.clipboard_mime_types(kind))
+    }
+
+    #[inline]
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        x11_or_wayland!(match self; Window(w) => w.keyboard_layout())
+    }
+
+    #[inline]
+    pub fn key_label(&self, physical_key: KeyCode) -> Option<Key> {
+        x11_or_wayland!(match self; Window(w) => w.key_label(physical_key))
+    }
+
+    #[inline]
+    pub fn start_drag(&self, data: DragData) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.start_drag(data))
+    }
+
+    #[inline]
+    pub fn accept_drag(&self, mime_type: &str, action: DndAction) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.accept_drag(mime_type, action))
+    }
+
+    #[inline]
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.reject_drag())
+    }
+
     #[inline]
     pub fn scale_factor(&self) -> f64 {
-        x11_or_wayland!(match self; Window(w) => w.scale_factor() as f64)
+        x11_or_wayland!(match self; Window(w) => w.scale_factor())
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) {
+        x11_or_wayland!(match self; Window(w) => w.set_custom_cursor(cursor))
     }
 
     #[inline]
@@ -475,6 +605,7 @@ impl Window {
             Window::X(ref w) => w.set_always_on_top(_always_on_top),
             #[cfg(feature = "wayland")]
             Window::Wayland(_) => (),
+            Window::Headless(ref w) => w.set_always_on_top(_always_on_top),
         }
     }
 
@@ -485,6 +616,7 @@ impl Window {
             Window::X(ref w) => w.set_window_icon(_window_icon),
             #[cfg(feature = "wayland")]
             Window::Wayland(_) => (),
+            Window::Headless(ref w) => w.set_window_icon(_window_icon),
         }
     }
 
@@ -493,6 +625,30 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_ime_position(position))
     }
 
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_cursor_area(position, size))
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(
+        &self,
+        text: &str,
+        cursor: usize,
+        anchor: usize,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_surrounding_text(text, cursor, anchor))
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(
+        &self,
+        purpose: ImePurpose,
+        hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_purpose(purpose, hints))
+    }
+
     #[inline]
     pub fn set_ime_allowed(&self, allowed: bool) {
         x11_or_wayland!(match self; Window(w) => w.set_ime_allowed(allowed))
@@ -505,15 +661,26 @@ impl Window {
             Window::X(ref w) => w.focus_window(),
             #[cfg(feature = "wayland")]
             Window::Wayland(_) => (),
+            Window::Headless(ref w) => w.focus_window(),
         }
     }
     pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
-        match self {
-            #[cfg(feature = "x11")]
-            Window::X(ref w) => w.request_user_attention(request_type),
-            #[cfg(feature = "wayland")]
-            Window::Wayland(ref w) => w.request_user_attention(request_type),
-        }
+        x11_or_wayland!(match self; Window(w) => w.request_user_attention(request_type))
+    }
+
+    #[inline]
+    pub fn set_idle_inhibit(&self, inhibit: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_idle_inhibit(inhibit))
+    }
+
+    #[inline]
+    pub fn set_shortcuts_inhibited(&self, inhibit: bool) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(w) => w.set_shortcuts_inhibited(inhibit))
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        x11_or_wayland!(match self; Window(w) => w.theme())
     }
 
     #[inline]
@@ -538,6 +705,12 @@ impl Window {
                     inner: current_monitor,
                 })
             }
+            Window::Headless(ref window) => {
+                let current_monitor = MonitorHandle::Headless(window.current_monitor()?);
+                Some(RootMonitorHandle {
+                    inner: current_monitor,
+                })
+            }
         }
     }
 
@@ -556,6 +729,11 @@ impl Window {
                 .into_iter()
                 .map(MonitorHandle::Wayland)
                 .collect(),
+            Window::Headless(ref window) => window
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -571,6 +749,12 @@ impl Window {
             }
             #[cfg(feature = "wayland")]
             Window::Wayland(ref window) => window.primary_monitor(),
+            Window::Headless(ref window) => {
+                let primary_monitor = MonitorHandle::Headless(window.primary_monitor()?);
+                Some(RootMonitorHandle {
+                    inner: primary_monitor,
+                })
+            }
         }
     }
 
@@ -637,6 +821,7 @@ pub enum EventLoop<T: 'static> {
     Wayland(Box<wayland::EventLoop<T>>),
     #[cfg(feature = "x11")]
     X(x11::EventLoop<T>),
+    Headless(headless::EventLoop<T>),
 }
 
 pub enum EventLoopProxy<T: 'static> {
@@ -644,6 +829,7 @@ pub enum EventLoopProxy<T: 'static> {
     X(x11::EventLoopProxy<T>),
     #[cfg(feature = "wayland")]
     Wayland(wayland::EventLoopProxy<T>),
+    Headless(headless::EventLoopProxy<T>),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -675,6 +861,10 @@ impl<T: 'static> EventLoop<T> {
             return EventLoop::new_wayland_any_thread().expect("failed to open Wayland connection");
         }
 
+        if attributes.forced_backend == Some(Backend::Headless) {
+            return EventLoop::new_headless();
+        }
+
         if let Ok(env_var) = env::var(BACKEND_PREFERENCE_ENV_VAR) {
             match env_var.as_str() {
                 "x11" => {
@@ -692,8 +882,9 @@ impl<T: 'static> EventLoop<T> {
                     #[cfg(not(feature = "wayland"))]
                     panic!("wayland feature is not enabled");
                 }
+                "headless" => return EventLoop::new_headless(),
                 _ => panic!(
-                    "Unknown environment variable value for {}, try one of `x11`,`wayland`",
+                    "Unknown environment variable value for {}, try one of `x11`,`wayland`,`headless`",
                     BACKEND_PREFERENCE_ENV_VAR,
                 ),
             }
@@ -737,6 +928,10 @@ impl<T: 'static> EventLoop<T> {
         Ok(EventLoop::X(x11::EventLoop::new(xconn)))
     }
 
+    fn new_headless() -> EventLoop<T> {
+        EventLoop::Headless(headless::EventLoop::new())
+    }
+
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
         x11_or_wayland!(match self; EventLoop(evlp) => evlp.create_proxy(); as EventLoopProxy)
     }
@@ -771,19 +966,35 @@ pub enum EventLoopWindowTarget<T> {
     Wayland(wayland::EventLoopWindowTarget<T>),
     #[cfg(feature = "x11")]
     X(x11::EventLoopWindowTarget<T>),
+    Headless(headless::EventLoopWindowTarget<T>),
 }
 
 impl<T> EventLoopWindowTarget<T> {
     #[inline]
+    #[cfg(feature = "wayland")]
     pub fn is_wayland(&self) -> bool {
         match *self {
             #[cfg(feature = "wayland")]
             EventLoopWindowTarget::Wayland(_) => true,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    #[cfg(feature = "x11")]
+    pub fn is_x11(&self) -> bool {
+        match *self {
             #[cfg(feature = "x11")]
+            EventLoopWindowTarget::X(_) => true,
             _ => false,
         }
     }
 
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        matches!(*self, EventLoopWindowTarget::Headless(_))
+    }
+
     #[inline]
     pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
         match *self {
@@ -800,6 +1011,11 @@ impl<T> EventLoopWindowTarget<T> {
                 .into_iter()
                 .map(MonitorHandle::X)
                 .collect(),
+            EventLoopWindowTarget::Headless(ref evlp) => evlp
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -815,6 +1031,7 @@ impl<T> EventLoopWindowTarget<T> {
                     inner: primary_monitor,
                 })
             }
+            EventLoopWindowTarget::Headless(ref evlp) => evlp.primary_monitor(),
         }
     }
 
@@ -825,6 +1042,7 @@ impl<T> EventLoopWindowTarget<T> {
             EventLoopWindowTarget::Wayland(_) => (),
             #[cfg(feature = "x11")]
             EventLoopWindowTarget::X(ref evlp) => evlp.set_device_event_filter(_filter),
+            EventLoopWindowTarget::Headless(_) => (),
         }
     }
 
@@ -850,6 +1068,34 @@ fn sticky_exit_callback<T, F>(
     }
 }
 
+/// Events older than this are assumed to use another clock than the local monotonic clock.
+const MAX_EVENT_AGE: Duration = Duration::from_secs(60);
+
+/// Converts the millisecond timestamp of an input event to an `Instant`.
+///
+/// X servers and Wayland compositors take these timestamps from `CLOCK_MONOTONIC`, which is also
+/// the clock behind `Instant`, truncated to 32 bits. Timestamps that don't fit that clock are
+/// replaced with the current time.
+#[cfg(any(feature = "x11", feature = "wayland"))]
+fn instant_from_event_time(time: u32) -> Instant {
+    let now = Instant::now();
+    let mut monotonic = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut monotonic) } != 0 {
+        return now;
+    }
+
+    let now_millis = (monotonic.tv_sec as u64 * 1000 + monotonic.tv_nsec as u64 / 1_000_000) as u32;
+    // Timestamps from the future wrap around to a very large age.
+    let age = Duration::from_millis(now_millis.wrapping_sub(time).into());
+    if age > MAX_EVENT_AGE {
+        return now;
+    }
+    now.checked_sub(age).unwrap_or(now)
+}
+
 #[cfg(target_os = "linux")]
 fn is_main_thread() -> bool {
     use libc::{c_long, getpid, syscall, SYS_gettid};
@@ -0,0 +1,290 @@
+//! Shared clipboard state.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::os::raw::c_int;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sctk::reexports::client::protocol::wl_data_offer::WlDataOffer;
+use sctk::reexports::client::Display;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_offer_v1::ZwpPrimarySelectionOfferV1;
+
+use crate::clipboard::{ClipboardContents, ClipboardKind, TEXT_MIME_TYPE};
+
+/// How long the owner of a selection may stay silent before we give up on a transfer.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// MIME types used by other clients for UTF-8 text, in order of preference.
+pub const TEXT_MIME_TYPES: [&str; 5] = [
+    TEXT_MIME_TYPE,
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+    "TEXT",
+];
+
+/// An offer for one of the selections.
+pub enum SelectionOffer {
+    Clipboard(WlDataOffer),
+    Primary(ZwpPrimarySelectionOfferV1),
+}
+
+impl SelectionOffer {
+    /// The MIME types announced for the offer.
+    ///
+    /// They're collected in the offer's user data by the data device handlers.
+    fn mime_types(&self) -> Vec<String> {
+        let user_data = match self {
+            SelectionOffer::Clipboard(offer) => offer.as_ref().user_data(),
+            SelectionOffer::Primary(offer) => offer.as_ref().user_data(),
+        };
+
+        user_data
+            .get::<Mutex<Vec<String>>>()
+            .map(|mime_types| mime_types.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+
+    fn receive(&self, mime_type: String, fd: RawFd) {
+        match self {
+            SelectionOffer::Clipboard(offer) => offer.receive(mime_type, fd),
+            SelectionOffer::Primary(offer) => offer.receive(mime_type, fd),
+        }
+    }
+
+    fn destroy(&self) {
+        match self {
+            SelectionOffer::Clipboard(offer) => offer.destroy(),
+            SelectionOffer::Primary(offer) => offer.destroy(),
+        }
+    }
+}
+
+/// Contents that the application has put on a selection.
+struct OwnedContents {
+    /// Identifies the `Window::set_clipboard` call these contents come from.
+    generation: u64,
+
+    contents: ClipboardContents,
+}
+
+#[derive(Default)]
+struct ClipboardState {
+    /// The current offer for the regular clipboard.
+    clipboard_offer: Option<SelectionOffer>,
+
+    /// The current offer for the primary selection.
+    primary_offer: Option<SelectionOffer>,
+
+    /// Contents we're currently serving.
+    owned: HashMap<ClipboardKind, OwnedContents>,
+
+    /// Generation of the latest owned contents.
+    generation: u64,
+
+    /// Number of seats whose keyboard focus is on one of our surfaces.
+    focused_seats: usize,
+}
+
+/// A handle to the clipboard state shared between the event loop and the windows.
+#[derive(Clone, Default)]
+pub struct Clipboard {
+    state: Arc<Mutex<ClipboardState>>,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Replaces the current offer for the given selection, destroying the old one.
+    pub fn set_offer(&self, kind: ClipboardKind, offer: Option<SelectionOffer>) {
+        let mut state = self.state.lock().unwrap();
+        let current_offer = match kind {
+            ClipboardKind::Clipboard => &mut state.clipboard_offer,
+            ClipboardKind::Primary => &mut state.primary_offer,
+        };
+
+        if let Some(old_offer) = std::mem::replace(current_offer, offer) {
+            old_offer.destroy();
+        }
+    }
+
+    /// Returns the generation of the next contents we'll serve, to be passed to
+    /// `take_ownership` and `release_ownership`.
+    pub fn next_generation(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.generation
+    }
+
+    /// Records `contents` as what we're serving on the given selection, once its source is set.
+    pub fn take_ownership(
+        &self,
+        kind: ClipboardKind,
+        generation: u64,
+        contents: ClipboardContents,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        state.owned.insert(
+            kind,
+            OwnedContents {
+                generation,
+                contents,
+            },
+        );
+    }
+
+    /// Forgets the contents we were serving, unless they were replaced in the meantime.
+    pub fn release_ownership(&self, kind: ClipboardKind, generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state
+            .owned
+            .get(&kind)
+            .map_or(false, |owned| owned.generation == generation)
+        {
+            state.owned.remove(&kind);
+        }
+    }
+
+    pub fn keyboard_focus_changed(&self, focused: bool) {
+        let mut state = self.state.lock().unwrap();
+        if focused {
+            state.focused_seats += 1;
+        } else {
+            state.focused_seats = state.focused_seats.saturating_sub(1);
+        }
+    }
+
+    /// Whether a seat is focused on one of our surfaces, which is required to set a selection.
+    pub fn has_keyboard_focus(&self) -> bool {
+        self.state.lock().unwrap().focused_seats > 0
+    }
+
+    pub fn mime_types(&self, kind: ClipboardKind) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        match state.owned.get(&kind) {
+            Some(owned) => owned.contents.mime_types().map(String::from).collect(),
+            None => match Self::offer(&state, kind) {
+                Some(offer) => reported_mime_types(offer.mime_types()),
+                None => Vec::new(),
+            },
+        }
+    }
+
+    /// Reads the given selection, blocking until its owner sent the data.
+    pub fn load(&self, kind: ClipboardKind, mime_type: &str, display: &Display) -> Option<Vec<u8>> {
+        let state = self.state.lock().unwrap();
+        if let Some(owned) = state.owned.get(&kind) {
+            return owned.contents.get(mime_type).map(|data| data.to_vec());
+        }
+
+        let offer = Self::offer(&state, kind)?;
+        let offered = offer.mime_types();
+        let mime_type = requested_mime_type(&offered, mime_type)?;
+
+        let (read_fd, write_fd) = pipe()?;
+        let mut file = unsafe { File::from_raw_fd(read_fd) };
+        offer.receive(mime_type.to_owned(), write_fd);
+        unsafe {
+            libc::close(write_fd);
+        }
+
+        // Don't hold the lock while the owner is sending data, the event loop may need it.
+        drop(state);
+        let _ = display.flush();
+
+        read_to_end(&mut file)
+    }
+
+    fn offer(state: &ClipboardState, kind: ClipboardKind) -> Option<&SelectionOffer> {
+        match kind {
+            ClipboardKind::Clipboard => state.clipboard_offer.as_ref(),
+            ClipboardKind::Primary => state.primary_offer.as_ref(),
+        }
+    }
+}
+
+/// Converts the MIME types offered by another client to the ones reported to the application,
+/// which only know `TEXT_MIME_TYPE` for text.
+pub fn reported_mime_types(offered: Vec<String>) -> Vec<String> {
+    let mut mime_types = Vec::new();
+    for mime_type in offered {
+        let mime_type = if TEXT_MIME_TYPES.contains(&mime_type.as_str()) {
+            TEXT_MIME_TYPE.to_owned()
+        } else if mime_type.contains('/') {
+            mime_type
+        } else {
+            continue;
+        };
+
+        if !mime_types.contains(&mime_type) {
+            mime_types.push(mime_type);
+        }
+    }
+
+    mime_types
+}
+
+/// Finds the offered MIME type to request `mime_type` from another client with.
+pub fn requested_mime_type<'a>(offered: &'a [String], mime_type: &str) -> Option<&'a str> {
+    if mime_type == TEXT_MIME_TYPE {
+        TEXT_MIME_TYPES.iter().find_map(|text_mime_type| {
+            offered
+                .iter()
+                .find(|mime| mime == text_mime_type)
+                .map(String::as_str)
+        })
+    } else {
+        offered
+            .iter()
+            .find(|mime| *mime == mime_type)
+            .map(String::as_str)
+    }
+}
+
+/// Creates a pipe whose ends are closed on exec.
+pub fn pipe() -> Option<(RawFd, RawFd)> {
+    let mut fds: [c_int; 2] = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } == 0 {
+        Some((fds[0], fds[1]))
+    } else {
+        None
+    }
+}
+
+/// Reads `file` until the writer closes it, giving up if it stays silent for `TRANSFER_TIMEOUT`.
+fn read_to_end(file: &mut File) -> Option<Vec<u8>> {
+    let mut deadline = Instant::now() + TRANSFER_TIMEOUT;
+    let mut data = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return None;
+        }
+
+        let mut fd = libc::pollfd {
+            fd: file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = (deadline - now).as_millis() as c_int;
+        if unsafe { libc::poll(&mut fd, 1, timeout) } <= 0 {
+            continue;
+        }
+
+        match file.read(&mut buf) {
+            Ok(0) => return Some(data),
+            Ok(len) => {
+                data.extend_from_slice(&buf[..len]);
+                deadline = Instant::now() + TRANSFER_TIMEOUT;
+            }
+            Err(err) if err.kind() == ErrorKind::Interrupted => (),
+            Err(_) => return None,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! SCTK environment setup.
 
 use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_data_device_manager::WlDataDeviceManager;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
 use sctk::reexports::protocols::unstable::xdg_shell::v6::client::zxdg_shell_v6::ZxdgShellV6;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
@@ -13,7 +14,17 @@ use sctk::reexports::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::text_input::v1::client::zwp_text_input_manager_v1::ZwpTextInputManagerV1;
 use sctk::reexports::protocols::staging::xdg_activation::v1::client::xdg_activation_v1::XdgActivationV1;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+use sctk::reexports::protocols::viewporter::client::wp_viewporter::WpViewporter;
+use sctk::reexports::protocols::unstable::idle_inhibit::v1::client::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1;
+use sctk::reexports::protocols::unstable::keyboard_shortcuts_inhibit::v1::client::zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1;
+use sctk::reexports::protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_shell_v1::ZwlrLayerShellV1;
+
+use super::protocols::fractional_scale::v1::client::wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1;
+use super::protocols::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
+use super::protocols::text_input::v2::client::zwp_text_input_manager_v2::ZwpTextInputManagerV2;
 
 use sctk::environment::{Environment, SimpleGlobal};
 use sctk::output::{OutputHandler, OutputHandling, OutputInfo, OutputStatusListener};
@@ -21,11 +32,18 @@ use sctk::seat::{SeatData, SeatHandler, SeatHandling, SeatListener};
 use sctk::shell::{Shell, ShellHandler, ShellHandling};
 use sctk::shm::ShmHandler;
 
+use crate::platform::unix::TextInputProtocol;
+
 /// Set of extra features that are supported by the compositor.
 #[derive(Debug, Clone, Copy)]
 pub struct WindowingFeatures {
     pointer_constraints: bool,
     xdg_activation: bool,
+    text_input: Option<TextInputProtocol>,
+    idle_inhibit: bool,
+    shortcuts_inhibit: bool,
+    data_device: bool,
+    primary_selection: bool,
 }
 
 impl WindowingFeatures {
@@ -33,9 +51,31 @@ impl WindowingFeatures {
     pub fn new(env: &Environment<WinitEnv>) -> Self {
         let pointer_constraints = env.get_global::<ZwpPointerConstraintsV1>().is_some();
         let xdg_activation = env.get_global::<XdgActivationV1>().is_some();
+        let text_input = if env.get_global::<ZwpTextInputManagerV3>().is_some() {
+            Some(TextInputProtocol::V3)
+        } else if env.get_global::<ZwpTextInputManagerV2>().is_some() {
+            Some(TextInputProtocol::V2)
+        } else if env.get_global::<ZwpTextInputManagerV1>().is_some() {
+            Some(TextInputProtocol::V1)
+        } else {
+            None
+        };
+        let idle_inhibit = env.get_global::<ZwpIdleInhibitManagerV1>().is_some();
+        let shortcuts_inhibit = env
+            .get_global::<ZwpKeyboardShortcutsInhibitManagerV1>()
+            .is_some();
+        let data_device = env.get_global::<WlDataDeviceManager>().is_some();
+        let primary_selection = env
+            .get_global::<ZwpPrimarySelectionDeviceManagerV1>()
+            .is_some();
         Self {
             pointer_constraints,
             xdg_activation,
+            text_input,
+            idle_inhibit,
+            shortcuts_inhibit,
+            data_device,
+            primary_selection,
         }
     }
 
@@ -46,6 +86,28 @@ impl WindowingFeatures {
     pub fn xdg_activation(&self) -> bool {
         self.xdg_activation
     }
+
+    /// The text input protocol used for IME, the newest one available.
+    pub fn text_input(&self) -> Option<TextInputProtocol> {
+        self.text_input
+    }
+
+    pub fn idle_inhibit(&self) -> bool {
+        self.idle_inhibit
+    }
+
+    pub fn shortcuts_inhibit(&self) -> bool {
+        self.shortcuts_inhibit
+    }
+
+    /// Whether the regular clipboard and drag and drop are available.
+    pub fn data_device(&self) -> bool {
+        self.data_device
+    }
+
+    pub fn primary_selection(&self) -> bool {
+        self.primary_selection
+    }
 }
 
 sctk::environment!(WinitEnv,
@@ -59,8 +121,18 @@ sctk::environment!(WinitEnv,
         ZxdgDecorationManagerV1 => decoration_manager,
         ZwpRelativePointerManagerV1 => relative_pointer_manager,
         ZwpPointerConstraintsV1 => pointer_constraints,
+        ZwpPointerGesturesV1 => pointer_gestures,
         ZwpTextInputManagerV3 => text_input_manager,
+        ZwpTextInputManagerV2 => text_input_manager_v2,
+        ZwpTextInputManagerV1 => text_input_manager_v1,
         XdgActivationV1 => xdg_activation,
+        WlDataDeviceManager => data_device_manager,
+        ZwpPrimarySelectionDeviceManagerV1 => primary_selection_manager,
+        WpViewporter => viewporter,
+        WpFractionalScaleManagerV1 => fractional_scale_manager,
+        ZwlrLayerShellV1 => layer_shell,
+        ZwpIdleInhibitManagerV1 => idle_inhibit_manager,
+        ZwpKeyboardShortcutsInhibitManagerV1 => shortcuts_inhibit_manager,
     ],
     multis = [
         WlSeat => seats,
@@ -86,11 +158,31 @@ pub struct WinitEnv {
 
     pointer_constraints: SimpleGlobal<ZwpPointerConstraintsV1>,
 
+    pointer_gestures: SimpleGlobal<ZwpPointerGesturesV1>,
+
     text_input_manager: SimpleGlobal<ZwpTextInputManagerV3>,
 
+    text_input_manager_v2: SimpleGlobal<ZwpTextInputManagerV2>,
+
+    text_input_manager_v1: SimpleGlobal<ZwpTextInputManagerV1>,
+
     decoration_manager: SimpleGlobal<ZxdgDecorationManagerV1>,
 
     xdg_activation: SimpleGlobal<XdgActivationV1>,
+
+    data_device_manager: SimpleGlobal<WlDataDeviceManager>,
+
+    primary_selection_manager: SimpleGlobal<ZwpPrimarySelectionDeviceManagerV1>,
+
+    viewporter: SimpleGlobal<WpViewporter>,
+
+    fractional_scale_manager: SimpleGlobal<WpFractionalScaleManagerV1>,
+
+    layer_shell: SimpleGlobal<ZwlrLayerShellV1>,
+
+    idle_inhibit_manager: SimpleGlobal<ZwpIdleInhibitManagerV1>,
+
+    shortcuts_inhibit_manager: SimpleGlobal<ZwpKeyboardShortcutsInhibitManagerV1>,
 }
 
 impl WinitEnv {
@@ -119,12 +211,38 @@ impl WinitEnv {
         // Pointer grab functionality.
         let pointer_constraints = SimpleGlobal::new();
 
+        // Touchpad gestures.
+        let pointer_gestures = SimpleGlobal::new();
+
         // IME handling.
         let text_input_manager = SimpleGlobal::new();
 
+        // IME handling on compositors without text input v3.
+        let text_input_manager_v2 = SimpleGlobal::new();
+        let text_input_manager_v1 = SimpleGlobal::new();
+
         // Surface activation.
         let xdg_activation = SimpleGlobal::new();
 
+        // Clipboard.
+        let data_device_manager = SimpleGlobal::new();
+
+        // Primary selection.
+        let primary_selection_manager = SimpleGlobal::new();
+
+        // Fractional scaling.
+        let viewporter = SimpleGlobal::new();
+        let fractional_scale_manager = SimpleGlobal::new();
+
+        // Panels, docks and overlays.
+        let layer_shell = SimpleGlobal::new();
+
+        // Keeping the screen awake.
+        let idle_inhibit_manager = SimpleGlobal::new();
+
+        // Sending the system shortcuts to the window.
+        let shortcuts_inhibit_manager = SimpleGlobal::new();
+
         Self {
             seats,
             outputs,
@@ -135,8 +253,18 @@ impl WinitEnv {
             decoration_manager,
             relative_pointer_manager,
             pointer_constraints,
+            pointer_gestures,
             text_input_manager,
+            text_input_manager_v2,
+            text_input_manager_v1,
             xdg_activation,
+            data_device_manager,
+            primary_selection_manager,
+            viewporter,
+            fractional_scale_manager,
+            layer_shell,
+            idle_inhibit_manager,
+            shortcuts_inhibit_manager,
         }
     }
 }
@@ -5,6 +5,7 @@ use std::io::Result as IOResult;
 use std::mem;
 use std::process;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use raw_window_handle::{RawDisplayHandle, WaylandDisplayHandle};
@@ -21,9 +22,12 @@ use sctk::WaylandSource;
 
 use crate::event::{Event, StartCause, WindowEvent};
 use crate::event_loop::{ControlFlow, EventLoopWindowTarget as RootEventLoopWindowTarget};
+use crate::platform_impl::platform::common::{portal, xkb::SharedLayout};
 use crate::platform_impl::platform::sticky_exit_callback;
 use crate::platform_impl::EventLoopWindowTarget as PlatformEventLoopWindowTarget;
+use crate::window::Theme;
 
+use super::clipboard::Clipboard;
 use super::env::{WindowingFeatures, WinitEnv};
 use super::output::OutputManager;
 use super::seat::SeatManager;
@@ -71,6 +75,15 @@ pub struct EventLoopWindowTarget<T> {
     /// multiple similar themes.
     pub theme_manager: ThemeManager,
 
+    /// Clipboard state shared between the seats and the windows.
+    pub clipboard: Clipboard,
+
+    /// Keyboard layout shared between the seats and the windows.
+    pub keyboard_layout: SharedLayout,
+
+    /// The theme of the desktop, from the settings portal.
+    pub system_theme: Arc<Mutex<Option<Theme>>>,
+
     _marker: std::marker::PhantomData<T>,
 }
 
@@ -125,8 +138,16 @@ impl<T: 'static> EventLoop<T> {
         let shm = env.require_global::<WlShm>();
         let theme_manager = ThemeManager::init(ThemeSpec::System, compositor, shm);
 
+        let clipboard = Clipboard::new();
+        let keyboard_layout = SharedLayout::default();
+
         // Setup theme seat and output managers.
-        let seat_manager = SeatManager::new(&env, event_loop.handle(), theme_manager.clone());
+        let seat_manager = SeatManager::new(
+            &env,
+            event_loop.handle(),
+            theme_manager.clone(),
+            clipboard.clone(),
+        );
         let output_manager = OutputManager::new(&env);
 
         // A source of events that we plug into our event loop.
@@ -161,6 +182,32 @@ impl<T: 'static> EventLoop<T> {
                 }
             })?;
 
+        // The portal is followed from its own thread.
+        let (theme_sender, theme_channel) = calloop::channel::channel();
+        portal::watch_color_scheme(move |theme| {
+            let _ = theme_sender.send(theme);
+        });
+        let system_theme = Arc::new(Mutex::new(None));
+        let system_theme_clone = system_theme.clone();
+        event_loop
+            .handle()
+            .insert_source(theme_channel, move |event, _, winit_state| {
+                let theme = match event {
+                    calloop::channel::Event::Msg(theme) => theme,
+                    calloop::channel::Event::Closed => return,
+                };
+                let old_theme = mem::replace(&mut *system_theme_clone.lock().unwrap(), theme);
+                let theme = match theme {
+                    Some(theme) if Some(theme) != old_theme => theme,
+                    _ => return,
+                };
+                for window_id in winit_state.window_map.keys() {
+                    winit_state
+                        .event_sink
+                        .push_window_event(WindowEvent::ThemeChanged(theme), *window_id);
+                }
+            })?;
+
         // An event's loop awakener to wake up for window events from winit's windows.
         let (event_loop_awakener, event_loop_awakener_source) = calloop::ping::make_ping()?;
 
@@ -188,6 +235,7 @@ impl<T: 'static> EventLoop<T> {
                 event_sink,
                 window_user_requests,
                 window_compositor_updates,
+                keyboard_layout: keyboard_layout.clone(),
             }),
             event_loop_handle,
             output_manager,
@@ -195,6 +243,9 @@ impl<T: 'static> EventLoop<T> {
             wayland_dispatcher: wayland_dispatcher.clone(),
             windowing_features,
             theme_manager,
+            clipboard,
+            keyboard_layout,
+            system_theme,
             _marker: std::marker::PhantomData,
         };
 
@@ -268,7 +319,6 @@ impl<T: 'static> EventLoop<T> {
                     PlatformEventLoopWindowTarget::Wayland(window_target) => {
                         window_target.state.get_mut()
                     }
-                    #[cfg(feature = "x11")]
                     _ => unreachable!(),
                 };
 
@@ -374,10 +424,10 @@ impl<T: 'static> EventLoop<T> {
             });
 
             for (window_id, window_compositor_update) in window_compositor_updates.iter_mut() {
-                if let Some(scale_factor) = window_compositor_update.scale_factor.map(|f| f as f64)
-                {
+                if let Some(scale_factor) = window_compositor_update.scale_factor {
                     let mut physical_size = self.with_state(|state| {
                         let window_handle = state.window_map.get(window_id).unwrap();
+                        window_handle.scale_factor_changed();
                         let mut size = window_handle.size.lock().unwrap();
 
                         // Update the new logical size if it was changed.
@@ -419,9 +469,7 @@ impl<T: 'static> EventLoop<T> {
                             None
                         } else {
                             *window_size = size;
-                            let scale_factor =
-                                sctk::get_surface_scale_factor(window_handle.window.surface());
-                            let physical_size = size.to_physical(scale_factor as f64);
+                            let physical_size = size.to_physical(window_handle.scale_factor());
                             Some(physical_size)
                         };
 
@@ -429,6 +477,7 @@ impl<T: 'static> EventLoop<T> {
                         // hasn't changed, since GNOME relies on `set_geometry` calls after
                         // configures.
                         window_handle.window.resize(size.width, size.height);
+                        window_handle.resize_viewport(size);
                         window_handle.window.refresh();
 
                         // Mark that refresh isn't required, since we've done it right now.
@@ -561,7 +610,6 @@ impl<T: 'static> EventLoop<T> {
     fn with_state<U, F: FnOnce(&mut WinitState) -> U>(&mut self, f: F) -> U {
         let state = match &mut self.window_target.p {
             PlatformEventLoopWindowTarget::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
@@ -571,7 +619,6 @@ impl<T: 'static> EventLoop<T> {
     fn loop_dispatch<D: Into<Option<std::time::Duration>>>(&mut self, timeout: D) -> IOResult<()> {
         let state = match &mut self.window_target.p {
             PlatformEventLoopWindowTarget::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
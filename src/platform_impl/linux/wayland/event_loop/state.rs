@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 
 use super::EventSink;
+use crate::platform_impl::platform::common::xkb::SharedLayout;
 use crate::platform_impl::wayland::window::shim::{
     WindowCompositorUpdate, WindowHandle, WindowUserRequest,
 };
@@ -28,4 +29,7 @@ pub struct WinitState {
     /// and requests from winit's windows are being forwarded to them either via
     /// `WindowUpdate` or buffer on the associated with it `WindowHandle`.
     pub window_map: HashMap<WindowId, WindowHandle>,
+
+    /// Keyboard layout shared with winit's windows.
+    pub keyboard_layout: SharedLayout,
 }
@@ -6,6 +6,8 @@
     target_os = "openbsd"
 ))]
 
+use std::collections::HashMap;
+
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 
 pub use crate::platform_impl::platform::WindowId;
@@ -13,9 +15,13 @@ pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
 pub use output::{MonitorHandle, VideoMode};
 pub use window::Window;
 
+use window::shim::WindowHandle;
+
+mod clipboard;
 mod env;
 mod event_loop;
 mod output;
+mod protocols;
 mod seat;
 mod window;
 
@@ -26,9 +32,23 @@ impl DeviceId {
     pub const unsafe fn dummy() -> Self {
         DeviceId
     }
+
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        0
+    }
 }
 
 #[inline]
 fn make_wid(surface: &WlSurface) -> WindowId {
     WindowId(surface.as_ref().c_ptr() as u64)
 }
+
+/// The scale factor of the window on the given surface, which may be fractional.
+#[inline]
+fn scale_factor(window_map: &HashMap<WindowId, WindowHandle>, surface: &WlSurface) -> f64 {
+    match window_map.get(&make_wid(surface)) {
+        Some(window_handle) => window_handle.scale_factor(),
+        None => sctk::get_surface_scale_factor(surface) as f64,
+    }
+}
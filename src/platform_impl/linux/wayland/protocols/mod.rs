@@ -0,0 +1,99 @@
+//! Bindings to the protocols that aren't in `wayland-protocols` yet, generated by the build
+//! script.
+
+/// Lets the compositor pick a fractional scale for the surfaces.
+pub mod fractional_scale {
+    pub mod v1 {
+        pub use self::generated::client;
+
+        mod generated {
+            #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+            #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+            #![allow(missing_docs, clippy::all)]
+            // The interfaces are mutable statics, which newer compilers warn about.
+            #![allow(unknown_lints, static_mut_refs)]
+
+            pub mod client {
+                pub(crate) use sctk::reexports::client::protocol::wl_surface;
+                pub(crate) use sctk::reexports::client::{
+                    sys, AnonymousObject, Attached, Main, Proxy, ProxyMap,
+                };
+                pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+                pub(crate) use wayland_commons::smallvec;
+                pub(crate) use wayland_commons::wire::{
+                    Argument, ArgumentType, Message, MessageDesc,
+                };
+                pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+                include!(concat!(
+                    env!("OUT_DIR"),
+                    "/fractional-scale-v1_client_api.rs"
+                ));
+            }
+        }
+    }
+}
+
+/// Text input v2, which KDE and GNOME compositors supported before text input v3.
+pub mod text_input {
+    pub mod v2 {
+        pub use self::generated::client;
+
+        mod generated {
+            #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+            #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+            #![allow(missing_docs, clippy::all)]
+            #![allow(unknown_lints, static_mut_refs)]
+
+            pub mod client {
+                pub(crate) use sctk::reexports::client::protocol::{wl_seat, wl_surface};
+                pub(crate) use sctk::reexports::client::{
+                    sys, AnonymousObject, Attached, Main, Proxy, ProxyMap,
+                };
+                pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+                pub(crate) use wayland_commons::smallvec;
+                pub(crate) use wayland_commons::wire::{
+                    Argument, ArgumentType, Message, MessageDesc,
+                };
+                pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+                include!(concat!(
+                    env!("OUT_DIR"),
+                    "/text-input-unstable-v2_client_api.rs"
+                ));
+            }
+        }
+    }
+}
+
+/// Touchpad gestures, version 3 of which added the hold gesture.
+pub mod pointer_gestures {
+    pub mod v1 {
+        pub use self::generated::client;
+
+        mod generated {
+            #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+            #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+            #![allow(missing_docs, clippy::all)]
+            #![allow(unknown_lints, static_mut_refs)]
+
+            pub mod client {
+                pub(crate) use sctk::reexports::client::protocol::{wl_pointer, wl_surface};
+                pub(crate) use sctk::reexports::client::{
+                    sys, AnonymousObject, Attached, Main, Proxy, ProxyMap,
+                };
+                pub(crate) use wayland_commons::map::{Object, ObjectMetadata};
+                pub(crate) use wayland_commons::smallvec;
+                pub(crate) use wayland_commons::wire::{
+                    Argument, ArgumentType, Message, MessageDesc,
+                };
+                pub(crate) use wayland_commons::{Interface, MessageGroup};
+
+                include!(concat!(
+                    env!("OUT_DIR"),
+                    "/pointer-gestures-unstable-v1_client_api.rs"
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Handling of data device events.
+
+use std::io::{ErrorKind, Read};
+use std::sync::Mutex;
+
+use sctk::reexports::calloop::generic::Generic;
+use sctk::reexports::calloop::{Interest, LoopHandle, Mode, PostAction};
+use sctk::reexports::client::protocol::wl_data_device::Event as DataDeviceEvent;
+use sctk::reexports::client::protocol::wl_data_offer::Event as DataOfferEvent;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_v1::Event as PrimarySelectionDeviceEvent;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_offer_v1::Event as PrimarySelectionOfferEvent;
+
+use crate::clipboard::{ClipboardContents, ClipboardKind};
+use crate::dpi::LogicalPosition;
+use crate::event::WindowEvent;
+use crate::platform_impl::wayland;
+use crate::platform_impl::wayland::clipboard::{Clipboard, SelectionOffer};
+use crate::platform_impl::wayland::event_loop::WinitState;
+
+use super::DragOffer;
+
+#[inline]
+pub(super) fn handle_data_device(
+    event: DataDeviceEvent,
+    clipboard: &Clipboard,
+    drag: &mut Option<(DragOffer, WlSurface)>,
+    loop_handle: &LoopHandle<'static, WinitState>,
+    winit_state: &mut WinitState,
+) {
+    match event {
+        DataDeviceEvent::DataOffer { id } => {
+            // Collect the MIME types, they're announced right after the offer is introduced.
+            id.as_ref()
+                .user_data()
+                .set_threadsafe(|| Mutex::new(Vec::<String>::new()));
+            id.quick_assign(|offer, event, _| {
+                if let DataOfferEvent::Offer { mime_type } = event {
+                    if let Some(mime_types) = offer.as_ref().user_data().get::<Mutex<Vec<String>>>()
+                    {
+                        mime_types.lock().unwrap().push(mime_type);
+                    }
+                }
+            });
+        }
+        DataDeviceEvent::Enter {
+            serial,
+            surface,
+            x,
+            y,
+            id: Some(offer),
+        } => {
+            let window_id = wayland::make_wid(&surface);
+            let window_handle = match winit_state.window_map.get_mut(&window_id) {
+                Some(window_handle) => window_handle,
+                None => {
+                    offer.destroy();
+                    return;
+                }
+            };
+
+            let drag_offer = DragOffer::new(offer, serial);
+            window_handle.drag_entered(drag_offer.clone());
+
+            let scale_factor = wayland::scale_factor(&winit_state.window_map, &surface);
+            let position = LogicalPosition::new(x, y).to_physical(scale_factor);
+            winit_state.event_sink.push_window_event(
+                WindowEvent::DragEnter {
+                    mime_types: drag_offer.mime_types(),
+                    position,
+                },
+                window_id,
+            );
+
+            *drag = Some((drag_offer, surface));
+        }
+        DataDeviceEvent::Motion { x, y, .. } => {
+            let surface = match drag.as_ref() {
+                Some((_, surface)) => surface,
+                None => return,
+            };
+
+            let scale_factor = wayland::scale_factor(&winit_state.window_map, surface);
+            let position = LogicalPosition::new(x, y).to_physical(scale_factor);
+            winit_state.event_sink.push_window_event(
+                WindowEvent::DragOver { position },
+                wayland::make_wid(surface),
+            );
+        }
+        DataDeviceEvent::Leave => {
+            let (drag_offer, surface) = match drag.take() {
+                Some(drag) => drag,
+                None => return,
+            };
+
+            let window_id = wayland::make_wid(&surface);
+            if let Some(window_handle) = winit_state.window_map.get_mut(&window_id) {
+                window_handle.drag_left(&drag_offer);
+            }
+
+            drag_offer.finish(false);
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::DragLeave, window_id);
+        }
+        DataDeviceEvent::Drop => {
+            let (drag_offer, surface) = match drag.take() {
+                Some(drag) => drag,
+                None => return,
+            };
+
+            let window_id = wayland::make_wid(&surface);
+            if let Some(window_handle) = winit_state.window_map.get_mut(&window_id) {
+                window_handle.drag_left(&drag_offer);
+            }
+
+            match drag_offer.receive() {
+                Some((mime_type, file)) => {
+                    receive_drop(drag_offer, mime_type, file, window_id, loop_handle)
+                }
+                None => drag_offer.finish(false),
+            }
+        }
+        DataDeviceEvent::Selection { id } => {
+            clipboard.set_offer(ClipboardKind::Clipboard, id.map(SelectionOffer::Clipboard));
+            clipboard_changed(ClipboardKind::Clipboard, winit_state);
+        }
+        _ => (),
+    }
+}
+
+#[inline]
+pub(super) fn handle_primary_selection_device(
+    event: PrimarySelectionDeviceEvent,
+    clipboard: &Clipboard,
+    winit_state: &mut WinitState,
+) {
+    match event {
+        PrimarySelectionDeviceEvent::DataOffer { offer } => {
+            offer
+                .as_ref()
+                .user_data()
+                .set_threadsafe(|| Mutex::new(Vec::<String>::new()));
+            offer.quick_assign(|offer, event, _| {
+                if let PrimarySelectionOfferEvent::Offer { mime_type } = event {
+                    if let Some(mime_types) = offer.as_ref().user_data().get::<Mutex<Vec<String>>>()
+                    {
+                        mime_types.lock().unwrap().push(mime_type);
+                    }
+                }
+            });
+        }
+        PrimarySelectionDeviceEvent::Selection { id } => {
+            clipboard.set_offer(ClipboardKind::Primary, id.map(SelectionOffer::Primary));
+            clipboard_changed(ClipboardKind::Primary, winit_state);
+        }
+        _ => (),
+    }
+}
+
+/// Read the dropped data without blocking the event loop, since the source may be one of our
+/// windows, and deliver it once the source is done writing.
+fn receive_drop(
+    drag_offer: DragOffer,
+    mime_type: String,
+    file: std::fs::File,
+    window_id: wayland::WindowId,
+    loop_handle: &LoopHandle<'static, WinitState>,
+) {
+    let mut data = Vec::new();
+    let mut mime_type = Some(mime_type);
+    let source = Generic::new(file, Interest::READ, Mode::Level);
+    let result = loop_handle.insert_source(source, move |_, file, winit_state| {
+        let mut buf = [0; 4096];
+        match file.read(&mut buf) {
+            Ok(0) => (),
+            Ok(len) => {
+                data.extend_from_slice(&buf[..len]);
+                return Ok(PostAction::Continue);
+            }
+            Err(err) if err.kind() == ErrorKind::Interrupted => {
+                return Ok(PostAction::Continue);
+            }
+            Err(err) => {
+                warn!("Failed to read dropped data: {}", err);
+                drag_offer.finish(false);
+                return Ok(PostAction::Remove);
+            }
+        }
+
+        drag_offer.finish(true);
+        if let Some(mime_type) = mime_type.take() {
+            let data = ClipboardContents::new().with_data(mime_type, std::mem::take(&mut data));
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::DragDrop { data }, window_id);
+        }
+
+        Ok(PostAction::Remove)
+    });
+
+    if result.is_err() {
+        warn!("Failed to read dropped data: couldn't register the pipe");
+    }
+}
+
+/// Notify all the windows about the new contents of a selection.
+fn clipboard_changed(kind: ClipboardKind, winit_state: &mut WinitState) {
+    let event_sink = &mut winit_state.event_sink;
+    for window_id in winit_state.window_map.keys() {
+        event_sink.push_window_event(WindowEvent::ClipboardChanged(kind), *window_id);
+    }
+}
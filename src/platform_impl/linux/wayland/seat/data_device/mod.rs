@@ -0,0 +1,503 @@
+//! Clipboard handling through data devices.
+
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::Write;
+use std::os::unix::io::{FromRawFd, RawFd};
+use std::rc::Rc;
+use std::thread;
+
+use sctk::reexports::calloop::LoopHandle;
+use sctk::reexports::client::protocol::wl_data_device::WlDataDevice;
+use sctk::reexports::client::protocol::wl_data_device_manager::{
+    DndAction as WlDndAction, WlDataDeviceManager,
+};
+use sctk::reexports::client::protocol::wl_data_offer::WlDataOffer;
+use sctk::reexports::client::protocol::wl_data_source::Event as DataSourceEvent;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
+
+use sctk::environment::Environment;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_manager_v1::ZwpPrimarySelectionDeviceManagerV1;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1;
+use sctk::reexports::protocols::unstable::primary_selection::v1::client::zwp_primary_selection_source_v1::Event as PrimarySelectionSourceEvent;
+
+use crate::clipboard::{ClipboardContents, ClipboardKind, TEXT_MIME_TYPE};
+use crate::dnd::{DndAction, DragData};
+use crate::event::WindowEvent;
+use crate::platform_impl::wayland;
+use crate::platform_impl::wayland::clipboard::{self, Clipboard, TEXT_MIME_TYPES};
+use crate::platform_impl::wayland::env::WinitEnv;
+use crate::platform_impl::wayland::event_loop::WinitState;
+
+mod handlers;
+
+/// A handler for the data devices of a seat that we're advertising for `WindowHandle`.
+#[derive(Clone)]
+pub struct DataDeviceHandler {
+    data_device: Option<(WlDataDevice, Attached<WlDataDeviceManager>)>,
+
+    primary_selection_device: Option<(
+        ZwpPrimarySelectionDeviceV1,
+        Attached<ZwpPrimarySelectionDeviceManagerV1>,
+    )>,
+
+    /// Clipboard state shared with the windows.
+    clipboard: Clipboard,
+
+    /// Latest serial of a keyboard event on the seat, used to set selections.
+    latest_serial: Rc<Cell<u32>>,
+
+    /// Whether the seat has keyboard focus on one of our surfaces.
+    has_focus: Rc<Cell<bool>>,
+}
+
+impl PartialEq for DataDeviceHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.latest_serial, &other.latest_serial)
+    }
+}
+
+impl Eq for DataDeviceHandler {}
+
+impl DataDeviceHandler {
+    /// Keyboard focus of the seat entered one of our surfaces.
+    pub fn keyboard_entered(&self, serial: u32) {
+        self.latest_serial.replace(serial);
+        if !self.has_focus.replace(true) {
+            self.clipboard.keyboard_focus_changed(true);
+        }
+    }
+
+    /// Keyboard focus of the seat left our surfaces.
+    pub fn keyboard_left(&self) {
+        if self.has_focus.replace(false) {
+            self.clipboard.keyboard_focus_changed(false);
+        }
+    }
+
+    /// A key was pressed or released on the seat.
+    pub fn key(&self, serial: u32) {
+        self.latest_serial.replace(serial);
+    }
+
+    /// Serve the contents on a selection, recording them as ours once the source is set.
+    pub fn set_selection(&self, kind: ClipboardKind, contents: ClipboardContents) {
+        let serial = self.latest_serial.get();
+        let mime_types = offered_mime_types(&contents);
+        let clipboard = self.clipboard.clone();
+        let generation = self.clipboard.next_generation();
+        let owned_contents = contents.clone();
+
+        match kind {
+            ClipboardKind::Clipboard => {
+                let (data_device, data_device_manager) = match self.data_device.as_ref() {
+                    Some(data_device) => data_device,
+                    None => return,
+                };
+
+                let source = data_device_manager.create_data_source();
+                source.quick_assign(move |source, event, _| match event {
+                    DataSourceEvent::Send { mime_type, fd } => {
+                        send_data(&contents, &mime_type, fd);
+                    }
+                    DataSourceEvent::Cancelled => {
+                        clipboard.release_ownership(kind, generation);
+                        source.destroy();
+                    }
+                    _ => (),
+                });
+
+                for mime_type in mime_types {
+                    source.offer(mime_type);
+                }
+
+                data_device.set_selection(Some(&source), serial);
+            }
+            ClipboardKind::Primary => {
+                let (device, manager) = match self.primary_selection_device.as_ref() {
+                    Some(primary_selection_device) => primary_selection_device,
+                    None => return,
+                };
+
+                let source = manager.create_source();
+                source.quick_assign(move |source, event, _| match event {
+                    PrimarySelectionSourceEvent::Send { mime_type, fd } => {
+                        send_data(&contents, &mime_type, fd);
+                    }
+                    PrimarySelectionSourceEvent::Cancelled => {
+                        clipboard.release_ownership(kind, generation);
+                        source.destroy();
+                    }
+                    _ => (),
+                });
+
+                for mime_type in mime_types {
+                    source.offer(mime_type);
+                }
+
+                device.set_selection(Some(&source), serial);
+            }
+        }
+
+        self.clipboard
+            .take_ownership(kind, generation, owned_contents);
+    }
+
+    /// Start a drag from `surface`, using the serial of the button press which started it.
+    ///
+    /// Returns whether the drag started, which fails without a data device manager.
+    pub fn start_drag(&self, surface: &WlSurface, serial: u32, data: DragData) -> bool {
+        let (data_device, data_device_manager) = match self.data_device.as_ref() {
+            Some(data_device) => data_device,
+            None => return false,
+        };
+
+        let window_id = wayland::make_wid(surface);
+        let mime_types = offered_mime_types(data.contents());
+        let actions = dnd_actions_to_wayland(data.actions());
+        let source = data_device_manager.create_data_source();
+        let mut action = None;
+        source.quick_assign(move |source, event, mut dispatch_data| {
+            let finished_action = match event {
+                DataSourceEvent::Send { mime_type, fd } => {
+                    send_data(data.contents(), &mime_type, fd);
+                    return;
+                }
+                DataSourceEvent::Action { dnd_action } => {
+                    action = dnd_action_from_wayland(dnd_action);
+                    return;
+                }
+                DataSourceEvent::DndFinished => action,
+                DataSourceEvent::Cancelled => None,
+                _ => return,
+            };
+
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::DragFinished(finished_action), window_id);
+            source.destroy();
+        });
+
+        for mime_type in mime_types {
+            source.offer(mime_type);
+        }
+
+        // Actions were added in version 3, older compositors assume copy.
+        if source.as_ref().version() >= 3 {
+            source.set_actions(actions);
+        }
+
+        data_device.start_drag(Some(&source), surface, None, serial);
+        true
+    }
+}
+
+/// The answer of the application to a drag offer.
+struct DragAnswer {
+    /// The MIME type the application asked for.
+    mime_type: String,
+
+    /// The MIME type we request from the source, which differs for text.
+    requested_mime_type: String,
+}
+
+struct DragOfferInner {
+    offer: WlDataOffer,
+
+    /// Serial of the enter event, used to answer the offer.
+    serial: u32,
+
+    answer: Option<DragAnswer>,
+}
+
+/// A drag over one of our surfaces, which the window under it answers.
+#[derive(Clone)]
+pub struct DragOffer {
+    inner: Rc<RefCell<DragOfferInner>>,
+}
+
+impl PartialEq for DragOffer {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for DragOffer {}
+
+impl DragOffer {
+    fn new(offer: WlDataOffer, serial: u32) -> Self {
+        // Drags are rejected until the application accepts them.
+        offer.accept(serial, None);
+        if offer.as_ref().version() >= 3 {
+            offer.set_actions(WlDndAction::None, WlDndAction::None);
+        }
+
+        let inner = DragOfferInner {
+            offer,
+            serial,
+            answer: None,
+        };
+
+        Self {
+            inner: Rc::new(RefCell::new(inner)),
+        }
+    }
+
+    /// The MIME types offered by the source, as reported to the application.
+    fn mime_types(&self) -> Vec<String> {
+        clipboard::reported_mime_types(offered_mime_types_of(&self.inner.borrow().offer))
+    }
+
+    pub fn accept(&self, mime_type: &str, action: DndAction) {
+        let mut inner = self.inner.borrow_mut();
+        let offered = offered_mime_types_of(&inner.offer);
+        let requested_mime_type = match clipboard::requested_mime_type(&offered, mime_type) {
+            Some(requested_mime_type) => requested_mime_type.to_owned(),
+            None => {
+                warn!(
+                    "Accepted a drag with MIME type `{}` which it doesn't offer",
+                    mime_type
+                );
+                drop(inner);
+                self.reject();
+                return;
+            }
+        };
+
+        inner
+            .offer
+            .accept(inner.serial, Some(requested_mime_type.clone()));
+        if inner.offer.as_ref().version() >= 3 {
+            let action = dnd_actions_to_wayland(&[action]);
+            inner.offer.set_actions(action, action);
+        }
+
+        inner.answer = Some(DragAnswer {
+            mime_type: mime_type.to_owned(),
+            requested_mime_type,
+        });
+    }
+
+    pub fn reject(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.offer.accept(inner.serial, None);
+        if inner.offer.as_ref().version() >= 3 {
+            inner
+                .offer
+                .set_actions(WlDndAction::None, WlDndAction::None);
+        }
+
+        inner.answer = None;
+    }
+
+    /// Asks the source for the data of the accepted MIME type.
+    ///
+    /// Returns the MIME type reported to the application, and the pipe the data is written to.
+    fn receive(&self) -> Option<(String, File)> {
+        let inner = self.inner.borrow();
+        let answer = inner.answer.as_ref()?;
+        let (read_fd, write_fd) = clipboard::pipe()?;
+        inner
+            .offer
+            .receive(answer.requested_mime_type.clone(), write_fd);
+        unsafe {
+            libc::close(write_fd);
+        }
+
+        Some((answer.mime_type.clone(), unsafe {
+            File::from_raw_fd(read_fd)
+        }))
+    }
+
+    /// Tells the source that we're done with the offer, which can't be used afterwards.
+    fn finish(&self, transferred: bool) {
+        let inner = self.inner.borrow();
+        if transferred && inner.offer.as_ref().version() >= 3 {
+            inner.offer.finish();
+        }
+
+        inner.offer.destroy();
+    }
+}
+
+/// Globals used to create the data devices of seats.
+pub struct DataDeviceManager {
+    data_device_manager: Option<Attached<WlDataDeviceManager>>,
+
+    primary_selection_manager: Option<Attached<ZwpPrimarySelectionDeviceManagerV1>>,
+
+    /// Clipboard state shared with the windows.
+    clipboard: Clipboard,
+
+    /// Loop handle, used to read dropped data.
+    loop_handle: LoopHandle<'static, WinitState>,
+}
+
+impl DataDeviceManager {
+    pub fn new(
+        env: &Environment<WinitEnv>,
+        clipboard: Clipboard,
+        loop_handle: LoopHandle<'static, WinitState>,
+    ) -> Self {
+        Self {
+            data_device_manager: env.get_global::<WlDataDeviceManager>(),
+            primary_selection_manager: env.get_global::<ZwpPrimarySelectionDeviceManagerV1>(),
+            clipboard,
+            loop_handle,
+        }
+    }
+}
+
+/// The data devices of a seat.
+pub struct DataDevice {
+    handler: DataDeviceHandler,
+}
+
+impl DataDevice {
+    pub fn new(seat: &Attached<WlSeat>, manager: &DataDeviceManager) -> Option<Self> {
+        if manager.data_device_manager.is_none() && manager.primary_selection_manager.is_none() {
+            return None;
+        }
+
+        let clipboard = manager.clipboard.clone();
+        let data_device = manager
+            .data_device_manager
+            .as_ref()
+            .map(|data_device_manager| {
+                let data_device = data_device_manager.get_data_device(seat);
+                let clipboard = clipboard.clone();
+                let loop_handle = manager.loop_handle.clone();
+                let mut drag = None;
+                data_device.quick_assign(move |_, event, mut dispatch_data| {
+                    let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                    handlers::handle_data_device(
+                        event,
+                        &clipboard,
+                        &mut drag,
+                        &loop_handle,
+                        winit_state,
+                    );
+                });
+
+                (data_device.detach(), data_device_manager.clone())
+            });
+
+        let primary_selection_device = manager.primary_selection_manager.as_ref().map(|manager| {
+            let device = manager.get_device(seat);
+            let clipboard = clipboard.clone();
+            device.quick_assign(move |_, event, mut dispatch_data| {
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                handlers::handle_primary_selection_device(event, &clipboard, winit_state);
+            });
+
+            (device.detach(), manager.clone())
+        });
+
+        let handler = DataDeviceHandler {
+            data_device,
+            primary_selection_device,
+            clipboard,
+            latest_serial: Rc::new(Cell::new(0)),
+            has_focus: Rc::new(Cell::new(false)),
+        };
+
+        Some(Self { handler })
+    }
+
+    pub fn handler(&self) -> DataDeviceHandler {
+        self.handler.clone()
+    }
+}
+
+impl Drop for DataDevice {
+    fn drop(&mut self) {
+        // The seat is going away, so it won't report that it left our surfaces.
+        self.handler.keyboard_left();
+
+        if let Some((data_device, _)) = self.handler.data_device.as_ref() {
+            if data_device.as_ref().version() >= 2 {
+                data_device.release();
+            }
+        }
+
+        if let Some((device, _)) = self.handler.primary_selection_device.as_ref() {
+            device.destroy();
+        }
+    }
+}
+
+/// The MIME types to offer for `contents`, including the aliases other clients use for text.
+fn offered_mime_types(contents: &ClipboardContents) -> Vec<String> {
+    let mut mime_types: Vec<String> = contents.mime_types().map(String::from).collect();
+    if contents.get(TEXT_MIME_TYPE).is_some() {
+        for mime_type in TEXT_MIME_TYPES.iter().skip(1) {
+            if contents.get(mime_type).is_none() {
+                mime_types.push(String::from(*mime_type));
+            }
+        }
+    }
+
+    mime_types
+}
+
+/// The MIME types announced for an offer, which the data device handlers collect in its user
+/// data.
+fn offered_mime_types_of(offer: &WlDataOffer) -> Vec<String> {
+    offer
+        .as_ref()
+        .user_data()
+        .get::<std::sync::Mutex<Vec<String>>>()
+        .map(|mime_types| mime_types.lock().unwrap().clone())
+        .unwrap_or_default()
+}
+
+/// Wayland has no equivalent of `DndAction::Link`, so drags offering only it are copied.
+fn dnd_actions_to_wayland(actions: &[DndAction]) -> WlDndAction {
+    let wayland_actions = actions
+        .iter()
+        .fold(WlDndAction::None, |wayland_actions, action| {
+            wayland_actions
+                | match action {
+                    DndAction::Copy => WlDndAction::Copy,
+                    DndAction::Move => WlDndAction::Move,
+                    DndAction::Link => WlDndAction::None,
+                }
+        });
+
+    if wayland_actions.is_empty() {
+        WlDndAction::Copy
+    } else {
+        wayland_actions
+    }
+}
+
+fn dnd_action_from_wayland(action: WlDndAction) -> Option<DndAction> {
+    if action.contains(WlDndAction::Copy) {
+        Some(DndAction::Copy)
+    } else if action.contains(WlDndAction::Move) {
+        Some(DndAction::Move)
+    } else {
+        None
+    }
+}
+
+/// Write the data requested by another client to `fd` without blocking the event loop.
+fn send_data(contents: &ClipboardContents, mime_type: &str, fd: RawFd) {
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    let data = match contents.get(mime_type) {
+        Some(data) => data,
+        None if TEXT_MIME_TYPES.contains(&mime_type) => match contents.get(TEXT_MIME_TYPE) {
+            Some(data) => data,
+            None => return,
+        },
+        None => return,
+    };
+
+    let data = data.to_vec();
+    thread::spawn(move || {
+        let _ = file.write_all(&data);
+    });
+}
@@ -0,0 +1,112 @@
+//! Compose and dead key sequences, which the compositor leaves to the clients.
+
+use std::env;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStringExt;
+use std::ptr;
+
+use xkbcommon_dl::{
+    xkb_compose_compile_flags, xkb_compose_feed_result, xkb_compose_state, xkb_compose_state_flags,
+    xkb_compose_status, xkb_compose_table, xkb_context, XkbCommonCompose,
+};
+
+/// What a key press does to the compose sequence.
+pub enum ComposeResult {
+    /// The key isn't part of a sequence, so it produces its own text.
+    Nothing,
+
+    /// The key started, continued or cancelled a sequence, so it produces no text.
+    Pending,
+
+    /// The key finished a sequence, which produced the given text.
+    Composed(Option<String>),
+}
+
+/// The compose table of the locale and the state of the current sequence.
+pub struct Compose {
+    xkb: XkbCommonCompose,
+    table: *mut xkb_compose_table,
+    state: *mut xkb_compose_state,
+}
+
+impl Compose {
+    /// Loads the compose table of the locale.
+    ///
+    /// Returns `None` if `libxkbcommon` isn't available or the locale has no compose table.
+    ///
+    /// # Safety
+    ///
+    /// `context` must be a valid context.
+    pub unsafe fn new(context: *mut xkb_context) -> Option<Self> {
+        let xkb = XkbCommonCompose::open("libxkbcommon.so.0")
+            .or_else(|_| XkbCommonCompose::open("libxkbcommon.so"))
+            .ok()?;
+
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(env::var_os)
+            .find(|locale| !locale.is_empty())
+            .unwrap_or_else(|| "C".into());
+        let locale = CString::new(locale.into_vec()).ok()?;
+
+        let table = (xkb.xkb_compose_table_new_from_locale)(
+            context,
+            locale.as_ptr(),
+            xkb_compose_compile_flags::XKB_COMPOSE_COMPILE_NO_FLAGS,
+        );
+        if table.is_null() {
+            return None;
+        }
+
+        let state =
+            (xkb.xkb_compose_state_new)(table, xkb_compose_state_flags::XKB_COMPOSE_STATE_NO_FLAGS);
+        if state.is_null() {
+            (xkb.xkb_compose_table_unref)(table);
+            return None;
+        }
+
+        Some(Self { xkb, table, state })
+    }
+
+    /// Feeds the keysym of a pressed key to the sequence.
+    pub fn feed(&mut self, keysym: u32) -> ComposeResult {
+        unsafe {
+            let result = (self.xkb.xkb_compose_state_feed)(self.state, keysym);
+            if result == xkb_compose_feed_result::XKB_COMPOSE_FEED_IGNORED {
+                return ComposeResult::Pending;
+            }
+
+            match (self.xkb.xkb_compose_state_get_status)(self.state) {
+                xkb_compose_status::XKB_COMPOSE_NOTHING => ComposeResult::Nothing,
+                xkb_compose_status::XKB_COMPOSE_COMPOSED => ComposeResult::Composed(self.text()),
+                _ => ComposeResult::Pending,
+            }
+        }
+    }
+
+    unsafe fn text(&self) -> Option<String> {
+        let size = (self.xkb.xkb_compose_state_get_utf8)(self.state, ptr::null_mut(), 0);
+        if size <= 0 {
+            return None;
+        }
+
+        let mut buffer = vec![0u8; size as usize + 1];
+        (self.xkb.xkb_compose_state_get_utf8)(
+            self.state,
+            buffer.as_mut_ptr() as *mut c_char,
+            buffer.len(),
+        );
+        buffer.truncate(size as usize);
+        String::from_utf8(buffer).ok()
+    }
+}
+
+impl Drop for Compose {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xkb.xkb_compose_state_unref)(self.state);
+            (self.xkb.xkb_compose_table_unref)(self.table);
+        }
+    }
+}
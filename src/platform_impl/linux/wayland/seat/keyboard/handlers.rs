@@ -1,27 +1,78 @@
 //! Handling of various keyboard events.
 
-use sctk::reexports::client::protocol::wl_keyboard::KeyState;
+use std::cell::RefCell;
+use std::fs::File;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::ptr;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
 
-use sctk::seat::keyboard::Event as KeyboardEvent;
+use sctk::reexports::calloop::timer::{TimeoutAction, Timer};
+use sctk::reexports::client::protocol::wl_keyboard::{self, KeyState, KeymapFormat};
 
 use crate::event::{ElementState, KeyboardInput, ModifiersState, WindowEvent};
+use crate::platform_impl::platform::common::keymap;
+use crate::platform_impl::platform::common::xkb::XkbKeymap;
+use crate::platform_impl::platform::instant_from_event_time;
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId};
 
-use super::keymap;
+use super::compose::{Compose, ComposeResult};
 use super::KeyboardInner;
 
+/// XKB keycodes are evdev scancodes offset by 8.
+const KEYCODE_OFFSET: u32 = 8;
+
 #[inline]
 pub(super) fn handle_keyboard(
-    event: KeyboardEvent<'_>,
-    inner: &mut KeyboardInner,
+    event: wl_keyboard::Event,
+    keyboard: &Rc<RefCell<KeyboardInner>>,
     winit_state: &mut WinitState,
 ) {
+    let mut inner = keyboard.borrow_mut();
+    let inner = &mut *inner;
     let event_sink = &mut winit_state.event_sink;
     match event {
-        KeyboardEvent::Enter { surface, .. } => {
+        wl_keyboard::Event::Keymap { format, fd, size } => {
+            let fd = unsafe { File::from_raw_fd(fd) };
+            if format != KeymapFormat::XkbV1 {
+                return;
+            }
+
+            if inner.keymap.is_none() {
+                inner.keymap = XkbKeymap::new(winit_state.keyboard_layout.clone());
+                inner.compose = inner
+                    .keymap
+                    .as_ref()
+                    .and_then(|keymap| unsafe { Compose::new(keymap.context()) });
+            }
+            let keymap = match inner.keymap.as_mut() {
+                Some(keymap) => keymap,
+                None => return,
+            };
+
+            let layouts = keymap.layouts();
+            if load_keymap(keymap, &fd, size as usize) && keymap.layouts() != layouts {
+                layout_changed(inner, winit_state);
+            }
+        }
+        wl_keyboard::Event::Enter {
+            surface, serial, ..
+        } => {
             let window_id = wayland::make_wid(&surface);
 
+            // Let the window set selections through the seat.
+            if let Some(data_device) = inner.data_device.as_ref() {
+                data_device.keyboard_entered(serial);
+                if let Some(window_handle) = winit_state.window_map.get_mut(&window_id) {
+                    window_handle.data_device_entered(data_device.clone());
+                }
+            }
+
+            if let Some(window_handle) = winit_state.window_map.get_mut(&window_id) {
+                window_handle.keyboard_entered(&inner.seat);
+            }
+
             // Window gained focus.
             event_sink.push_window_event(WindowEvent::Focused(true), window_id);
 
@@ -31,11 +82,25 @@ pub(super) fn handle_keyboard(
                 event_sink.push_window_event(WindowEvent::ModifiersChanged(modifiers), window_id);
             }
 
+            // Move the text input to the focused surface.
+            if let Some(text_input) = inner.text_input.as_ref() {
+                text_input.keyboard_entered(&surface, winit_state);
+            }
+
             inner.target_window_id = Some(window_id);
         }
-        KeyboardEvent::Leave { surface, .. } => {
+        wl_keyboard::Event::Leave { surface, .. } => {
             let window_id = wayland::make_wid(&surface);
 
+            inner.stop_repeat();
+
+            if let Some(data_device) = inner.data_device.as_ref() {
+                data_device.keyboard_left();
+                if let Some(window_handle) = winit_state.window_map.get_mut(&window_id) {
+                    window_handle.data_device_left(data_device);
+                }
+            }
+
             // Notify that no modifiers are being pressed.
             if !inner.modifiers_state.borrow().is_empty() {
                 event_sink.push_window_event(
@@ -44,98 +109,82 @@ pub(super) fn handle_keyboard(
                 );
             }
 
+            if let Some(window_handle) = winit_state.window_map.get_mut(&window_id) {
+                window_handle.keyboard_left(&inner.seat);
+            }
+
             // Window lost focus.
             event_sink.push_window_event(WindowEvent::Focused(false), window_id);
 
+            if let Some(text_input) = inner.text_input.as_ref() {
+                text_input.keyboard_left(&surface, winit_state);
+            }
+
             // Reset the id.
             inner.target_window_id = None;
         }
-        KeyboardEvent::Key {
-            rawkey,
-            keysym,
+        wl_keyboard::Event::Key {
+            serial,
+            time,
+            key,
             state,
-            utf8,
-            ..
         } => {
-            let window_id = match inner.target_window_id {
-                Some(window_id) => window_id,
-                None => return,
-            };
+            if let Some(data_device) = inner.data_device.as_ref() {
+                data_device.key(serial);
+            }
 
             let state = match state {
                 KeyState::Pressed => ElementState::Pressed,
                 KeyState::Released => ElementState::Released,
-                _ => unreachable!(),
+                _ => return,
             };
 
-            let virtual_keycode = keymap::keysym_to_vkey(keysym);
-
-            event_sink.push_window_event(
-                #[allow(deprecated)]
-                WindowEvent::KeyboardInput {
-                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
-                    )),
-                    input: KeyboardInput {
-                        state,
-                        scancode: rawkey,
-                        virtual_keycode,
-                        modifiers: *inner.modifiers_state.borrow(),
-                    },
-                    is_synthetic: false,
-                },
-                window_id,
-            );
+            let keycode = key + KEYCODE_OFFSET;
+            let keysym = inner
+                .keymap
+                .as_ref()
+                .map_or(0, |keymap| keymap.keysym(keycode));
+            let text = if state == ElementState::Pressed {
+                match inner.compose.as_mut().map(|compose| compose.feed(keysym)) {
+                    Some(ComposeResult::Composed(text)) => text,
+                    Some(ComposeResult::Pending) => None,
+                    Some(ComposeResult::Nothing) | None => inner
+                        .keymap
+                        .as_ref()
+                        .and_then(|keymap| keymap.text(keycode)),
+                }
+            } else {
+                None
+            };
 
-            // Send ReceivedCharacter event only on ElementState::Pressed.
-            if ElementState::Released == state {
-                return;
+            let repeats = inner
+                .keymap
+                .as_ref()
+                .map_or(false, |keymap| keymap.key_repeats(keycode));
+            if state == ElementState::Pressed && repeats {
+                start_repeat(keyboard, inner, key, time);
+            } else if matches!(inner.repeat, Some((repeated, _)) if repeated == key) {
+                inner.stop_repeat();
             }
 
-            if let Some(txt) = utf8 {
-                for ch in txt.chars() {
-                    event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
-                }
-            }
+            push_key_event(inner, winit_state, key, keysym, text, state, false, time);
         }
-        KeyboardEvent::Repeat {
-            rawkey,
-            keysym,
-            utf8,
+        wl_keyboard::Event::Modifiers {
+            mods_depressed,
+            mods_latched,
+            mods_locked,
+            group,
             ..
         } => {
-            let window_id = match inner.target_window_id {
-                Some(window_id) => window_id,
-                None => return,
+            let (modifiers, layout_changed) = match inner.keymap.as_mut() {
+                Some(keymap) => {
+                    let changed =
+                        keymap.update_mask(mods_depressed, mods_latched, mods_locked, 0, 0, group);
+                    (keymap.modifiers(), changed)
+                }
+                None => (ModifiersState::empty(), false),
             };
 
-            let virtual_keycode = keymap::keysym_to_vkey(keysym);
-
-            event_sink.push_window_event(
-                #[allow(deprecated)]
-                WindowEvent::KeyboardInput {
-                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
-                        DeviceId,
-                    )),
-                    input: KeyboardInput {
-                        state: ElementState::Pressed,
-                        scancode: rawkey,
-                        virtual_keycode,
-                        modifiers: *inner.modifiers_state.borrow(),
-                    },
-                    is_synthetic: false,
-                },
-                window_id,
-            );
-
-            if let Some(txt) = utf8 {
-                for ch in txt.chars() {
-                    event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
-                }
-            }
-        }
-        KeyboardEvent::Modifiers { modifiers } => {
-            let modifiers = ModifiersState::from(modifiers);
             if let Some(window_id) = inner.target_window_id {
                 *inner.modifiers_state.borrow_mut() = modifiers;
 
@@ -146,6 +195,165 @@ pub(super) fn handle_keyboard(
                 // them on wl_keyboard::enter.
                 inner.pending_modifers_state = Some(modifiers);
             }
+
+            if layout_changed {
+                self::layout_changed(inner, winit_state);
+            }
+        }
+        wl_keyboard::Event::RepeatInfo { rate, delay } => {
+            inner.repeat_info.gap = match rate {
+                rate if rate <= 0 => None,
+                rate => Some(Duration::from_micros(1_000_000 / rate as u64).max(MIN_REPEAT_GAP)),
+            };
+            inner.repeat_info.delay = Duration::from_millis(delay.max(0) as u64);
+        }
+        _ => (),
+    }
+}
+
+/// The shortest time between repeats, so a huge rate doesn't keep the event loop busy.
+const MIN_REPEAT_GAP: Duration = Duration::from_micros(1);
+
+/// Sends the events of a key press, release or repeat to the focused window.
+#[allow(clippy::too_many_arguments)]
+fn push_key_event(
+    inner: &KeyboardInner,
+    winit_state: &mut WinitState,
+    rawkey: u32,
+    keysym: u32,
+    text: Option<String>,
+    state: ElementState,
+    repeat: bool,
+    time: u32,
+) {
+    let window_id = match inner.target_window_id {
+        Some(window_id) => window_id,
+        None => return,
+    };
+
+    let virtual_keycode = keymap::keysym_to_vkey(keysym);
+    let key_event = keymap::key_event(rawkey, keysym, text.clone(), state, repeat);
+
+    let event_sink = &mut winit_state.event_sink;
+    event_sink.push_window_event(
+        #[allow(deprecated)]
+        WindowEvent::KeyboardInput {
+            device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId)),
+            input: KeyboardInput {
+                state,
+                scancode: rawkey,
+                virtual_keycode,
+                modifiers: *inner.modifiers_state.borrow(),
+            },
+            event: key_event,
+            is_synthetic: false,
+            timestamp: instant_from_event_time(time),
+        },
+        window_id,
+    );
+
+    // Send ReceivedCharacter event only on ElementState::Pressed.
+    if ElementState::Released == state {
+        return;
+    }
+
+    if let Some(txt) = text {
+        for ch in txt.chars() {
+            event_sink.push_window_event(WindowEvent::ReceivedCharacter(ch), window_id);
         }
     }
 }
+
+/// Repeats `key` while it's held down, replacing the key repeated so far.
+fn start_repeat(
+    keyboard: &Rc<RefCell<KeyboardInner>>,
+    inner: &mut KeyboardInner,
+    key: u32,
+    time: u32,
+) {
+    inner.stop_repeat();
+
+    let gap = match inner.repeat_info.gap {
+        Some(gap) => gap,
+        None => return,
+    };
+
+    let keyboard: Weak<RefCell<KeyboardInner>> = Rc::downgrade(keyboard);
+    let start = Instant::now();
+    let timer = Timer::from_duration(inner.repeat_info.delay);
+    let result = inner
+        .loop_handle
+        .insert_source(timer, move |last_trigger, _, winit_state| {
+            let keyboard = match keyboard.upgrade() {
+                Some(keyboard) => keyboard,
+                // The keyboard is gone.
+                None => return TimeoutAction::Drop,
+            };
+            let inner = keyboard.borrow();
+
+            // The repeats use the current modifiers and layout.
+            let keycode = key + KEYCODE_OFFSET;
+            let (keysym, text) = match inner.keymap.as_ref() {
+                Some(keymap) => (keymap.keysym(keycode), keymap.text(keycode)),
+                None => (0, None),
+            };
+            let time = time.wrapping_add((last_trigger - start).as_millis() as u32);
+            push_key_event(
+                &inner,
+                winit_state,
+                key,
+                keysym,
+                text,
+                ElementState::Pressed,
+                true,
+                time,
+            );
+
+            TimeoutAction::ToInstant(last_trigger + gap)
+        });
+
+    match result {
+        Ok(timer) => inner.repeat = Some((key, timer)),
+        Err(err) => warn!("Failed to repeat the key: {}", err),
+    }
+}
+
+/// Reports the active layout to the focused window.
+fn layout_changed(inner: &KeyboardInner, winit_state: &mut WinitState) {
+    let layout = inner.keymap.as_ref().and_then(XkbKeymap::layout);
+    if let (Some(window_id), Some(layout)) = (inner.target_window_id, layout) {
+        winit_state
+            .event_sink
+            .push_window_event(WindowEvent::KeyboardLayoutChanged(layout), window_id);
+    }
+}
+
+/// Replaces the keymap with the one the compositor shared in `fd`.
+fn load_keymap(keymap: &mut XkbKeymap, fd: &File, size: usize) -> bool {
+    unsafe {
+        let text = map_fd(fd.as_raw_fd(), size);
+        if text.is_null() {
+            return false;
+        }
+
+        let loaded = keymap.set_keymap_from_string(text as *const _);
+        libc::munmap(text, size);
+        loaded
+    }
+}
+
+unsafe fn map_fd(fd: RawFd, size: usize) -> *mut libc::c_void {
+    let map = libc::mmap(
+        ptr::null_mut(),
+        size,
+        libc::PROT_READ,
+        libc::MAP_PRIVATE,
+        fd,
+        0,
+    );
+    if map == libc::MAP_FAILED {
+        ptr::null_mut()
+    } else {
+        map
+    }
+}
@@ -2,24 +2,31 @@
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 
 use sctk::reexports::client::protocol::wl_keyboard::WlKeyboard;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::Attached;
 
-use sctk::reexports::calloop::LoopHandle;
-
-use sctk::seat::keyboard;
+use sctk::reexports::calloop::{LoopHandle, RegistrationToken};
 
 use crate::event::ModifiersState;
+use crate::platform_impl::platform::common::xkb::XkbKeymap;
 use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::seat::data_device::DataDeviceHandler;
+use crate::platform_impl::wayland::seat::text_input::TextInputV1Focus;
 use crate::platform_impl::wayland::WindowId;
 
+mod compose;
 mod handlers;
-mod keymap;
+
+use compose::Compose;
 
 pub(crate) struct Keyboard {
     pub keyboard: WlKeyboard,
+
+    /// The state of the keyboard, shared with the key repeat timer.
+    inner: Rc<RefCell<KeyboardInner>>,
 }
 
 impl Keyboard {
@@ -27,26 +34,34 @@ impl Keyboard {
         seat: &Attached<WlSeat>,
         loop_handle: LoopHandle<'static, WinitState>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
-    ) -> Option<Self> {
-        let mut inner = KeyboardInner::new(modifiers_state);
-        let keyboard = keyboard::map_keyboard_repeat(
-            loop_handle.clone(),
+        data_device: Option<DataDeviceHandler>,
+        text_input: Option<TextInputV1Focus>,
+    ) -> Self {
+        let inner = Rc::new(RefCell::new(KeyboardInner::new(
             seat,
-            None,
-            keyboard::RepeatKind::System,
-            move |event, _, mut dispatch_data| {
-                let winit_state = dispatch_data.get::<WinitState>().unwrap();
-                handlers::handle_keyboard(event, &mut inner, winit_state);
-            },
-        )
-        .ok()?;
-
-        Some(Self { keyboard })
+            loop_handle,
+            modifiers_state,
+            data_device,
+            text_input,
+        )));
+
+        let keyboard = seat.get_keyboard();
+        let handler = inner.clone();
+        keyboard.quick_assign(move |_, event, mut dispatch_data| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            handlers::handle_keyboard(event, &handler, winit_state);
+        });
+
+        Self {
+            keyboard: keyboard.detach(),
+            inner,
+        }
     }
 }
 
 impl Drop for Keyboard {
     fn drop(&mut self) {
+        self.inner.borrow_mut().stop_repeat();
         if self.keyboard.as_ref().version() >= 3 {
             self.keyboard.release();
         }
@@ -54,6 +69,18 @@ impl Drop for Keyboard {
 }
 
 struct KeyboardInner {
+    /// The seat of the keyboard.
+    seat: WlSeat,
+
+    /// Loop handle, used to repeat keys.
+    loop_handle: LoopHandle<'static, WinitState>,
+
+    /// The keymap, once received and if `libxkbcommon` is available.
+    keymap: Option<XkbKeymap>,
+
+    /// The compose sequence, if the locale has a compose table.
+    compose: Option<Compose>,
+
     /// Currently focused surface.
     target_window_id: Option<WindowId>,
 
@@ -66,25 +93,67 @@ struct KeyboardInner {
 
     /// Current state of modifiers keys.
     modifiers_state: Rc<RefCell<ModifiersState>>,
+
+    /// The repeat rate and delay set by the compositor.
+    repeat_info: RepeatInfo,
+
+    /// The key being repeated and its timer.
+    repeat: Option<(u32, RegistrationToken)>,
+
+    /// Data devices of the seat, which need keyboard serials to set selections.
+    data_device: Option<DataDeviceHandler>,
+
+    /// Text input v1 of the seat, which follows the keyboard focus.
+    text_input: Option<TextInputV1Focus>,
 }
 
 impl KeyboardInner {
-    fn new(modifiers_state: Rc<RefCell<ModifiersState>>) -> Self {
+    fn new(
+        seat: &Attached<WlSeat>,
+        loop_handle: LoopHandle<'static, WinitState>,
+        modifiers_state: Rc<RefCell<ModifiersState>>,
+        data_device: Option<DataDeviceHandler>,
+        text_input: Option<TextInputV1Focus>,
+    ) -> Self {
         Self {
+            seat: seat.detach(),
+            loop_handle,
+            keymap: None,
+            compose: None,
             target_window_id: None,
             pending_modifers_state: None,
             modifiers_state,
+            repeat_info: RepeatInfo::default(),
+            repeat: None,
+            data_device,
+            text_input,
         }
     }
+
+    /// Stops repeating the current key, if any.
+    fn stop_repeat(&mut self) {
+        if let Some((_, timer)) = self.repeat.take() {
+            self.loop_handle.remove(timer);
+        }
+    }
+}
+
+/// The key repeat settings of the compositor.
+#[derive(Debug, Clone, Copy)]
+struct RepeatInfo {
+    /// The time between repeats, or `None` if keys don't repeat.
+    gap: Option<Duration>,
+
+    /// The time a key is held down before it repeats.
+    delay: Duration,
 }
 
-impl From<keyboard::ModifiersState> for ModifiersState {
-    fn from(mods: keyboard::ModifiersState) -> ModifiersState {
-        let mut wl_mods = ModifiersState::empty();
-        wl_mods.set(ModifiersState::SHIFT, mods.shift);
-        wl_mods.set(ModifiersState::CTRL, mods.ctrl);
-        wl_mods.set(ModifiersState::ALT, mods.alt);
-        wl_mods.set(ModifiersState::LOGO, mods.logo);
-        wl_mods
+impl Default for RepeatInfo {
+    /// Keys don't repeat until the compositor tells the rate.
+    fn default() -> Self {
+        Self {
+            gap: None,
+            delay: Duration::from_millis(200),
+        }
     }
 }
@@ -6,6 +6,7 @@ use std::rc::Rc;
 use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
+use sctk::reexports::protocols::unstable::text_input::v1::client::zwp_text_input_manager_v1::ZwpTextInputManagerV1;
 
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::Attached;
@@ -15,18 +16,23 @@ use sctk::reexports::calloop::LoopHandle;
 use sctk::seat::pointer::ThemeManager;
 use sctk::seat::{SeatData, SeatListener};
 
+use super::clipboard::Clipboard;
 use super::env::WinitEnv;
 use super::event_loop::WinitState;
+use super::protocols::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
+use super::protocols::text_input::v2::client::zwp_text_input_manager_v2::ZwpTextInputManagerV2;
 use crate::event::ModifiersState;
 
+pub mod data_device;
 mod keyboard;
 pub mod pointer;
 pub mod text_input;
 mod touch;
 
+use data_device::{DataDevice, DataDeviceManager};
 use keyboard::Keyboard;
-use pointer::Pointers;
-use text_input::TextInput;
+use pointer::{CustomCursorManager, PointerGlobals, Pointers};
+use text_input::{TextInput, TextInputV1, TextInputV2};
 use touch::Touch;
 
 pub struct SeatManager {
@@ -39,16 +45,33 @@ impl SeatManager {
         env: &Environment<WinitEnv>,
         loop_handle: LoopHandle<'static, WinitState>,
         theme_manager: ThemeManager,
+        clipboard: Clipboard,
     ) -> Self {
-        let relative_pointer_manager = env.get_global::<ZwpRelativePointerManagerV1>();
-        let pointer_constraints = env.get_global::<ZwpPointerConstraintsV1>();
-        let text_input_manager = env.get_global::<ZwpTextInputManagerV3>();
+        let pointer_globals = PointerGlobals {
+            relative_pointer_manager: env.get_global::<ZwpRelativePointerManagerV1>(),
+            pointer_constraints: env.get_global::<ZwpPointerConstraintsV1>(),
+            pointer_gestures: env.get_global::<ZwpPointerGesturesV1>(),
+        };
+        let text_input_manager = env
+            .get_global::<ZwpTextInputManagerV3>()
+            .map(TextInputManager::V3)
+            .or_else(|| {
+                env.get_global::<ZwpTextInputManagerV2>()
+                    .map(TextInputManager::V2)
+            })
+            .or_else(|| {
+                env.get_global::<ZwpTextInputManagerV1>()
+                    .map(TextInputManager::V1)
+            });
+        let data_device_manager = DataDeviceManager::new(env, clipboard, loop_handle.clone());
+        let custom_cursor_manager = CustomCursorManager::new(env, loop_handle.clone());
 
         let mut inner = SeatManagerInner::new(
             theme_manager,
-            relative_pointer_manager,
-            pointer_constraints,
+            pointer_globals,
             text_input_manager,
+            data_device_manager,
+            custom_cursor_manager,
             loop_handle,
         );
 
@@ -80,34 +103,39 @@ struct SeatManagerInner {
     /// Loop handle.
     loop_handle: LoopHandle<'static, WinitState>,
 
-    /// Relative pointer manager.
-    relative_pointer_manager: Option<Attached<ZwpRelativePointerManagerV1>>,
-
-    /// Pointer constraints.
-    pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
+    /// Globals extending the pointers.
+    pointer_globals: PointerGlobals,
 
     /// Text input manager.
-    text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
+    text_input_manager: Option<TextInputManager>,
+
+    /// Data device manager.
+    data_device_manager: DataDeviceManager,
 
     /// A theme manager.
     theme_manager: ThemeManager,
+
+    /// Custom cursor manager.
+    custom_cursor_manager: Option<CustomCursorManager>,
 }
 
 impl SeatManagerInner {
     fn new(
         theme_manager: ThemeManager,
-        relative_pointer_manager: Option<Attached<ZwpRelativePointerManagerV1>>,
-        pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
-        text_input_manager: Option<Attached<ZwpTextInputManagerV3>>,
+        pointer_globals: PointerGlobals,
+        text_input_manager: Option<TextInputManager>,
+        data_device_manager: DataDeviceManager,
+        custom_cursor_manager: Option<CustomCursorManager>,
         loop_handle: LoopHandle<'static, WinitState>,
     ) -> Self {
         Self {
             seats: Vec::new(),
             loop_handle,
-            relative_pointer_manager,
-            pointer_constraints,
+            pointer_globals,
             text_input_manager,
+            data_device_manager,
             theme_manager,
+            custom_cursor_manager,
         }
     }
 
@@ -123,15 +151,42 @@ impl SeatManagerInner {
 
         let seat_info = &mut self.seats[index];
 
+        // Handle data devices, the keyboard relies on them.
+        if seat_data.defunct {
+            seat_info.data_device = None;
+        } else if seat_info.data_device.is_none() {
+            seat_info.data_device = DataDevice::new(seat, &self.data_device_manager);
+        }
+
+        // Handle text input, the keyboard relies on it with text input v1.
+        match self.text_input_manager.as_ref() {
+            _ if seat_data.defunct => {
+                seat_info.text_input = None;
+                seat_info.text_input_v2 = None;
+                seat_info.text_input_v1 = None;
+            }
+            Some(TextInputManager::V3(text_input_manager)) if seat_info.text_input.is_none() => {
+                seat_info.text_input = Some(TextInput::new(seat, text_input_manager));
+            }
+            Some(TextInputManager::V2(text_input_manager)) if seat_info.text_input_v2.is_none() => {
+                seat_info.text_input_v2 = Some(TextInputV2::new(seat, text_input_manager));
+            }
+            Some(TextInputManager::V1(text_input_manager)) if seat_info.text_input_v1.is_none() => {
+                seat_info.text_input_v1 = Some(TextInputV1::new(seat, text_input_manager));
+            }
+            _ => (),
+        }
+
         // Pointer handling.
         if seat_data.has_pointer && !seat_data.defunct {
             if seat_info.pointer.is_none() {
                 seat_info.pointer = Some(Pointers::new(
                     seat,
                     &self.theme_manager,
-                    &self.relative_pointer_manager,
-                    &self.pointer_constraints,
+                    &self.pointer_globals,
                     seat_info.modifiers_state.clone(),
+                    seat_info.data_device.as_ref().map(DataDevice::handler),
+                    self.custom_cursor_manager.as_ref(),
                 ));
             }
         } else {
@@ -141,11 +196,13 @@ impl SeatManagerInner {
         // Handle keyboard.
         if seat_data.has_keyboard && !seat_data.defunct {
             if seat_info.keyboard.is_none() {
-                seat_info.keyboard = Keyboard::new(
+                seat_info.keyboard = Some(Keyboard::new(
                     seat,
                     self.loop_handle.clone(),
                     seat_info.modifiers_state.clone(),
-                );
+                    seat_info.data_device.as_ref().map(DataDevice::handler),
+                    seat_info.text_input_v1.as_ref().map(TextInputV1::focus),
+                ));
             }
         } else {
             seat_info.keyboard = None;
@@ -159,18 +216,18 @@ impl SeatManagerInner {
         } else {
             seat_info.touch = None;
         }
-
-        // Handle text input.
-        if let Some(text_input_manager) = self.text_input_manager.as_ref() {
-            if seat_data.defunct {
-                seat_info.text_input = None;
-            } else if seat_info.text_input.is_none() {
-                seat_info.text_input = Some(TextInput::new(seat, text_input_manager));
-            }
-        }
     }
 }
 
+/// The manager of the text input protocol supported by the compositor.
+enum TextInputManager {
+    V3(Attached<ZwpTextInputManagerV3>),
+    /// Fallback for compositors without text input v3.
+    V2(Attached<ZwpTextInputManagerV2>),
+    /// Fallback for compositors without text input v3 or v2.
+    V1(Attached<ZwpTextInputManagerV1>),
+}
+
 /// Resources associtated with a given seat.
 struct SeatInfo {
     /// Seat to which this `SeatInfo` belongs.
@@ -188,6 +245,13 @@ struct SeatInfo {
     /// Text input handling aka IME.
     text_input: Option<TextInput>,
 
+    /// Text input handling with the older protocols.
+    text_input_v2: Option<TextInputV2>,
+    text_input_v1: Option<TextInputV1>,
+
+    /// Clipboard handling.
+    data_device: Option<DataDevice>,
+
     /// The current state of modifiers observed in keyboard handler.
     ///
     /// We keep modifiers state on a seat, since it's being used by pointer events as well.
@@ -202,6 +266,9 @@ impl SeatInfo {
             pointer: None,
             touch: None,
             text_input: None,
+            text_input_v2: None,
+            text_input_v1: None,
+            data_device: None,
             modifiers_state: Rc::new(RefCell::new(ModifiersState::default())),
         }
     }
@@ -0,0 +1,290 @@
+//! Custom cursor images drawn on a cursor surface.
+
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::process;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sctk::environment::Environment;
+use sctk::reexports::calloop::timer::{TimeoutAction, Timer};
+use sctk::reexports::calloop::{LoopHandle, RegistrationToken};
+use sctk::reexports::client::protocol::wl_buffer::WlBuffer;
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_shm::{Format, WlShm};
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
+
+use crate::cursor::CursorImage;
+use crate::platform_impl::wayland::env::WinitEnv;
+use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::window::CustomCursor;
+
+/// The shortest time a frame of an animated cursor is shown, so a zero frame duration doesn't
+/// keep the event loop busy.
+const MIN_FRAME_DURATION: Duration = Duration::from_millis(1);
+
+/// Shared state used to create cursor surfaces.
+#[derive(Clone)]
+pub struct CustomCursorManager {
+    compositor: Attached<WlCompositor>,
+
+    /// Shared memory the cursor images are copied into.
+    shm: Attached<WlShm>,
+
+    /// Loop handle, used to animate cursors.
+    loop_handle: LoopHandle<'static, WinitState>,
+}
+
+impl CustomCursorManager {
+    pub fn new(
+        env: &Environment<WinitEnv>,
+        loop_handle: LoopHandle<'static, WinitState>,
+    ) -> Option<Self> {
+        Some(Self {
+            compositor: env.require_global::<WlCompositor>(),
+            shm: env.get_global::<WlShm>()?,
+            loop_handle,
+        })
+    }
+
+    pub fn create_surface(&self) -> Rc<CursorSurface> {
+        let surface = self.compositor.create_surface();
+        surface.quick_assign(|_, _, _| {});
+
+        Rc::new(CursorSurface {
+            manager: self.clone(),
+            surface: surface.detach(),
+            buffers: RefCell::new(None),
+            animation: Cell::new(None),
+        })
+    }
+
+    /// Uploads the frames of `cursor`, scaled down by `scale`.
+    fn create_buffers(&self, cursor: &CustomCursor, scale: i32) -> io::Result<CursorBuffers> {
+        let mut data = Vec::new();
+        let mut offsets = Vec::with_capacity(cursor.images.len());
+        for image in cursor.images.iter() {
+            offsets.push(data.len() as i32);
+            for pixel in image.argb.iter() {
+                data.extend_from_slice(&pixel.to_le_bytes());
+            }
+        }
+
+        let file = anonymous_file(&data)?;
+        let pool = self.shm.create_pool(file.as_raw_fd(), data.len() as i32);
+        let buffers = cursor
+            .images
+            .iter()
+            .zip(offsets)
+            .map(|(image, offset)| {
+                let width = image.width as i32;
+                let height = image.height as i32;
+                let buffer = pool.create_buffer(offset, width, height, width * 4, Format::Argb8888);
+                // The buffers are never written to again, so they can be attached any time.
+                buffer.quick_assign(|_, _, _| {});
+                buffer.detach()
+            })
+            .collect();
+        // The buffers keep the memory alive.
+        pool.destroy();
+
+        Ok(CursorBuffers {
+            images: cursor.images.clone(),
+            scale,
+            buffers,
+        })
+    }
+}
+
+/// The frames of a custom cursor, uploaded to the compositor.
+struct CursorBuffers {
+    /// The cursor the buffers were created for, compared by identity.
+    images: Arc<Vec<CursorImage>>,
+
+    /// The buffer scale the frames are shown with.
+    scale: i32,
+
+    /// A buffer per frame.
+    buffers: Vec<WlBuffer>,
+}
+
+impl Drop for CursorBuffers {
+    fn drop(&mut self) {
+        for buffer in self.buffers.iter() {
+            buffer.destroy();
+        }
+    }
+}
+
+/// A pointer's surface for custom cursors.
+pub struct CursorSurface {
+    manager: CustomCursorManager,
+
+    surface: WlSurface,
+
+    /// The frames of the last shown cursor, reused while it stays the same.
+    buffers: RefCell<Option<CursorBuffers>>,
+
+    /// The timer animating the current cursor.
+    animation: Cell<Option<RegistrationToken>>,
+}
+
+impl CursorSurface {
+    /// Stops animating the current custom cursor, if any.
+    pub fn stop(&self) {
+        if let Some(animation) = self.animation.take() {
+            self.manager.loop_handle.remove(animation);
+        }
+    }
+
+    /// Shows `cursor` on the pointer, animating it when it has multiple frames.
+    ///
+    /// `scale_factor` is the one of the surface under the pointer, so the cursor images keep
+    /// their size in physical pixels.
+    pub fn show(
+        self: &Rc<Self>,
+        pointer: &WlPointer,
+        serial: u32,
+        cursor: &CustomCursor,
+        scale_factor: f64,
+    ) {
+        self.stop();
+
+        let scale = buffer_scale(cursor, scale_factor);
+        let cached = matches!(
+            self.buffers.borrow().as_ref(),
+            Some(buffers) if Arc::ptr_eq(&buffers.images, &cursor.images) && buffers.scale == scale
+        );
+        if !cached {
+            let buffers = match self.manager.create_buffers(cursor, scale) {
+                Ok(buffers) => buffers,
+                Err(err) => {
+                    warn!("Failed to allocate the custom cursor buffers: {}", err);
+                    return;
+                }
+            };
+            self.surface.set_buffer_scale(scale);
+            self.show_frame(pointer, serial, &buffers, 0);
+            // The old buffers are only destroyed once they're no longer shown.
+            self.buffers.replace(Some(buffers));
+        } else {
+            self.show_frame(pointer, serial, self.buffers.borrow().as_ref().unwrap(), 0);
+        }
+
+        let images = cursor.images.clone();
+        if images.len() == 1 {
+            return;
+        }
+
+        let cursor_surface: Weak<Self> = Rc::downgrade(self);
+        let pointer = pointer.clone();
+        let mut index = 0;
+
+        let timer = Timer::from_duration(images[0].duration.max(MIN_FRAME_DURATION));
+        let result = self
+            .manager
+            .loop_handle
+            .insert_source(timer, move |_, _, _| {
+                let cursor_surface = match cursor_surface.upgrade() {
+                    Some(cursor_surface) => cursor_surface,
+                    // The pointer is gone.
+                    None => return TimeoutAction::Drop,
+                };
+
+                index = (index + 1) % images.len();
+                if let Some(buffers) = cursor_surface.buffers.borrow().as_ref() {
+                    cursor_surface.show_frame(&pointer, serial, buffers, index);
+                }
+
+                TimeoutAction::ToDuration(images[index].duration.max(MIN_FRAME_DURATION))
+            });
+
+        match result {
+            Ok(animation) => self.animation.set(Some(animation)),
+            Err(err) => warn!("Failed to animate the custom cursor: {}", err),
+        }
+    }
+
+    fn show_frame(&self, pointer: &WlPointer, serial: u32, buffers: &CursorBuffers, index: usize) {
+        let image = &buffers.images[index];
+        let width = image.width as i32;
+        let height = image.height as i32;
+
+        self.surface.attach(Some(&buffers.buffers[index]), 0, 0);
+        if self.surface.as_ref().version() >= 4 {
+            self.surface.damage_buffer(0, 0, width, height);
+        } else {
+            self.surface.damage(0, 0, width, height);
+        }
+        self.surface.commit();
+
+        // The hotspot is in surface coordinates.
+        pointer.set_cursor(
+            serial,
+            Some(&self.surface),
+            image.hotspot_x as i32 / buffers.scale,
+            image.hotspot_y as i32 / buffers.scale,
+        );
+    }
+}
+
+impl Drop for CursorSurface {
+    fn drop(&mut self) {
+        self.stop();
+        self.buffers.replace(None);
+        self.surface.destroy();
+    }
+}
+
+/// Picks the integer buffer scale closest to `scale_factor` that divides the size of every
+/// frame, since the compositor rejects buffers whose size isn't a multiple of the scale.
+fn buffer_scale(cursor: &CustomCursor, scale_factor: f64) -> i32 {
+    let scale = (scale_factor.round() as i32).max(1);
+    let divides = |scale: u32| {
+        cursor
+            .images
+            .iter()
+            .all(|image| image.width % scale == 0 && image.height % scale == 0)
+    };
+    (1..=scale)
+        .rev()
+        .find(|&scale| divides(scale as u32))
+        .unwrap_or(1)
+}
+
+/// Creates an unlinked file holding `data`, to share it with the compositor.
+fn anonymous_file(data: &[u8]) -> io::Result<File> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "XDG_RUNTIME_DIR is not set"))?;
+    loop {
+        let path = runtime_dir.join(format!(
+            "winit-cursor-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut file = match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        };
+        fs::remove_file(&path)?;
+        file.write_all(data)?;
+        return Ok(file);
+    }
+}
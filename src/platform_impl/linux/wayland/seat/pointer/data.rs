@@ -2,6 +2,7 @@
 
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use std::time::Instant;
 
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
@@ -10,6 +11,9 @@ use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_c
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
 
 use crate::event::{ModifiersState, TouchPhase};
+use crate::platform_impl::wayland::seat::data_device::DataDeviceHandler;
+
+use super::CursorSurface;
 
 /// A data being used by pointer handlers.
 pub(super) struct PointerData {
@@ -34,8 +38,17 @@ pub(super) struct PointerData {
     /// Latest observed serial in pointer enter events.
     pub latest_enter_serial: Rc<Cell<u32>>,
 
+    /// Data device of the seat, used to start drags.
+    pub data_device: Option<DataDeviceHandler>,
+
+    /// Surface showing custom cursors.
+    pub custom_cursor: Option<Rc<CursorSurface>>,
+
     /// The currently accumulated axis data on a pointer.
     pub axis_data: AxisData,
+
+    /// Number of buttons held over the current surface.
+    pub pressed_buttons: u32,
 }
 
 impl PointerData {
@@ -44,6 +57,8 @@ impl PointerData {
         locked_pointer: Rc<RefCell<Option<ZwpLockedPointerV1>>>,
         pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
         modifiers_state: Rc<RefCell<ModifiersState>>,
+        data_device: Option<DataDeviceHandler>,
+        custom_cursor: Option<Rc<CursorSurface>>,
     ) -> Self {
         Self {
             surface: None,
@@ -53,7 +68,10 @@ impl PointerData {
             locked_pointer,
             modifiers_state,
             pointer_constraints,
+            data_device,
+            custom_cursor,
             axis_data: AxisData::new(),
+            pressed_buttons: 0,
         }
     }
 }
@@ -69,6 +87,9 @@ pub(super) struct AxisData {
 
     /// A buffer for `LineDelta` event.
     pub axis_discrete_buffer: Option<(f32, f32)>,
+
+    /// The time of the latest buffered axis event.
+    pub axis_time: Option<Instant>,
 }
 
 impl AxisData {
@@ -77,6 +98,7 @@ impl AxisData {
             axis_state: TouchPhase::Ended,
             axis_buffer: None,
             axis_discrete_buffer: None,
+            axis_time: None,
         }
     }
 }
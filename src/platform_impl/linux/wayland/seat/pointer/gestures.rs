@@ -0,0 +1,255 @@
+//! Touchpad gestures.
+
+use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use crate::platform_impl::wayland::protocols::pointer_gestures::v1::client::zwp_pointer_gesture_hold_v1::{
+    Event as HoldEvent, ZwpPointerGestureHoldV1,
+};
+use crate::platform_impl::wayland::protocols::pointer_gestures::v1::client::zwp_pointer_gesture_pinch_v1::{
+    Event as PinchEvent, ZwpPointerGesturePinchV1,
+};
+use crate::platform_impl::wayland::protocols::pointer_gestures::v1::client::zwp_pointer_gesture_swipe_v1::{
+    Event as SwipeEvent, ZwpPointerGestureSwipeV1,
+};
+use crate::platform_impl::wayland::protocols::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
+
+use crate::dpi::LogicalPosition;
+use crate::event::{TouchPhase, WindowEvent};
+use crate::platform_impl::platform::instant_from_event_time;
+use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::{self, DeviceId};
+
+/// The gestures of a pointer.
+pub(super) struct PointerGestures {
+    swipe: ZwpPointerGestureSwipeV1,
+    pinch: ZwpPointerGesturePinchV1,
+
+    /// Only available since version 3 of the protocol.
+    hold: Option<ZwpPointerGestureHoldV1>,
+}
+
+impl PointerGestures {
+    pub(super) fn new(pointer_gestures: &ZwpPointerGesturesV1, pointer: &WlPointer) -> Self {
+        let swipe = pointer_gestures.get_swipe_gesture(pointer);
+        let mut swipe_data = SwipeData::default();
+        swipe.quick_assign(move |_, event, mut dispatch_data| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            handle_swipe(event, &mut swipe_data, winit_state);
+        });
+
+        let pinch = pointer_gestures.get_pinch_gesture(pointer);
+        let mut pinch_data = PinchData::default();
+        pinch.quick_assign(move |_, event, mut dispatch_data| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            handle_pinch(event, &mut pinch_data, winit_state);
+        });
+
+        let hold = if pointer_gestures.as_ref().version() >= 3 {
+            let hold = pointer_gestures.get_hold_gesture(pointer);
+            let mut hold_data = HoldData::default();
+            hold.quick_assign(move |_, event, mut dispatch_data| {
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                handle_hold(event, &mut hold_data, winit_state);
+            });
+            Some(hold.detach())
+        } else {
+            None
+        };
+
+        Self {
+            swipe: swipe.detach(),
+            pinch: pinch.detach(),
+            hold,
+        }
+    }
+}
+
+impl Drop for PointerGestures {
+    fn drop(&mut self) {
+        self.swipe.destroy();
+        self.pinch.destroy();
+        if let Some(hold) = self.hold.take() {
+            hold.destroy();
+        }
+    }
+}
+
+#[derive(Default)]
+struct SwipeData {
+    /// Surface the swipe started on.
+    surface: Option<WlSurface>,
+
+    /// Number of fingers of the swipe.
+    fingers: u32,
+}
+
+#[derive(Default)]
+struct PinchData {
+    /// Surface the pinch started on.
+    surface: Option<WlSurface>,
+
+    /// Scale of the previous event, the compositor sends the scale since the start.
+    scale: f64,
+}
+
+#[derive(Default)]
+struct HoldData {
+    /// Surface the hold started on.
+    surface: Option<WlSurface>,
+
+    /// Number of fingers of the hold.
+    fingers: u32,
+}
+
+fn handle_swipe(event: SwipeEvent, swipe_data: &mut SwipeData, winit_state: &mut WinitState) {
+    let (time, delta, phase) = match event {
+        SwipeEvent::Begin {
+            time,
+            surface,
+            fingers,
+            ..
+        } => {
+            swipe_data.surface = Some(surface);
+            swipe_data.fingers = fingers;
+            (time, (0., 0.), TouchPhase::Started)
+        }
+        SwipeEvent::Update { time, dx, dy } => (time, (dx, dy), TouchPhase::Moved),
+        SwipeEvent::End {
+            time, cancelled, ..
+        } => {
+            let phase = if cancelled != 0 {
+                TouchPhase::Cancelled
+            } else {
+                TouchPhase::Ended
+            };
+            (time, (0., 0.), phase)
+        }
+    };
+
+    let surface = match swipe_data.surface.as_ref() {
+        Some(surface) => surface,
+        None => return,
+    };
+
+    let scale_factor = wayland::scale_factor(&winit_state.window_map, surface);
+    winit_state.event_sink.push_window_event(
+        WindowEvent::SwipeGesture {
+            device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId)),
+            delta: LogicalPosition::new(delta.0, delta.1).to_physical(scale_factor),
+            fingers: swipe_data.fingers,
+            phase,
+            timestamp: instant_from_event_time(time),
+        },
+        wayland::make_wid(surface),
+    );
+
+    if phase == TouchPhase::Ended || phase == TouchPhase::Cancelled {
+        swipe_data.surface = None;
+    }
+}
+
+fn handle_pinch(event: PinchEvent, pinch_data: &mut PinchData, winit_state: &mut WinitState) {
+    let (time, scale_delta, rotation, phase) = match event {
+        PinchEvent::Begin { time, surface, .. } => {
+            pinch_data.surface = Some(surface);
+            pinch_data.scale = 1.;
+            (time, 0., 0., TouchPhase::Started)
+        }
+        PinchEvent::Update {
+            time,
+            scale,
+            rotation,
+            ..
+        } => {
+            let scale_delta = scale - pinch_data.scale;
+            pinch_data.scale = scale;
+            (time, scale_delta, rotation, TouchPhase::Moved)
+        }
+        PinchEvent::End {
+            time, cancelled, ..
+        } => {
+            let phase = if cancelled != 0 {
+                TouchPhase::Cancelled
+            } else {
+                TouchPhase::Ended
+            };
+            (time, 0., 0., phase)
+        }
+    };
+
+    let window_id = match pinch_data.surface.as_ref() {
+        Some(surface) => wayland::make_wid(surface),
+        None => return,
+    };
+
+    let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+    let timestamp = instant_from_event_time(time);
+    let event_sink = &mut winit_state.event_sink;
+    event_sink.push_window_event(
+        WindowEvent::PinchGesture {
+            device_id,
+            delta: scale_delta,
+            phase,
+            timestamp,
+        },
+        window_id,
+    );
+    // The compositor sends the rotation clockwise.
+    event_sink.push_window_event(
+        WindowEvent::RotationGesture {
+            device_id,
+            delta: -rotation as f32,
+            phase,
+            timestamp,
+        },
+        window_id,
+    );
+
+    if phase == TouchPhase::Ended || phase == TouchPhase::Cancelled {
+        pinch_data.surface = None;
+    }
+}
+
+fn handle_hold(event: HoldEvent, hold_data: &mut HoldData, winit_state: &mut WinitState) {
+    let (time, phase) = match event {
+        HoldEvent::Begin {
+            time,
+            surface,
+            fingers,
+            ..
+        } => {
+            hold_data.surface = Some(surface);
+            hold_data.fingers = fingers;
+            (time, TouchPhase::Started)
+        }
+        HoldEvent::End {
+            time, cancelled, ..
+        } => {
+            let phase = if cancelled != 0 {
+                TouchPhase::Cancelled
+            } else {
+                TouchPhase::Ended
+            };
+            (time, phase)
+        }
+    };
+
+    let window_id = match hold_data.surface.as_ref() {
+        Some(surface) => wayland::make_wid(surface),
+        None => return,
+    };
+
+    winit_state.event_sink.push_window_event(
+        WindowEvent::HoldGesture {
+            device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId)),
+            fingers: hold_data.fingers,
+            phase,
+            timestamp: instant_from_event_time(time),
+        },
+        window_id,
+    );
+
+    if phase == TouchPhase::Ended || phase == TouchPhase::Cancelled {
+        hold_data.surface = None;
+    }
+}
@@ -2,6 +2,8 @@
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
 
 use sctk::reexports::client::protocol::wl_pointer::{self, Event as PointerEvent};
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
@@ -13,6 +15,7 @@ use crate::dpi::LogicalPosition;
 use crate::event::{
     DeviceEvent, ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
 };
+use crate::platform_impl::platform::instant_from_event_time;
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId};
 
@@ -53,7 +56,7 @@ pub(super) fn handle_pointer(
                 None => return,
             };
 
-            let scale_factor = sctk::get_surface_scale_factor(&surface) as f64;
+            let scale_factor = window_handle.scale_factor();
             pointer_data.surface = Some(surface);
 
             // Notify window that pointer entered the surface.
@@ -64,6 +67,8 @@ pub(super) fn handle_pointer(
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
                 latest_enter_serial: pointer_data.latest_enter_serial.clone(),
+                data_device: pointer_data.data_device.clone(),
+                custom_cursor: pointer_data.custom_cursor.clone(),
                 seat,
             };
             window_handle.pointer_entered(winit_pointer);
@@ -85,6 +90,8 @@ pub(super) fn handle_pointer(
                         DeviceId,
                     )),
                     position,
+                    // `wl_pointer::enter` doesn't have a timestamp.
+                    timestamp: Instant::now(),
                     modifiers: *pointer_data.modifiers_state.borrow(),
                 },
                 window_id,
@@ -93,6 +100,8 @@ pub(super) fn handle_pointer(
         PointerEvent::Leave { surface, serial } => {
             pointer_data.surface = None;
             pointer_data.latest_serial.replace(serial);
+            // The compositor may end the implicit grab without releasing the buttons.
+            pointer_data.pressed_buttons = 0;
 
             let window_id = wayland::make_wid(&surface);
 
@@ -100,6 +109,9 @@ pub(super) fn handle_pointer(
                 Some(window_handle) => window_handle,
                 None => return,
             };
+            window_handle
+                .pointer_button_pressed
+                .store(false, Ordering::Relaxed);
 
             // Notify a window that pointer is no longer observing it.
             let winit_pointer = WinitPointer {
@@ -109,6 +121,8 @@ pub(super) fn handle_pointer(
                 pointer_constraints: pointer_data.pointer_constraints.clone(),
                 latest_serial: pointer_data.latest_serial.clone(),
                 latest_enter_serial: pointer_data.latest_enter_serial.clone(),
+                data_device: pointer_data.data_device.clone(),
+                custom_cursor: pointer_data.custom_cursor.clone(),
                 seat,
             };
             window_handle.pointer_left(winit_pointer);
@@ -123,9 +137,9 @@ pub(super) fn handle_pointer(
             );
         }
         PointerEvent::Motion {
+            time,
             surface_x,
             surface_y,
-            ..
         } => {
             let surface = match pointer_data.surface.as_ref() {
                 Some(surface) => surface,
@@ -134,7 +148,7 @@ pub(super) fn handle_pointer(
 
             let window_id = wayland::make_wid(surface);
 
-            let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+            let scale_factor = wayland::scale_factor(&winit_state.window_map, surface);
             let position = LogicalPosition::new(surface_x, surface_y).to_physical(scale_factor);
 
             event_sink.push_window_event(
@@ -143,6 +157,7 @@ pub(super) fn handle_pointer(
                         DeviceId,
                     )),
                     position,
+                    timestamp: instant_from_event_time(time),
                     modifiers: *pointer_data.modifiers_state.borrow(),
                 },
                 window_id,
@@ -152,7 +167,7 @@ pub(super) fn handle_pointer(
             button,
             state,
             serial,
-            ..
+            time,
         } => {
             pointer_data.latest_serial.replace(serial);
             let window_id = match pointer_data.surface.as_ref().map(wayland::make_wid) {
@@ -166,6 +181,16 @@ pub(super) fn handle_pointer(
                 _ => unreachable!(),
             };
 
+            pointer_data.pressed_buttons = match state {
+                ElementState::Pressed => pointer_data.pressed_buttons + 1,
+                ElementState::Released => pointer_data.pressed_buttons.saturating_sub(1),
+            };
+            if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                window_handle
+                    .pointer_button_pressed
+                    .store(pointer_data.pressed_buttons > 0, Ordering::Relaxed);
+            }
+
             let button = match button {
                 BTN_LEFT => MouseButton::Left,
                 BTN_RIGHT => MouseButton::Right,
@@ -180,12 +205,13 @@ pub(super) fn handle_pointer(
                     )),
                     state,
                     button,
+                    timestamp: instant_from_event_time(time),
                     modifiers: *pointer_data.modifiers_state.borrow(),
                 },
                 window_id,
             );
         }
-        PointerEvent::Axis { axis, value, .. } => {
+        PointerEvent::Axis { axis, value, time } => {
             let surface = match pointer_data.surface.as_ref() {
                 Some(surface) => surface,
                 None => return,
@@ -204,7 +230,7 @@ pub(super) fn handle_pointer(
                     _ => unreachable!(),
                 }
 
-                let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+                let scale_factor = wayland::scale_factor(&winit_state.window_map, surface);
                 let delta = LogicalPosition::new(x as f64, y as f64).to_physical(scale_factor);
 
                 event_sink.push_window_event(
@@ -214,6 +240,7 @@ pub(super) fn handle_pointer(
                         )),
                         delta: MouseScrollDelta::PixelDelta(delta),
                         phase: TouchPhase::Moved,
+                        timestamp: instant_from_event_time(time),
                         modifiers: *pointer_data.modifiers_state.borrow(),
                     },
                     window_id,
@@ -228,6 +255,7 @@ pub(super) fn handle_pointer(
                 }
 
                 pointer_data.axis_data.axis_buffer = Some((x, y));
+                pointer_data.axis_data.axis_time = Some(instant_from_event_time(time));
 
                 pointer_data.axis_data.axis_state = match pointer_data.axis_data.axis_state {
                     TouchPhase::Started | TouchPhase::Moved => TouchPhase::Moved,
@@ -262,6 +290,12 @@ pub(super) fn handle_pointer(
         PointerEvent::Frame => {
             let axis_buffer = pointer_data.axis_data.axis_buffer.take();
             let axis_discrete_buffer = pointer_data.axis_data.axis_discrete_buffer.take();
+            // Discrete axis events don't have a timestamp, but come with a regular one.
+            let timestamp = pointer_data
+                .axis_data
+                .axis_time
+                .take()
+                .unwrap_or_else(Instant::now);
 
             let surface = match pointer_data.surface.as_ref() {
                 Some(surface) => surface,
@@ -276,10 +310,11 @@ pub(super) fn handle_pointer(
                     )),
                     delta: MouseScrollDelta::LineDelta(x, y),
                     phase: pointer_data.axis_data.axis_state,
+                    timestamp,
                     modifiers: *pointer_data.modifiers_state.borrow(),
                 }
             } else if let Some((x, y)) = axis_buffer {
-                let scale_factor = sctk::get_surface_scale_factor(surface) as f64;
+                let scale_factor = wayland::scale_factor(&winit_state.window_map, surface);
                 let delta = LogicalPosition::new(x, y).to_physical(scale_factor);
 
                 WindowEvent::MouseWheel {
@@ -288,6 +323,7 @@ pub(super) fn handle_pointer(
                     )),
                     delta: MouseScrollDelta::PixelDelta(delta),
                     phase: pointer_data.axis_data.axis_state,
+                    timestamp,
                     modifiers: *pointer_data.modifiers_state.borrow(),
                 }
             } else {
@@ -12,19 +12,28 @@ use sctk::reexports::protocols::unstable::relative_pointer::v1::client::zwp_rela
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_pointer_constraints_v1::{ZwpPointerConstraintsV1, Lifetime};
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_confined_pointer_v1::ZwpConfinedPointerV1;
 use sctk::reexports::protocols::unstable::pointer_constraints::v1::client::zwp_locked_pointer_v1::ZwpLockedPointerV1;
+use sctk::reexports::protocols::xdg_shell::client::xdg_popup::XdgPopup;
 
 use sctk::seat::pointer::{ThemeManager, ThemedPointer};
-use sctk::window::Window;
 
+use crate::dnd::DragData;
 use crate::event::ModifiersState;
 use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::platform_impl::wayland::window::WinitFrame;
-use crate::window::CursorIcon;
+use crate::platform_impl::wayland::protocols::pointer_gestures::v1::client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1;
+use crate::platform_impl::wayland::seat::data_device::DataDeviceHandler;
+use crate::platform_impl::wayland::window::ShellWindow;
+use crate::window::{CursorIcon, CustomCursor};
 
+mod cursor;
 mod data;
+mod gestures;
 mod handlers;
 
+pub use cursor::CustomCursorManager;
+
+use cursor::CursorSurface;
 use data::PointerData;
+use gestures::PointerGestures;
 
 /// A proxy to Wayland pointer, which serves requests from a `WindowHandle`.
 pub struct WinitPointer {
@@ -47,6 +56,12 @@ pub struct WinitPointer {
     /// used by Window::set_cursor()
     latest_enter_serial: Rc<Cell<u32>>,
 
+    /// Data device of the seat, used to start drags.
+    data_device: Option<DataDeviceHandler>,
+
+    /// Surface showing custom cursors.
+    custom_cursor: Option<Rc<CursorSurface>>,
+
     /// Seat.
     seat: WlSeat,
 }
@@ -64,6 +79,10 @@ impl WinitPointer {
     ///
     /// Providing `None` will hide the cursor.
     pub fn set_cursor(&self, cursor_icon: Option<CursorIcon>) {
+        if let Some(custom_cursor) = self.custom_cursor.as_ref() {
+            custom_cursor.stop();
+        }
+
         let cursor_icon = match cursor_icon {
             Some(cursor_icon) => cursor_icon,
             None => {
@@ -127,6 +146,26 @@ impl WinitPointer {
         warn!("Failed to set cursor to {:?}", cursor_icon);
     }
 
+    /// Set a custom cursor image, for a surface with the given scale factor.
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor, scale_factor: f64) {
+        match self.custom_cursor.as_ref() {
+            Some(custom_cursor) => custom_cursor.show(
+                &self.pointer,
+                self.latest_enter_serial.get(),
+                cursor,
+                scale_factor,
+            ),
+            None => warn!("Custom cursors are unavailable"),
+        }
+    }
+
+    /// Stop animating the custom cursor, since the pointer left the surface.
+    pub fn stop_custom_cursor(&self) {
+        if let Some(custom_cursor) = self.custom_cursor.as_ref() {
+            custom_cursor.stop();
+        }
+    }
+
     /// Confine the pointer to a surface.
     pub fn confine(&self, surface: &WlSurface) {
         let pointer_constraints = match &self.pointer_constraints {
@@ -208,11 +247,37 @@ impl WinitPointer {
         }
     }
 
-    pub fn drag_window(&self, window: &Window<WinitFrame>) {
+    pub fn drag_window(&self, window: &ShellWindow) {
         // WlPointer::setart_interactive_move() expects the last serial of *any*
         // pointer event (compare to set_cursor()).
         window.start_interactive_move(&self.seat, self.latest_serial.get());
     }
+
+    /// Grabs the input of the seat for a popup, which must be opened by the last button press.
+    pub fn grab_popup(&self, popup: &XdgPopup) {
+        popup.grab(&self.seat, self.latest_serial.get());
+    }
+
+    /// Returns whether the drag started, which fails if the seat has no data device.
+    pub fn start_drag(&self, surface: &WlSurface, data: DragData) -> bool {
+        // Like for interactive moves, the serial must be the one of the button press.
+        match self.data_device.as_ref() {
+            Some(data_device) => data_device.start_drag(surface, self.latest_serial.get(), data),
+            None => false,
+        }
+    }
+}
+
+/// Globals extending the pointers, when the compositor supports them.
+pub(super) struct PointerGlobals {
+    /// Relative pointer manager.
+    pub relative_pointer_manager: Option<Attached<ZwpRelativePointerManagerV1>>,
+
+    /// Pointer constraints.
+    pub pointer_constraints: Option<Attached<ZwpPointerConstraintsV1>>,
+
+    /// Touchpad gestures.
+    pub pointer_gestures: Option<Attached<ZwpPointerGesturesV1>>,
 }
 
 /// A pointer wrapper for easy releasing and managing pointers.
@@ -223,6 +288,9 @@ pub(super) struct Pointers {
     /// A relative pointer handler.
     relative_pointer: Option<ZwpRelativePointerV1>,
 
+    /// Touchpad gestures.
+    gestures: Option<PointerGestures>,
+
     /// Confined pointer.
     confined_pointer: Rc<RefCell<Option<ZwpConfinedPointerV1>>>,
 
@@ -234,9 +302,10 @@ impl Pointers {
     pub(super) fn new(
         seat: &Attached<WlSeat>,
         theme_manager: &ThemeManager,
-        relative_pointer_manager: &Option<Attached<ZwpRelativePointerManagerV1>>,
-        pointer_constraints: &Option<Attached<ZwpPointerConstraintsV1>>,
+        pointer_globals: &PointerGlobals,
         modifiers_state: Rc<RefCell<ModifiersState>>,
+        data_device: Option<DataDeviceHandler>,
+        custom_cursor_manager: Option<&CustomCursorManager>,
     ) -> Self {
         let confined_pointer = Rc::new(RefCell::new(None));
         let locked_pointer = Rc::new(RefCell::new(None));
@@ -244,8 +313,10 @@ impl Pointers {
         let pointer_data = Rc::new(RefCell::new(PointerData::new(
             confined_pointer.clone(),
             locked_pointer.clone(),
-            pointer_constraints.clone(),
+            pointer_globals.pointer_constraints.clone(),
             modifiers_state,
+            data_device,
+            custom_cursor_manager.map(CustomCursorManager::create_surface),
         )));
 
         let pointer_seat = seat.detach();
@@ -264,15 +335,23 @@ impl Pointers {
         );
 
         // Setup relative_pointer if it's available.
-        let relative_pointer = relative_pointer_manager
+        let relative_pointer =
+            pointer_globals
+                .relative_pointer_manager
+                .as_ref()
+                .map(|relative_pointer_manager| {
+                    init_relative_pointer(relative_pointer_manager, &*pointer)
+                });
+
+        let gestures = pointer_globals
+            .pointer_gestures
             .as_ref()
-            .map(|relative_pointer_manager| {
-                init_relative_pointer(relative_pointer_manager, &*pointer)
-            });
+            .map(|pointer_gestures| PointerGestures::new(pointer_gestures, &pointer));
 
         Self {
             pointer,
             relative_pointer,
+            gestures,
             confined_pointer,
             locked_pointer,
         }
@@ -286,6 +365,9 @@ impl Drop for Pointers {
             relative_pointer.destroy();
         }
 
+        // Drop gestures.
+        self.gestures = None;
+
         // Drop confined pointer.
         if let Some(confined_pointer) = self.confined_pointer.borrow_mut().take() {
             confined_pointer.destroy();
@@ -29,17 +29,16 @@ pub(super) fn handle_text_input(
             };
             inner.target_window_id = Some(window_id);
 
+            let text_input_handler = TextInputHandler::V3(text_input.detach());
+
             // Enable text input on that surface.
             if window_handle.ime_allowed.get() {
-                text_input.enable();
-                text_input.commit();
+                let state = window_handle.text_input_state.borrow();
+                text_input_handler.set_input_allowed(true, &state);
                 event_sink.push_window_event(WindowEvent::Ime(Ime::Enabled), window_id);
             }
 
             // Notify a window we're currently over about text input handler.
-            let text_input_handler = TextInputHandler {
-                text_input: text_input.detach(),
-            };
             window_handle.text_input_entered(text_input_handler);
         }
         TextInputEvent::Leave { surface } => {
@@ -57,9 +56,7 @@ pub(super) fn handle_text_input(
             inner.target_window_id = None;
 
             // Remove text input handler from the window we're leaving.
-            let text_input_handler = TextInputHandler {
-                text_input: text_input.detach(),
-            };
+            let text_input_handler = TextInputHandler::V3(text_input.detach());
             window_handle.text_input_left(text_input_handler);
             event_sink.push_window_event(WindowEvent::Ime(Ime::Disabled), window_id);
         }
@@ -77,6 +74,12 @@ pub(super) fn handle_text_input(
                 cursor_end,
             });
         }
+        TextInputEvent::DeleteSurroundingText {
+            before_length,
+            after_length,
+        } => {
+            inner.pending_delete = Some((before_length as usize, after_length as usize));
+        }
         TextInputEvent::CommitString { text } => {
             // Update currenly commited string and reset previous preedit.
             inner.pending_preedit = None;
@@ -88,6 +91,15 @@ pub(super) fn handle_text_input(
                 _ => return,
             };
 
+            // The deletion applies before the commit, per the protocol.
+            if let Some((before_length, after_length)) = inner.pending_delete.take() {
+                let event = Ime::DeleteSurrounding {
+                    before_length,
+                    after_length,
+                };
+                event_sink.push_window_event(WindowEvent::Ime(event), window_id);
+            }
+
             if let Some(text) = inner.pending_commit.take() {
                 event_sink.push_window_event(WindowEvent::Ime(Ime::Commit(text)), window_id);
             }
@@ -98,7 +110,7 @@ pub(super) fn handle_text_input(
                     .cursor_begin
                     .map(|b| (b, preedit.cursor_end.unwrap_or(b)));
 
-                let event = Ime::Preedit(preedit.text, cursor_range);
+                let event = Ime::Preedit(preedit.text, cursor_range, Vec::new());
                 event_sink.push_window_event(WindowEvent::Ime(event), window_id);
             }
         }
@@ -1,35 +1,268 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use sctk::reexports::client::protocol::wl_keyboard::KeyState;
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Attached;
+use sctk::reexports::protocols::unstable::text_input::v1::client::zwp_text_input_v1::ZwpTextInputV1;
 use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3;
-use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_v3::ZwpTextInputV3;
+use sctk::reexports::protocols::unstable::text_input::v3::client::zwp_text_input_v3::{
+    ContentHint, ContentPurpose, ZwpTextInputV3,
+};
 
-use crate::platform_impl::wayland::event_loop::WinitState;
-use crate::platform_impl::wayland::WindowId;
+use crate::event::{ElementState, Ime, KeyboardInput, ModifiersState, PreeditSpan, WindowEvent};
+use crate::platform_impl::platform::common::keymap;
+use crate::platform_impl::platform::instant_from_event_time;
+use crate::platform_impl::wayland::event_loop::{EventSink, WinitState};
+use crate::platform_impl::wayland::protocols::text_input::v2::client::zwp_text_input_v2::ZwpTextInputV2;
+use crate::platform_impl::wayland::{DeviceId, WindowId};
+use crate::window::{ImeHints, ImePurpose};
 
 mod handlers;
+mod v1;
+mod v2;
+
+pub use v1::{TextInputV1, TextInputV1Focus};
+pub use v2::TextInputV2;
+
+/// The largest surrounding text the protocol allows, in bytes.
+const MAX_SURROUNDING_TEXT_LENGTH: usize = 4000;
 
 /// A handler for text input that we're advertising for `WindowHandle`.
 #[derive(Eq, PartialEq)]
-pub struct TextInputHandler {
-    text_input: ZwpTextInputV3,
+pub enum TextInputHandler {
+    V3(ZwpTextInputV3),
+
+    /// Text input v1 isn't created for a seat, so it's activated with the seat on the surface.
+    V1 {
+        text_input: ZwpTextInputV1,
+        seat: WlSeat,
+        surface: WlSurface,
+    },
+
+    /// Text input v2 is enabled per surface, and its state updates carry the serial of the last
+    /// `enter` or `input_method_changed` event.
+    V2 {
+        text_input: ZwpTextInputV2,
+        surface: WlSurface,
+        serial: Rc<Cell<u32>>,
+    },
 }
 
 impl TextInputHandler {
     #[inline]
-    pub fn set_ime_position(&self, x: i32, y: i32) {
-        self.text_input.set_cursor_rectangle(x, y, 0, 0);
-        self.text_input.commit();
+    pub fn set_state(&self, state: &TextInputState) {
+        match self {
+            TextInputHandler::V3(text_input) => {
+                send_state(text_input, state);
+                text_input.commit();
+            }
+            TextInputHandler::V1 { text_input, .. } => v1::set_state(text_input, state),
+            TextInputHandler::V2 {
+                text_input, serial, ..
+            } => v2::set_state(text_input, serial.get(), state, false),
+        }
     }
 
     #[inline]
-    pub fn set_input_allowed(&self, allowed: bool) {
-        if allowed {
-            self.text_input.enable();
+    pub fn set_input_allowed(&self, allowed: bool, state: &TextInputState) {
+        match self {
+            TextInputHandler::V3(text_input) => {
+                if allowed {
+                    text_input.enable();
+                    // Enabling resets the state, so it's sent again along with the request.
+                    send_state(text_input, state);
+                } else {
+                    text_input.disable();
+                }
+
+                text_input.commit();
+            }
+            TextInputHandler::V1 {
+                text_input,
+                seat,
+                surface,
+            } => {
+                if allowed {
+                    text_input.activate(seat, surface);
+                    v1::set_state(text_input, state);
+                    text_input.show_input_panel();
+                } else {
+                    text_input.deactivate(seat);
+                }
+            }
+            TextInputHandler::V2 {
+                text_input,
+                surface,
+                serial,
+            } => {
+                if allowed {
+                    text_input.enable(surface);
+                    v2::set_state(text_input, serial.get(), state, true);
+                    text_input.show_input_panel();
+                } else {
+                    text_input.disable(surface);
+                }
+            }
+        }
+    }
+}
+
+fn send_state(text_input: &ZwpTextInputV3, state: &TextInputState) {
+    let (x, y, width, height) = state.cursor_area;
+    text_input.set_cursor_rectangle(x, y, width, height);
+
+    if let Some((text, cursor, anchor)) = state.surrounding_text.as_ref() {
+        text_input.set_surrounding_text(text.clone(), *cursor, *anchor);
+    }
+
+    let hint = ContentHint::from_bits_truncate(state.hints.bits());
+    text_input.set_content_type(hint, content_purpose(state.purpose));
+}
+
+/// The IME state of a window, which is sent to the text inputs on its surface.
+#[derive(Default)]
+pub struct TextInputState {
+    /// The position and size of the cursor, in surface coordinates.
+    pub cursor_area: (i32, i32, i32, i32),
+
+    /// The text around the cursor, with the byte offsets of the cursor and of the anchor.
+    pub surrounding_text: Option<(String, i32, i32)>,
+
+    pub purpose: ImePurpose,
+
+    pub hints: ImeHints,
+}
+
+impl TextInputState {
+    /// Sets the surrounding text, trimmed around the cursor to fit in a message.
+    pub fn set_surrounding_text(&mut self, text: &str, cursor: usize, anchor: usize) {
+        // Keep the selection in view, centered, or the text around the cursor when it doesn't
+        // fit.
+        let (start, end) = (cursor.min(anchor), cursor.max(anchor));
+        let center = if end - start <= MAX_SURROUNDING_TEXT_LENGTH {
+            start + (end - start) / 2
         } else {
-            self.text_input.disable();
+            cursor
+        };
+
+        let max_start = text.len().saturating_sub(MAX_SURROUNDING_TEXT_LENGTH);
+        let mut start = center
+            .saturating_sub(MAX_SURROUNDING_TEXT_LENGTH / 2)
+            .min(max_start);
+        while !text.is_char_boundary(start) {
+            start += 1;
         }
+        let mut end = (start + MAX_SURROUNDING_TEXT_LENGTH).min(text.len());
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let offset = |position: usize| (position.max(start).min(end) - start) as i32;
+        self.surrounding_text = Some((text[start..end].to_owned(), offset(cursor), offset(anchor)));
+    }
+}
+
+/// The modifiers of the keysyms sent by text input v1 and v2, by their index in the mask.
+#[derive(Default)]
+struct ModifiersMap(Vec<ModifiersState>);
+
+impl ModifiersMap {
+    /// Reads the NUL-terminated modifier names of a `modifiers_map` event.
+    fn new(map: &[u8]) -> Self {
+        let modifiers = map
+            .split(|&byte| byte == 0)
+            .map(|name| match name {
+                b"Shift" => ModifiersState::SHIFT,
+                b"Control" => ModifiersState::CTRL,
+                b"Mod1" | b"Alt" => ModifiersState::ALT,
+                b"Mod4" | b"Super" => ModifiersState::LOGO,
+                _ => ModifiersState::empty(),
+            })
+            .collect();
+        Self(modifiers)
+    }
+
+    fn modifiers(&self, mask: u32) -> ModifiersState {
+        self.0
+            .iter()
+            .take(32)
+            .enumerate()
+            .filter(|(index, _)| mask & (1 << index) != 0)
+            .fold(ModifiersState::empty(), |modifiers, (_, modifier)| {
+                modifiers | *modifier
+            })
+    }
+}
+
+/// Sends a key that the input method of text input v1 or v2 didn't handle, like the arrows.
+///
+/// The keysym doesn't come with the key that produced it, so there's no scancode.
+fn push_keysym(
+    event_sink: &mut EventSink,
+    window_id: WindowId,
+    time: u32,
+    sym: u32,
+    state: u32,
+    modifiers: ModifiersState,
+) {
+    let state = if state == KeyState::Pressed as u32 {
+        ElementState::Pressed
+    } else {
+        ElementState::Released
+    };
 
-        self.text_input.commit();
+    event_sink.push_window_event(
+        #[allow(deprecated)]
+        WindowEvent::KeyboardInput {
+            device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId)),
+            input: KeyboardInput {
+                state,
+                scancode: 0,
+                virtual_keycode: keymap::keysym_to_vkey(sym),
+                modifiers,
+            },
+            event: keymap::key_event(0, sym, None, state, false),
+            is_synthetic: false,
+            timestamp: instant_from_event_time(time),
+        },
+        window_id,
+    );
+}
+
+/// Builds the preedit of text input v1 and v2 from the styling and cursor sent before it.
+///
+/// `cursor` is `None` when no cursor was sent, which puts it at the end of the text, and
+/// `Some(None)` when it's hidden.
+fn preedit(text: String, cursor: Option<Option<usize>>, mut spans: Vec<PreeditSpan>) -> Ime {
+    let cursor = cursor.unwrap_or(Some(text.len()));
+    let cursor_range = cursor
+        .filter(|_| !text.is_empty())
+        .map(|cursor| (cursor, cursor));
+
+    spans.retain(|span| span.start < span.end && span.end <= text.len());
+    spans.sort_by_key(|span| span.start);
+
+    Ime::Preedit(text, cursor_range, spans)
+}
+
+fn content_purpose(purpose: ImePurpose) -> ContentPurpose {
+    match purpose {
+        ImePurpose::Normal => ContentPurpose::Normal,
+        ImePurpose::Alpha => ContentPurpose::Alpha,
+        ImePurpose::Digits => ContentPurpose::Digits,
+        ImePurpose::Number => ContentPurpose::Number,
+        ImePurpose::Phone => ContentPurpose::Phone,
+        ImePurpose::Url => ContentPurpose::Url,
+        ImePurpose::Email => ContentPurpose::Email,
+        ImePurpose::Name => ContentPurpose::Name,
+        ImePurpose::Password => ContentPurpose::Password,
+        ImePurpose::Pin => ContentPurpose::Pin,
+        ImePurpose::Date => ContentPurpose::Date,
+        ImePurpose::Time => ContentPurpose::Time,
+        ImePurpose::DateTime => ContentPurpose::Datetime,
+        ImePurpose::Terminal => ContentPurpose::Terminal,
     }
 }
 
@@ -68,6 +301,9 @@ struct TextInputInner {
 
     /// Pending preedit event which will be dispatched on `text_input_v3::Done`.
     pending_preedit: Option<Preedit>,
+
+    /// Pending deletion of surrounding text which will be dispatched on `text_input_v3::Done`.
+    pending_delete: Option<(usize, usize)>,
 }
 
 struct Preedit {
@@ -82,6 +318,28 @@ impl TextInputInner {
             target_window_id: None,
             pending_commit: None,
             pending_preedit: None,
+            pending_delete: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_map() {
+        let map = ModifiersMap::new(b"Shift\0Lock\0Control\0Mod1\0Mod4\0");
+        assert_eq!(map.modifiers(0), ModifiersState::empty());
+        assert_eq!(map.modifiers(0b10), ModifiersState::empty());
+        assert_eq!(
+            map.modifiers(0b10101),
+            ModifiersState::SHIFT | ModifiersState::CTRL | ModifiersState::LOGO
+        );
+        assert_eq!(map.modifiers(1 << 31), ModifiersState::empty());
+        assert_eq!(
+            ModifiersMap::default().modifiers(u32::MAX),
+            ModifiersState::empty()
+        );
+    }
+}
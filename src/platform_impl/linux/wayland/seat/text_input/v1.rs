@@ -0,0 +1,245 @@
+//! Text input v1, for compositors that don't support text input v3.
+//!
+//! Unlike v3, the client picks the surface to input text in, so the text input is activated on
+//! the surfaces focused by the keyboard of the seat.
+
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Attached;
+use sctk::reexports::protocols::unstable::text_input::v1::client::zwp_text_input_manager_v1::ZwpTextInputManagerV1;
+use sctk::reexports::protocols::unstable::text_input::v1::client::zwp_text_input_v1::{
+    ContentHint, ContentPurpose, Event as TextInputEvent, PreeditStyle as TextInputPreeditStyle,
+    ZwpTextInputV1,
+};
+
+use crate::event::{Ime, PreeditSpan, PreeditStyle, WindowEvent};
+use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::{self, WindowId};
+use crate::window::ImePurpose;
+
+use super::{ModifiersMap, TextInputHandler, TextInputState};
+
+/// The text input of a seat.
+pub struct TextInputV1 {
+    focus: TextInputV1Focus,
+}
+
+impl TextInputV1 {
+    pub fn new(seat: &Attached<WlSeat>, text_input_manager: &ZwpTextInputManagerV1) -> Self {
+        let text_input = text_input_manager.create_text_input();
+        let mut inner = TextInputV1Inner::default();
+        text_input.quick_assign(move |_, event, mut dispatch_data| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            inner.handle_event(event, winit_state);
+        });
+
+        Self {
+            focus: TextInputV1Focus {
+                text_input: text_input.detach(),
+                seat: seat.detach(),
+            },
+        }
+    }
+
+    /// The handle the keyboard of the seat uses to move the text input to the surface it focuses.
+    pub fn focus(&self) -> TextInputV1Focus {
+        self.focus.clone()
+    }
+}
+
+impl Drop for TextInputV1 {
+    fn drop(&mut self) {
+        // The protocol doesn't have a destructor.
+        self.focus.text_input.deactivate(&self.focus.seat);
+    }
+}
+
+/// Follows the keyboard focus of a seat with its text input.
+#[derive(Clone)]
+pub struct TextInputV1Focus {
+    text_input: ZwpTextInputV1,
+    seat: WlSeat,
+}
+
+impl TextInputV1Focus {
+    pub fn keyboard_entered(&self, surface: &WlSurface, winit_state: &mut WinitState) {
+        let window_id = wayland::make_wid(surface);
+        let window_handle = match winit_state.window_map.get_mut(&window_id) {
+            Some(window_handle) => window_handle,
+            None => return,
+        };
+
+        let text_input_handler = self.handler(surface);
+        if window_handle.ime_allowed.get() {
+            let state = window_handle.text_input_state.borrow();
+            text_input_handler.set_input_allowed(true, &state);
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::Ime(Ime::Enabled), window_id);
+        }
+
+        window_handle.text_input_entered(text_input_handler);
+    }
+
+    pub fn keyboard_left(&self, surface: &WlSurface, winit_state: &mut WinitState) {
+        self.text_input.deactivate(&self.seat);
+
+        let window_id = wayland::make_wid(surface);
+        let window_handle = match winit_state.window_map.get_mut(&window_id) {
+            Some(window_handle) => window_handle,
+            None => return,
+        };
+
+        window_handle.text_input_left(self.handler(surface));
+        winit_state
+            .event_sink
+            .push_window_event(WindowEvent::Ime(Ime::Disabled), window_id);
+    }
+
+    fn handler(&self, surface: &WlSurface) -> TextInputHandler {
+        TextInputHandler::V1 {
+            text_input: self.text_input.clone(),
+            seat: self.seat.clone(),
+            surface: surface.clone(),
+        }
+    }
+}
+
+pub(super) fn set_state(text_input: &ZwpTextInputV1, state: &TextInputState) {
+    let (x, y, width, height) = state.cursor_area;
+    text_input.set_cursor_rectangle(x, y, width, height);
+
+    if let Some((text, cursor, anchor)) = state.surrounding_text.as_ref() {
+        text_input.set_surrounding_text(text.clone(), *cursor as u32, *anchor as u32);
+    }
+
+    let hint = ContentHint::from_bits_truncate(state.hints.bits());
+    text_input.set_content_type(hint, content_purpose(state.purpose));
+
+    // Nothing is matched against the serial in the events.
+    text_input.commit_state(0);
+}
+
+fn content_purpose(purpose: ImePurpose) -> ContentPurpose {
+    match purpose {
+        ImePurpose::Normal => ContentPurpose::Normal,
+        ImePurpose::Alpha => ContentPurpose::Alpha,
+        ImePurpose::Digits => ContentPurpose::Digits,
+        ImePurpose::Number => ContentPurpose::Number,
+        ImePurpose::Phone => ContentPurpose::Phone,
+        ImePurpose::Url => ContentPurpose::Url,
+        ImePurpose::Email => ContentPurpose::Email,
+        ImePurpose::Name => ContentPurpose::Name,
+        ImePurpose::Password | ImePurpose::Pin => ContentPurpose::Password,
+        ImePurpose::Date => ContentPurpose::Date,
+        ImePurpose::Time => ContentPurpose::Time,
+        ImePurpose::DateTime => ContentPurpose::Datetime,
+        ImePurpose::Terminal => ContentPurpose::Terminal,
+    }
+}
+
+#[derive(Default)]
+struct TextInputV1Inner {
+    /// Currently focused surface.
+    target_window_id: Option<WindowId>,
+
+    /// Styles of the next preedit string.
+    pending_spans: Vec<PreeditSpan>,
+
+    /// Cursor in the next preedit string, `None` when it's hidden.
+    pending_cursor: Option<Option<usize>>,
+
+    /// Deletion of surrounding text which comes with the next commit.
+    pending_delete: Option<(usize, usize)>,
+
+    /// The modifiers of the keysyms.
+    modifiers_map: ModifiersMap,
+}
+
+impl TextInputV1Inner {
+    fn handle_event(&mut self, event: TextInputEvent, winit_state: &mut WinitState) {
+        let event_sink = &mut winit_state.event_sink;
+        match event {
+            TextInputEvent::Enter { surface } => {
+                self.target_window_id = Some(wayland::make_wid(&surface));
+            }
+            TextInputEvent::Leave => {
+                self.target_window_id = None;
+            }
+            TextInputEvent::PreeditStyling {
+                index,
+                length,
+                style,
+            } => {
+                let style = match style {
+                    TextInputPreeditStyle::Active
+                    | TextInputPreeditStyle::Highlight
+                    | TextInputPreeditStyle::Selection => PreeditStyle::Highlight,
+                    TextInputPreeditStyle::None => return,
+                    _ => PreeditStyle::Underline,
+                };
+                self.pending_spans.push(PreeditSpan {
+                    start: index as usize,
+                    end: index.saturating_add(length) as usize,
+                    style,
+                });
+            }
+            TextInputEvent::PreeditCursor { index } => {
+                self.pending_cursor = Some(usize::try_from(index).ok());
+            }
+            TextInputEvent::PreeditString { text, .. } => {
+                let window_id = match self.target_window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                let spans = std::mem::take(&mut self.pending_spans);
+                let event = super::preedit(text, self.pending_cursor.take(), spans);
+                event_sink.push_window_event(WindowEvent::Ime(event), window_id);
+            }
+            TextInputEvent::DeleteSurroundingText { index, length } => {
+                // The deleted range starts at `index` from the cursor.
+                let before_length = index.min(0).unsigned_abs() as usize;
+                let after_length = (length as usize).saturating_sub(before_length);
+                self.pending_delete = Some((before_length, after_length));
+            }
+            TextInputEvent::CommitString { text, .. } => {
+                let window_id = match self.target_window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                if let Some((before_length, after_length)) = self.pending_delete.take() {
+                    let event = Ime::DeleteSurrounding {
+                        before_length,
+                        after_length,
+                    };
+                    event_sink.push_window_event(WindowEvent::Ime(event), window_id);
+                }
+
+                event_sink.push_window_event(WindowEvent::Ime(Ime::Commit(text)), window_id);
+            }
+            TextInputEvent::ModifiersMap { map } => {
+                self.modifiers_map = ModifiersMap::new(&map);
+            }
+            // Keys that the input method doesn't handle, like the arrows, are sent back as
+            // keysyms rather than through the keyboard.
+            TextInputEvent::Keysym {
+                time,
+                sym,
+                state,
+                modifiers,
+                ..
+            } => {
+                let window_id = match self.target_window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                let modifiers = self.modifiers_map.modifiers(modifiers);
+                super::push_keysym(event_sink, window_id, time, sym, state, modifiers);
+            }
+            _ => (),
+        }
+    }
+}
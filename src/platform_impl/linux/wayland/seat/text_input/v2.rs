@@ -0,0 +1,270 @@
+//! Text input v2, for compositors that don't support text input v3.
+//!
+//! Like v3, the text input of a seat follows its keyboard focus, but the surfaces are enabled
+//! one by one and the state updates are matched against the serial of the last focus change.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::{Attached, Main};
+
+use crate::event::{Ime, PreeditSpan, PreeditStyle, WindowEvent};
+use crate::platform_impl::wayland::event_loop::WinitState;
+use crate::platform_impl::wayland::protocols::text_input::v2::client::zwp_text_input_manager_v2::ZwpTextInputManagerV2;
+use crate::platform_impl::wayland::protocols::text_input::v2::client::zwp_text_input_v2::{
+    ContentHint, ContentPurpose, Event as TextInputEvent, PreeditStyle as TextInputPreeditStyle,
+    UpdateState, ZwpTextInputV2,
+};
+use crate::platform_impl::wayland::{self, WindowId};
+use crate::window::ImePurpose;
+
+use super::{ModifiersMap, TextInputHandler, TextInputState};
+
+/// The text input of a seat.
+pub struct TextInputV2 {
+    text_input: ZwpTextInputV2,
+}
+
+impl TextInputV2 {
+    pub fn new(seat: &Attached<WlSeat>, text_input_manager: &ZwpTextInputManagerV2) -> Self {
+        let text_input = text_input_manager.get_text_input(seat);
+        let mut inner = TextInputV2Inner::default();
+        text_input.quick_assign(move |text_input, event, mut dispatch_data| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            inner.handle_event(text_input, event, winit_state);
+        });
+
+        Self {
+            text_input: text_input.detach(),
+        }
+    }
+}
+
+impl Drop for TextInputV2 {
+    fn drop(&mut self) {
+        self.text_input.destroy();
+    }
+}
+
+/// Sends the whole state, or only what may have changed when `full` is false.
+pub(super) fn set_state(
+    text_input: &ZwpTextInputV2,
+    serial: u32,
+    state: &TextInputState,
+    full: bool,
+) {
+    let (x, y, width, height) = state.cursor_area;
+    text_input.set_cursor_rectangle(x, y, width, height);
+
+    if let Some((text, cursor, anchor)) = state.surrounding_text.as_ref() {
+        text_input.set_surrounding_text(text.clone(), *cursor, *anchor);
+    }
+
+    let hint = ContentHint::from_bits_truncate(state.hints.bits());
+    text_input.set_content_type(hint, content_purpose(state.purpose));
+
+    let reason = if full {
+        UpdateState::Full
+    } else {
+        UpdateState::Change
+    };
+    text_input.update_state(serial, reason);
+}
+
+fn content_purpose(purpose: ImePurpose) -> ContentPurpose {
+    match purpose {
+        ImePurpose::Normal => ContentPurpose::Normal,
+        ImePurpose::Alpha => ContentPurpose::Alpha,
+        ImePurpose::Digits => ContentPurpose::Digits,
+        ImePurpose::Number => ContentPurpose::Number,
+        ImePurpose::Phone => ContentPurpose::Phone,
+        ImePurpose::Url => ContentPurpose::Url,
+        ImePurpose::Email => ContentPurpose::Email,
+        ImePurpose::Name => ContentPurpose::Name,
+        ImePurpose::Password | ImePurpose::Pin => ContentPurpose::Password,
+        ImePurpose::Date => ContentPurpose::Date,
+        ImePurpose::Time => ContentPurpose::Time,
+        ImePurpose::DateTime => ContentPurpose::Datetime,
+        ImePurpose::Terminal => ContentPurpose::Terminal,
+    }
+}
+
+#[derive(Default)]
+struct TextInputV2Inner {
+    /// Currently focused surface.
+    target_window_id: Option<WindowId>,
+
+    /// The serial of the last `enter` or `input_method_changed` event, shared with the handlers.
+    serial: Rc<Cell<u32>>,
+
+    /// Styles of the next preedit string.
+    pending_spans: Vec<PreeditSpan>,
+
+    /// Cursor in the next preedit string, `None` when it's hidden.
+    pending_cursor: Option<Option<usize>>,
+
+    /// Deletion of surrounding text which comes with the next commit or preedit string.
+    pending_delete: Option<(usize, usize)>,
+
+    /// The modifiers of the keysyms.
+    modifiers_map: ModifiersMap,
+}
+
+impl TextInputV2Inner {
+    fn handle_event(
+        &mut self,
+        text_input: Main<ZwpTextInputV2>,
+        event: TextInputEvent,
+        winit_state: &mut WinitState,
+    ) {
+        let event_sink = &mut winit_state.event_sink;
+        match event {
+            TextInputEvent::Enter { serial, surface } => {
+                self.serial.set(serial);
+
+                let window_id = wayland::make_wid(&surface);
+                let window_handle = match winit_state.window_map.get_mut(&window_id) {
+                    Some(window_handle) => window_handle,
+                    None => return,
+                };
+                self.target_window_id = Some(window_id);
+
+                let text_input_handler = TextInputHandler::V2 {
+                    text_input: text_input.detach(),
+                    surface,
+                    serial: self.serial.clone(),
+                };
+                if window_handle.ime_allowed.get() {
+                    let state = window_handle.text_input_state.borrow();
+                    text_input_handler.set_input_allowed(true, &state);
+                    event_sink.push_window_event(WindowEvent::Ime(Ime::Enabled), window_id);
+                }
+
+                window_handle.text_input_entered(text_input_handler);
+            }
+            TextInputEvent::Leave { surface, .. } => {
+                // Always issue a disable.
+                text_input.disable(&surface);
+
+                let window_id = wayland::make_wid(&surface);
+                let window_handle = match winit_state.window_map.get_mut(&window_id) {
+                    Some(window_handle) => window_handle,
+                    None => return,
+                };
+
+                self.target_window_id = None;
+
+                let text_input_handler = TextInputHandler::V2 {
+                    text_input: text_input.detach(),
+                    surface,
+                    serial: self.serial.clone(),
+                };
+                window_handle.text_input_left(text_input_handler);
+                event_sink.push_window_event(WindowEvent::Ime(Ime::Disabled), window_id);
+            }
+            // The new input method knows nothing about the text, so it's sent again.
+            TextInputEvent::InputMethodChanged { serial, .. } => {
+                self.serial.set(serial);
+
+                let window_handle = match self
+                    .target_window_id
+                    .and_then(|window_id| winit_state.window_map.get(&window_id))
+                {
+                    Some(window_handle) => window_handle,
+                    None => return,
+                };
+                if window_handle.ime_allowed.get() {
+                    let state = window_handle.text_input_state.borrow();
+                    set_state(&text_input, serial, &state, true);
+                }
+            }
+            TextInputEvent::PreeditStyling {
+                index,
+                length,
+                style,
+            } => {
+                let style = match style {
+                    TextInputPreeditStyle::Active
+                    | TextInputPreeditStyle::Highlight
+                    | TextInputPreeditStyle::Selection => PreeditStyle::Highlight,
+                    TextInputPreeditStyle::None => return,
+                    _ => PreeditStyle::Underline,
+                };
+                self.pending_spans.push(PreeditSpan {
+                    start: index as usize,
+                    end: index.saturating_add(length) as usize,
+                    style,
+                });
+            }
+            TextInputEvent::PreeditCursor { index } => {
+                self.pending_cursor = Some(usize::try_from(index).ok());
+            }
+            TextInputEvent::DeleteSurroundingText {
+                before_length,
+                after_length,
+            } => {
+                self.pending_delete = Some((before_length as usize, after_length as usize));
+            }
+            TextInputEvent::PreeditString { text, .. } => {
+                let window_id = match self.target_window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                self.push_pending_delete(winit_state, window_id);
+
+                let spans = std::mem::take(&mut self.pending_spans);
+                let event = super::preedit(text, self.pending_cursor.take(), spans);
+                winit_state
+                    .event_sink
+                    .push_window_event(WindowEvent::Ime(event), window_id);
+            }
+            TextInputEvent::CommitString { text } => {
+                let window_id = match self.target_window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                self.push_pending_delete(winit_state, window_id);
+
+                winit_state
+                    .event_sink
+                    .push_window_event(WindowEvent::Ime(Ime::Commit(text)), window_id);
+            }
+            TextInputEvent::ModifiersMap { map } => {
+                self.modifiers_map = ModifiersMap::new(&map);
+            }
+            // Keys that the input method doesn't handle, like the arrows, are sent back as
+            // keysyms rather than through the keyboard.
+            TextInputEvent::Keysym {
+                time,
+                sym,
+                state,
+                modifiers,
+            } => {
+                let window_id = match self.target_window_id {
+                    Some(window_id) => window_id,
+                    None => return,
+                };
+
+                let modifiers = self.modifiers_map.modifiers(modifiers);
+                super::push_keysym(event_sink, window_id, time, sym, state, modifiers);
+            }
+            _ => (),
+        }
+    }
+
+    /// The deletion applies before the text that comes with it, per the protocol.
+    fn push_pending_delete(&mut self, winit_state: &mut WinitState, window_id: WindowId) {
+        if let Some((before_length, after_length)) = self.pending_delete.take() {
+            let event = Ime::DeleteSurrounding {
+                before_length,
+                after_length,
+            };
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::Ime(event), window_id);
+        }
+    }
+}
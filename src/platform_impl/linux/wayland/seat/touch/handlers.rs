@@ -1,10 +1,13 @@
 //! Various handlers for touch events.
 
+use std::time::Instant;
+
 use sctk::reexports::client::protocol::wl_touch::Event as TouchEvent;
 
 use crate::dpi::LogicalPosition;
 use crate::event::{TouchPhase, WindowEvent};
 
+use crate::platform_impl::platform::instant_from_event_time;
 use crate::platform_impl::wayland::event_loop::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId};
 
@@ -21,14 +24,19 @@ pub(super) fn handle_touch(
 
     match event {
         TouchEvent::Down {
-            surface, id, x, y, ..
+            time,
+            surface,
+            id,
+            x,
+            y,
+            ..
         } => {
             let window_id = wayland::make_wid(&surface);
             if !winit_state.window_map.contains_key(&window_id) {
                 return;
             }
 
-            let scale_factor = sctk::get_surface_scale_factor(&surface) as f64;
+            let scale_factor = wayland::scale_factor(&winit_state.window_map, &surface);
             let position = LogicalPosition::new(x, y);
 
             event_sink.push_window_event(
@@ -40,6 +48,7 @@ pub(super) fn handle_touch(
                     location: position.to_physical(scale_factor),
                     force: None, // TODO
                     id: id as u64,
+                    timestamp: instant_from_event_time(time),
                 }),
                 window_id,
             );
@@ -54,13 +63,13 @@ pub(super) fn handle_touch(
                     .push(TouchPoint::new(surface, position, id));
             }
         }
-        TouchEvent::Up { id, .. } => {
+        TouchEvent::Up { time, id, .. } => {
             let touch_point = match inner.touch_points.iter().find(|p| p.id == id) {
                 Some(touch_point) => touch_point,
                 None => return,
             };
 
-            let scale_factor = sctk::get_surface_scale_factor(&touch_point.surface) as f64;
+            let scale_factor = wayland::scale_factor(&winit_state.window_map, &touch_point.surface);
             let location = touch_point.position.to_physical(scale_factor);
             let window_id = wayland::make_wid(&touch_point.surface);
 
@@ -73,11 +82,12 @@ pub(super) fn handle_touch(
                     location,
                     force: None, // TODO
                     id: id as u64,
+                    timestamp: instant_from_event_time(time),
                 }),
                 window_id,
             );
         }
-        TouchEvent::Motion { id, x, y, .. } => {
+        TouchEvent::Motion { time, id, x, y } => {
             let touch_point = match inner.touch_points.iter_mut().find(|p| p.id == id) {
                 Some(touch_point) => touch_point,
                 None => return,
@@ -85,7 +95,7 @@ pub(super) fn handle_touch(
 
             touch_point.position = LogicalPosition::new(x, y);
 
-            let scale_factor = sctk::get_surface_scale_factor(&touch_point.surface) as f64;
+            let scale_factor = wayland::scale_factor(&winit_state.window_map, &touch_point.surface);
             let location = touch_point.position.to_physical(scale_factor);
             let window_id = wayland::make_wid(&touch_point.surface);
 
@@ -98,14 +108,18 @@ pub(super) fn handle_touch(
                     location,
                     force: None, // TODO
                     id: id as u64,
+                    timestamp: instant_from_event_time(time),
                 }),
                 window_id,
             );
         }
         TouchEvent::Frame => (),
         TouchEvent::Cancel => {
+            // `wl_touch::cancel` doesn't have a timestamp.
+            let timestamp = Instant::now();
             for touch_point in inner.touch_points.drain(..) {
-                let scale_factor = sctk::get_surface_scale_factor(&touch_point.surface) as f64;
+                let scale_factor =
+                    wayland::scale_factor(&winit_state.window_map, &touch_point.surface);
                 let location = touch_point.position.to_physical(scale_factor);
                 let window_id = wayland::make_wid(&touch_point.surface);
 
@@ -118,6 +132,7 @@ pub(super) fn handle_touch(
                         location,
                         force: None, // TODO
                         id: touch_point.id as u64,
+                        timestamp,
                     }),
                     window_id,
                 );
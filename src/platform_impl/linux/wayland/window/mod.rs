@@ -1,36 +1,67 @@
+use std::cell::Cell;
 use std::collections::VecDeque;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
-use sctk::reexports::client::Display;
+use sctk::reexports::client::{DispatchData, Display};
+use sctk::reexports::protocols::viewporter::client::wp_viewporter::WpViewporter;
+use sctk::reexports::protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_shell_v1::{
+    Layer as WlrLayer, ZwlrLayerShellV1,
+};
+use sctk::reexports::protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_surface_v1::{
+    Anchor, Event as LayerSurfaceEvent, KeyboardInteractivity as WlrKeyboardInteractivity,
+    ZwlrLayerSurfaceV1,
+};
+
+use sctk::reexports::protocols::xdg_shell::client::xdg_popup::Event as PopupEvent;
+use sctk::reexports::protocols::xdg_shell::client::xdg_positioner::{
+    Anchor as PositionerAnchor, Gravity,
+};
+use sctk::reexports::protocols::xdg_shell::client::xdg_surface::Event as XdgSurfaceEvent;
 
 use sctk::reexports::calloop;
+use sctk::shell::Shell;
+use sctk::window::State;
 
 use raw_window_handle::{
     RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
 };
-use sctk::window::Decorations;
 
-use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
+use crate::clipboard::{ClipboardContents, ClipboardKind};
+use crate::dnd::{DndAction, DragData};
+use crate::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
+use crate::keyboard::{Key, KeyCode, KeyboardLayout};
 use crate::monitor::MonitorHandle as RootMonitorHandle;
+use crate::platform::unix::{KeyboardInteractivity, Layer, LayerShell, Popup, PopupAnchor};
+use crate::platform_impl::platform::common::xkb::SharedLayout;
 use crate::platform_impl::{
     MonitorHandle as PlatformMonitorHandle, OsError,
     PlatformSpecificWindowBuilderAttributes as PlatformAttributes,
 };
 use crate::window::{
-    CursorGrabMode, CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes,
+    CursorGrabMode, CursorIcon, CustomCursor, DecorationMode, Fullscreen, ImeHints, ImePurpose,
+    Rect, Region, Theme, UserAttentionType, WindowAttributes,
 };
 
+use super::clipboard::Clipboard;
 use super::env::WindowingFeatures;
 use super::event_loop::WinitState;
 use super::output::{MonitorHandle, OutputManagerHandle};
+use super::protocols::fractional_scale::v1::client::wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1;
+use super::protocols::fractional_scale::v1::client::wp_fractional_scale_v1::Event as FractionalScaleEvent;
 use super::{EventLoopWindowTarget, WindowId};
 
+mod shell;
 pub mod shim;
+mod toplevel;
 
+pub use shell::ShellWindow;
 use shim::{WindowCompositorUpdate, WindowHandle, WindowRequest, WindowUserRequest};
+use toplevel::{Event as ToplevelEvent, Toplevel};
 
 #[cfg(feature = "sctk-adwaita")]
 pub type WinitFrame = sctk_adwaita::AdwaitaFrame;
@@ -53,6 +84,9 @@ pub struct Window {
     /// The current window size.
     size: Arc<Mutex<LogicalSize<u32>>>,
 
+    /// The current scale factor, which is fractional when the compositor supports it.
+    scale_factor: Arc<Mutex<f64>>,
+
     /// A handle to output manager.
     output_manager_handle: OutputManagerHandle,
 
@@ -65,6 +99,12 @@ pub struct Window {
     /// Maximized state.
     maximized: Arc<AtomicBool>,
 
+    /// The position relative to the parent, for child windows.
+    position: Arc<Mutex<Option<LogicalPosition<i32>>>>,
+
+    /// Whether the window is a popup, which is placed by the compositor.
+    popup: bool,
+
     /// Available windowing features.
     windowing_features: WindowingFeatures,
 
@@ -77,8 +117,23 @@ pub struct Window {
     /// Whether the window is decorated.
     decorated: AtomicBool,
 
+    /// The decoration mode negotiated with the compositor.
+    decoration_mode: Arc<Mutex<Option<DecorationMode>>>,
+
+    /// Whether a pointer button is held over the window.
+    pointer_button_pressed: Arc<AtomicBool>,
+
     /// Grabbing mode.
     cursor_grab_mode: Mutex<CursorGrabMode>,
+
+    /// Clipboard state shared with the event loop.
+    clipboard: Clipboard,
+
+    /// Keyboard layout shared with the event loop.
+    keyboard_layout: SharedLayout,
+
+    /// The theme of the desktop, updated by the event loop.
+    system_theme: Arc<Mutex<Option<Theme>>>,
 }
 
 impl Window {
@@ -87,9 +142,23 @@ impl Window {
         attributes: WindowAttributes,
         platform_attributes: PlatformAttributes,
     ) -> Result<Self, RootOsError> {
-        let surface = event_loop_window_target
-            .env
+        let env = &event_loop_window_target.env;
+
+        // The fractional scale is applied to the buffers through the viewport, so the integer
+        // scale is only used when either of the globals is missing.
+        let fractional_scaling = env
+            .get_global::<WpFractionalScaleManagerV1>()
+            .zip(env.get_global::<WpViewporter>());
+
+        let use_integer_scale = fractional_scaling.is_none();
+        let scale_factor = Arc::new(Mutex::new(1.));
+        let scale_factor_clone = scale_factor.clone();
+        let surface = env
             .create_surface_with_scale_callback(move |scale, surface, mut dispatch_data| {
+                if !use_integer_scale {
+                    return;
+                }
+
                 let winit_state = dispatch_data.get::<WinitState>().unwrap();
 
                 // Get the window that received the event.
@@ -107,145 +176,131 @@ impl Window {
                     .refresh_frame = true;
 
                 // Set pending scale factor.
-                window_compositor_update.scale_factor = Some(scale);
+                window_compositor_update.scale_factor = Some(scale as f64);
+                *scale_factor_clone.lock().unwrap() = scale as f64;
 
                 surface.set_buffer_scale(scale);
             })
             .detach();
 
-        let scale_factor = sctk::get_surface_scale_factor(&surface);
-
         let window_id = super::make_wid(&surface);
-        let maximized = Arc::new(AtomicBool::new(false));
-        let maximized_clone = maximized.clone();
-        let fullscreen = Arc::new(AtomicBool::new(false));
-        let fullscreen_clone = fullscreen.clone();
 
-        let (width, height) = attributes
-            .inner_size
-            .map(|size| size.to_logical::<f64>(scale_factor as f64).into())
-            .unwrap_or((800, 600));
-
-        let theme_manager = event_loop_window_target.theme_manager.clone();
-        let mut window = event_loop_window_target
-            .env
-            .create_window::<WinitFrame, _>(
-                surface.clone(),
-                Some(theme_manager),
-                (width, height),
-                move |event, mut dispatch_data| {
-                    use sctk::window::{Event, State};
+        let (viewport, fractional_scale) = match fractional_scaling {
+            Some((fractional_scale_manager, viewporter)) => {
+                let viewport = viewporter.get_viewport(&surface).detach();
+                let fractional_scale = fractional_scale_manager.get_fractional_scale(&surface);
 
+                let scale_factor = scale_factor.clone();
+                fractional_scale.quick_assign(move |_, event, mut dispatch_data| {
+                    let FractionalScaleEvent::PreferredScale { scale } = event;
                     let winit_state = dispatch_data.get::<WinitState>().unwrap();
-                    let mut window_compositor_update = winit_state
-                        .window_compositor_updates
-                        .get_mut(&window_id)
-                        .unwrap();
 
-                    let mut window_user_requests = winit_state
+                    // The scale is sent as the numerator of a fraction over 120.
+                    let scale = scale as f64 / 120.;
+                    *scale_factor.lock().unwrap() = scale;
+
+                    winit_state
                         .window_user_requests
                         .get_mut(&window_id)
-                        .unwrap();
-
-                    match event {
-                        Event::Refresh => {
-                            window_user_requests.refresh_frame = true;
-                        }
-                        Event::Configure { new_size, states } => {
-                            let is_maximized = states.contains(&State::Maximized);
-                            maximized_clone.store(is_maximized, Ordering::Relaxed);
-                            let is_fullscreen = states.contains(&State::Fullscreen);
-                            fullscreen_clone.store(is_fullscreen, Ordering::Relaxed);
-
-                            window_user_requests.refresh_frame = true;
-                            if let Some((w, h)) = new_size {
-                                window_compositor_update.size = Some(LogicalSize::new(w, h));
-                            }
-                        }
-                        Event::Close => {
-                            window_compositor_update.close_window = true;
-                        }
-                    }
-                },
-            )
-            .map_err(|_| os_error!(OsError::WaylandMisc("failed to create window.")))?;
-
-        // Set CSD frame config
-        #[cfg(feature = "sctk-adwaita")]
-        {
-            let theme = platform_attributes.csd_theme.unwrap_or_else(|| {
-                let env = std::env::var(WAYLAND_CSD_THEME_ENV_VAR).unwrap_or_default();
-                match env.to_lowercase().as_str() {
-                    "dark" => Theme::Dark,
-                    _ => Theme::Light,
-                }
-            });
-
-            window.set_frame_config(theme.into());
-        }
-
-        // Set decorations.
-        if attributes.decorations {
-            window.set_decorate(Decorations::FollowServer);
-        } else {
-            window.set_decorate(Decorations::None);
-        }
-
-        // Min dimensions.
-        let min_size = attributes
-            .min_inner_size
-            .map(|size| size.to_logical::<f64>(scale_factor as f64).into());
-        window.set_min_size(min_size);
+                        .unwrap()
+                        .refresh_frame = true;
+                    winit_state
+                        .window_compositor_updates
+                        .get_mut(&window_id)
+                        .unwrap()
+                        .scale_factor = Some(scale);
+                });
 
-        // Max dimensions.
-        let max_size = attributes
-            .max_inner_size
-            .map(|size| size.to_logical::<f64>(scale_factor as f64).into());
-        window.set_max_size(max_size);
+                (Some(viewport), Some(fractional_scale.detach()))
+            }
+            None => {
+                *scale_factor.lock().unwrap() = sctk::get_surface_scale_factor(&surface) as f64;
+                (None, None)
+            }
+        };
 
-        // Set Wayland specific window attributes.
-        if let Some(name) = platform_attributes.name {
-            window.set_app_id(name.general);
+        let initial_scale_factor = *scale_factor.lock().unwrap();
+
+        let parent_id = attributes.parent.map(|parent| parent.0);
+        if let Some(parent_id) = parent_id {
+            if !event_loop_window_target
+                .state
+                .borrow()
+                .window_map
+                .contains_key(&parent_id)
+            {
+                return Err(os_error!(OsError::WaylandMisc(
+                    "the parent window doesn't exist."
+                )));
+            }
         }
 
-        // Set common window attributes.
-        //
-        // We set resizable after other attributes, since it touches min and max size under
-        // the hood.
-        window.set_resizable(attributes.resizable);
-        window.set_title(attributes.title);
+        let maximized = Arc::new(AtomicBool::new(false));
+        let fullscreen = Arc::new(AtomicBool::new(false));
 
-        // Set fullscreen/maximized if so was requested.
-        match attributes.fullscreen {
-            Some(Fullscreen::Exclusive(_)) => {
-                warn!("`Fullscreen::Exclusive` is ignored on Wayland")
-            }
-            Some(Fullscreen::Borderless(monitor)) => {
-                let monitor =
-                    monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
-                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
-                    });
+        let (width, height) = attributes
+            .inner_size
+            .map(|size| size.to_logical::<f64>(initial_scale_factor).into())
+            .unwrap_or((800, 600));
 
-                window.set_fullscreen(monitor.as_ref());
-            }
-            None => {
-                if attributes.maximized {
-                    window.set_maximized();
+        // Layer surfaces ignore the parent, while popups are placed by the compositor against
+        // their parent and the other child windows are subsurfaces placed relatively to it.
+        let position = Arc::new(Mutex::new(None));
+        let popup = parent_id.and(platform_attributes.popup);
+        let window = match (platform_attributes.layer_shell.as_ref(), parent_id, popup) {
+            (Some(layer_shell), ..) => ShellWindow::Layer {
+                surface: surface.clone(),
+                layer_surface: Self::create_layer_surface(
+                    event_loop_window_target,
+                    &surface,
+                    (width, height),
+                    layer_shell,
+                )?,
+            },
+            (None, Some(parent_id), Some(popup)) => Self::create_popup(
+                event_loop_window_target,
+                &surface,
+                parent_id,
+                &popup,
+                (width, height),
+                initial_scale_factor,
+                position.clone(),
+            )?,
+            (None, Some(parent_id), None) => {
+                let child_position = attributes
+                    .position
+                    .map(|position| position.to_logical::<i32>(initial_scale_factor))
+                    .unwrap_or_default();
+                *position.lock().unwrap() = Some(child_position);
+
+                let subcompositor = env.get_global::<WlSubcompositor>().ok_or_else(|| {
+                    os_error!(OsError::WaylandMisc(
+                        "the compositor doesn't support subsurfaces."
+                    ))
+                })?;
+                let winit_state = event_loop_window_target.state.borrow();
+                let parent_surface = winit_state.window_map[&parent_id].window.surface();
+                let subsurface = subcompositor.get_subsurface(&surface, parent_surface);
+                subsurface.set_position(child_position.x, child_position.y);
+
+                // The child window is drawn with its own commits rather than its parent's.
+                subsurface.set_desync();
+
+                ShellWindow::Subsurface {
+                    surface: surface.clone(),
+                    subsurface: subsurface.detach(),
                 }
             }
-        }
-
-        // Without this commit here at least on kwin 5.23.3 the initial configure
-        // will have a size (1,1), the second configure including the decoration
-        // mode will have the min_size as its size. With this commit the initial
-        // configure will have no size, the application will draw it's content
-        // with the initial size and everything works as expected afterwards.
-        //
-        // The window commit must be after setting on top level properties, but right before any
-        // buffer attachments commits.
-        window.surface().commit();
+            (None, None, _) => Self::create_toplevel(
+                event_loop_window_target,
+                &surface,
+                (width, height),
+                initial_scale_factor,
+                &attributes,
+                &platform_attributes,
+                (maximized.clone(), fullscreen.clone()),
+            )?,
+        };
 
         let size = Arc::new(Mutex::new(LogicalSize::new(width, height)));
 
@@ -254,20 +309,32 @@ impl Window {
         window_user_request.refresh_frame = true;
         window_user_request.redraw_requested = true;
 
-        let window_id = super::make_wid(&surface);
         let window_requests = Arc::new(Mutex::new(Vec::with_capacity(64)));
 
+        // Only toplevels are decorated.
+        let decorated = attributes.decorations
+            && matches!(
+                window,
+                ShellWindow::Toplevel(_) | ShellWindow::LegacyToplevel { .. }
+            );
+
         // Create a handle that performs all the requests on underlying sctk a window.
         let window_handle = WindowHandle::new(
             &event_loop_window_target.env,
             window,
             size.clone(),
+            scale_factor.clone(),
             window_requests.clone(),
+            viewport,
+            fractional_scale,
         );
 
         // Set resizable state, so we can determine how to handle `Window::set_inner_size`.
         window_handle.is_resizable.set(attributes.resizable);
 
+        let decoration_mode = window_handle.decoration_mode.clone();
+        let pointer_button_pressed = window_handle.pointer_button_pressed.clone();
+
         let mut winit_state = event_loop_window_target.state.borrow_mut();
 
         winit_state.window_map.insert(window_id, window_handle);
@@ -311,18 +378,435 @@ impl Window {
             display: event_loop_window_target.display.clone(),
             output_manager_handle,
             size,
+            scale_factor,
             window_requests,
             event_loop_awakener: event_loop_window_target.event_loop_awakener.clone(),
             fullscreen,
             maximized,
+            position,
+            popup: popup.is_some(),
             windowing_features,
             resizeable: AtomicBool::new(attributes.resizable),
-            decorated: AtomicBool::new(attributes.decorations),
+            decorated: AtomicBool::new(decorated),
+            decoration_mode,
+            pointer_button_pressed,
             cursor_grab_mode: Mutex::new(CursorGrabMode::None),
+            clipboard: event_loop_window_target.clipboard.clone(),
+            keyboard_layout: event_loop_window_target.keyboard_layout.clone(),
+            system_theme: event_loop_window_target.system_theme.clone(),
         };
 
         Ok(window)
     }
+
+    /// Creates the layer surface of a panel, dock or overlay.
+    fn create_layer_surface<T>(
+        event_loop_window_target: &EventLoopWindowTarget<T>,
+        surface: &WlSurface,
+        (width, height): (u32, u32),
+        layer_shell: &LayerShell,
+    ) -> Result<ZwlrLayerSurfaceV1, RootOsError> {
+        let layer_shell_global = event_loop_window_target
+            .env
+            .get_global::<ZwlrLayerShellV1>()
+            .ok_or_else(|| {
+                os_error!(OsError::WaylandMisc(
+                    "the compositor doesn't support layer shell."
+                ))
+            })?;
+
+        let output =
+            layer_shell
+                .output
+                .as_ref()
+                .and_then(|RootMonitorHandle { inner: monitor }| match monitor {
+                    PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy.clone()),
+                    _ => None,
+                });
+
+        let layer = match layer_shell.layer {
+            Layer::Background => WlrLayer::Background,
+            Layer::Bottom => WlrLayer::Bottom,
+            Layer::Top => WlrLayer::Top,
+            Layer::Overlay => WlrLayer::Overlay,
+        };
+
+        let layer_surface = layer_shell_global.get_layer_surface(
+            surface,
+            output.as_ref(),
+            layer,
+            layer_shell.namespace.clone(),
+        );
+
+        layer_surface.set_size(width, height);
+        layer_surface.set_anchor(Anchor::from_bits_truncate(layer_shell.anchor.bits()));
+        layer_surface.set_exclusive_zone(layer_shell.exclusive_zone);
+        let (top, right, bottom, left) = layer_shell.margin;
+        layer_surface.set_margin(top, right, bottom, left);
+
+        let keyboard_interactivity = match layer_shell.keyboard_interactivity {
+            KeyboardInteractivity::None => WlrKeyboardInteractivity::None,
+            KeyboardInteractivity::Exclusive => WlrKeyboardInteractivity::Exclusive,
+            // The on demand focus came with the version 4 of the protocol.
+            KeyboardInteractivity::OnDemand if layer_shell_global.as_ref().version() >= 4 => {
+                WlrKeyboardInteractivity::OnDemand
+            }
+            KeyboardInteractivity::OnDemand => WlrKeyboardInteractivity::None,
+        };
+        layer_surface.set_keyboard_interactivity(keyboard_interactivity);
+
+        let window_id = super::make_wid(surface);
+        layer_surface.quick_assign(move |layer_surface, event, mut dispatch_data| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            let window_size = winit_state
+                .window_map
+                .get(&window_id)
+                .map(|window_handle| *window_handle.size.lock().unwrap());
+
+            let window_compositor_update = winit_state
+                .window_compositor_updates
+                .get_mut(&window_id)
+                .unwrap();
+
+            match event {
+                LayerSurfaceEvent::Configure {
+                    serial,
+                    width,
+                    height,
+                } => {
+                    layer_surface.ack_configure(serial);
+
+                    // The dimensions which are zero are left to us.
+                    let mut size = window_size.unwrap_or_else(|| LogicalSize::new(width, height));
+                    if width != 0 {
+                        size.width = width;
+                    }
+                    if height != 0 {
+                        size.height = height;
+                    }
+                    window_compositor_update.size = Some(size);
+
+                    winit_state
+                        .window_user_requests
+                        .get_mut(&window_id)
+                        .unwrap()
+                        .refresh_frame = true;
+                }
+                LayerSurfaceEvent::Closed => {
+                    window_compositor_update.close_window = true;
+                }
+                _ => (),
+            }
+        });
+
+        // The compositor sends the first configure after a commit without a buffer.
+        surface.commit();
+
+        Ok(layer_surface.detach())
+    }
+
+    /// Creates the `xdg_popup` of a popup, which the compositor places against its parent.
+    fn create_popup<T>(
+        event_loop_window_target: &EventLoopWindowTarget<T>,
+        surface: &WlSurface,
+        parent_id: WindowId,
+        popup: &Popup,
+        (width, height): (u32, u32),
+        initial_scale_factor: f64,
+        position: Arc<Mutex<Option<LogicalPosition<i32>>>>,
+    ) -> Result<ShellWindow, RootOsError> {
+        let xdg_wm_base = match event_loop_window_target.env.get_shell() {
+            Some(Shell::Xdg(xdg_wm_base)) => xdg_wm_base,
+            _ => {
+                return Err(os_error!(OsError::WaylandMisc(
+                    "the compositor doesn't support xdg_shell popups."
+                )))
+            }
+        };
+
+        let winit_state = event_loop_window_target.state.borrow();
+        let parent = &winit_state.window_map[&parent_id];
+
+        // Popups of layer surfaces are attached through the layer surface rather than to an
+        // `xdg_surface`.
+        let parent_xdg_surface = parent.window.xdg_surface();
+        let parent_layer_surface = match &*parent.window {
+            ShellWindow::Layer { layer_surface, .. } => Some(layer_surface),
+            _ => None,
+        };
+        if parent_xdg_surface.is_none() && parent_layer_surface.is_none() {
+            return Err(os_error!(OsError::WaylandMisc(
+                "the parent window can't have popups."
+            )));
+        }
+
+        // The anchor rectangle is relative to the window geometry of the parent, which includes
+        // its decorations, while the popup's is relative to its client area.
+        let (geometry_x, geometry_y) = parent.window.geometry_position();
+        let anchor_position = popup
+            .anchor_position
+            .to_logical::<i32>(initial_scale_factor);
+        let anchor_size = popup.anchor_size.to_logical::<u32>(initial_scale_factor);
+
+        let positioner = xdg_wm_base.create_positioner();
+        positioner.set_size(width.max(1) as i32, height.max(1) as i32);
+        positioner.set_anchor_rect(
+            anchor_position.x - geometry_x,
+            anchor_position.y - geometry_y,
+            anchor_size.width.max(1) as i32,
+            anchor_size.height.max(1) as i32,
+        );
+        positioner.set_anchor(positioner_anchor(popup.anchor));
+        positioner.set_gravity(positioner_gravity(popup.gravity));
+        // The flags have the values of the protocol.
+        positioner.set_constraint_adjustment(popup.constraint_adjustment.bits());
+
+        let xdg_surface = xdg_wm_base.get_xdg_surface(surface);
+        let xdg_popup = xdg_surface.get_popup(parent_xdg_surface, &positioner);
+        if let Some(layer_surface) = parent_layer_surface {
+            layer_surface.get_popup(&xdg_popup);
+        }
+        positioner.destroy();
+
+        // The popup is placed with the next configure of its surface.
+        let window_id = super::make_wid(surface);
+        let pending_configure = Rc::new(Cell::new(None));
+        let popup_configure = pending_configure.clone();
+        xdg_popup.quick_assign(move |_, event, mut dispatch_data| match event {
+            PopupEvent::Configure {
+                x,
+                y,
+                width,
+                height,
+            } => popup_configure.set(Some((x, y, width, height))),
+            // The compositor dismissed the popup, like after a click outside of the application.
+            PopupEvent::PopupDone => {
+                let winit_state = dispatch_data.get::<WinitState>().unwrap();
+                if let Some(update) = winit_state.window_compositor_updates.get_mut(&window_id) {
+                    update.close_window = true;
+                }
+            }
+            _ => (),
+        });
+
+        xdg_surface.quick_assign(move |xdg_surface, event, mut dispatch_data| {
+            let serial = match event {
+                XdgSurfaceEvent::Configure { serial } => serial,
+                _ => return,
+            };
+            xdg_surface.ack_configure(serial);
+
+            let (x, y, width, height) = match pending_configure.take() {
+                Some(configure) => configure,
+                None => return,
+            };
+            *position.lock().unwrap() = Some(LogicalPosition::new(x + geometry_x, y + geometry_y));
+
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            if let Some(update) = winit_state.window_compositor_updates.get_mut(&window_id) {
+                if width > 0 && height > 0 {
+                    update.size = Some(LogicalSize::new(width as u32, height as u32));
+                }
+            }
+        });
+
+        if popup.grab && !parent.grab_popup(&xdg_popup) {
+            warn!("The popup can't grab the input without a pointer over its parent");
+        }
+
+        surface.commit();
+
+        Ok(ShellWindow::Popup {
+            surface: surface.clone(),
+            xdg_surface: xdg_surface.detach(),
+            popup: xdg_popup.detach(),
+        })
+    }
+
+    /// Creates the toplevel of a window which isn't a child of another window.
+    fn create_toplevel<T>(
+        event_loop_window_target: &EventLoopWindowTarget<T>,
+        surface: &WlSurface,
+        (width, height): (u32, u32),
+        initial_scale_factor: f64,
+        attributes: &WindowAttributes,
+        platform_attributes: &PlatformAttributes,
+        (maximized, fullscreen): (Arc<AtomicBool>, Arc<AtomicBool>),
+    ) -> Result<ShellWindow, RootOsError> {
+        let window_id = super::make_wid(surface);
+        let env = &event_loop_window_target.env;
+        let theme_manager = event_loop_window_target.theme_manager.clone();
+        let handler = move |event: ToplevelEvent, mut dispatch_data: DispatchData<'_>| {
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            let mut window_compositor_update = winit_state
+                .window_compositor_updates
+                .get_mut(&window_id)
+                .unwrap();
+
+            let mut window_user_requests = winit_state
+                .window_user_requests
+                .get_mut(&window_id)
+                .unwrap();
+
+            match event {
+                ToplevelEvent::Refresh => {
+                    window_user_requests.refresh_frame = true;
+                }
+                ToplevelEvent::Configure {
+                    new_size,
+                    states,
+                    decoration_mode,
+                } => {
+                    let is_maximized = states.contains(&State::Maximized);
+                    maximized.store(is_maximized, Ordering::Relaxed);
+                    let is_fullscreen = states.contains(&State::Fullscreen);
+                    fullscreen.store(is_fullscreen, Ordering::Relaxed);
+
+                    window_user_requests.refresh_frame = true;
+                    if let Some((w, h)) = new_size {
+                        window_compositor_update.size = Some(LogicalSize::new(w, h));
+                    }
+
+                    if let Some(window_handle) = winit_state.window_map.get(&window_id) {
+                        let event_sink = &mut winit_state.event_sink;
+                        window_handle.set_decoration_mode(decoration_mode, event_sink);
+                    }
+                }
+                ToplevelEvent::Close => {
+                    window_compositor_update.close_window = true;
+                }
+            }
+        };
+
+        let mut window = match env.get_shell() {
+            Some(Shell::Xdg(xdg_wm_base)) => {
+                let toplevel = Toplevel::new(
+                    env,
+                    &xdg_wm_base,
+                    surface,
+                    theme_manager,
+                    (width, height),
+                    platform_attributes.decoration_mode,
+                    handler,
+                )
+                .map_err(|_| os_error!(OsError::WaylandMisc("failed to create window.")))?;
+                ShellWindow::Toplevel(toplevel)
+            }
+            Some(_) => {
+                // The older shells have no decoration negotiation, so the decorations are always
+                // drawn on the client side.
+                let window = env
+                    .create_window::<WinitFrame, _>(
+                        surface.clone(),
+                        Some(theme_manager),
+                        (width, height),
+                        move |event, dispatch_data| {
+                            let event = match event {
+                                sctk::window::Event::Refresh => ToplevelEvent::Refresh,
+                                sctk::window::Event::Configure { new_size, states } => {
+                                    ToplevelEvent::Configure {
+                                        new_size,
+                                        states,
+                                        decoration_mode: DecorationMode::Client,
+                                    }
+                                }
+                                sctk::window::Event::Close => ToplevelEvent::Close,
+                            };
+                            handler(event, dispatch_data);
+                        },
+                    )
+                    .map_err(|_| os_error!(OsError::WaylandMisc("failed to create window.")))?;
+                ShellWindow::LegacyToplevel {
+                    window,
+                    server_only: platform_attributes.decoration_mode
+                        == Some(DecorationMode::Server),
+                }
+            }
+            None => {
+                return Err(os_error!(OsError::WaylandMisc(
+                    "the compositor doesn't support any shell."
+                )))
+            }
+        };
+
+        // Set CSD frame config
+        #[cfg(feature = "sctk-adwaita")]
+        {
+            let theme = platform_attributes
+                .csd_theme
+                .or_else(|| {
+                    let env = std::env::var(WAYLAND_CSD_THEME_ENV_VAR).ok()?;
+                    match env.to_lowercase().as_str() {
+                        "dark" => Some(Theme::Dark),
+                        _ => Some(Theme::Light),
+                    }
+                })
+                .or_else(|| *event_loop_window_target.system_theme.lock().unwrap())
+                .unwrap_or(Theme::Light);
+
+            window.set_frame_config(theme.into());
+        }
+
+        // Set decorations.
+        window.set_decorated(attributes.decorations);
+
+        // Min dimensions.
+        let min_size = attributes
+            .min_inner_size
+            .map(|size| size.to_logical::<f64>(initial_scale_factor).into());
+        window.set_min_size(min_size);
+
+        // Max dimensions.
+        let max_size = attributes
+            .max_inner_size
+            .map(|size| size.to_logical::<f64>(initial_scale_factor).into());
+        window.set_max_size(max_size);
+
+        // Set Wayland specific window attributes.
+        if let Some(name) = platform_attributes.name.as_ref() {
+            window.set_app_id(name.general.clone());
+        }
+
+        // Set common window attributes.
+        //
+        // We set resizable after other attributes, since it touches min and max size under
+        // the hood.
+        window.set_resizable(attributes.resizable);
+        window.set_title(attributes.title.clone());
+
+        // Set fullscreen/maximized if so was requested.
+        match attributes.fullscreen.clone() {
+            Some(Fullscreen::Exclusive(_)) => {
+                warn!("`Fullscreen::Exclusive` is ignored on Wayland")
+            }
+            Some(Fullscreen::Borderless(monitor)) => {
+                let monitor =
+                    monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
+                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
+                        _ => None,
+                    });
+
+                window.set_fullscreen(monitor.as_ref());
+            }
+            None => {
+                if attributes.maximized {
+                    window.set_maximized();
+                }
+            }
+        }
+
+        // Without this commit here at least on kwin 5.23.3 the initial configure
+        // will have a size (1,1), the second configure including the decoration
+        // mode will have the min_size as its size. With this commit the initial
+        // configure will have no size, the application will draw it's content
+        // with the initial size and everything works as expected afterwards.
+        //
+        // The window commit must be after setting on top level properties, but right before any
+        // buffer attachments commits.
+        window.surface().commit();
+
+        Ok(window)
+    }
 }
 
 impl Window {
@@ -348,24 +832,36 @@ impl Window {
 
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Err(NotSupportedError::new())
+        // Child windows don't have decorations.
+        self.inner_position()
     }
 
     #[inline]
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Err(NotSupportedError::new())
+        let position = self
+            .position
+            .lock()
+            .unwrap()
+            .ok_or_else(NotSupportedError::new)?;
+        Ok(position.to_physical(self.scale_factor()))
     }
 
     #[inline]
-    pub fn set_outer_position(&self, _: Position) {
-        // Not possible on Wayland.
+    pub fn set_outer_position(&self, position: Position) {
+        // Only the position of child windows relative to their parent can be set, while popups
+        // are placed by the compositor.
+        let mut current_position = self.position.lock().unwrap();
+        if current_position.is_none() || self.popup {
+            return;
+        }
+
+        let position = position.to_logical(self.scale_factor());
+        *current_position = Some(position);
+        self.send_request(WindowRequest::Position(position));
     }
 
     pub fn inner_size(&self) -> PhysicalSize<u32> {
-        self.size
-            .lock()
-            .unwrap()
-            .to_physical(self.scale_factor() as f64)
+        self.size.lock().unwrap().to_physical(self.scale_factor())
     }
 
     #[inline]
@@ -375,15 +871,12 @@ impl Window {
 
     #[inline]
     pub fn outer_size(&self) -> PhysicalSize<u32> {
-        self.size
-            .lock()
-            .unwrap()
-            .to_physical(self.scale_factor() as f64)
+        self.size.lock().unwrap().to_physical(self.scale_factor())
     }
 
     #[inline]
     pub fn set_inner_size(&self, size: Size) {
-        let scale_factor = self.scale_factor() as f64;
+        let scale_factor = self.scale_factor();
 
         let size = size.to_logical::<u32>(scale_factor);
         *self.size.lock().unwrap() = size;
@@ -393,7 +886,7 @@ impl Window {
 
     #[inline]
     pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
-        let scale_factor = self.scale_factor() as f64;
+        let scale_factor = self.scale_factor();
         let size = dimensions.map(|size| size.to_logical::<u32>(scale_factor));
 
         self.send_request(WindowRequest::MinSize(size));
@@ -401,7 +894,7 @@ impl Window {
 
     #[inline]
     pub fn set_max_inner_size(&self, dimensions: Option<Size>) {
-        let scale_factor = self.scale_factor() as f64;
+        let scale_factor = self.scale_factor();
         let size = dimensions.map(|size| size.to_logical::<u32>(scale_factor));
 
         self.send_request(WindowRequest::MaxSize(size));
@@ -419,10 +912,8 @@ impl Window {
     }
 
     #[inline]
-    pub fn scale_factor(&self) -> u32 {
-        // The scale factor from `get_surface_scale_factor` is always greater than zero, so
-        // u32 conversion is safe.
-        sctk::get_surface_scale_factor(&self.surface) as u32
+    pub fn scale_factor(&self) -> f64 {
+        *self.scale_factor.lock().unwrap()
     }
 
     #[inline]
@@ -436,6 +927,16 @@ impl Window {
         self.decorated.load(Ordering::Relaxed)
     }
 
+    #[inline]
+    pub fn decoration_mode(&self) -> Option<DecorationMode> {
+        *self.decoration_mode.lock().unwrap()
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        *self.system_theme.lock().unwrap()
+    }
+
     #[inline]
     pub fn set_csd_theme(&self, theme: Theme) {
         self.send_request(WindowRequest::CsdThemeVariant(theme));
@@ -485,8 +986,7 @@ impl Window {
                 let monitor =
                     monitor.and_then(|RootMonitorHandle { inner: monitor }| match monitor {
                         PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                        #[cfg(feature = "x11")]
-                        PlatformMonitorHandle::X(_) => None,
+                        _ => None,
                     });
 
                 WindowRequest::Fullscreen(monitor)
@@ -502,6 +1002,11 @@ impl Window {
         self.send_request(WindowRequest::NewCursorIcon(cursor));
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) {
+        self.send_request(WindowRequest::NewCustomCursor(cursor.clone()));
+    }
+
     #[inline]
     pub fn set_cursor_visible(&self, visible: bool) {
         self.send_request(WindowRequest::ShowCursor(visible));
@@ -532,6 +1037,56 @@ impl Window {
         self.send_request(WindowRequest::Attention(request_type));
     }
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, inhibit: bool) -> Result<(), ExternalError> {
+        if !self.windowing_features.idle_inhibit() {
+            if !inhibit {
+                return Ok(());
+            }
+
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        self.send_request(WindowRequest::IdleInhibit(inhibit));
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_shortcuts_inhibited(&self, inhibit: bool) -> Result<(), ExternalError> {
+        if !self.windowing_features.shortcuts_inhibit() {
+            if !inhibit {
+                return Ok(());
+            }
+
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        self.send_request(WindowRequest::ShortcutsInhibit(inhibit));
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        // There's no protocol for the opacity of a whole surface.
+        if opacity >= 1. {
+            return Ok(());
+        }
+
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_blur(&self, blur: bool) -> Result<(), ExternalError> {
+        // KWin's blur protocol is the only one, and its LGPL definition can't be vendored here.
+        if !blur {
+            return Ok(());
+        }
+
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
         // Positon can be set only for locked cursor.
@@ -541,7 +1096,7 @@ impl Window {
             ))));
         }
 
-        let scale_factor = self.scale_factor() as f64;
+        let scale_factor = self.scale_factor();
         let position = position.to_logical(scale_factor);
         self.send_request(WindowRequest::SetLockedCursorPosition(position));
 
@@ -562,15 +1117,154 @@ impl Window {
         Ok(())
     }
 
+    #[inline]
+    pub fn set_input_region(&self, region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        self.send_request(WindowRequest::InputRegion(region.map(<[Rect]>::to_vec)));
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_shape(&self, _shape: Option<Region>) -> Result<(), ExternalError> {
+        // Surfaces can't be cut, only the buffers the application draws have an alpha channel.
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        let supported = match kind {
+            ClipboardKind::Clipboard => self.windowing_features.data_device(),
+            ClipboardKind::Primary => self.windowing_features.primary_selection(),
+        };
+        if !supported {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        // Compositors only let the focused client set selections.
+        if !self.clipboard.has_keyboard_focus() {
+            return Err(ExternalError::Os(os_error!(OsError::WaylandMisc(
+                "clipboard can be set only while a window has keyboard focus."
+            ))));
+        }
+
+        self.send_request(WindowRequest::SetClipboard(kind, contents));
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn start_drag(&self, data: DragData) -> Result<(), ExternalError> {
+        if !self.windowing_features.data_device() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        // Compositors only start drags from the implicit grab of a pressed button.
+        if !self.pointer_button_pressed.load(Ordering::Relaxed) {
+            return Err(ExternalError::Os(os_error!(OsError::WaylandMisc(
+                "drag can be started only while a mouse button is held over the window."
+            ))));
+        }
+
+        self.send_request(WindowRequest::StartDrag(data));
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn accept_drag(&self, mime_type: &str, action: DndAction) -> Result<(), ExternalError> {
+        self.send_request(WindowRequest::AcceptDrag(mime_type.to_owned(), action));
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        self.send_request(WindowRequest::RejectDrag);
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn clipboard(&self, kind: ClipboardKind, mime_type: &str) -> Option<Vec<u8>> {
+        self.clipboard.load(kind, mime_type, &self.display)
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Vec<String> {
+        self.clipboard.mime_types(kind)
+    }
+
+    #[inline]
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        self.keyboard_layout.layout()
+    }
+
+    #[inline]
+    pub fn key_label(&self, physical_key: KeyCode) -> Option<Key> {
+        self.keyboard_layout.key_label(physical_key)
+    }
+
     #[inline]
     pub fn set_ime_position(&self, position: Position) {
-        let scale_factor = self.scale_factor() as f64;
+        let scale_factor = self.scale_factor();
+        let position = position.to_logical(scale_factor);
+        self.send_request(WindowRequest::ImeCursorArea(
+            position,
+            LogicalSize::new(0, 0),
+        ));
+    }
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+        let scale_factor = self.scale_factor();
         let position = position.to_logical(scale_factor);
-        self.send_request(WindowRequest::ImePosition(position));
+        let size = size.to_logical(scale_factor);
+        self.send_request(WindowRequest::ImeCursorArea(position, size));
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(
+        &self,
+        text: &str,
+        cursor: usize,
+        anchor: usize,
+    ) -> Result<(), ExternalError> {
+        if self.windowing_features.text_input().is_none() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        self.send_request(WindowRequest::ImeSurroundingText(
+            text.to_owned(),
+            cursor,
+            anchor,
+        ));
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(
+        &self,
+        purpose: ImePurpose,
+        hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        if self.windowing_features.text_input().is_none() {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        self.send_request(WindowRequest::ImePurpose(purpose, hints));
+        Ok(())
     }
 
     #[inline]
     pub fn set_ime_allowed(&self, allowed: bool) {
+        if allowed && self.windowing_features.text_input().is_none() {
+            warn!("IME isn't supported by the compositor");
+        }
+
         self.send_request(WindowRequest::AllowIme(allowed));
     }
 
@@ -627,6 +1321,36 @@ impl Drop for Window {
     }
 }
 
+/// The point of the anchor rectangle a popup is attached to.
+fn positioner_anchor(anchor: PopupAnchor) -> PositionerAnchor {
+    match anchor {
+        PopupAnchor::Center => PositionerAnchor::None,
+        PopupAnchor::Top => PositionerAnchor::Top,
+        PopupAnchor::Bottom => PositionerAnchor::Bottom,
+        PopupAnchor::Left => PositionerAnchor::Left,
+        PopupAnchor::Right => PositionerAnchor::Right,
+        PopupAnchor::TopLeft => PositionerAnchor::TopLeft,
+        PopupAnchor::BottomLeft => PositionerAnchor::BottomLeft,
+        PopupAnchor::TopRight => PositionerAnchor::TopRight,
+        PopupAnchor::BottomRight => PositionerAnchor::BottomRight,
+    }
+}
+
+/// The direction a popup extends to from its anchor point.
+fn positioner_gravity(gravity: PopupAnchor) -> Gravity {
+    match gravity {
+        PopupAnchor::Center => Gravity::None,
+        PopupAnchor::Top => Gravity::Top,
+        PopupAnchor::Bottom => Gravity::Bottom,
+        PopupAnchor::Left => Gravity::Left,
+        PopupAnchor::Right => Gravity::Right,
+        PopupAnchor::TopLeft => Gravity::TopLeft,
+        PopupAnchor::BottomLeft => Gravity::BottomLeft,
+        PopupAnchor::TopRight => Gravity::TopRight,
+        PopupAnchor::BottomRight => Gravity::BottomRight,
+    }
+}
+
 #[cfg(feature = "sctk-adwaita")]
 impl From<Theme> for sctk_adwaita::FrameConfig {
     fn from(theme: Theme) -> Self {
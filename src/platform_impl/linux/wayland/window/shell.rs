@@ -0,0 +1,244 @@
+//! The role of the surface of a window.
+
+use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_subsurface::WlSubsurface;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::protocols::wlr::unstable::layer_shell::v1::client::zwlr_layer_surface_v1::ZwlrLayerSurfaceV1;
+use sctk::reexports::protocols::xdg_shell::client::xdg_popup::XdgPopup;
+use sctk::reexports::protocols::xdg_shell::client::xdg_surface::XdgSurface;
+
+use sctk::window::{Decorations, Window};
+
+use crate::dpi::LogicalPosition;
+
+use super::{Toplevel, WinitFrame};
+
+/// The surface of a window, with the role it was given.
+///
+/// The requests which don't apply to the role are ignored.
+pub enum ShellWindow {
+    /// A toplevel window on `xdg_shell`.
+    Toplevel(Toplevel),
+
+    /// A toplevel window on the older shells, managed by SCTK, which always draws the
+    /// decorations on the client side.
+    LegacyToplevel {
+        window: Window<WinitFrame>,
+
+        /// Whether the window asked for server-side decorations only, so it stays undecorated.
+        server_only: bool,
+    },
+
+    /// A popup, placed by the compositor against its parent.
+    Popup {
+        surface: WlSurface,
+        xdg_surface: XdgSurface,
+        popup: XdgPopup,
+    },
+
+    /// A child window, embedded into the surface of its parent.
+    Subsurface {
+        surface: WlSurface,
+        subsurface: WlSubsurface,
+    },
+
+    /// A panel, dock or overlay, placed by the compositor in a layer of the desktop.
+    Layer {
+        surface: WlSurface,
+        layer_surface: ZwlrLayerSurfaceV1,
+    },
+}
+
+impl ShellWindow {
+    pub fn surface(&self) -> &WlSurface {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.surface(),
+            ShellWindow::LegacyToplevel { window, .. } => window.surface(),
+            ShellWindow::Popup { surface, .. } => surface,
+            ShellWindow::Subsurface { surface, .. } => surface,
+            ShellWindow::Layer { surface, .. } => surface,
+        }
+    }
+
+    /// The `xdg_surface` of the window, which popups are attached to.
+    pub fn xdg_surface(&self) -> Option<&XdgSurface> {
+        match self {
+            ShellWindow::Toplevel(toplevel) => Some(toplevel.xdg_surface()),
+            ShellWindow::Popup { xdg_surface, .. } => Some(xdg_surface),
+            _ => None,
+        }
+    }
+
+    /// The position of the window geometry in the surface, which popups are placed relatively to.
+    pub fn geometry_position(&self) -> (i32, i32) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.geometry_position(),
+            _ => (0, 0),
+        }
+    }
+
+    /// Moves a child window relatively to its parent, once the parent is committed.
+    pub fn set_position(&self, position: LogicalPosition<i32>) {
+        if let ShellWindow::Subsurface { subsurface, .. } = self {
+            subsurface.set_position(position.x, position.y);
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.refresh(),
+            ShellWindow::LegacyToplevel { window, .. } => window.refresh(),
+            _ => (),
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.resize(width, height),
+            ShellWindow::LegacyToplevel { window, .. } => window.resize(width, height),
+            _ => (),
+        }
+    }
+
+    /// Asks the compositor for a new size, since it picks the size of layer surfaces.
+    pub fn request_size(&self, width: u32, height: u32) {
+        if let ShellWindow::Layer { layer_surface, .. } = self {
+            layer_surface.set_size(width, height);
+        }
+    }
+
+    pub fn set_decorated(&mut self, decorated: bool) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_decorated(decorated),
+            ShellWindow::LegacyToplevel {
+                window,
+                server_only,
+            } => {
+                let decorations = if decorated && !*server_only {
+                    Decorations::ClientSide
+                } else {
+                    Decorations::None
+                };
+                window.set_decorate(decorations);
+            }
+            _ => (),
+        }
+    }
+
+    #[cfg(feature = "sctk-adwaita")]
+    pub fn set_frame_config(&mut self, config: <WinitFrame as sctk::window::Frame>::Config) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_frame_config(config),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_frame_config(config),
+            _ => (),
+        }
+    }
+
+    pub fn set_app_id(&self, app_id: String) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_app_id(app_id),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_app_id(app_id),
+            _ => (),
+        }
+    }
+
+    pub fn set_fullscreen(&self, output: Option<&WlOutput>) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_fullscreen(output),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_fullscreen(output),
+            _ => (),
+        }
+    }
+
+    pub fn unset_fullscreen(&self) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.unset_fullscreen(),
+            ShellWindow::LegacyToplevel { window, .. } => window.unset_fullscreen(),
+            _ => (),
+        }
+    }
+
+    pub fn set_maximized(&self) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_maximized(),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_maximized(),
+            _ => (),
+        }
+    }
+
+    pub fn unset_maximized(&self) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.unset_maximized(),
+            ShellWindow::LegacyToplevel { window, .. } => window.unset_maximized(),
+            _ => (),
+        }
+    }
+
+    pub fn set_minimized(&self) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_minimized(),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_minimized(),
+            _ => (),
+        }
+    }
+
+    pub fn set_resizable(&self, resizable: bool) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_resizable(resizable),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_resizable(resizable),
+            _ => (),
+        }
+    }
+
+    pub fn set_title(&self, title: String) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_title(title),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_title(title),
+            _ => (),
+        }
+    }
+
+    pub fn set_min_size(&mut self, size: Option<(u32, u32)>) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_min_size(size),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_min_size(size),
+            _ => (),
+        }
+    }
+
+    pub fn set_max_size(&mut self, size: Option<(u32, u32)>) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.set_max_size(size),
+            ShellWindow::LegacyToplevel { window, .. } => window.set_max_size(size),
+            _ => (),
+        }
+    }
+
+    pub fn start_interactive_move(&self, seat: &WlSeat, serial: u32) {
+        match self {
+            ShellWindow::Toplevel(toplevel) => toplevel.start_interactive_move(seat, serial),
+            ShellWindow::LegacyToplevel { window, .. } => {
+                window.start_interactive_move(seat, serial)
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Drop for ShellWindow {
+    fn drop(&mut self) {
+        // The toplevels destroy their roles themselves.
+        match self {
+            ShellWindow::Toplevel(_) | ShellWindow::LegacyToplevel { .. } => (),
+            ShellWindow::Popup {
+                xdg_surface, popup, ..
+            } => {
+                popup.destroy();
+                xdg_surface.destroy();
+            }
+            ShellWindow::Subsurface { subsurface, .. } => subsurface.destroy(),
+            ShellWindow::Layer { layer_surface, .. } => layer_surface.destroy(),
+        }
+    }
+}
@@ -1,28 +1,44 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::mem::ManuallyDrop;
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
 
 use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
 use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::Attached;
 use sctk::reexports::protocols::staging::xdg_activation::v1::client::xdg_activation_token_v1;
 use sctk::reexports::protocols::staging::xdg_activation::v1::client::xdg_activation_v1::XdgActivationV1;
+use sctk::reexports::protocols::unstable::idle_inhibit::v1::client::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1;
+use sctk::reexports::protocols::unstable::idle_inhibit::v1::client::zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1;
+use sctk::reexports::protocols::unstable::keyboard_shortcuts_inhibit::v1::client::zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1;
+use sctk::reexports::protocols::unstable::keyboard_shortcuts_inhibit::v1::client::zwp_keyboard_shortcuts_inhibitor_v1::{
+    Event as ShortcutsInhibitorEvent, ZwpKeyboardShortcutsInhibitorV1,
+};
+use sctk::reexports::protocols::viewporter::client::wp_viewport::WpViewport;
+use sctk::reexports::protocols::xdg_shell::client::xdg_popup::XdgPopup;
 
 use sctk::environment::Environment;
-use sctk::window::{Decorations, Window};
 
+use crate::clipboard::{ClipboardContents, ClipboardKind};
+use crate::dnd::{DndAction, DragData};
 use crate::dpi::{LogicalPosition, LogicalSize};
 
 use crate::event::{Ime, WindowEvent};
 use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::env::WinitEnv;
 use crate::platform_impl::wayland::event_loop::{EventSink, WinitState};
+use crate::platform_impl::wayland::protocols::fractional_scale::v1::client::wp_fractional_scale_v1::WpFractionalScaleV1;
+use crate::platform_impl::wayland::seat::data_device::{DataDeviceHandler, DragOffer};
 use crate::platform_impl::wayland::seat::pointer::WinitPointer;
-use crate::platform_impl::wayland::seat::text_input::TextInputHandler;
+use crate::platform_impl::wayland::seat::text_input::{TextInputHandler, TextInputState};
 use crate::platform_impl::wayland::WindowId;
-use crate::window::{CursorGrabMode, CursorIcon, Theme, UserAttentionType};
+use crate::window::{
+    CursorGrabMode, CursorIcon, CustomCursor, DecorationMode, ImeHints, ImePurpose, Rect,
+    Theme, UserAttentionType,
+};
 
-use super::WinitFrame;
+use super::ShellWindow;
 
 /// A request to SCTK window from Winit window.
 #[derive(Debug, Clone)]
@@ -41,6 +57,9 @@ pub enum WindowRequest {
     /// Change the cursor icon.
     NewCursorIcon(CursorIcon),
 
+    /// Show a custom cursor image instead of the cursor icon.
+    NewCustomCursor(CustomCursor),
+
     /// Change cursor grabbing mode.
     SetCursorGrabMode(CursorGrabMode),
 
@@ -50,6 +69,15 @@ pub enum WindowRequest {
     /// Drag window.
     DragWindow,
 
+    /// Drag data out of the window.
+    StartDrag(DragData),
+
+    /// Accept the drag over the window with the given MIME type and action.
+    AcceptDrag(String, DndAction),
+
+    /// Reject the drag over the window.
+    RejectDrag,
+
     /// Maximize the window.
     Maximize(bool),
 
@@ -77,8 +105,17 @@ pub enum WindowRequest {
     /// New frame size.
     FrameSize(LogicalSize<u32>),
 
-    /// Set IME window position.
-    ImePosition(LogicalPosition<u32>),
+    /// Move a child window relatively to its parent.
+    Position(LogicalPosition<i32>),
+
+    /// Set the area of the IME cursor.
+    ImeCursorArea(LogicalPosition<u32>, LogicalSize<u32>),
+
+    /// Set the text around the IME cursor, with the byte offsets of the cursor and anchor.
+    ImeSurroundingText(String, usize, usize),
+
+    /// Set the kind of text expected by the IME.
+    ImePurpose(ImePurpose, ImeHints),
 
     /// Enable IME on the given window.
     AllowIme(bool),
@@ -91,6 +128,20 @@ pub enum WindowRequest {
     /// Passthrough mouse input to underlying windows.
     PassthroughMouseInput(bool),
 
+    /// Restrict the input to the given rectangles.
+    ///
+    /// `None` lets the whole window receive input.
+    InputRegion(Option<Vec<Rect>>),
+
+    /// Keep the screen awake while the window is visible.
+    IdleInhibit(bool),
+
+    /// Send the system shortcuts to the window while it has keyboard focus.
+    ShortcutsInhibit(bool),
+
+    /// Serve the given contents on a selection.
+    SetClipboard(ClipboardKind, ClipboardContents),
+
     /// Redraw was requested.
     Redraw,
 
@@ -105,7 +156,7 @@ pub struct WindowCompositorUpdate {
     pub size: Option<LogicalSize<u32>>,
 
     /// New scale factor.
-    pub scale_factor: Option<i32>,
+    pub scale_factor: Option<f64>,
 
     /// Close the window.
     pub close_window: bool,
@@ -137,23 +188,44 @@ impl WindowUserRequest {
 /// and react to events.
 pub struct WindowHandle {
     /// An actual window.
-    pub window: ManuallyDrop<Window<WinitFrame>>,
+    pub window: ManuallyDrop<ShellWindow>,
 
     /// The current size of the window.
     pub size: Arc<Mutex<LogicalSize<u32>>>,
 
+    /// The current scale factor of the window.
+    scale_factor: Arc<Mutex<f64>>,
+
+    /// The viewport scaling the buffers down to the window size, with fractional scaling.
+    viewport: Option<WpViewport>,
+
+    /// The object receiving the fractional scale of the window.
+    fractional_scale: Option<WpFractionalScaleV1>,
+
     /// A pending requests to SCTK window.
     pub pending_window_requests: Arc<Mutex<Vec<WindowRequest>>>,
 
     /// Current cursor icon.
     pub cursor_icon: Cell<CursorIcon>,
 
+    /// Custom cursor shown instead of the cursor icon.
+    custom_cursor: RefCell<Option<CustomCursor>>,
+
     /// Whether the window is resizable.
     pub is_resizable: Cell<bool>,
 
+    /// The decoration mode last reported to the application.
+    pub decoration_mode: Arc<Mutex<Option<DecorationMode>>>,
+
+    /// Whether a pointer button is held over the window, so that drags can be started.
+    pub pointer_button_pressed: Arc<AtomicBool>,
+
     /// Allow IME events for that window.
     pub ime_allowed: Cell<bool>,
 
+    /// The IME state, which is sent to the text inputs.
+    pub text_input_state: RefCell<TextInputState>,
+
     /// Visible cursor or not.
     cursor_visible: Cell<bool>,
 
@@ -166,12 +238,42 @@ pub struct WindowHandle {
     /// Text inputs on the current surface.
     text_inputs: Vec<TextInputHandler>,
 
+    /// Data devices of the seats focused on the current surface.
+    data_devices: Vec<DataDeviceHandler>,
+
+    /// Drag from another client, or this one, over the current surface.
+    drag_offer: Option<DragOffer>,
+
     /// XdgActivation object.
     xdg_activation: Option<Attached<XdgActivationV1>>,
 
     /// Indicator whether user attention is requested.
     attention_requested: Cell<bool>,
 
+    /// Whether the window receives the pointer input at all.
+    cursor_hittest: Cell<bool>,
+
+    /// The part of the window receiving input, `None` for the whole window.
+    input_region: RefCell<Option<Vec<Rect>>>,
+
+    /// Idle inhibit manager.
+    idle_inhibit_manager: Option<Attached<ZwpIdleInhibitManagerV1>>,
+
+    /// The inhibitor keeping the screen awake, if it was requested.
+    idle_inhibitor: RefCell<Option<ZwpIdleInhibitorV1>>,
+
+    /// Keyboard shortcuts inhibit manager.
+    shortcuts_inhibit_manager: Option<Attached<ZwpKeyboardShortcutsInhibitManagerV1>>,
+
+    /// Whether the application asked for the system shortcuts.
+    shortcuts_inhibited: Cell<bool>,
+
+    /// Seats with keyboard focus on the current surface.
+    keyboard_seats: Vec<WlSeat>,
+
+    /// The inhibitors of the seats which focused the surface since the shortcuts were asked for.
+    shortcuts_inhibitors: RefCell<Vec<(WlSeat, ZwpKeyboardShortcutsInhibitorV1)>>,
+
     /// Compositor
     compositor: Attached<WlCompositor>,
 }
@@ -179,29 +281,80 @@ pub struct WindowHandle {
 impl WindowHandle {
     pub fn new(
         env: &Environment<WinitEnv>,
-        window: Window<WinitFrame>,
+        window: ShellWindow,
         size: Arc<Mutex<LogicalSize<u32>>>,
+        scale_factor: Arc<Mutex<f64>>,
         pending_window_requests: Arc<Mutex<Vec<WindowRequest>>>,
+        viewport: Option<WpViewport>,
+        fractional_scale: Option<WpFractionalScaleV1>,
     ) -> Self {
         let xdg_activation = env.get_global::<XdgActivationV1>();
+        let idle_inhibit_manager = env.get_global::<ZwpIdleInhibitManagerV1>();
+        let shortcuts_inhibit_manager = env.get_global::<ZwpKeyboardShortcutsInhibitManagerV1>();
         // Unwrap is safe, since we can't create window without compositor anyway and won't be
         // here.
         let compositor = env.get_global::<WlCompositor>().unwrap();
 
+        if let Some(viewport) = viewport.as_ref() {
+            let size = size.lock().unwrap();
+            viewport.set_destination(size.width as i32, size.height as i32);
+        }
+
         Self {
             window: ManuallyDrop::new(window),
             size,
+            scale_factor,
+            viewport,
+            fractional_scale,
             pending_window_requests,
             cursor_icon: Cell::new(CursorIcon::Default),
+            custom_cursor: RefCell::new(None),
             is_resizable: Cell::new(true),
+            decoration_mode: Arc::new(Mutex::new(None)),
+            pointer_button_pressed: Arc::new(AtomicBool::new(false)),
             cursor_grab_mode: Cell::new(CursorGrabMode::None),
             cursor_visible: Cell::new(true),
             pointers: Vec::new(),
             text_inputs: Vec::new(),
+            data_devices: Vec::new(),
+            drag_offer: None,
             xdg_activation,
             attention_requested: Cell::new(false),
+            cursor_hittest: Cell::new(true),
+            input_region: RefCell::new(None),
+            idle_inhibit_manager,
+            idle_inhibitor: RefCell::new(None),
+            shortcuts_inhibit_manager,
+            shortcuts_inhibited: Cell::new(false),
+            keyboard_seats: Vec::new(),
+            shortcuts_inhibitors: RefCell::new(Vec::new()),
             compositor,
             ime_allowed: Cell::new(false),
+            text_input_state: RefCell::new(TextInputState::default()),
+        }
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        *self.scale_factor.lock().unwrap()
+    }
+
+    /// Updates the state depending on the scale factor, once it changed.
+    pub fn scale_factor_changed(&self) {
+        // Custom cursors keep their size in physical pixels.
+        if self.custom_cursor.borrow().is_some() {
+            self.set_cursor_visible(self.cursor_visible.get());
+        }
+
+        // The input region may be in physical pixels, unlike the surface.
+        if self.input_region.borrow().is_some() {
+            self.update_input_region();
+        }
+    }
+
+    /// Scale the buffers to the new window size, when the scale factor is fractional.
+    pub fn resize_viewport(&self, size: LogicalSize<u32>) {
+        if let Some(viewport) = self.viewport.as_ref() {
+            viewport.set_destination(size.width as i32, size.height as i32);
         }
     }
 
@@ -278,6 +431,19 @@ impl WindowHandle {
         self.attention_requested.replace(true);
     }
 
+    /// Grabs the input for a popup of the window, with a pointer over it.
+    ///
+    /// Returns whether the window had a pointer to grab.
+    pub fn grab_popup(&self, popup: &XdgPopup) -> bool {
+        match self.pointers.first() {
+            Some(pointer) => {
+                pointer.grab_popup(popup);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Pointer appeared over the window.
     pub fn pointer_entered(&mut self, pointer: WinitPointer) {
         let position = self.pointers.iter().position(|p| *p == pointer);
@@ -303,6 +469,7 @@ impl WindowHandle {
 
         if let Some(position) = position {
             let pointer = self.pointers.remove(position);
+            pointer.stop_custom_cursor();
 
             // Drop the grabbing mode.
             match self.cursor_grab_mode.get() {
@@ -325,28 +492,194 @@ impl WindowHandle {
         }
     }
 
-    pub fn set_ime_position(&self, position: LogicalPosition<u32>) {
+    pub fn data_device_entered(&mut self, data_device: DataDeviceHandler) {
+        if !self.data_devices.contains(&data_device) {
+            self.data_devices.push(data_device);
+        }
+    }
+
+    pub fn data_device_left(&mut self, data_device: &DataDeviceHandler) {
+        if let Some(position) = self.data_devices.iter().position(|d| d == data_device) {
+            self.data_devices.remove(position);
+        }
+    }
+
+    pub fn drag_entered(&mut self, drag_offer: DragOffer) {
+        self.drag_offer = Some(drag_offer);
+    }
+
+    pub fn drag_left(&mut self, drag_offer: &DragOffer) {
+        if self.drag_offer.as_ref() == Some(drag_offer) {
+            self.drag_offer = None;
+        }
+    }
+
+    pub fn set_ime_cursor_area(&self, position: LogicalPosition<u32>, size: LogicalSize<u32>) {
         // XXX This won't fly unless user will have a way to request IME window per seat, since
         // the ime windows will be overlapping, but winit doesn't expose API to specify for
         // which seat we're setting IME position.
-        let (x, y) = (position.x as i32, position.y as i32);
+        self.text_input_state.borrow_mut().cursor_area = (
+            position.x as i32,
+            position.y as i32,
+            size.width as i32,
+            size.height as i32,
+        );
+        self.update_text_inputs();
+    }
+
+    pub fn set_ime_surrounding_text(&self, text: &str, cursor: usize, anchor: usize) {
+        self.text_input_state
+            .borrow_mut()
+            .set_surrounding_text(text, cursor, anchor);
+        self.update_text_inputs();
+    }
+
+    pub fn set_ime_purpose(&self, purpose: ImePurpose, hints: ImeHints) {
+        let mut state = self.text_input_state.borrow_mut();
+        state.purpose = purpose;
+        state.hints = hints;
+        drop(state);
+        self.update_text_inputs();
+    }
+
+    /// Sends the IME state to the text inputs, unless they're disabled, since enabling them sends
+    /// it anyway.
+    fn update_text_inputs(&self) {
+        if !self.ime_allowed.get() {
+            return;
+        }
+
+        let state = self.text_input_state.borrow();
         for text_input in self.text_inputs.iter() {
-            text_input.set_ime_position(x, y);
+            text_input.set_state(&state);
+        }
+    }
+
+    pub fn set_idle_inhibit(&self, inhibit: bool) {
+        let idle_inhibit_manager = match self.idle_inhibit_manager.as_ref() {
+            Some(idle_inhibit_manager) => idle_inhibit_manager,
+            None => return,
+        };
+
+        let mut idle_inhibitor = self.idle_inhibitor.borrow_mut();
+        match (inhibit, idle_inhibitor.take()) {
+            (true, None) => {
+                let inhibitor = idle_inhibit_manager.create_inhibitor(self.window.surface());
+                *idle_inhibitor = Some(inhibitor.detach());
+            }
+            (false, Some(inhibitor)) => inhibitor.destroy(),
+            (_, inhibitor) => *idle_inhibitor = inhibitor,
+        }
+    }
+
+    pub fn set_shortcuts_inhibited(&self, inhibit: bool, event_sink: &mut EventSink) {
+        if self.shortcuts_inhibited.replace(inhibit) == inhibit {
+            return;
+        }
+
+        if inhibit {
+            for seat in self.keyboard_seats.iter() {
+                self.inhibit_shortcuts(seat);
+            }
+            return;
+        }
+
+        // The compositor doesn't confirm that destroyed inhibitors are inactive.
+        let mut shortcuts_inhibitors = self.shortcuts_inhibitors.borrow_mut();
+        if !shortcuts_inhibitors.is_empty() {
+            let window_id = wayland::make_wid(self.window.surface());
+            event_sink.push_window_event(WindowEvent::ShortcutsInhibited(false), window_id);
+        }
+        for (_, inhibitor) in shortcuts_inhibitors.drain(..) {
+            inhibitor.destroy();
+        }
+    }
+
+    /// Create the inhibitor of the seat, which the compositor activates while the seat focuses
+    /// the surface.
+    fn inhibit_shortcuts(&self, seat: &WlSeat) {
+        let shortcuts_inhibit_manager = match self.shortcuts_inhibit_manager.as_ref() {
+            Some(shortcuts_inhibit_manager) => shortcuts_inhibit_manager,
+            None => return,
+        };
+
+        // Only one inhibitor is allowed for each seat.
+        let mut shortcuts_inhibitors = self.shortcuts_inhibitors.borrow_mut();
+        if shortcuts_inhibitors.iter().any(|(s, _)| s == seat) {
+            return;
+        }
+
+        let surface = self.window.surface();
+        let window_id = wayland::make_wid(surface);
+        let inhibitor = shortcuts_inhibit_manager.inhibit_shortcuts(surface, seat);
+        inhibitor.quick_assign(move |_, event, mut dispatch_data| {
+            let inhibited = match event {
+                ShortcutsInhibitorEvent::Active => true,
+                ShortcutsInhibitorEvent::Inactive => false,
+                _ => return,
+            };
+
+            let winit_state = dispatch_data.get::<WinitState>().unwrap();
+            winit_state
+                .event_sink
+                .push_window_event(WindowEvent::ShortcutsInhibited(inhibited), window_id);
+        });
+
+        shortcuts_inhibitors.push((seat.clone(), inhibitor.detach()));
+    }
+
+    pub fn keyboard_entered(&mut self, seat: &WlSeat) {
+        if !self.keyboard_seats.contains(seat) {
+            self.keyboard_seats.push(seat.clone());
+        }
+
+        if self.shortcuts_inhibited.get() {
+            self.inhibit_shortcuts(seat);
+        }
+    }
+
+    pub fn keyboard_left(&mut self, seat: &WlSeat) {
+        if let Some(position) = self.keyboard_seats.iter().position(|s| s == seat) {
+            self.keyboard_seats.remove(position);
         }
     }
 
     pub fn passthrough_mouse_input(&self, passthrough_mouse_input: bool) {
-        if passthrough_mouse_input {
-            let region = self.compositor.create_region();
-            region.add(0, 0, 0, 0);
-            self.window
-                .surface()
-                .set_input_region(Some(&region.detach()));
-            region.destroy();
-        } else {
-            // Using `None` results in the entire window being clickable.
-            self.window.surface().set_input_region(None);
+        self.cursor_hittest.replace(!passthrough_mouse_input);
+        self.update_input_region();
+    }
+
+    pub fn set_input_region(&self, input_region: Option<Vec<Rect>>) {
+        self.input_region.replace(input_region);
+        self.update_input_region();
+    }
+
+    /// Sets the input region of the surface from the hittest and the requested region.
+    fn update_input_region(&self) {
+        let input_region = self.input_region.borrow();
+        let rects = match (self.cursor_hittest.get(), input_region.as_ref()) {
+            // An empty region lets all the input through.
+            (false, _) => &[][..],
+            (true, Some(rects)) => rects.as_slice(),
+            (true, None) => {
+                // Using `None` results in the entire window being clickable.
+                self.window.surface().set_input_region(None);
+                return;
+            }
+        };
+
+        // The region is in surface coordinates.
+        let scale_factor = self.scale_factor();
+        let region = self.compositor.create_region();
+        for rect in rects {
+            let position = rect.position.to_logical::<i32>(scale_factor);
+            let size = rect.size.to_logical::<i32>(scale_factor);
+            region.add(position.x, position.y, size.width, size.height);
         }
+        self.window
+            .surface()
+            .set_input_region(Some(&region.detach()));
+        region.destroy();
     }
 
     pub fn set_ime_allowed(&self, allowed: bool, event_sink: &mut EventSink) {
@@ -357,8 +690,9 @@ impl WindowHandle {
         self.ime_allowed.replace(allowed);
         let window_id = wayland::make_wid(self.window.surface());
 
+        let state = self.text_input_state.borrow();
         for text_input in self.text_inputs.iter() {
-            text_input.set_input_allowed(allowed);
+            text_input.set_input_allowed(allowed, &state);
         }
 
         let event = if allowed {
@@ -370,8 +704,27 @@ impl WindowHandle {
         event_sink.push_window_event(event, window_id);
     }
 
+    /// Report the decoration mode picked by the compositor when it changed since the last time.
+    pub fn set_decoration_mode(&self, mode: DecorationMode, event_sink: &mut EventSink) {
+        let mut decoration_mode = self.decoration_mode.lock().unwrap();
+        if *decoration_mode == Some(mode) {
+            return;
+        }
+
+        *decoration_mode = Some(mode);
+        let window_id = wayland::make_wid(self.window.surface());
+        event_sink.push_window_event(WindowEvent::DecorationModeChanged(mode), window_id);
+    }
+
     pub fn set_cursor_visible(&self, visible: bool) {
         self.cursor_visible.replace(visible);
+        if let (true, Some(custom_cursor)) = (visible, self.custom_cursor.borrow().as_ref()) {
+            for pointer in self.pointers.iter() {
+                pointer.set_custom_cursor(custom_cursor, self.scale_factor());
+            }
+            return;
+        }
+
         let cursor_icon = match visible {
             true => Some(self.cursor_icon.get()),
             false => None,
@@ -382,8 +735,19 @@ impl WindowHandle {
         }
     }
 
+    pub fn set_custom_cursor(&self, custom_cursor: CustomCursor) {
+        if self.cursor_visible.get() {
+            for pointer in self.pointers.iter() {
+                pointer.set_custom_cursor(&custom_cursor, self.scale_factor());
+            }
+        }
+
+        self.custom_cursor.replace(Some(custom_cursor));
+    }
+
     pub fn set_cursor_icon(&self, cursor_icon: CursorIcon) {
         self.cursor_icon.replace(cursor_icon);
+        self.custom_cursor.replace(None);
 
         if !self.cursor_visible.get() {
             return;
@@ -399,6 +763,15 @@ impl WindowHandle {
             pointer.drag_window(&self.window);
         }
     }
+
+    /// Returns whether the drag started, which fails if the pointer left in the meantime.
+    pub fn start_drag(&self, data: DragData) -> bool {
+        // Only one seat can drag the data, pick the one the pointer belongs to.
+        match self.pointers.first() {
+            Some(pointer) => pointer.start_drag(self.window.surface(), data),
+            None => false,
+        }
+    }
 }
 
 #[inline]
@@ -407,6 +780,7 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
     let window_user_requests = &mut winit_state.window_user_requests;
     let window_compositor_updates = &mut winit_state.window_compositor_updates;
     let mut windows_to_close: Vec<WindowId> = Vec::new();
+    let mut clipboard_requests = Vec::new();
 
     // Process the rest of the events.
     for (window_id, window_handle) in window_map.iter_mut() {
@@ -426,8 +800,17 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                 WindowRequest::NewCursorIcon(cursor_icon) => {
                     window_handle.set_cursor_icon(cursor_icon);
                 }
-                WindowRequest::ImePosition(position) => {
-                    window_handle.set_ime_position(position);
+                WindowRequest::NewCustomCursor(custom_cursor) => {
+                    window_handle.set_custom_cursor(custom_cursor);
+                }
+                WindowRequest::ImeCursorArea(position, size) => {
+                    window_handle.set_ime_cursor_area(position, size);
+                }
+                WindowRequest::ImeSurroundingText(text, cursor, anchor) => {
+                    window_handle.set_ime_surrounding_text(&text, cursor, anchor);
+                }
+                WindowRequest::ImePurpose(purpose, hints) => {
+                    window_handle.set_ime_purpose(purpose, hints);
                 }
                 WindowRequest::AllowIme(allow) => {
                     let event_sink = &mut winit_state.event_sink;
@@ -442,6 +825,23 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                 WindowRequest::DragWindow => {
                     window_handle.drag_window();
                 }
+                WindowRequest::StartDrag(data) => {
+                    if !window_handle.start_drag(data) {
+                        winit_state
+                            .event_sink
+                            .push_window_event(WindowEvent::DragFinished(None), *window_id);
+                    }
+                }
+                WindowRequest::AcceptDrag(mime_type, action) => {
+                    if let Some(drag_offer) = window_handle.drag_offer.as_ref() {
+                        drag_offer.accept(&mime_type, action);
+                    }
+                }
+                WindowRequest::RejectDrag => {
+                    if let Some(drag_offer) = window_handle.drag_offer.as_ref() {
+                        drag_offer.reject();
+                    }
+                }
                 WindowRequest::Maximize(maximize) => {
                     if maximize {
                         window_handle.window.set_maximized();
@@ -453,12 +853,7 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                     window_handle.window.set_minimized();
                 }
                 WindowRequest::Decorate(decorate) => {
-                    let decorations = match decorate {
-                        true => Decorations::FollowServer,
-                        false => Decorations::None,
-                    };
-
-                    window_handle.window.set_decorate(decorations);
+                    window_handle.window.set_decorated(decorate);
 
                     // We should refresh the frame to apply decorations change.
                     let window_request = window_user_requests.get_mut(window_id).unwrap();
@@ -511,20 +906,42 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
                     }
 
                     window_handle.window.resize(size.width, size.height);
+                    window_handle.window.request_size(size.width, size.height);
+                    window_handle.resize_viewport(size);
 
                     // We should refresh the frame after resize.
                     let window_request = window_user_requests.get_mut(window_id).unwrap();
                     window_request.refresh_frame = true;
                 }
+                WindowRequest::Position(position) => {
+                    window_handle.window.set_position(position);
+                }
                 WindowRequest::PassthroughMouseInput(passthrough) => {
                     window_handle.passthrough_mouse_input(passthrough);
 
                     let window_request = window_user_requests.get_mut(window_id).unwrap();
                     window_request.refresh_frame = true;
                 }
+                WindowRequest::InputRegion(input_region) => {
+                    window_handle.set_input_region(input_region);
+
+                    let window_request = window_user_requests.get_mut(window_id).unwrap();
+                    window_request.refresh_frame = true;
+                }
+                WindowRequest::IdleInhibit(inhibit) => {
+                    window_handle.set_idle_inhibit(inhibit);
+                }
+                WindowRequest::ShortcutsInhibit(inhibit) => {
+                    let event_sink = &mut winit_state.event_sink;
+                    window_handle.set_shortcuts_inhibited(inhibit, event_sink);
+                }
                 WindowRequest::Attention(request_type) => {
                     window_handle.set_user_attention(request_type);
                 }
+                WindowRequest::SetClipboard(kind, contents) => {
+                    // Selections are per seat, so any focused window can set them.
+                    clipboard_requests.push((kind, contents));
+                }
                 WindowRequest::Redraw => {
                     let window_request = window_user_requests.get_mut(window_id).unwrap();
                     window_request.redraw_requested = true;
@@ -541,6 +958,17 @@ pub fn handle_window_requests(winit_state: &mut WinitState) {
         }
     }
 
+    // Set the selections through whichever seat is focused on our windows.
+    let data_device = window_map
+        .values()
+        .find_map(|window_handle| window_handle.data_devices.first());
+    // Otherwise the focus was lost in the meantime, and the contents are dropped.
+    if let Some(data_device) = data_device {
+        for (kind, contents) in clipboard_requests {
+            data_device.set_selection(kind, contents);
+        }
+    }
+
     // Close the windows.
     for window in windows_to_close {
         let _ = window_map.remove(&window);
@@ -553,6 +981,20 @@ impl Drop for WindowHandle {
     fn drop(&mut self) {
         unsafe {
             let surface = self.window.surface().clone();
+
+            if let Some(idle_inhibitor) = self.idle_inhibitor.get_mut().take() {
+                idle_inhibitor.destroy();
+            }
+            for (_, shortcuts_inhibitor) in self.shortcuts_inhibitors.get_mut().drain(..) {
+                shortcuts_inhibitor.destroy();
+            }
+            if let Some(fractional_scale) = self.fractional_scale.take() {
+                fractional_scale.destroy();
+            }
+            if let Some(viewport) = self.viewport.take() {
+                viewport.destroy();
+            }
+
             // The window must be destroyed before wl_surface.
             ManuallyDrop::drop(&mut self.window);
             surface.destroy();
@@ -0,0 +1,524 @@
+//! Toplevel windows of `xdg_shell`.
+//!
+//! SCTK's window keeps its `xdg_toplevel` and decoration objects private, so it can't report the
+//! decoration mode picked by the compositor. Toplevels are thus managed here on `xdg_shell`,
+//! drawing the client-side decorations with the same frame as SCTK.
+
+use std::cell::RefCell;
+use std::cmp::{max, min};
+use std::convert::TryInto;
+use std::rc::Rc;
+
+use sctk::environment::Environment;
+use sctk::reexports::client::protocol::wl_compositor::WlCompositor;
+use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_shm::WlShm;
+use sctk::reexports::client::protocol::wl_subcompositor::WlSubcompositor;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{Attached, DispatchData};
+use sctk::reexports::protocols::unstable::xdg_decoration::v1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1;
+use sctk::reexports::protocols::unstable::xdg_decoration::v1::client::zxdg_toplevel_decoration_v1::{
+    Event as DecorationEvent, Mode, ZxdgToplevelDecorationV1,
+};
+use sctk::reexports::protocols::xdg_shell::client::xdg_surface::{
+    Event as XdgSurfaceEvent, XdgSurface,
+};
+use sctk::reexports::protocols::xdg_shell::client::xdg_toplevel::{
+    Event as XdgToplevelEvent, XdgToplevel,
+};
+use sctk::reexports::protocols::xdg_shell::client::xdg_wm_base::XdgWmBase;
+use sctk::seat::pointer::ThemeManager;
+use sctk::seat::SeatListener;
+use sctk::window::{Frame, FrameRequest, State};
+
+use crate::window::DecorationMode;
+
+use super::super::env::WinitEnv;
+use super::WinitFrame;
+
+/// The minimum size of the window, 2 pixels wide to work around a bug in mutter, see
+/// https://gitlab.gnome.org/GNOME/mutter/issues/259.
+const MIN_WINDOW_SIZE: (u32, u32) = (2, 1);
+
+/// The events of a toplevel.
+pub enum Event {
+    /// The frame needs to be redrawn.
+    Refresh,
+
+    /// The compositor configured the window.
+    Configure {
+        /// The size of the content of the window, or `None` to keep the current one.
+        new_size: Option<(u32, u32)>,
+
+        /// The states of the window.
+        states: Vec<State>,
+
+        /// Who draws the decorations, either [`DecorationMode::Client`] or
+        /// [`DecorationMode::Server`].
+        decoration_mode: DecorationMode,
+    },
+
+    /// The user asked to close the window.
+    Close,
+}
+
+type Callback = Rc<RefCell<Box<dyn FnMut(Event, DispatchData<'_>)>>>;
+
+/// The size and states of a toplevel configure.
+type Configure = (Option<(u32, u32)>, Vec<State>);
+
+/// A toplevel window, with its decorations.
+pub struct Toplevel {
+    surface: WlSurface,
+    xdg_surface: XdgSurface,
+    xdg_toplevel: XdgToplevel,
+
+    /// Negotiates the decoration mode, if the compositor supports it and the window doesn't ask
+    /// for client-side decorations only.
+    decoration: Option<ZxdgToplevelDecorationV1>,
+
+    /// The client-side decorations.
+    frame: Rc<RefCell<WinitFrame>>,
+
+    inner: Rc<RefCell<ToplevelInner>>,
+
+    /// Shows the frame to the pointers of new seats.
+    _seat_listener: SeatListener,
+}
+
+struct ToplevelInner {
+    /// The minimum size of the content.
+    min_size: (u32, u32),
+
+    /// The maximum size of the content.
+    max_size: Option<(u32, u32)>,
+
+    /// The size of the content.
+    current_size: (u32, u32),
+
+    /// The size to restore when the window is no longer maximized, fullscreen or tiled.
+    old_size: Option<(u32, u32)>,
+
+    /// Whether the window should be decorated.
+    decorated: bool,
+
+    /// The decoration mode asked for, the compositor picks it when it's `None`.
+    preferred_mode: Option<DecorationMode>,
+
+    /// The decoration mode of the last decoration configure.
+    decoration_mode: DecorationMode,
+
+    /// The size and states of the configure being received.
+    pending_configure: Option<Configure>,
+
+    /// The states of the last configure.
+    states: Vec<State>,
+}
+
+impl ToplevelInner {
+    /// Whether the frame is drawn.
+    ///
+    /// The frame is hidden when the compositor draws the decorations, and a window asking for
+    /// server-side decorations only is left undecorated when the compositor refuses them.
+    fn frame_visible(&self) -> bool {
+        self.decorated
+            && self.decoration_mode == DecorationMode::Client
+            && self.preferred_mode != Some(DecorationMode::Server)
+    }
+}
+
+impl Toplevel {
+    /// Gives the toplevel role to `surface`.
+    ///
+    /// The surface isn't committed, so that the properties of the window can be set before the
+    /// compositor configures it.
+    pub fn new<F>(
+        env: &Environment<WinitEnv>,
+        xdg_wm_base: &Attached<XdgWmBase>,
+        surface: &WlSurface,
+        theme_manager: ThemeManager,
+        (width, height): (u32, u32),
+        preferred_mode: Option<DecorationMode>,
+        callback: F,
+    ) -> Result<Self, <WinitFrame as Frame>::Error>
+    where
+        F: FnMut(Event, DispatchData<'_>) + 'static,
+    {
+        let compositor = env.require_global::<WlCompositor>();
+        let subcompositor = env.require_global::<WlSubcompositor>();
+        let shm = env.require_global::<WlShm>();
+
+        let callback: Callback = Rc::new(RefCell::new(Box::new(callback)));
+
+        let xdg_surface = xdg_wm_base.get_xdg_surface(surface);
+        let xdg_toplevel = xdg_surface.get_toplevel();
+
+        let frame_toplevel = xdg_toplevel.detach();
+        let frame_callback = callback.clone();
+        let mut frame = WinitFrame::init(
+            surface,
+            &compositor,
+            &subcompositor,
+            &shm,
+            Some(theme_manager),
+            Box::new(move |request, serial, dispatch_data| match request {
+                FrameRequest::Minimize => frame_toplevel.set_minimized(),
+                FrameRequest::Maximize => frame_toplevel.set_maximized(),
+                FrameRequest::UnMaximize => frame_toplevel.unset_maximized(),
+                FrameRequest::Move(seat) => frame_toplevel._move(&seat, serial),
+                FrameRequest::Resize(seat, edges) => frame_toplevel.resize(&seat, serial, edges),
+                FrameRequest::ShowMenu(seat, x, y) => {
+                    frame_toplevel.show_window_menu(&seat, serial, x, y)
+                }
+                FrameRequest::Close => (frame_callback.borrow_mut())(Event::Close, dispatch_data),
+                FrameRequest::Refresh => {
+                    (frame_callback.borrow_mut())(Event::Refresh, dispatch_data)
+                }
+            }),
+        )?;
+
+        let inner = ToplevelInner {
+            min_size: MIN_WINDOW_SIZE,
+            max_size: None,
+            current_size: (width, height),
+            old_size: None,
+            decorated: true,
+            preferred_mode,
+            decoration_mode: DecorationMode::Client,
+            pending_configure: None,
+            states: Vec::new(),
+        };
+        frame.set_hidden(!inner.frame_visible());
+        frame.resize((width, height));
+        let frame = Rc::new(RefCell::new(frame));
+        let inner = Rc::new(RefCell::new(inner));
+
+        let toplevel_inner = inner.clone();
+        let toplevel_callback = callback.clone();
+        xdg_toplevel.quick_assign(move |_, event, dispatch_data| match event {
+            XdgToplevelEvent::Configure {
+                width,
+                height,
+                states,
+            } => {
+                // The window picks its size when either dimension is zero.
+                let new_size = if width > 0 && height > 0 {
+                    Some((width as u32, height as u32))
+                } else {
+                    None
+                };
+                let states = states
+                    .chunks_exact(4)
+                    .map(|state| u32::from_ne_bytes(state.try_into().unwrap()))
+                    .flat_map(State::from_raw)
+                    .collect();
+
+                toplevel_inner.borrow_mut().pending_configure = Some((new_size, states));
+            }
+            XdgToplevelEvent::Close => {
+                (toplevel_callback.borrow_mut())(Event::Close, dispatch_data);
+            }
+            _ => (),
+        });
+
+        let decoration = match env.get_global::<ZxdgDecorationManagerV1>() {
+            Some(manager) if preferred_mode != Some(DecorationMode::Client) => {
+                let decoration = manager.get_toplevel_decoration(&xdg_toplevel);
+                let decoration_inner = inner.clone();
+                decoration.quick_assign(move |_, event, _| {
+                    // The mode applies with the next configure of the surface.
+                    if let DecorationEvent::Configure { mode } = event {
+                        decoration_inner.borrow_mut().decoration_mode = match mode {
+                            Mode::ServerSide => DecorationMode::Server,
+                            _ => DecorationMode::Client,
+                        };
+                    }
+                });
+                Some(decoration.detach())
+            }
+            _ => None,
+        };
+
+        let surface_frame = frame.clone();
+        let surface_inner = inner.clone();
+        xdg_surface.quick_assign(move |xdg_surface, event, mut dispatch_data| {
+            let serial = match event {
+                XdgSurfaceEvent::Configure { serial } => serial,
+                _ => return,
+            };
+            xdg_surface.ack_configure(serial);
+
+            let mut frame = surface_frame.borrow_mut();
+            let mut inner = surface_inner.borrow_mut();
+
+            // The decoration mode may change without the toplevel being configured again.
+            let (mut new_size, states) = match inner.pending_configure.take() {
+                Some(configure) => configure,
+                None => (None, inner.states.clone()),
+            };
+            inner.states = states.clone();
+
+            // The borders depend on the decorations and states of the window, so they're applied
+            // before the size.
+            frame.set_hidden(!inner.frame_visible());
+            let need_refresh = frame.set_states(&states);
+
+            new_size = new_size.map(|(width, height)| {
+                let (mut width, mut height) = frame.subtract_borders(width as i32, height as i32);
+                width = max(width, inner.min_size.0 as i32);
+                height = max(height, inner.min_size.1 as i32);
+                if let Some((max_width, max_height)) = inner.max_size {
+                    width = min(width, max_width as i32);
+                    height = min(height, max_height as i32);
+                }
+                (max(width, 1) as u32, max(height, 1) as u32)
+            });
+
+            // Remember the size while the window is maximized, fullscreen or tiled, to restore it
+            // afterwards.
+            let stash_size = states.iter().any(|state| {
+                matches!(
+                    state,
+                    State::Maximized
+                        | State::Fullscreen
+                        | State::TiledTop
+                        | State::TiledRight
+                        | State::TiledBottom
+                        | State::TiledLeft
+                )
+            });
+            if stash_size {
+                if inner.old_size.is_none() {
+                    inner.old_size = Some(inner.current_size);
+                }
+            } else if new_size.is_none() {
+                new_size = inner.old_size.take();
+            } else {
+                inner.old_size = None;
+            }
+
+            let decoration_mode = inner.decoration_mode;
+            drop(frame);
+            drop(inner);
+
+            let mut callback = callback.borrow_mut();
+            if need_refresh {
+                callback(Event::Refresh, dispatch_data.reborrow());
+            }
+            callback(
+                Event::Configure {
+                    new_size,
+                    states,
+                    decoration_mode,
+                },
+                dispatch_data,
+            );
+        });
+
+        // Show the frame to the pointers.
+        let mut seats = Vec::<WlSeat>::new();
+        for seat in env.get_all_seats() {
+            sctk::seat::with_seat_data(&seat, |seat_data| {
+                if seat_data.has_pointer && !seat_data.defunct {
+                    seats.push(seat.detach());
+                    frame.borrow_mut().new_seat(&seat);
+                }
+            });
+        }
+
+        let seat_frame = frame.clone();
+        let seat_listener = env.listen_for_seats(move |seat, seat_data, _| {
+            let is_known = seats.contains(&seat);
+            if !is_known && seat_data.has_pointer && !seat_data.defunct {
+                seat_frame.borrow_mut().new_seat(&seat);
+                seats.push(seat.detach());
+            } else if is_known && (!seat_data.has_pointer || seat_data.defunct) {
+                seat_frame.borrow_mut().remove_seat(&seat);
+                seats.retain(|known_seat| known_seat != &*seat);
+            }
+        });
+
+        let toplevel = Self {
+            surface: surface.clone(),
+            xdg_surface: xdg_surface.detach(),
+            xdg_toplevel: xdg_toplevel.detach(),
+            decoration,
+            frame,
+            inner,
+            _seat_listener: seat_listener,
+        };
+
+        toplevel.set_min_size(None);
+        toplevel.resize(width, height);
+        toplevel.request_decoration_mode();
+
+        Ok(toplevel)
+    }
+
+    pub fn surface(&self) -> &WlSurface {
+        &self.surface
+    }
+
+    /// The `xdg_surface` popups are attached to.
+    pub fn xdg_surface(&self) -> &XdgSurface {
+        &self.xdg_surface
+    }
+
+    /// The position of the window geometry, which includes the decorations, in the surface.
+    pub fn geometry_position(&self) -> (i32, i32) {
+        self.frame.borrow().location()
+    }
+
+    /// Redraws the frame.
+    pub fn refresh(&mut self) {
+        self.frame.borrow_mut().redraw();
+    }
+
+    /// Asks the compositor for the decoration mode.
+    fn request_decoration_mode(&self) {
+        let decoration = match self.decoration.as_ref() {
+            Some(decoration) => decoration,
+            None => return,
+        };
+
+        let inner = self.inner.borrow();
+        if !inner.decorated {
+            // The frame is hidden, which only works with client-side decorations.
+            decoration.set_mode(Mode::ClientSide);
+            return;
+        }
+
+        match inner.preferred_mode {
+            None => decoration.unset_mode(),
+            Some(DecorationMode::Server) | Some(DecorationMode::PreferServer) => {
+                decoration.set_mode(Mode::ServerSide)
+            }
+            Some(DecorationMode::Client) | Some(DecorationMode::PreferClient) => {
+                decoration.set_mode(Mode::ClientSide)
+            }
+        }
+    }
+
+    /// Shows or hides the decorations.
+    pub fn set_decorated(&mut self, decorated: bool) {
+        let visible = {
+            let mut inner = self.inner.borrow_mut();
+            inner.decorated = decorated;
+            inner.frame_visible()
+        };
+
+        self.frame.borrow_mut().set_hidden(!visible);
+        self.request_decoration_mode();
+    }
+
+    pub fn set_title(&self, mut title: String) {
+        // Keep the title short enough for the protocol messages.
+        if title.len() > 1024 {
+            let mut len = 1024;
+            while !title.is_char_boundary(len) {
+                len -= 1;
+            }
+            title.truncate(len);
+        }
+
+        self.frame.borrow_mut().set_title(title.clone());
+        self.xdg_toplevel.set_title(title);
+    }
+
+    pub fn set_app_id(&self, app_id: String) {
+        self.xdg_toplevel.set_app_id(app_id);
+    }
+
+    /// Locks the size of the window to its current size when it's not resizable.
+    pub fn set_resizable(&self, resizable: bool) {
+        let mut frame = self.frame.borrow_mut();
+        frame.set_resizable(resizable);
+
+        let inner = self.inner.borrow();
+        let (min_size, max_size) = if resizable {
+            (inner.min_size, inner.max_size)
+        } else {
+            (inner.current_size, Some(inner.current_size))
+        };
+
+        let (width, height) = frame.add_borders(min_size.0 as i32, min_size.1 as i32);
+        self.xdg_toplevel.set_min_size(width, height);
+        let (width, height) = max_size
+            .map(|(width, height)| frame.add_borders(width as i32, height as i32))
+            .unwrap_or((0, 0));
+        self.xdg_toplevel.set_max_size(width, height);
+    }
+
+    /// Resizes the frame around content of the given size.
+    pub fn resize(&self, width: u32, height: u32) {
+        let width = max(width, 1);
+        let height = max(height, 1);
+        self.inner.borrow_mut().current_size = (width, height);
+
+        let mut frame = self.frame.borrow_mut();
+        frame.resize((width, height));
+        let (width, height) = frame.add_borders(width as i32, height as i32);
+        let (x, y) = frame.location();
+        self.xdg_surface.set_window_geometry(x, y, width, height);
+    }
+
+    pub fn set_maximized(&self) {
+        self.xdg_toplevel.set_maximized();
+    }
+
+    pub fn unset_maximized(&self) {
+        self.xdg_toplevel.unset_maximized();
+    }
+
+    pub fn set_minimized(&self) {
+        self.xdg_toplevel.set_minimized();
+    }
+
+    pub fn set_fullscreen(&self, output: Option<&WlOutput>) {
+        self.xdg_toplevel.set_fullscreen(output);
+    }
+
+    pub fn unset_fullscreen(&self) {
+        self.xdg_toplevel.unset_fullscreen();
+    }
+
+    /// Sets the minimum size of the content.
+    pub fn set_min_size(&self, size: Option<(u32, u32)>) {
+        let size = size.unwrap_or(MIN_WINDOW_SIZE);
+        let (width, height) = self
+            .frame
+            .borrow()
+            .add_borders(size.0 as i32, size.1 as i32);
+        self.xdg_toplevel.set_min_size(width, height);
+        self.inner.borrow_mut().min_size = size;
+    }
+
+    /// Sets the maximum size of the content.
+    pub fn set_max_size(&self, size: Option<(u32, u32)>) {
+        let (width, height) = size
+            .map(|(width, height)| self.frame.borrow().add_borders(width as i32, height as i32))
+            .unwrap_or((0, 0));
+        self.xdg_toplevel.set_max_size(width, height);
+        self.inner.borrow_mut().max_size = size;
+    }
+
+    #[cfg(feature = "sctk-adwaita")]
+    pub fn set_frame_config(&self, config: <WinitFrame as Frame>::Config) {
+        self.frame.borrow_mut().set_config(config);
+    }
+
+    pub fn start_interactive_move(&self, seat: &WlSeat, serial: u32) {
+        self.xdg_toplevel._move(seat, serial);
+    }
+}
+
+impl Drop for Toplevel {
+    fn drop(&mut self) {
+        if let Some(decoration) = self.decoration.take() {
+            decoration.destroy();
+        }
+        self.xdg_toplevel.destroy();
+        self.xdg_surface.destroy();
+    }
+}
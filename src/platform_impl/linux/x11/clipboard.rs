@@ -0,0 +1,770 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::{CStr, CString},
+    mem,
+    os::raw::*,
+    ptr, slice,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use super::{ffi, util, XConnection, XError};
+use crate::{
+    clipboard::{ClipboardContents, ClipboardKind, TEXT_MIME_TYPE},
+    platform_impl::platform::OsError,
+};
+
+/// How long the selection owner may stay silent before we give up on a transfer.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Payloads larger than this are sent in several steps using the `INCR` mechanism.
+///
+/// This stays well below the maximum request size of servers without BIG-REQUESTS.
+const INCR_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub struct ClipboardAtoms {
+    pub clipboard: ffi::Atom,
+    pub primary: ffi::Atom,
+    pub targets: ffi::Atom,
+    pub timestamp: ffi::Atom,
+    pub incr: ffi::Atom,
+    pub atom: ffi::Atom,
+    pub utf8_string: ffi::Atom,
+    pub string: ffi::Atom,
+    pub text: ffi::Atom,
+    pub text_plain: ffi::Atom,
+    pub text_plain_utf8: ffi::Atom,
+    /// Property on our own window which receives the transferred data.
+    pub transfer: ffi::Atom,
+}
+
+impl ClipboardAtoms {
+    pub fn new(xconn: &Arc<XConnection>) -> Result<Self, XError> {
+        let names = [
+            b"CLIPBOARD\0".as_ptr() as *mut c_char,
+            b"PRIMARY\0".as_ptr() as *mut c_char,
+            b"TARGETS\0".as_ptr() as *mut c_char,
+            b"TIMESTAMP\0".as_ptr() as *mut c_char,
+            b"INCR\0".as_ptr() as *mut c_char,
+            b"ATOM\0".as_ptr() as *mut c_char,
+            b"UTF8_STRING\0".as_ptr() as *mut c_char,
+            b"STRING\0".as_ptr() as *mut c_char,
+            b"TEXT\0".as_ptr() as *mut c_char,
+            b"text/plain\0".as_ptr() as *mut c_char,
+            b"text/plain;charset=utf-8\0".as_ptr() as *mut c_char,
+            b"_WINIT_CLIPBOARD_TRANSFER\0".as_ptr() as *mut c_char,
+        ];
+        let atoms = unsafe { xconn.get_atoms(&names) }?;
+        Ok(ClipboardAtoms {
+            clipboard: atoms[0],
+            primary: atoms[1],
+            targets: atoms[2],
+            timestamp: atoms[3],
+            incr: atoms[4],
+            atom: atoms[5],
+            utf8_string: atoms[6],
+            string: atoms[7],
+            text: atoms[8],
+            text_plain: atoms[9],
+            text_plain_utf8: atoms[10],
+            transfer: atoms[11],
+        })
+    }
+
+    /// The targets other clients may use to ask for text, in order of preference.
+    fn text_targets(&self) -> [ffi::Atom; 5] {
+        [
+            self.utf8_string,
+            self.text_plain_utf8,
+            self.text_plain,
+            self.string,
+            self.text,
+        ]
+    }
+}
+
+/// An outgoing `INCR` transfer, driven by the requestor deleting the property we wrote.
+struct IncrTransfer {
+    requestor: ffi::Window,
+    property: ffi::Atom,
+    target: ffi::Atom,
+    data: Vec<u8>,
+    offset: usize,
+    /// When the requestor last asked for a chunk.
+    last_activity: Instant,
+}
+
+/// A selection we own.
+struct OwnedSelection {
+    contents: ClipboardContents,
+    /// The server time at which we took ownership, answered to the `TIMESTAMP` target.
+    time: ffi::Time,
+}
+
+#[derive(Default)]
+struct ClipboardState {
+    /// Contents we're serving, by selection atom.
+    owned: HashMap<ffi::Atom, OwnedSelection>,
+    incr_transfers: Vec<IncrTransfer>,
+    /// Number of transfers currently waiting for events on our window.
+    pending_reads: usize,
+    /// Events for our window that the event loop dequeued while a transfer was pending.
+    pending_events: VecDeque<ffi::XEvent>,
+}
+
+/// Owner and reader of the `CLIPBOARD` and `PRIMARY` selections.
+///
+/// Selections are owned by a hidden window, so that they outlive the window the user set them
+/// from, and transfers never interfere with the events of the user's windows.
+pub struct Clipboard {
+    xconn: Arc<XConnection>,
+    window: ffi::Window,
+    pub atoms: ClipboardAtoms,
+    /// The event type of `XFixesSelectionNotify`, if the XFixes extension is available.
+    xfixes_selection_notify: Option<c_int>,
+    state: Mutex<ClipboardState>,
+}
+
+unsafe impl Send for Clipboard {}
+unsafe impl Sync for Clipboard {}
+
+impl Clipboard {
+    pub fn new(xconn: Arc<XConnection>, root: ffi::Window) -> Result<Self, XError> {
+        let atoms = ClipboardAtoms::new(&xconn)?;
+
+        let window = unsafe {
+            let window = (xconn.xlib.XCreateSimpleWindow)(xconn.display, root, 0, 0, 1, 1, 0, 0, 0);
+            (xconn.xlib.XSelectInput)(xconn.display, window, ffi::PropertyChangeMask);
+            window
+        };
+        xconn.check_errors()?;
+
+        // Get notified when another client takes ownership of a selection.
+        let xfixes_selection_notify = xconn.xfixes.as_ref().and_then(|xfixes| unsafe {
+            let mut event_base = 0;
+            let mut error_base = 0;
+            if (xfixes.XFixesQueryExtension)(xconn.display, &mut event_base, &mut error_base)
+                == ffi::False
+            {
+                return None;
+            }
+            for selection in [atoms.clipboard, atoms.primary] {
+                (xfixes.XFixesSelectSelectionInput)(
+                    xconn.display,
+                    window,
+                    selection,
+                    ffi::XFixesSetSelectionOwnerNotifyMask,
+                );
+            }
+            Some(event_base + ffi::XFixesSelectionNotify)
+        });
+
+        Ok(Clipboard {
+            xconn,
+            window,
+            atoms,
+            xfixes_selection_notify,
+            state: Default::default(),
+        })
+    }
+
+    #[inline]
+    pub fn window(&self) -> ffi::Window {
+        self.window
+    }
+
+    #[inline]
+    pub fn xfixes_selection_notify(&self) -> Option<c_int> {
+        self.xfixes_selection_notify
+    }
+
+    pub fn selection_atom(&self, kind: ClipboardKind) -> ffi::Atom {
+        match kind {
+            ClipboardKind::Clipboard => self.atoms.clipboard,
+            ClipboardKind::Primary => self.atoms.primary,
+        }
+    }
+
+    pub fn selection_kind(&self, selection: ffi::Atom) -> Option<ClipboardKind> {
+        if selection == self.atoms.clipboard {
+            Some(ClipboardKind::Clipboard)
+        } else if selection == self.atoms.primary {
+            Some(ClipboardKind::Primary)
+        } else {
+            None
+        }
+    }
+
+    /// Takes ownership of `selection`, serving `contents` to other clients.
+    pub fn store(&self, selection: ffi::Atom, contents: ClipboardContents) -> Result<(), OsError> {
+        // ICCCM forbids claiming a selection with `CurrentTime`, since it can't be compared
+        // against the time of later claims.
+        let time = self
+            .server_time()
+            .ok_or(OsError::XMisc("Timed out waiting for the server time"))?;
+
+        // The contents must be in place before any request for them can arrive.
+        self.state
+            .lock()
+            .owned
+            .insert(selection, OwnedSelection { contents, time });
+        unsafe {
+            (self.xconn.xlib.XSetSelectionOwner)(self.xconn.display, selection, self.window, time);
+        }
+        if let Err(err) = self.xconn.check_errors() {
+            self.state.lock().owned.remove(&selection);
+            return Err(OsError::XError(err));
+        }
+
+        // The server ignores the request if someone claimed the selection after `time`.
+        if !self.owns(selection) {
+            self.state.lock().owned.remove(&selection);
+            return Err(OsError::XMisc("Failed to take ownership of the selection"));
+        }
+        Ok(())
+    }
+
+    /// Gets the current server time, from the notification of an empty append to a property of
+    /// our window.
+    fn server_time(&self) -> Option<ffi::Time> {
+        self.with_pending_reads(|| unsafe {
+            let deadline = Instant::now() + TRANSFER_TIMEOUT;
+            self.xconn
+                .change_property(
+                    self.window,
+                    self.atoms.timestamp,
+                    self.atoms.timestamp,
+                    util::PropMode::Append,
+                    &[] as &[c_uchar],
+                )
+                .flush()
+                .ok()?;
+            loop {
+                let xev = self.wait_for_event(ffi::PropertyNotify, deadline)?;
+                let xev: &ffi::XPropertyEvent = xev.as_ref();
+                if xev.atom == self.atoms.timestamp {
+                    return Some(xev.time);
+                }
+            }
+        })
+    }
+
+    /// Returns whether we still own `selection`.
+    fn owns(&self, selection: ffi::Atom) -> bool {
+        let owner = unsafe { (self.xconn.xlib.XGetSelectionOwner)(self.xconn.display, selection) };
+        owner == self.window
+    }
+
+    pub fn load(&self, selection: ffi::Atom, mime_type: &str) -> Option<Vec<u8>> {
+        // We can't ask ourselves through the server, since answering requires the event loop.
+        if self.owns(selection) {
+            let state = self.state.lock();
+            return state
+                .owned
+                .get(&selection)
+                .and_then(|owned| owned.contents.get(mime_type))
+                .map(<[u8]>::to_vec);
+        }
+
+        if mime_type == TEXT_MIME_TYPE {
+            // Ask for the offered targets first, so that an unresponsive owner only makes us wait
+            // once rather than for every text target.
+            let targets = atoms_from_bytes(&self.convert(selection, self.atoms.targets)?);
+            self.convert(selection, self.offered_target(&targets, mime_type)?)
+        } else {
+            self.convert(selection, self.mime_atom(mime_type)?)
+        }
+    }
+
+    pub fn mime_types(&self, selection: ffi::Atom) -> Vec<String> {
+        if self.owns(selection) {
+            let state = self.state.lock();
+            return state
+                .owned
+                .get(&selection)
+                .map(|owned| owned.contents.mime_types().map(str::to_owned).collect())
+                .unwrap_or_default();
+        }
+
+        match self.convert(selection, self.atoms.targets) {
+            Some(targets) => self.target_mime_types(&atoms_from_bytes(&targets)),
+            None => Vec::new(),
+        }
+    }
+
+    /// Converts the targets a selection owner offers to MIME types.
+    pub fn target_mime_types(&self, targets: &[ffi::Atom]) -> Vec<String> {
+        let mut mime_types: Vec<String> = Vec::new();
+        for &target in targets {
+            let mime_type = if self.atoms.text_targets().contains(&target) {
+                TEXT_MIME_TYPE.to_owned()
+            } else {
+                match self.atom_name(target) {
+                    // Everything else without a slash is a protocol target, not a data type.
+                    Some(name) if name.contains('/') => name,
+                    _ => continue,
+                }
+            };
+            if !mime_types.contains(&mime_type) {
+                mime_types.push(mime_type);
+            }
+        }
+        mime_types
+    }
+
+    /// Hands over an event for our window that the event loop dequeued, in case a transfer is
+    /// waiting for it on another thread.
+    pub fn queue_event(&self, xev: &ffi::XEvent) {
+        let mut state = self.state.lock();
+        if state.pending_reads > 0 {
+            state.pending_events.push_back(*xev);
+        }
+    }
+
+    /// Answers a `SelectionRequest` for one of the selections we own.
+    pub fn handle_request(&self, request: &ffi::XSelectionRequestEvent) {
+        // Obsolete clients don't specify a property, in which case the target is used instead.
+        let property = if request.property == 0 {
+            request.target
+        } else {
+            request.property
+        };
+
+        let converted = self.convert_for_request(request, property);
+
+        let event = ffi::XSelectionEvent {
+            type_: ffi::SelectionNotify,
+            serial: 0,
+            send_event: ffi::True,
+            display: self.xconn.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: if converted { property } else { 0 },
+            time: request.time,
+        };
+        self.xconn
+            .send_event(request.requestor, None, event)
+            .queue();
+        let _ = self.xconn.flush_requests();
+    }
+
+    fn convert_for_request(
+        &self,
+        request: &ffi::XSelectionRequestEvent,
+        property: ffi::Atom,
+    ) -> bool {
+        let mut state = self.state.lock();
+        self.prune_incr_transfers(&mut state);
+        let (contents, time) = match state.owned.get(&request.selection) {
+            Some(owned) => (&owned.contents, owned.time),
+            None => return false,
+        };
+
+        // Refuse requests from before we owned the selection.
+        if request.time != ffi::CurrentTime && request.time < time {
+            return false;
+        }
+
+        if request.target == self.atoms.timestamp {
+            self.xconn
+                .change_property(
+                    request.requestor,
+                    property,
+                    ffi::XA_INTEGER,
+                    util::PropMode::Replace,
+                    &[time as c_ulong],
+                )
+                .queue();
+            return true;
+        }
+
+        if request.target == self.atoms.targets {
+            let mut targets = vec![self.atoms.targets, self.atoms.timestamp];
+            targets.extend(self.data_targets(contents));
+            self.xconn
+                .change_property(
+                    request.requestor,
+                    property,
+                    self.atoms.atom,
+                    util::PropMode::Replace,
+                    &targets,
+                )
+                .queue();
+            return true;
+        }
+
+        let (property_type, data) = if self.atoms.text_targets().contains(&request.target) {
+            match contents.get(TEXT_MIME_TYPE) {
+                Some(data) => (self.atoms.utf8_string, data),
+                None => return false,
+            }
+        } else {
+            let data = self
+                .atom_name(request.target)
+                .and_then(|mime_type| contents.get(&mime_type));
+            match data {
+                Some(data) => (request.target, data),
+                None => return false,
+            }
+        };
+
+        if data.len() <= INCR_CHUNK_SIZE {
+            self.xconn
+                .change_property(
+                    request.requestor,
+                    property,
+                    property_type,
+                    util::PropMode::Replace,
+                    data,
+                )
+                .queue();
+            return true;
+        }
+
+        // The requestor deleting the `INCR` property is our cue to send the first chunk.
+        let transfer = IncrTransfer {
+            requestor: request.requestor,
+            property,
+            target: property_type,
+            data: data.to_vec(),
+            offset: 0,
+            last_activity: Instant::now(),
+        };
+        unsafe {
+            (self.xconn.xlib.XSelectInput)(
+                self.xconn.display,
+                request.requestor,
+                ffi::PropertyChangeMask,
+            );
+        }
+        self.xconn
+            .change_property(
+                request.requestor,
+                property,
+                self.atoms.incr,
+                util::PropMode::Replace,
+                &[transfer.data.len() as c_ulong],
+            )
+            .queue();
+        state.incr_transfers.push(transfer);
+        true
+    }
+
+    /// Returns the targets under which `contents` can be requested.
+    pub fn data_targets(&self, contents: &ClipboardContents) -> Vec<ffi::Atom> {
+        let mut targets = Vec::new();
+        for mime_type in contents.mime_types() {
+            if mime_type == TEXT_MIME_TYPE {
+                targets.extend_from_slice(&self.atoms.text_targets());
+            } else {
+                targets.extend(self.mime_atom(mime_type));
+            }
+        }
+        targets
+    }
+
+    /// Continues an outgoing `INCR` transfer.
+    ///
+    /// Returns `true` if the event belonged to one of our transfers.
+    pub fn handle_property_notify(&self, xev: &ffi::XPropertyEvent) -> bool {
+        let mut state = self.state.lock();
+        self.prune_incr_transfers(&mut state);
+        let index =
+            match state.incr_transfers.iter().position(|transfer| {
+                transfer.requestor == xev.window && transfer.property == xev.atom
+            }) {
+                Some(index) => index,
+                None => return false,
+            };
+
+        if xev.state != ffi::PropertyDelete {
+            return true;
+        }
+
+        let transfer = &mut state.incr_transfers[index];
+        transfer.last_activity = Instant::now();
+        let end = (transfer.offset + INCR_CHUNK_SIZE).min(transfer.data.len());
+        // Writing an empty chunk marks the end of the transfer.
+        self.xconn
+            .change_property(
+                transfer.requestor,
+                transfer.property,
+                transfer.target,
+                util::PropMode::Replace,
+                &transfer.data[transfer.offset..end],
+            )
+            .queue();
+        let finished = transfer.offset == end;
+        transfer.offset = end;
+
+        if finished {
+            let transfer = state.incr_transfers.swap_remove(index);
+            self.unwatch_requestor(&state, transfer.requestor);
+        }
+        let _ = self.xconn.flush_requests();
+        true
+    }
+
+    /// Abandons the outgoing `INCR` transfers whose requestor stopped asking for chunks, e.g.
+    /// because it crashed.
+    fn prune_incr_transfers(&self, state: &mut ClipboardState) {
+        let now = Instant::now();
+        let mut abandoned = Vec::new();
+        state.incr_transfers.retain(|transfer| {
+            let alive = now.duration_since(transfer.last_activity) < TRANSFER_TIMEOUT;
+            if !alive {
+                abandoned.push(transfer.requestor);
+            }
+            alive
+        });
+        for requestor in abandoned {
+            self.unwatch_requestor(state, requestor);
+        }
+    }
+
+    /// Stops listening to the property changes of `requestor`, unless another `INCR` transfer to
+    /// it is still running.
+    fn unwatch_requestor(&self, state: &ClipboardState, requestor: ffi::Window) {
+        if state
+            .incr_transfers
+            .iter()
+            .any(|transfer| transfer.requestor == requestor)
+        {
+            return;
+        }
+        unsafe {
+            (self.xconn.xlib.XSelectInput)(self.xconn.display, requestor, ffi::NoEventMask);
+        }
+    }
+
+    /// Forgets the contents of a selection we lost ownership of.
+    pub fn handle_selection_clear(&self, selection: ffi::Atom) {
+        let mut state = self.state.lock();
+        state.owned.remove(&selection);
+        self.prune_incr_transfers(&mut state);
+    }
+
+    /// Asks the owner of `selection` to convert it to `target` and waits for the result.
+    fn convert(&self, selection: ffi::Atom, target: ffi::Atom) -> Option<Vec<u8>> {
+        self.with_pending_reads(|| unsafe { self.convert_inner(selection, target) })
+    }
+
+    /// Runs `f` while making the event loop hand over the events for our window.
+    fn with_pending_reads<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.state.lock().pending_reads += 1;
+        let result = f();
+        let mut state = self.state.lock();
+        state.pending_reads -= 1;
+        if state.pending_reads == 0 {
+            state.pending_events.clear();
+        }
+        result
+    }
+
+    unsafe fn convert_inner(&self, selection: ffi::Atom, target: ffi::Atom) -> Option<Vec<u8>> {
+        let mut deadline = Instant::now() + TRANSFER_TIMEOUT;
+
+        (self.xconn.xlib.XConvertSelection)(
+            self.xconn.display,
+            selection,
+            target,
+            self.atoms.transfer,
+            self.window,
+            ffi::CurrentTime,
+        );
+        self.xconn.flush_requests().ok()?;
+
+        let notify = loop {
+            let xev = self.wait_for_event(ffi::SelectionNotify, deadline)?;
+            let notify: &ffi::XSelectionEvent = xev.as_ref();
+            if notify.selection == selection && notify.target == target {
+                break *notify;
+            }
+        };
+        if notify.property == 0 {
+            return None;
+        }
+
+        // Property notifications received so far are stale, and would confuse an `INCR` transfer.
+        let mut xev = mem::zeroed();
+        while (self.xconn.xlib.XCheckTypedWindowEvent)(
+            self.xconn.display,
+            self.window,
+            ffi::PropertyNotify,
+            &mut xev,
+        ) == ffi::True
+        {}
+        self.state
+            .lock()
+            .pending_events
+            .retain(|xev| xev.get_type() != ffi::PropertyNotify);
+
+        let (property_type, data) = self.read_property(notify.property)?;
+        if property_type != self.atoms.incr {
+            return Some(data);
+        }
+
+        // The owner sends the data in chunks, each time we delete the property.
+        let mut data = Vec::new();
+        loop {
+            let xev = self.wait_for_event(ffi::PropertyNotify, deadline)?;
+            let xev: &ffi::XPropertyEvent = xev.as_ref();
+            if xev.atom != notify.property || xev.state != ffi::PropertyNewValue {
+                continue;
+            }
+            let (_, chunk) = self.read_property(notify.property)?;
+            if chunk.is_empty() {
+                return Some(data);
+            }
+            data.extend_from_slice(&chunk);
+            // The owner only has to keep up, not to finish within the timeout.
+            deadline = Instant::now() + TRANSFER_TIMEOUT;
+        }
+    }
+
+    /// Waits for an event of `event_type` targeting our window.
+    unsafe fn wait_for_event(&self, event_type: c_int, deadline: Instant) -> Option<ffi::XEvent> {
+        let mut xev = mem::zeroed();
+        loop {
+            {
+                let mut state = self.state.lock();
+                let queued = state
+                    .pending_events
+                    .iter()
+                    .position(|xev| xev.get_type() == event_type);
+                if let Some(index) = queued {
+                    return state.pending_events.remove(index);
+                }
+            }
+
+            if (self.xconn.xlib.XCheckTypedWindowEvent)(
+                self.xconn.display,
+                self.window,
+                event_type,
+                &mut xev,
+            ) == ffi::True
+            {
+                return Some(xev);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+
+            // The event loop may be reading from the connection on another thread, so don't
+            // block for too long on the socket.
+            let timeout = (deadline - now).min(Duration::from_millis(10));
+            let mut fd = libc::pollfd {
+                fd: self.xconn.x11_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            libc::poll(&mut fd, 1, timeout.as_millis() as c_int);
+        }
+    }
+
+    /// Reads and deletes a property of our window, whatever its type.
+    unsafe fn read_property(&self, property: ffi::Atom) -> Option<(ffi::Atom, Vec<u8>)> {
+        read_property(&self.xconn, self.window, property)
+    }
+
+    /// Picks the target under which an owner offering `targets` provides `mime_type`.
+    pub fn offered_target(&self, targets: &[ffi::Atom], mime_type: &str) -> Option<ffi::Atom> {
+        if mime_type == TEXT_MIME_TYPE {
+            self.atoms
+                .text_targets()
+                .iter()
+                .copied()
+                .find(|target| targets.contains(target))
+        } else {
+            self.mime_atom(mime_type)
+                .filter(|target| targets.contains(target))
+        }
+    }
+
+    fn mime_atom(&self, mime_type: &str) -> Option<ffi::Atom> {
+        CString::new(mime_type)
+            .ok()
+            .map(|mime_type| self.xconn.get_atom(mime_type))
+    }
+
+    fn atom_name(&self, atom: ffi::Atom) -> Option<String> {
+        unsafe {
+            let name = (self.xconn.xlib.XGetAtomName)(self.xconn.display, atom);
+            if name.is_null() {
+                let _ = self.xconn.check_errors();
+                return None;
+            }
+            let result = CStr::from_ptr(name).to_string_lossy().into_owned();
+            (self.xconn.xlib.XFree)(name as _);
+            Some(result)
+        }
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xconn.xlib.XDestroyWindow)(self.xconn.display, self.window);
+        }
+        let _ = self.xconn.check_errors();
+    }
+}
+
+/// Reads and deletes a property of `window`, whatever its type.
+pub unsafe fn read_property(
+    xconn: &XConnection,
+    window: ffi::Window,
+    property: ffi::Atom,
+) -> Option<(ffi::Atom, Vec<u8>)> {
+    let mut property_type = 0;
+    let mut format = 0;
+    let mut item_count = 0;
+    let mut bytes_after = 0;
+    let mut buf: *mut c_uchar = ptr::null_mut();
+
+    (xconn.xlib.XGetWindowProperty)(
+        xconn.display,
+        window,
+        property,
+        0,
+        // This is in 32-bit chunks, so it allows for a bit less than 8GiB.
+        c_long::from(i32::MAX),
+        ffi::True,
+        ffi::AnyPropertyType as ffi::Atom,
+        &mut property_type,
+        &mut format,
+        &mut item_count,
+        &mut bytes_after,
+        &mut buf,
+    );
+    xconn.check_errors().ok()?;
+
+    if buf.is_null() {
+        return Some((property_type, Vec::new()));
+    }
+    let item_size = match format {
+        16 => mem::size_of::<c_short>(),
+        32 => mem::size_of::<c_long>(),
+        _ => 1,
+    };
+    let data = slice::from_raw_parts(buf, item_count as usize * item_size).to_vec();
+    (xconn.xlib.XFree)(buf as _);
+    Some((property_type, data))
+}
+
+/// Interprets the data of a format 32 `ATOM` property.
+fn atoms_from_bytes(data: &[u8]) -> Vec<ffi::Atom> {
+    data.chunks_exact(mem::size_of::<ffi::Atom>())
+        .map(|chunk| {
+            let mut bytes = [0; mem::size_of::<ffi::Atom>()];
+            bytes.copy_from_slice(chunk);
+            ffi::Atom::from_ne_bytes(bytes)
+        })
+        .collect()
+}
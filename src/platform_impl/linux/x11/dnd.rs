@@ -1,14 +1,32 @@
 use std::{
-    io,
+    io, mem,
     os::raw::*,
     path::{Path, PathBuf},
     str::Utf8Error,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
+use parking_lot::Mutex;
 use percent_encoding::percent_decode;
 
-use super::{ffi, util, XConnection, XError};
+use super::{
+    clipboard::{self, Clipboard},
+    ffi, util, XConnection, XError,
+};
+use crate::{
+    dnd::{DndAction, DragData},
+    window::CursorIcon,
+};
+
+/// The XDND version we implement.
+const XDND_VERSION: c_long = 5;
+
+/// How long we wait for the target to confirm a drop with `XdndFinished` before giving up.
+const FINISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long we wait for each part of the dropped data before giving up.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(2);
 
 #[derive(Debug)]
 pub struct DndAtoms {
@@ -19,11 +37,17 @@ pub struct DndAtoms {
     pub position: ffi::Atom,
     pub status: ffi::Atom,
     pub action_private: ffi::Atom,
+    pub action_copy: ffi::Atom,
+    pub action_move: ffi::Atom,
+    pub action_link: ffi::Atom,
     pub selection: ffi::Atom,
     pub finished: ffi::Atom,
     pub type_list: ffi::Atom,
     pub uri_list: ffi::Atom,
     pub none: ffi::Atom,
+    pub incr: ffi::Atom,
+    /// Property on the drop target window which receives the dropped data.
+    pub drop_data: ffi::Atom,
 }
 
 impl DndAtoms {
@@ -36,11 +60,16 @@ impl DndAtoms {
             b"XdndPosition\0".as_ptr() as *mut c_char,
             b"XdndStatus\0".as_ptr() as *mut c_char,
             b"XdndActionPrivate\0".as_ptr() as *mut c_char,
+            b"XdndActionCopy\0".as_ptr() as *mut c_char,
+            b"XdndActionMove\0".as_ptr() as *mut c_char,
+            b"XdndActionLink\0".as_ptr() as *mut c_char,
             b"XdndSelection\0".as_ptr() as *mut c_char,
             b"XdndFinished\0".as_ptr() as *mut c_char,
             b"XdndTypeList\0".as_ptr() as *mut c_char,
             b"text/uri-list\0".as_ptr() as *mut c_char,
             b"None\0".as_ptr() as *mut c_char,
+            b"INCR\0".as_ptr() as *mut c_char,
+            b"_WINIT_DND_DATA\0".as_ptr() as *mut c_char,
         ];
         let atoms = unsafe { xconn.get_atoms(&names) }?;
         Ok(DndAtoms {
@@ -51,19 +80,38 @@ impl DndAtoms {
             position: atoms[4],
             status: atoms[5],
             action_private: atoms[6],
-            selection: atoms[7],
-            finished: atoms[8],
-            type_list: atoms[9],
-            uri_list: atoms[10],
-            none: atoms[11],
+            action_copy: atoms[7],
+            action_move: atoms[8],
+            action_link: atoms[9],
+            selection: atoms[10],
+            finished: atoms[11],
+            type_list: atoms[12],
+            uri_list: atoms[13],
+            none: atoms[14],
+            incr: atoms[15],
+            drop_data: atoms[16],
         })
     }
-}
 
-#[derive(Debug, Clone, Copy)]
-pub enum DndState {
-    Accepted,
-    Rejected,
+    pub fn action_atom(&self, action: DndAction) -> ffi::Atom {
+        match action {
+            DndAction::Copy => self.action_copy,
+            DndAction::Move => self.action_move,
+            DndAction::Link => self.action_link,
+        }
+    }
+
+    pub fn action(&self, atom: ffi::Atom) -> Option<DndAction> {
+        if atom == self.action_copy {
+            Some(DndAction::Copy)
+        } else if atom == self.action_move {
+            Some(DndAction::Move)
+        } else if atom == self.action_link {
+            Some(DndAction::Link)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -119,48 +167,6 @@ impl Dnd {
         self.result = None;
     }
 
-    pub unsafe fn send_status(
-        &self,
-        this_window: c_ulong,
-        target_window: c_ulong,
-        state: DndState,
-    ) -> Result<(), XError> {
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, self.atoms.action_private as c_long),
-            DndState::Rejected => (0, self.atoms.none as c_long),
-        };
-        self.xconn
-            .send_client_msg(
-                target_window,
-                target_window,
-                self.atoms.status,
-                None,
-                [this_window as c_long, accepted, 0, 0, action],
-            )
-            .flush()
-    }
-
-    pub unsafe fn send_finished(
-        &self,
-        this_window: c_ulong,
-        target_window: c_ulong,
-        state: DndState,
-    ) -> Result<(), XError> {
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, self.atoms.action_private as c_long),
-            DndState::Rejected => (0, self.atoms.none as c_long),
-        };
-        self.xconn
-            .send_client_msg(
-                target_window,
-                target_window,
-                self.atoms.finished,
-                None,
-                [this_window as c_long, accepted, action, 0, 0],
-            )
-            .flush()
-    }
-
     pub unsafe fn get_type_list(
         &self,
         source_window: c_ulong,
@@ -217,3 +223,840 @@ impl Dnd {
         }
     }
 }
+
+/// The application's answer to a drag over one of its windows.
+pub enum DropAnswer {
+    /// The application didn't answer, so only `text/uri-list` drops are accepted.
+    Undecided,
+    Rejected,
+    Accepted {
+        mime_type: String,
+        action: DndAction,
+        /// The offered target under which the source provides `mime_type`.
+        target: ffi::Atom,
+    },
+}
+
+/// A drag from another client, or one of our windows, over one of our windows.
+struct IncomingDrag {
+    window: ffi::Window,
+    source_window: ffi::Window,
+    /// The targets offered by the source.
+    targets: Vec<ffi::Atom>,
+    mime_types: Vec<String>,
+    /// Whether `DragEnter` was emitted, which waits for the first `XdndPosition` since
+    /// `XdndEnter` doesn't carry a position.
+    entered: bool,
+    /// Whether the application is handling an `XdndPosition`, whose `XdndStatus` then carries
+    /// the answer.
+    handling_position: bool,
+    answer: DropAnswer,
+}
+
+/// A dropped drag whose data we asked the source for.
+struct PendingDrop {
+    window: ffi::Window,
+    source_window: ffi::Window,
+    mime_type: String,
+    action: DndAction,
+    /// The data received so far, once the source started an `INCR` transfer.
+    incr_data: Option<Vec<u8>>,
+    /// When we give up on the transfer.
+    deadline: Instant,
+}
+
+/// A drop whose data transfer is over.
+pub struct FinishedDrop {
+    pub window: ffi::Window,
+    pub source_window: ffi::Window,
+    pub mime_type: String,
+    pub action: DndAction,
+    /// The dropped data, `None` if the transfer failed.
+    pub data: Option<Vec<u8>>,
+}
+
+/// XDND target state, shared with the windows so that they can answer the drag.
+pub struct DropTarget {
+    xconn: Arc<XConnection>,
+    /// Hidden window receiving the dropped data, so that transfers never interfere with the
+    /// events of the user's windows.
+    window: ffi::Window,
+    atoms: DndAtoms,
+    drag: Mutex<Option<IncomingDrag>>,
+    pending_drop: Mutex<Option<PendingDrop>>,
+}
+
+impl DropTarget {
+    pub fn new(xconn: Arc<XConnection>, root: ffi::Window) -> Result<Self, XError> {
+        let atoms = DndAtoms::new(&xconn)?;
+
+        let window = unsafe {
+            let window = (xconn.xlib.XCreateSimpleWindow)(xconn.display, root, 0, 0, 1, 1, 0, 0, 0);
+            (xconn.xlib.XSelectInput)(xconn.display, window, ffi::PropertyChangeMask);
+            window
+        };
+        xconn.check_errors()?;
+
+        Ok(DropTarget {
+            xconn,
+            window,
+            atoms,
+            drag: Mutex::new(None),
+            pending_drop: Mutex::new(None),
+        })
+    }
+
+    #[inline]
+    pub fn window(&self) -> ffi::Window {
+        self.window
+    }
+
+    /// Starts tracking a drag offering `targets`, as `mime_types`, which entered `window`.
+    pub fn enter(
+        &self,
+        window: ffi::Window,
+        source_window: ffi::Window,
+        targets: Vec<ffi::Atom>,
+        mime_types: Vec<String>,
+    ) {
+        *self.drag.lock() = Some(IncomingDrag {
+            window,
+            source_window,
+            targets,
+            mime_types,
+            entered: false,
+            handling_position: false,
+            answer: DropAnswer::Undecided,
+        });
+    }
+
+    /// Marks the application as handling a move of the drag over `window`.
+    ///
+    /// Returns the offered MIME types the first time, when `DragEnter` is due.
+    pub fn position(&self, window: ffi::Window) -> Option<Vec<String>> {
+        let mut drag = self.drag.lock();
+        match drag.as_mut() {
+            Some(drag) if drag.window == window => {
+                drag.handling_position = true;
+                if drag.entered {
+                    return None;
+                }
+                drag.entered = true;
+                Some(drag.mime_types.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Accepts the drag over `window`, asking for its data as `mime_type`.
+    ///
+    /// Rejects the drag if it doesn't offer `mime_type`.
+    pub fn accept(
+        &self,
+        window: ffi::Window,
+        mime_type: &str,
+        action: DndAction,
+        clipboard: &Clipboard,
+    ) -> Result<(), XError> {
+        let target = match self.drag.lock().as_ref() {
+            Some(drag) if drag.window == window => {
+                clipboard.offered_target(&drag.targets, mime_type)
+            }
+            _ => return Ok(()),
+        };
+
+        let answer = match target {
+            Some(target) => DropAnswer::Accepted {
+                mime_type: mime_type.to_owned(),
+                action,
+                target,
+            },
+            None => {
+                warn!(
+                    "Accepted a drag with MIME type `{}` which it doesn't offer",
+                    mime_type
+                );
+                DropAnswer::Rejected
+            }
+        };
+        self.answer(window, answer)
+    }
+
+    /// Records the application's answer for the drag over `window`, and tells the source.
+    pub fn answer(&self, window: ffi::Window, answer: DropAnswer) -> Result<(), XError> {
+        let mut drag = self.drag.lock();
+        match drag.as_mut() {
+            Some(drag) if drag.window == window => {
+                drag.answer = answer;
+                // Otherwise the answer goes with the status of the position being handled.
+                if drag.entered && !drag.handling_position {
+                    return self.send_status(drag);
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Answers the last `XdndPosition` on `window`, once the application handled it.
+    pub fn send_position_status(&self, window: ffi::Window) -> Result<(), XError> {
+        match self.drag.lock().as_mut() {
+            Some(drag) if drag.window == window => {
+                drag.handling_position = false;
+                self.send_status(drag)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Stops tracking the drag over `window` since it was dropped.
+    ///
+    /// Returns the application's answer.
+    pub fn take_drop(&self, window: ffi::Window) -> DropAnswer {
+        let mut drag = self.drag.lock();
+        match drag.take() {
+            Some(incoming) if incoming.window == window => incoming.answer,
+            incoming => {
+                *drag = incoming;
+                DropAnswer::Undecided
+            }
+        }
+    }
+
+    /// Stops tracking the drag over `window` since it left.
+    ///
+    /// Returns whether `DragEnter` was emitted, so that `DragLeave` is due.
+    pub fn leave(&self, window: ffi::Window) -> bool {
+        let mut drag = self.drag.lock();
+        match drag.take() {
+            Some(incoming) if incoming.window == window => incoming.entered,
+            incoming => {
+                *drag = incoming;
+                false
+            }
+        }
+    }
+
+    /// Asks the source of the drag dropped on `window` for its data, which arrives with events
+    /// for [`DropTarget::window`].
+    ///
+    /// Returns whether the transfer started.
+    pub fn request_data(
+        &self,
+        window: ffi::Window,
+        source_window: ffi::Window,
+        mime_type: String,
+        action: DndAction,
+        target: ffi::Atom,
+        time: ffi::Time,
+    ) -> bool {
+        unsafe {
+            (self.xconn.xlib.XConvertSelection)(
+                self.xconn.display,
+                self.atoms.selection,
+                target,
+                self.atoms.drop_data,
+                self.window,
+                time,
+            );
+        }
+        if self.xconn.flush_requests().is_err() {
+            return false;
+        }
+
+        *self.pending_drop.lock() = Some(PendingDrop {
+            window,
+            source_window,
+            mime_type,
+            action,
+            incr_data: None,
+            deadline: Instant::now() + TRANSFER_TIMEOUT,
+        });
+        true
+    }
+
+    /// Handles the source's answer to the request for the dropped data.
+    ///
+    /// Returns the drop if the transfer is over.
+    pub fn handle_selection_notify(&self, xev: &ffi::XSelectionEvent) -> Option<FinishedDrop> {
+        let mut pending_drop = self.pending_drop.lock();
+        if pending_drop.is_none() || xev.selection != self.atoms.selection {
+            return None;
+        }
+
+        let (property_type, data) = if xev.property == 0 {
+            (0, None)
+        } else {
+            match unsafe { clipboard::read_property(&self.xconn, self.window, xev.property) } {
+                Some((property_type, data)) => (property_type, Some(data)),
+                None => (0, None),
+            }
+        };
+
+        if property_type == self.atoms.incr {
+            // The source sends the data in chunks, each time we delete the property.
+            let drop = pending_drop.as_mut()?;
+            drop.incr_data = Some(Vec::new());
+            drop.deadline = Instant::now() + TRANSFER_TIMEOUT;
+            return None;
+        }
+
+        pending_drop.take().map(|drop| drop.finish(data))
+    }
+
+    /// Continues an `INCR` transfer of the dropped data.
+    ///
+    /// Returns the drop if the transfer is over.
+    pub fn handle_property_notify(&self, xev: &ffi::XPropertyEvent) -> Option<FinishedDrop> {
+        let mut pending_drop = self.pending_drop.lock();
+        let drop = pending_drop.as_mut()?;
+        if drop.incr_data.is_none()
+            || xev.atom != self.atoms.drop_data
+            || xev.state != ffi::PropertyNewValue
+        {
+            return None;
+        }
+
+        let chunk = unsafe { clipboard::read_property(&self.xconn, self.window, xev.atom) };
+        match chunk {
+            // An empty chunk marks the end of the transfer.
+            Some((_, chunk)) if chunk.is_empty() => {
+                let data = drop.incr_data.take();
+                pending_drop.take().map(|drop| drop.finish(data))
+            }
+            Some((_, chunk)) => {
+                drop.incr_data.as_mut()?.extend_from_slice(&chunk);
+                drop.deadline = Instant::now() + TRANSFER_TIMEOUT;
+                None
+            }
+            None => pending_drop.take().map(|drop| drop.finish(None)),
+        }
+    }
+
+    /// Returns when we give up on the transfer of the dropped data.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending_drop.lock().as_ref().map(|drop| drop.deadline)
+    }
+
+    /// Gives up on the transfer of the dropped data if the source is too slow.
+    pub fn handle_timeout(&self) -> Option<FinishedDrop> {
+        let mut pending_drop = self.pending_drop.lock();
+        match pending_drop.as_ref() {
+            Some(drop) if drop.deadline <= Instant::now() => {
+                pending_drop.take().map(|drop| drop.finish(None))
+            }
+            _ => None,
+        }
+    }
+
+    /// Tells the source whether we accept the drop, and with which action.
+    ///
+    /// `action` is `None` if we don't.
+    pub fn send_finished(
+        &self,
+        window: ffi::Window,
+        source_window: ffi::Window,
+        action: Option<ffi::Atom>,
+    ) -> Result<(), XError> {
+        let (accepted, action) = match action {
+            Some(action) => (1, action as c_long),
+            None => (0, self.atoms.none as c_long),
+        };
+        self.xconn
+            .send_client_msg(
+                source_window,
+                source_window,
+                self.atoms.finished,
+                None,
+                [window as c_long, accepted, action, 0, 0],
+            )
+            .flush()
+    }
+
+    fn send_status(&self, drag: &IncomingDrag) -> Result<(), XError> {
+        let action = match &drag.answer {
+            DropAnswer::Accepted { action, .. } => Some(self.atoms.action_atom(*action)),
+            DropAnswer::Undecided if drag.mime_types.iter().any(|mime| mime == "text/uri-list") => {
+                Some(self.atoms.action_private)
+            }
+            _ => None,
+        };
+        let (accepted, action) = match action {
+            Some(action) => (1, action as c_long),
+            None => (0, self.atoms.none as c_long),
+        };
+        // Ask for a position on every motion, since the application may answer differently
+        // depending on it.
+        let flags = accepted | 2;
+        self.xconn
+            .send_client_msg(
+                drag.source_window,
+                drag.source_window,
+                self.atoms.status,
+                None,
+                [drag.window as c_long, flags, 0, 0, action],
+            )
+            .flush()
+    }
+}
+
+impl Drop for DropTarget {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xconn.xlib.XDestroyWindow)(self.xconn.display, self.window);
+        }
+        let _ = self.xconn.check_errors();
+    }
+}
+
+impl PendingDrop {
+    fn finish(self, data: Option<Vec<u8>>) -> FinishedDrop {
+        FinishedDrop {
+            window: self.window,
+            source_window: self.source_window,
+            mime_type: self.mime_type,
+            action: self.action,
+            data,
+        }
+    }
+}
+
+/// The window we're currently dragging over.
+struct DragTarget {
+    window: ffi::Window,
+    version: c_long,
+    /// Whether the target would accept a drop at the last position.
+    accepted: bool,
+    /// The action the target would perform.
+    action: Option<DndAction>,
+    /// The event mask to restore once we stop watching the target's destruction, if we changed
+    /// it.
+    restore_event_mask: Option<c_long>,
+}
+
+/// A window that got destroyed while it was the target of our drag.
+pub struct DestroyedTarget {
+    /// The source window, if this cancelled a drop waiting for `XdndFinished`.
+    pub cancelled_drag: Option<ffi::Window>,
+    /// Whether the window isn't ours, and only reported its destruction to the drag source.
+    pub foreign: bool,
+}
+
+/// An outgoing drag, driven by events of the source window's pointer grab.
+struct Drag {
+    source_window: ffi::Window,
+    type_list: Vec<ffi::Atom>,
+    actions: Vec<DndAction>,
+    target: Option<DragTarget>,
+    /// Root coordinates of the pointer.
+    position: (c_int, c_int),
+    /// Whether an `XdndPosition` is waiting for its `XdndStatus`.
+    awaiting_status: bool,
+    /// Whether the pointer moved while we were waiting for an `XdndStatus`.
+    position_changed: bool,
+    /// Set once we sent `XdndDrop`, until when we wait for `XdndFinished`.
+    finish_deadline: Option<Instant>,
+}
+
+/// XDND source for drags started with `Window::start_drag`.
+pub struct DragSource {
+    xconn: Arc<XConnection>,
+    root: ffi::Window,
+    pub atoms: DndAtoms,
+    drag: Mutex<Option<Drag>>,
+}
+
+impl DragSource {
+    pub fn new(xconn: Arc<XConnection>, root: ffi::Window) -> Result<Self, XError> {
+        let atoms = DndAtoms::new(&xconn)?;
+        Ok(DragSource {
+            xconn,
+            root,
+            atoms,
+            drag: Mutex::new(None),
+        })
+    }
+
+    /// Starts dragging `data` from `window`, which must hold an implicit pointer grab.
+    pub fn start(
+        &self,
+        window: ffi::Window,
+        data: DragData,
+        clipboard: &Clipboard,
+    ) -> Result<(), &'static str> {
+        let mut drag = self.drag.lock();
+        if drag.is_some() {
+            return Err("Drag could not be started: another drag is in progress");
+        }
+
+        let type_list = clipboard.data_targets(data.contents());
+        self.xconn
+            .change_property(
+                window,
+                self.atoms.type_list,
+                ffi::XA_ATOM,
+                util::PropMode::Replace,
+                &type_list,
+            )
+            .queue();
+
+        // Turns the implicit grab of the pressed button into an active grab, so we keep
+        // getting the pointer events when it leaves the window.
+        let result = unsafe {
+            (self.xconn.xlib.XGrabPointer)(
+                self.xconn.display,
+                window,
+                ffi::False,
+                (ffi::ButtonReleaseMask | ffi::PointerMotionMask) as c_uint,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                0,
+                self.xconn.cached_cursor(Some(CursorIcon::NoDrop)),
+                ffi::CurrentTime,
+            )
+        };
+        if result != ffi::GrabSuccess {
+            return Err("Drag could not be started: failed to grab the pointer");
+        }
+
+        clipboard
+            .store(self.atoms.selection, data.contents().clone())
+            .map_err(|_| "Drag could not be started: failed to own the XdndSelection")?;
+
+        *drag = Some(Drag {
+            source_window: window,
+            type_list,
+            actions: data.actions().to_vec(),
+            target: None,
+            position: (0, 0),
+            awaiting_status: false,
+            position_changed: false,
+            finish_deadline: None,
+        });
+        Ok(())
+    }
+
+    /// Follows the pointer with `XdndEnter`, `XdndPosition` and `XdndLeave` messages.
+    pub fn handle_motion(&self, xev: &ffi::XMotionEvent) {
+        let mut drag = self.drag.lock();
+        let drag = match drag.as_mut() {
+            Some(drag) if drag.source_window == xev.window && drag.finish_deadline.is_none() => {
+                drag
+            }
+            _ => return,
+        };
+
+        drag.position = (xev.x_root, xev.y_root);
+        let target = self.find_target(xev.x_root, xev.y_root);
+        if drag.target.as_ref().map(|target| target.window) != target.map(|(window, _)| window) {
+            if let Some(old_target) = drag.target.take() {
+                self.send(drag, old_target.window, self.atoms.leave, [0, 0, 0]);
+                self.unwatch(&old_target);
+            }
+
+            if let Some((window, version)) = target {
+                let version = version.min(XDND_VERSION);
+                let more_types = (drag.type_list.len() > 3) as c_long;
+                let types: Vec<c_long> = (0..3)
+                    .map(|i| drag.type_list.get(i).copied().unwrap_or(0) as c_long)
+                    .collect();
+                self.send_raw(
+                    window,
+                    self.atoms.enter,
+                    [
+                        drag.source_window as c_long,
+                        (version << 24) | more_types,
+                        types[0],
+                        types[1],
+                        types[2],
+                    ],
+                );
+                drag.target = Some(DragTarget {
+                    window,
+                    version,
+                    accepted: false,
+                    action: None,
+                    restore_event_mask: self.watch(window),
+                });
+                drag.awaiting_status = false;
+            }
+
+            self.update_cursor(drag);
+        }
+
+        if drag.target.is_some() {
+            if drag.awaiting_status {
+                drag.position_changed = true;
+            } else {
+                self.send_position(drag, xev.time);
+            }
+        }
+    }
+
+    /// Handles the target's answer to `XdndPosition`.
+    pub fn handle_status(&self, xev: &ffi::XClientMessageEvent) {
+        let mut drag = self.drag.lock();
+        let drag = match drag.as_mut() {
+            Some(drag) => drag,
+            None => return,
+        };
+
+        let target = match drag.target.as_mut() {
+            Some(target) if target.window == xev.data.get_long(0) as ffi::Window => target,
+            _ => return,
+        };
+
+        target.accepted = xev.data.get_long(1) & 1 == 1;
+        target.action = if target.accepted {
+            // Targets may answer with an action we didn't offer, in which case they copy.
+            self.atoms
+                .action(xev.data.get_long(4) as ffi::Atom)
+                .or(Some(DndAction::Copy))
+        } else {
+            None
+        };
+        drag.awaiting_status = false;
+        self.update_cursor(drag);
+
+        if drag.position_changed && drag.finish_deadline.is_none() {
+            self.send_position(drag, ffi::CurrentTime);
+        }
+    }
+
+    /// Drops on the current target, if it accepts the data.
+    ///
+    /// Returns the source window and the outcome if the drag is over.
+    pub fn handle_button_release(
+        &self,
+        xev: &ffi::XButtonEvent,
+    ) -> Option<(ffi::Window, Option<DndAction>)> {
+        let mut drag_lock = self.drag.lock();
+        let drag = match drag_lock.as_mut() {
+            Some(drag) if drag.source_window == xev.window && drag.finish_deadline.is_none() => {
+                drag
+            }
+            _ => return None,
+        };
+
+        unsafe {
+            (self.xconn.xlib.XUngrabPointer)(self.xconn.display, xev.time);
+        }
+
+        match drag.target.as_ref() {
+            Some(target) if target.accepted => {
+                let window = target.window;
+                self.send(drag, window, self.atoms.drop, [0, xev.time as c_long, 0]);
+                drag.finish_deadline = Some(Instant::now() + FINISH_TIMEOUT);
+                None
+            }
+            target => {
+                if let Some(target) = target {
+                    let window = target.window;
+                    self.send(drag, window, self.atoms.leave, [0, 0, 0]);
+                    self.unwatch(target);
+                }
+                drag_lock.take().map(|drag| (drag.source_window, None))
+            }
+        }
+    }
+
+    /// Handles the target's confirmation that it is done with the data.
+    ///
+    /// Returns the source window and the outcome.
+    pub fn handle_finished(
+        &self,
+        xev: &ffi::XClientMessageEvent,
+    ) -> Option<(ffi::Window, Option<DndAction>)> {
+        let mut drag_lock = self.drag.lock();
+        let drag = drag_lock.as_ref()?;
+        let target = drag.target.as_ref()?;
+        if drag.finish_deadline.is_none() || target.window != xev.data.get_long(0) as ffi::Window {
+            return None;
+        }
+
+        // The outcome is only reported since version 5.
+        let action = if target.version < 5 {
+            target.action
+        } else if xev.data.get_long(1) & 1 == 1 {
+            self.atoms
+                .action(xev.data.get_long(2) as ffi::Atom)
+                .or(target.action)
+        } else {
+            None
+        };
+
+        self.unwatch(target);
+        let source_window = drag.source_window;
+        drag_lock.take();
+        Some((source_window, action))
+    }
+
+    /// Returns when we give up on a drop the target didn't confirm.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.drag
+            .lock()
+            .as_ref()
+            .and_then(|drag| drag.finish_deadline)
+    }
+
+    /// Gives up on a drop the target didn't confirm in time.
+    ///
+    /// Returns the source window if the drag got cancelled.
+    pub fn handle_timeout(&self) -> Option<ffi::Window> {
+        let mut drag_lock = self.drag.lock();
+        match drag_lock.as_ref()?.finish_deadline {
+            Some(deadline) if deadline <= Instant::now() => (),
+            _ => return None,
+        }
+
+        let drag = drag_lock.take()?;
+        if let Some(target) = drag.target.as_ref() {
+            self.unwatch(target);
+        }
+        Some(drag.source_window)
+    }
+
+    /// Forgets the target if it is `window`, which got destroyed.
+    pub fn handle_destroy(&self, window: ffi::Window) -> Option<DestroyedTarget> {
+        let mut drag_lock = self.drag.lock();
+        let drag = drag_lock.as_mut()?;
+        let foreign = match drag.target.as_ref() {
+            Some(target) if target.window == window => target.restore_event_mask.is_some(),
+            _ => return None,
+        };
+
+        // The destroyed window won't confirm a drop.
+        let cancelled_drag = if drag.finish_deadline.is_some() {
+            drag_lock.take().map(|drag| drag.source_window)
+        } else {
+            drag.target = None;
+            drag.awaiting_status = false;
+            self.update_cursor(drag);
+            None
+        };
+        Some(DestroyedTarget {
+            cancelled_drag,
+            foreign,
+        })
+    }
+
+    /// Gets notified when the target `window` is destroyed.
+    ///
+    /// Returns the event mask to restore later, unless the window was already watched, which is
+    /// the case for our own windows.
+    fn watch(&self, window: ffi::Window) -> Option<c_long> {
+        let event_mask = unsafe {
+            let mut attributes = mem::zeroed::<ffi::XWindowAttributes>();
+            (self.xconn.xlib.XGetWindowAttributes)(self.xconn.display, window, &mut attributes);
+            attributes.your_event_mask
+        };
+        if self.xconn.check_errors().is_err() || event_mask & ffi::StructureNotifyMask != 0 {
+            return None;
+        }
+
+        unsafe {
+            (self.xconn.xlib.XSelectInput)(
+                self.xconn.display,
+                window,
+                event_mask | ffi::StructureNotifyMask,
+            );
+        }
+        Some(event_mask)
+    }
+
+    fn unwatch(&self, target: &DragTarget) {
+        if let Some(event_mask) = target.restore_event_mask {
+            unsafe {
+                (self.xconn.xlib.XSelectInput)(self.xconn.display, target.window, event_mask);
+            }
+            // The target may be gone already.
+            let _ = self.xconn.check_errors();
+        }
+    }
+
+    /// Finds the XDND aware window under the pointer, and its protocol version.
+    fn find_target(&self, x_root: c_int, y_root: c_int) -> Option<(ffi::Window, c_long)> {
+        let mut window = self.root;
+        loop {
+            let (mut x, mut y, mut child) = (0, 0, 0);
+            let found = unsafe {
+                (self.xconn.xlib.XTranslateCoordinates)(
+                    self.xconn.display,
+                    self.root,
+                    window,
+                    x_root,
+                    y_root,
+                    &mut x,
+                    &mut y,
+                    &mut child,
+                )
+            };
+            if found == ffi::False || child == 0 {
+                return None;
+            }
+
+            window = child;
+            let aware = self
+                .xconn
+                .get_property::<c_ulong>(window, self.atoms.aware, ffi::XA_ATOM);
+            if let Some(&version) = aware.ok().as_ref().and_then(|aware| aware.first()) {
+                return Some((window, version as c_long));
+            }
+        }
+    }
+
+    fn send_position(&self, drag: &mut Drag, time: ffi::Time) {
+        let target = match drag.target.as_ref() {
+            Some(target) => target.window,
+            None => return,
+        };
+
+        let (x, y) = drag.position;
+        let action = self.atoms.action_atom(drag.actions[0]);
+        self.send(
+            drag,
+            target,
+            self.atoms.position,
+            [
+                ((x as c_long & 0xffff) << 16) | (y as c_long & 0xffff),
+                time as c_long,
+                action as c_long,
+            ],
+        );
+        drag.awaiting_status = true;
+        drag.position_changed = false;
+    }
+
+    fn update_cursor(&self, drag: &Drag) {
+        let cursor = match drag.target.as_ref().and_then(|target| target.action) {
+            Some(DndAction::Copy) => CursorIcon::Copy,
+            Some(DndAction::Move) => CursorIcon::Move,
+            Some(DndAction::Link) => CursorIcon::Alias,
+            None => CursorIcon::NoDrop,
+        };
+
+        unsafe {
+            (self.xconn.xlib.XChangeActivePointerGrab)(
+                self.xconn.display,
+                (ffi::ButtonReleaseMask | ffi::PointerMotionMask) as c_uint,
+                self.xconn.cached_cursor(Some(cursor)),
+                ffi::CurrentTime,
+            );
+        }
+    }
+
+    /// Sends an XDND message whose first field is the source window and second is unused.
+    fn send(&self, drag: &Drag, target: ffi::Window, message_type: ffi::Atom, data: [c_long; 3]) {
+        self.send_raw(
+            target,
+            message_type,
+            [drag.source_window as c_long, 0, data[0], data[1], data[2]],
+        );
+    }
+
+    fn send_raw(&self, target: ffi::Window, message_type: ffi::Atom, data: [c_long; 5]) {
+        self.xconn
+            .send_client_msg(target, target, message_type, None, data)
+            .queue();
+    }
+}
@@ -1,19 +1,31 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc, slice, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    slice,
+    sync::{mpsc::Receiver, Arc, Weak},
+    time::Instant,
+};
 
-use libc::{c_char, c_int, c_long, c_uint, c_ulong};
+use libc::{c_char, c_int, c_long, c_ulong};
 
 use parking_lot::MutexGuard;
 
 use super::{
-    events, ffi, get_xtarget, mkdid, mkwid, monitor, util, Device, DeviceId, DeviceInfo, Dnd,
-    DndState, GenericEventCookie, ImeReceiver, ScrollOrientation, UnownedWindow, WindowId,
-    XExtension,
+    ffi, get_xtarget, mkdid, mkwid, monitor, util, xkb::XkbKeyboard, xsettings, Device, DeviceId,
+    DeviceInfo, Dnd, DropAnswer, FinishedDrop, GenericEventCookie, ImeReceiver, ScrollOrientation,
+    SystemTheme, UnownedWindow, WindowId, XExtension,
 };
 
 use util::modifiers::{ModifierKeyState, ModifierKeymap};
 
-use crate::platform_impl::platform::x11::ime::{ImeEvent, ImeEventReceiver, ImeRequest};
+use crate::platform_impl::platform::{
+    common::keymap,
+    instant_from_event_time,
+    x11::ime::{ImeEvent, ImeEventReceiver, ImeRequest},
+};
 use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
     dpi::{PhysicalPosition, PhysicalSize},
     event::{
         DeviceEvent, ElementState, Event, Ime, KeyboardInput, ModifiersState, TouchPhase,
@@ -29,6 +41,7 @@ pub(super) struct EventProcessor<T: 'static> {
     pub(super) dnd: Dnd,
     pub(super) ime_receiver: ImeReceiver,
     pub(super) ime_event_receiver: ImeEventReceiver,
+    pub(super) shortcuts_inhibit_receiver: Receiver<WindowId>,
     pub(super) randr_event_offset: c_int,
     pub(super) devices: RefCell<HashMap<DeviceId, Device>>,
     pub(super) xi2ext: XExtension,
@@ -38,9 +51,15 @@ pub(super) struct EventProcessor<T: 'static> {
     // Number of touch events currently in progress
     pub(super) num_touch: u32,
     pub(super) first_touch: Option<u64>,
+    // Scale of the last pinch event, the server sends the scale since the start of the gesture
+    pub(super) pinch_scale: f64,
     // Currently focused window belonging to this process
     pub(super) active_window: Option<ffi::Window>,
     pub(super) is_composing: bool,
+    // Keys currently held down, to recognize repeated presses
+    pub(super) pressed_keys: HashSet<u32>,
+    // Keymaps of the keyboards, when libxkbcommon-x11 is available
+    pub(super) xkb: Option<XkbKeyboard>,
 }
 
 impl<T: 'static> EventProcessor<T> {
@@ -54,6 +73,33 @@ impl<T: 'static> EventProcessor<T> {
         }
     }
 
+    /// The popups grabbing the input which a click landed outside of.
+    ///
+    /// The grab reports the clicks outside of the application to the popup, and the clicks on the
+    /// windows of the application to them, including the popup itself. A click on a grabbing
+    /// popup only concerns that popup, so that clicking a parent menu doesn't close it.
+    fn popups_clicked_outside(&self, xev: &ffi::XButtonEvent) -> Vec<crate::window::WindowId> {
+        let clicked_popup =
+            self.with_window(xev.window, |window| window.popup_grab()) == Some(true);
+        let wt = get_xtarget(&self.target);
+        let windows = wt.windows.borrow();
+        windows
+            .values()
+            .filter_map(Weak::upgrade)
+            .filter(|window| window.popup_grab() && window.is_visible() == Some(true))
+            .filter(|popup| !clicked_popup || popup.id().0 as ffi::Window == xev.window)
+            .filter(|popup| {
+                let (x, y) = popup.inner_position_physical();
+                let (width, height) = popup.inner_size_physical();
+                xev.x_root < x
+                    || xev.y_root < y
+                    || xev.x_root >= x + width as i32
+                    || xev.y_root >= y + height as i32
+            })
+            .map(|popup| mkwid(popup.id().0 as ffi::Window))
+            .collect()
+    }
+
     fn with_window<F, Ret>(&self, window_id: ffi::Window, callback: F) -> Option<Ret>
     where
         F: Fn(&Arc<UnownedWindow>) -> Ret,
@@ -82,6 +128,131 @@ impl<T: 'static> EventProcessor<T> {
         self.with_window(window_id, |_| ()).is_some()
     }
 
+    /// Grabs or releases the keyboard of the windows which asked for the system shortcuts or
+    /// stopped asking since the last time.
+    pub(super) fn process_shortcuts_inhibit_requests<F>(&self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        while let Ok(window_id) = self.shortcuts_inhibit_receiver.try_recv() {
+            self.update_shortcuts_inhibit(window_id.0 as ffi::Window, &mut callback);
+        }
+    }
+
+    /// Cancels the drags and drops whose peer didn't answer in time.
+    pub(super) fn process_dnd_timeouts<F>(&mut self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        if let Some(source_window) = wt.drag_source.handle_timeout() {
+            callback(Event::WindowEvent {
+                window_id: mkwid(source_window),
+                event: WindowEvent::DragFinished(None),
+            });
+        }
+        if let Some(drop) = wt.drop_target.handle_timeout() {
+            self.finish_drop(drop, &mut callback);
+        }
+    }
+
+    /// Delivers the data of a drop, and tells the source the outcome.
+    fn finish_drop<F>(&mut self, drop: FinishedDrop, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        let window_id = mkwid(drop.window);
+        let action = match drop.data {
+            Some(data) => {
+                self.emit_dropped_files(window_id, callback);
+                callback(Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::DragDrop {
+                        data: ClipboardContents::new().with_data(drop.mime_type, data),
+                    },
+                });
+                Some(self.dnd.atoms.action_atom(drop.action))
+            }
+            None => {
+                callback(Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::DragLeave,
+                });
+                None
+            }
+        };
+
+        wt.drop_target
+            .send_finished(drop.window, drop.source_window, action)
+            .expect("Failed to send `XdndFinished` message.");
+        self.dnd.reset();
+    }
+
+    /// Moves the keyboard grab of a window which asked for the system shortcuts along with the
+    /// focus, and reports the outcome.
+    fn update_shortcuts_inhibit<F>(&self, window: ffi::Window, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let focused = self.active_window == Some(window);
+        let inhibited = self
+            .with_window(window, |window| window.update_shortcuts_inhibit(focused))
+            .flatten();
+        if let Some(inhibited) = inhibited {
+            callback(Event::WindowEvent {
+                window_id: mkwid(window),
+                event: WindowEvent::ShortcutsInhibited(inhibited),
+            });
+        }
+    }
+
+    /// Updates the theme of the desktop, and tells the windows when it changes.
+    pub(super) fn update_system_theme<U, F>(&self, update: U, mut callback: F)
+    where
+        U: FnOnce(&mut SystemTheme),
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        let (old_theme, new_theme) = {
+            let mut system_theme = wt.system_theme.lock();
+            let old_theme = system_theme.theme();
+            update(&mut system_theme);
+            (old_theme, system_theme.theme())
+        };
+
+        let theme = match new_theme {
+            Some(theme) if new_theme != old_theme => theme,
+            _ => return,
+        };
+        // The callback may create windows.
+        let window_ids: Vec<WindowId> = wt.windows.borrow().keys().copied().collect();
+        for window_id in window_ids {
+            callback(Event::WindowEvent {
+                window_id: crate::window::WindowId(window_id),
+                event: WindowEvent::ThemeChanged(theme),
+            });
+        }
+    }
+
+    /// Reads the XSETTINGS again, and tells the windows about the changes of the theme and of the
+    /// scale factor.
+    fn xsettings_changed<F>(&self, mut callback: F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        let wt = get_xtarget(&self.target);
+        let old_dpi = wt.xsettings.values().dpi;
+        wt.xsettings.refresh();
+        let values = wt.xsettings.values();
+
+        let theme = xsettings::theme(&values);
+        self.update_system_theme(|system_theme| system_theme.xsettings = theme, &mut callback);
+        if values.dpi != old_dpi {
+            Self::monitors_changed(wt, &mut callback);
+        }
+    }
+
     pub(super) fn poll(&self) -> bool {
         let wt = get_xtarget(&self.target);
         let result = unsafe { (wt.xconn.xlib.XPending)(wt.xconn.display) };
@@ -120,7 +291,9 @@ impl<T: 'static> EventProcessor<T> {
     where
         F: FnMut(Event<'_, T>),
     {
-        let wt = get_xtarget(&self.target);
+        // The target is cloned so that `self` can be borrowed mutably while using it.
+        let target = self.target.clone();
+        let wt = get_xtarget(&target);
         // XFilterEvent tells us when an event has been discarded by the input method.
         // Specifically, this involves all of the KeyPress events in compose/pre-edit sequences,
         // along with an extra copy of the KeyRelease events. This also prevents backspace and
@@ -158,6 +331,20 @@ impl<T: 'static> EventProcessor<T> {
         }
 
         let event_type = xev.get_type();
+        if let Some(xkb) = self.xkb.as_mut() {
+            if event_type == xkb.event_type() {
+                if xkb.process_event(xev) {
+                    if let (Some(window), Some(layout)) = (self.active_window, xkb.layout()) {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event: WindowEvent::KeyboardLayoutChanged(layout),
+                        });
+                    }
+                }
+                return;
+            }
+        }
+
         match event_type {
             ffi::MappingNotify => {
                 let mapping: &ffi::XMappingEvent = xev.as_ref();
@@ -183,7 +370,10 @@ impl<T: 'static> EventProcessor<T> {
                 let window = client_msg.window;
                 let window_id = mkwid(window);
 
-                if client_msg.data.get_long(0) as ffi::Atom == wt.wm_delete_window {
+                if wt.xsettings.is_manager_announcement(client_msg) {
+                    wt.xsettings.find_manager();
+                    self.xsettings_changed(&mut callback);
+                } else if client_msg.data.get_long(0) as ffi::Atom == wt.wm_delete_window {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::CloseRequested,
@@ -215,93 +405,133 @@ impl<T: 'static> EventProcessor<T> {
                     {
                         self.dnd.type_list = Some(more_types);
                     }
+
+                    let type_list: Vec<ffi::Atom> = self
+                        .dnd
+                        .type_list
+                        .iter()
+                        .flatten()
+                        .copied()
+                        .filter(|&atom| atom != 0)
+                        .collect();
+                    let mime_types = wt.clipboard.target_mime_types(&type_list);
+                    wt.drop_target
+                        .enter(window, source_window, type_list, mime_types);
                 } else if client_msg.message_type == self.dnd.atoms.position {
-                    // This event occurs every time the mouse moves while a file's being dragged
-                    // over our window. We emit HoveredFile in response; while the macOS backend
-                    // does that upon a drag entering, XDND doesn't have access to the actual drop
-                    // data until this event. For parity with other platforms, we only emit
-                    // `HoveredFile` the first time, though if winit's API is later extended to
-                    // supply position updates with `HoveredFile` or another event, implementing
-                    // that here would be trivial.
+                    // This event occurs every time the mouse moves while something's being
+                    // dragged over our window. XDND doesn't have access to the actual drop data
+                    // until this event, so we emit `HoveredFile` the first time we get it, while
+                    // the macOS backend does that upon a drag entering.
 
                     let source_window = client_msg.data.get_long(0) as c_ulong;
 
-                    // Equivalent to `(x << shift) | y`
-                    // where `shift = mem::size_of::<c_short>() * 8`
-                    // Note that coordinates are in "desktop space", not "window space"
-                    // (in X11 parlance, they're root window coordinates)
-                    //let packed_coordinates = client_msg.data.get_long(2);
-                    //let shift = mem::size_of::<libc::c_short>() * 8;
-                    //let x = packed_coordinates >> shift;
-                    //let y = packed_coordinates & !(x << shift);
+                    // Equivalent to `(x << 16) | y`, in root window coordinates, which are
+                    // negative left of or above the primary monitor.
+                    let packed_coordinates = client_msg.data.get_long(2);
+                    let x_root = ((packed_coordinates >> 16) & 0xffff) as i16 as f64;
+                    let y_root = (packed_coordinates & 0xffff) as i16 as f64;
+                    let position = match wt.xconn.translate_coords(window, wt.root) {
+                        Ok(origin) => PhysicalPosition::new(
+                            x_root - origin.x_rel_root as f64,
+                            y_root - origin.y_rel_root as f64,
+                        ),
+                        Err(_) => PhysicalPosition::new(x_root, y_root),
+                    };
 
                     // By our own state flow, `version` should never be `None` at this point.
                     let version = self.dnd.version.unwrap_or(5);
 
-                    // Action is specified in versions 2 and up, though we don't need it anyway.
-                    //let action = client_msg.data.get_long(4);
-
-                    let accepted = if let Some(ref type_list) = self.dnd.type_list {
+                    let has_uri_list = if let Some(ref type_list) = self.dnd.type_list {
                         type_list.contains(&self.dnd.atoms.uri_list)
                     } else {
                         false
                     };
 
-                    if accepted {
-                        self.dnd.source_window = Some(source_window);
-                        unsafe {
-                            if self.dnd.result.is_none() {
-                                let time = if version >= 1 {
-                                    client_msg.data.get_long(3) as c_ulong
-                                } else {
-                                    // In version 0, time isn't specified
-                                    ffi::CurrentTime
-                                };
-                                // This results in the `SelectionNotify` event below
-                                self.dnd.convert_selection(window, time);
-                            }
-                            self.dnd
-                                .send_status(window, source_window, DndState::Accepted)
-                                .expect("Failed to send `XdndStatus` message.");
-                        }
-                    } else {
-                        unsafe {
-                            self.dnd
-                                .send_status(window, source_window, DndState::Rejected)
-                                .expect("Failed to send `XdndStatus` message.");
-                        }
-                        self.dnd.reset();
+                    self.dnd.source_window = Some(source_window);
+                    if has_uri_list && self.dnd.result.is_none() {
+                        let time = if version >= 1 {
+                            client_msg.data.get_long(3) as c_ulong
+                        } else {
+                            // In version 0, time isn't specified
+                            ffi::CurrentTime
+                        };
+                        // This results in the `SelectionNotify` event below
+                        unsafe { self.dnd.convert_selection(window, time) };
                     }
+
+                    // The application answers with `Window::accept_drag` while handling the
+                    // event, and the answer is sent below.
+                    let event = match wt.drop_target.position(window) {
+                        Some(mime_types) => WindowEvent::DragEnter {
+                            mime_types,
+                            position,
+                        },
+                        None => WindowEvent::DragOver { position },
+                    };
+                    callback(Event::WindowEvent { window_id, event });
+
+                    wt.drop_target
+                        .send_position_status(window)
+                        .expect("Failed to send `XdndStatus` message.");
                 } else if client_msg.message_type == self.dnd.atoms.drop {
-                    let (source_window, state) = if let Some(source_window) = self.dnd.source_window
-                    {
-                        if let Some(Ok(ref path_list)) = self.dnd.result {
-                            for path in path_list {
-                                callback(Event::WindowEvent {
-                                    window_id,
-                                    event: WindowEvent::DroppedFile(path.clone()),
-                                });
+                    let source_window = client_msg.data.get_long(0) as c_ulong;
+                    let action = match wt.drop_target.take_drop(window) {
+                        DropAnswer::Accepted {
+                            mime_type,
+                            action,
+                            target,
+                        } => {
+                            // The data arrives with a `SelectionNotify`, after which the source
+                            // is told the outcome.
+                            let time = client_msg.data.get_long(2) as ffi::Time;
+                            if wt.drop_target.request_data(
+                                window,
+                                source_window,
+                                mime_type,
+                                action,
+                                target,
+                                time,
+                            ) {
+                                return;
                             }
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::DragLeave,
+                            });
+                            None
                         }
-                        (source_window, DndState::Accepted)
-                    } else {
-                        // `source_window` won't be part of our DND state if we already rejected the drop in our
-                        // `XdndPosition` handler.
-                        let source_window = client_msg.data.get_long(0) as c_ulong;
-                        (source_window, DndState::Rejected)
+                        DropAnswer::Undecided if matches!(self.dnd.result, Some(Ok(_))) => {
+                            self.emit_dropped_files(window_id, &mut callback);
+                            Some(self.dnd.atoms.action_private)
+                        }
+                        _ => None,
                     };
-                    unsafe {
-                        self.dnd
-                            .send_finished(window, source_window, state)
-                            .expect("Failed to send `XdndFinished` message.");
-                    }
+
+                    wt.drop_target
+                        .send_finished(window, source_window, action)
+                        .expect("Failed to send `XdndFinished` message.");
                     self.dnd.reset();
                 } else if client_msg.message_type == self.dnd.atoms.leave {
                     self.dnd.reset();
+                    if wt.drop_target.leave(window) {
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::DragLeave,
+                        });
+                    }
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::HoveredFileCancelled,
                     });
+                } else if client_msg.message_type == self.dnd.atoms.status {
+                    wt.drag_source.handle_status(client_msg);
+                } else if client_msg.message_type == self.dnd.atoms.finished {
+                    if let Some((window, action)) = wt.drag_source.handle_finished(client_msg) {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event: WindowEvent::DragFinished(action),
+                        });
+                    }
                 }
             }
 
@@ -311,6 +541,18 @@ impl<T: 'static> EventProcessor<T> {
                 let window = xsel.requestor;
                 let window_id = mkwid(window);
 
+                if window == wt.clipboard.window() {
+                    wt.clipboard.queue_event(xev);
+                    return;
+                }
+
+                if window == wt.drop_target.window() {
+                    if let Some(drop) = wt.drop_target.handle_selection_notify(xsel) {
+                        self.finish_drop(drop, &mut callback);
+                    }
+                    return;
+                }
+
                 if xsel.property == self.dnd.atoms.selection {
                     let mut result = None;
 
@@ -332,6 +574,69 @@ impl<T: 'static> EventProcessor<T> {
                 }
             }
 
+            ffi::SelectionRequest => {
+                let request: &ffi::XSelectionRequestEvent = xev.as_ref();
+                if request.owner == wt.clipboard.window() {
+                    wt.clipboard.handle_request(request);
+                }
+            }
+
+            ffi::SelectionClear => {
+                let xsel: &ffi::XSelectionClearEvent = xev.as_ref();
+                if xsel.window == wt.clipboard.window() {
+                    wt.clipboard.handle_selection_clear(xsel.selection);
+
+                    // Otherwise, XFixes reports the new owner.
+                    if wt.clipboard.xfixes_selection_notify().is_none() {
+                        if let Some(kind) = wt.clipboard.selection_kind(xsel.selection) {
+                            Self::clipboard_changed(wt, kind, &mut callback);
+                        }
+                    }
+                }
+            }
+
+            ffi::PropertyNotify => {
+                let xprop: &ffi::XPropertyEvent = xev.as_ref();
+                if wt.xsettings.is_settings_change(xprop) {
+                    self.xsettings_changed(&mut callback);
+                } else if xprop.window == wt.clipboard.window() {
+                    wt.clipboard.queue_event(xev);
+                } else if xprop.window == wt.drop_target.window() {
+                    if let Some(drop) = wt.drop_target.handle_property_notify(xprop) {
+                        self.finish_drop(drop, &mut callback);
+                    }
+                } else {
+                    wt.clipboard.handle_property_notify(xprop);
+                }
+            }
+
+            // Core pointer events are only delivered during the grab of an outgoing drag, or of a
+            // popup, which gets the clicks outside of the application.
+            ffi::ButtonPress => {
+                let xev: &ffi::XButtonEvent = xev.as_ref();
+                for window_id in self.popups_clicked_outside(xev) {
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::CloseRequested,
+                    });
+                }
+            }
+
+            ffi::MotionNotify => {
+                let xev: &ffi::XMotionEvent = xev.as_ref();
+                wt.drag_source.handle_motion(xev);
+            }
+
+            ffi::ButtonRelease => {
+                let xev: &ffi::XButtonEvent = xev.as_ref();
+                if let Some((window, action)) = wt.drag_source.handle_button_release(xev) {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(window),
+                        event: WindowEvent::DragFinished(action),
+                    });
+                }
+            }
+
             ffi::ConfigureNotify => {
                 let xev: &ffi::XConfigureEvent = xev.as_ref();
                 let xwindow = xev.window;
@@ -441,6 +746,8 @@ impl<T: 'static> EventProcessor<T> {
                             let old_inner_size = PhysicalSize::new(width, height);
                             let mut new_inner_size = PhysicalSize::new(new_width, new_height);
 
+                            window.scale_factor_changed(&shared_state_lock);
+
                             // Temporarily unlock shared state to prevent deadlock
                             MutexGuard::unlocked(&mut shared_state_lock, || {
                                 callback(Event::WindowEvent {
@@ -508,6 +815,25 @@ impl<T: 'static> EventProcessor<T> {
             ffi::DestroyNotify => {
                 let xev: &ffi::XDestroyWindowEvent = xev.as_ref();
 
+                // Until another manager announces itself, the settings are the defaults.
+                if wt.xsettings.is_manager_destroyed(xev) {
+                    wt.xsettings.find_manager();
+                    self.xsettings_changed(&mut callback);
+                    return;
+                }
+
+                if let Some(destroyed) = wt.drag_source.handle_destroy(xev.window) {
+                    if let Some(source_window) = destroyed.cancelled_drag {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(source_window),
+                            event: WindowEvent::DragFinished(None),
+                        });
+                    }
+                    if destroyed.foreign {
+                        return;
+                    }
+                }
+
                 let window = xev.window;
                 let window_id = mkwid(window);
 
@@ -573,13 +899,35 @@ impl<T: 'static> EventProcessor<T> {
                 let device = util::VIRTUAL_CORE_KEYBOARD;
                 let device_id = mkdid(device);
                 let keycode = xkev.keycode;
+                let timestamp = instant_from_event_time(xkev.time as u32);
+
+                let written = if state == Pressed {
+                    let ic = wt.ime.borrow().get_context(window);
+                    match ic {
+                        Some(ic) => Some(wt.xconn.lookup_utf8(ic, xkev)),
+                        None => self.xkb.as_ref().and_then(|xkb| xkb.text(keycode)),
+                    }
+                } else {
+                    None
+                };
 
                 // When a compose sequence or IME pre-edit is finished, it ends in a KeyPress with
                 // a keycode of 0.
                 if keycode != 0 && !self.is_composing {
                     let scancode = keycode - KEYCODE_OFFSET as u32;
-                    let keysym = wt.xconn.lookup_keysym(xkev);
-                    let virtual_keycode = events::keysym_to_element(keysym as c_uint);
+                    let keysym = match self.xkb {
+                        Some(ref xkb) => xkb.keysym(keycode),
+                        None => wt.xconn.lookup_keysym(xkev) as u32,
+                    };
+                    let virtual_keycode = keymap::keysym_to_vkey(keysym);
+                    let repeat = if state == Pressed {
+                        !self.pressed_keys.insert(keycode)
+                    } else {
+                        self.pressed_keys.remove(&keycode);
+                        false
+                    };
+                    let key_event =
+                        keymap::key_event(scancode, keysym, written.clone(), state, repeat);
 
                     update_modifiers!(
                         ModifiersState::from_x11_mask(xkev.state),
@@ -599,18 +947,14 @@ impl<T: 'static> EventProcessor<T> {
                                 virtual_keycode,
                                 modifiers,
                             },
+                            event: key_event,
                             is_synthetic: false,
+                            timestamp,
                         },
                     });
                 }
 
-                if state == Pressed {
-                    let written = if let Some(ic) = wt.ime.borrow().get_context(window) {
-                        wt.xconn.lookup_utf8(ic, xkev)
-                    } else {
-                        return;
-                    };
-
+                if let Some(written) = written {
                     // If we're composing right now, send the string we've got from X11 via
                     // Ime::Commit.
                     if self.is_composing && keycode == 0 && !written.is_empty() {
@@ -661,6 +1005,7 @@ impl<T: 'static> EventProcessor<T> {
                         let xev: &ffi::XIDeviceEvent = unsafe { &*(xev.data as *const _) };
                         let window_id = mkwid(xev.event);
                         let device_id = mkdid(xev.deviceid);
+                        let timestamp = instant_from_event_time(xev.time as u32);
                         if (xev.flags & ffi::XIPointerEmulated) != 0 {
                             // Deliver multi-touch events instead of emulated mouse events.
                             return;
@@ -681,6 +1026,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Left,
+                                    timestamp,
                                     modifiers,
                                 },
                             }),
@@ -690,6 +1036,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Middle,
+                                    timestamp,
                                     modifiers,
                                 },
                             }),
@@ -699,6 +1046,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Right,
+                                    timestamp,
                                     modifiers,
                                 },
                             }),
@@ -720,6 +1068,7 @@ impl<T: 'static> EventProcessor<T> {
                                                 _ => unreachable!(),
                                             },
                                             phase: TouchPhase::Moved,
+                                            timestamp,
                                             modifiers,
                                         },
                                     });
@@ -732,6 +1081,7 @@ impl<T: 'static> EventProcessor<T> {
                                     device_id,
                                     state,
                                     button: Other(x as u16),
+                                    timestamp,
                                     modifiers,
                                 },
                             }),
@@ -742,6 +1092,7 @@ impl<T: 'static> EventProcessor<T> {
                         let device_id = mkdid(xev.deviceid);
                         let window_id = mkwid(xev.event);
                         let new_cursor_pos = (xev.event_x, xev.event_y);
+                        let timestamp = instant_from_event_time(xev.time as u32);
 
                         let modifiers = ModifiersState::from_x11(&xev.mods);
                         update_modifiers!(modifiers, None);
@@ -758,6 +1109,7 @@ impl<T: 'static> EventProcessor<T> {
                                 event: CursorMoved {
                                     device_id,
                                     position,
+                                    timestamp,
                                     modifiers,
                                 },
                             });
@@ -805,6 +1157,7 @@ impl<T: 'static> EventProcessor<T> {
                                                     }
                                                 },
                                                 phase: TouchPhase::Moved,
+                                                timestamp,
                                                 modifiers,
                                             },
                                         });
@@ -832,6 +1185,7 @@ impl<T: 'static> EventProcessor<T> {
 
                         let window_id = mkwid(xev.event);
                         let device_id = mkdid(xev.deviceid);
+                        let timestamp = instant_from_event_time(xev.time as u32);
 
                         if let Some(all_info) = DeviceInfo::get(&wt.xconn, ffi::XIAllDevices) {
                             let mut devices = self.devices.borrow_mut();
@@ -877,6 +1231,7 @@ impl<T: 'static> EventProcessor<T> {
                                 event: CursorMoved {
                                     device_id,
                                     position,
+                                    timestamp,
                                     modifiers,
                                 },
                             });
@@ -916,6 +1271,7 @@ impl<T: 'static> EventProcessor<T> {
 
                             let window_id = mkwid(xev.event);
                             let position = PhysicalPosition::new(xev.event_x, xev.event_y);
+                            let timestamp = instant_from_event_time(xev.time as u32);
 
                             callback(Event::WindowEvent {
                                 window_id,
@@ -943,19 +1299,23 @@ impl<T: 'static> EventProcessor<T> {
                                 event: CursorMoved {
                                     device_id: mkdid(pointer_id),
                                     position,
+                                    timestamp,
                                     modifiers,
                                 },
                             });
 
-                            // Issue key press events for all pressed keys
-                            Self::handle_pressed_keys(
-                                wt,
+                            // Issue key press events for all pressed keys, whose next presses
+                            // are repeats
+                            self.pressed_keys =
+                                wt.xconn.query_keymap().into_iter().map(u32::from).collect();
+                            self.handle_pressed_keys(
                                 window_id,
+                                timestamp,
                                 ElementState::Pressed,
-                                &self.mod_keymap,
-                                &mut self.device_mod_state,
                                 &mut callback,
                             );
+
+                            self.update_shortcuts_inhibit(xev.event, &mut callback);
                         }
                     }
                     ffi::XI_FocusOut => {
@@ -971,16 +1331,16 @@ impl<T: 'static> EventProcessor<T> {
 
                         if self.active_window.take() == Some(xev.event) {
                             let window_id = mkwid(xev.event);
+                            let timestamp = instant_from_event_time(xev.time as u32);
 
                             wt.update_device_event_filter(false);
 
                             // Issue key release events for all pressed keys
-                            Self::handle_pressed_keys(
-                                wt,
+                            self.pressed_keys.clear();
+                            self.handle_pressed_keys(
                                 window_id,
+                                timestamp,
                                 ElementState::Released,
-                                &self.mod_keymap,
-                                &mut self.device_mod_state,
                                 &mut callback,
                             );
 
@@ -992,7 +1352,9 @@ impl<T: 'static> EventProcessor<T> {
                             callback(Event::WindowEvent {
                                 window_id,
                                 event: Focused(false),
-                            })
+                            });
+
+                            self.update_shortcuts_inhibit(xev.event, &mut callback);
                         }
                     }
 
@@ -1007,6 +1369,7 @@ impl<T: 'static> EventProcessor<T> {
                         };
                         if self.window_exists(xev.event) {
                             let id = xev.detail as u64;
+                            let timestamp = instant_from_event_time(xev.time as u32);
                             let modifiers = self.device_mod_state.modifiers();
                             let location =
                                 PhysicalPosition::new(xev.event_x as f64, xev.event_y as f64);
@@ -1020,6 +1383,7 @@ impl<T: 'static> EventProcessor<T> {
                                     event: WindowEvent::CursorMoved {
                                         device_id: mkdid(util::VIRTUAL_CORE_POINTER),
                                         position: location.cast(),
+                                        timestamp,
                                         modifiers,
                                     },
                                 });
@@ -1033,11 +1397,99 @@ impl<T: 'static> EventProcessor<T> {
                                     location,
                                     force: None, // TODO
                                     id,
+                                    timestamp,
                                 }),
                             })
                         }
                     }
 
+                    ffi::XI_GesturePinchBegin
+                    | ffi::XI_GesturePinchUpdate
+                    | ffi::XI_GesturePinchEnd => {
+                        let xev: &ffi::XIGesturePinchEvent = unsafe { &*(xev.data as *const _) };
+                        if !self.window_exists(xev.event) {
+                            return;
+                        }
+
+                        let phase = match xev.evtype {
+                            ffi::XI_GesturePinchBegin => TouchPhase::Started,
+                            ffi::XI_GesturePinchUpdate => TouchPhase::Moved,
+                            _ if xev.flags & ffi::XIGesturePinchEventCancelled != 0 => {
+                                TouchPhase::Cancelled
+                            }
+                            _ => TouchPhase::Ended,
+                        };
+                        let (delta, angle) = match phase {
+                            TouchPhase::Started => {
+                                self.pinch_scale = 1.0;
+                                (0.0, 0.0)
+                            }
+                            TouchPhase::Moved => {
+                                let delta = xev.scale - self.pinch_scale;
+                                self.pinch_scale = xev.scale;
+                                (delta, xev.delta_angle)
+                            }
+                            _ => (0.0, 0.0),
+                        };
+
+                        let window_id = mkwid(xev.event);
+                        let device_id = mkdid(xev.deviceid);
+                        let timestamp = instant_from_event_time(xev.time as u32);
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::PinchGesture {
+                                device_id,
+                                delta,
+                                phase,
+                                timestamp,
+                            },
+                        });
+                        // The server sends the angle clockwise.
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: WindowEvent::RotationGesture {
+                                device_id,
+                                delta: -angle as f32,
+                                phase,
+                                timestamp,
+                            },
+                        });
+                    }
+
+                    ffi::XI_GestureSwipeBegin
+                    | ffi::XI_GestureSwipeUpdate
+                    | ffi::XI_GestureSwipeEnd => {
+                        let xev: &ffi::XIGestureSwipeEvent = unsafe { &*(xev.data as *const _) };
+                        if !self.window_exists(xev.event) {
+                            return;
+                        }
+
+                        let phase = match xev.evtype {
+                            ffi::XI_GestureSwipeBegin => TouchPhase::Started,
+                            ffi::XI_GestureSwipeUpdate => TouchPhase::Moved,
+                            _ if xev.flags & ffi::XIGestureSwipeEventCancelled != 0 => {
+                                TouchPhase::Cancelled
+                            }
+                            _ => TouchPhase::Ended,
+                        };
+                        let delta = if phase == TouchPhase::Moved {
+                            PhysicalPosition::new(xev.delta_x, xev.delta_y)
+                        } else {
+                            PhysicalPosition::new(0.0, 0.0)
+                        };
+
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(xev.event),
+                            event: WindowEvent::SwipeGesture {
+                                device_id: mkdid(xev.deviceid),
+                                delta,
+                                fingers: xev.detail as u32,
+                                phase,
+                                timestamp: instant_from_event_time(xev.time as u32),
+                            },
+                        });
+                    }
+
                     ffi::XI_RawButtonPress | ffi::XI_RawButtonRelease => {
                         let xev: &ffi::XIRawEvent = unsafe { &*(xev.data as *const _) };
                         if xev.flags & ffi::XIPointerEmulated == 0 {
@@ -1121,8 +1573,13 @@ impl<T: 'static> EventProcessor<T> {
                         if scancode < 0 {
                             return;
                         }
-                        let keysym = wt.xconn.keycode_to_keysym(keycode as ffi::KeyCode);
-                        let virtual_keycode = events::keysym_to_element(keysym as c_uint);
+                        // The core key event that follows is translated with the keymap of the
+                        // keyboard it comes from.
+                        if let Some(xkb) = self.xkb.as_mut() {
+                            xkb.set_source(xev.sourceid);
+                        }
+                        let keysym = self.keycode_to_keysym(keycode as ffi::KeyCode);
+                        let virtual_keycode = keymap::keysym_to_vkey(keysym);
                         let modifiers = self.device_mod_state.modifiers();
 
                         #[allow(deprecated)]
@@ -1177,6 +1634,9 @@ impl<T: 'static> EventProcessor<T> {
                                 });
                                 let mut devices = self.devices.borrow_mut();
                                 devices.remove(&DeviceId(info.deviceid));
+                                if let Some(xkb) = self.xkb.as_mut() {
+                                    xkb.remove_device(info.deviceid);
+                                }
                             }
                         }
                     }
@@ -1184,64 +1644,15 @@ impl<T: 'static> EventProcessor<T> {
                     _ => {}
                 }
             }
+            _ if Some(event_type) == wt.clipboard.xfixes_selection_notify() => {
+                let xev = unsafe { &*(xev as *const _ as *const ffi::XFixesSelectionNotifyEvent) };
+                if let Some(kind) = wt.clipboard.selection_kind(xev.selection) {
+                    Self::clipboard_changed(wt, kind, &mut callback);
+                }
+            }
             _ => {
                 if event_type == self.randr_event_offset {
-                    // In the future, it would be quite easy to emit monitor hotplug events.
-                    let prev_list = monitor::invalidate_cached_monitor_list();
-                    if let Some(prev_list) = prev_list {
-                        let new_list = wt.xconn.available_monitors();
-                        for new_monitor in new_list {
-                            // Previous list may be empty, in case of disconnecting and
-                            // reconnecting the only one monitor. We still need to emit events in
-                            // this case.
-                            let maybe_prev_scale_factor = prev_list
-                                .iter()
-                                .find(|prev_monitor| prev_monitor.name == new_monitor.name)
-                                .map(|prev_monitor| prev_monitor.scale_factor);
-                            if Some(new_monitor.scale_factor) != maybe_prev_scale_factor {
-                                for (window_id, window) in wt.windows.borrow().iter() {
-                                    if let Some(window) = window.upgrade() {
-                                        // Check if the window is on this monitor
-                                        let monitor = window.current_monitor();
-                                        if monitor.name == new_monitor.name {
-                                            let (width, height) = window.inner_size_physical();
-                                            let (new_width, new_height) = window.adjust_for_dpi(
-                                                // If we couldn't determine the previous scale
-                                                // factor (e.g., because all monitors were closed
-                                                // before), just pick whatever the current monitor
-                                                // has set as a baseline.
-                                                maybe_prev_scale_factor
-                                                    .unwrap_or(monitor.scale_factor),
-                                                new_monitor.scale_factor,
-                                                width,
-                                                height,
-                                                &*window.shared_state.lock(),
-                                            );
-
-                                            let window_id = crate::window::WindowId(*window_id);
-                                            let old_inner_size = PhysicalSize::new(width, height);
-                                            let mut new_inner_size =
-                                                PhysicalSize::new(new_width, new_height);
-
-                                            callback(Event::WindowEvent {
-                                                window_id,
-                                                event: WindowEvent::ScaleFactorChanged {
-                                                    scale_factor: new_monitor.scale_factor,
-                                                    new_inner_size: &mut new_inner_size,
-                                                },
-                                            });
-
-                                            if new_inner_size != old_inner_size {
-                                                let (new_width, new_height) = new_inner_size.into();
-                                                window
-                                                    .set_inner_size_physical(new_width, new_height);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+                    Self::monitors_changed(wt, &mut callback);
                 }
             }
         }
@@ -1275,14 +1686,15 @@ impl<T: 'static> EventProcessor<T> {
                 self.is_composing = true;
                 callback(Event::WindowEvent {
                     window_id: mkwid(window),
-                    event: WindowEvent::Ime(Ime::Preedit("".to_owned(), None)),
+                    event: WindowEvent::Ime(Ime::Preedit("".to_owned(), None, Vec::new())),
                 });
             }
-            ImeEvent::Update(text, position) => {
+            ImeEvent::Update(text, position, spans) => {
                 if self.is_composing {
+                    let preedit = Ime::Preedit(text, Some((position, position)), spans);
                     callback(Event::WindowEvent {
                         window_id: mkwid(window),
-                        event: WindowEvent::Ime(Ime::Preedit(text, Some((position, position)))),
+                        event: WindowEvent::Ime(preedit),
                     });
                 }
             }
@@ -1291,7 +1703,7 @@ impl<T: 'static> EventProcessor<T> {
                 // Issue empty preedit on `Done`.
                 callback(Event::WindowEvent {
                     window_id: mkwid(window),
-                    event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                    event: WindowEvent::Ime(Ime::Preedit(String::new(), None, Vec::new())),
                 });
             }
             ImeEvent::Disabled => {
@@ -1304,18 +1716,117 @@ impl<T: 'static> EventProcessor<T> {
         }
     }
 
-    fn handle_pressed_keys<F>(
+    fn clipboard_changed<F>(
         wt: &super::EventLoopWindowTarget<T>,
+        kind: ClipboardKind,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<'_, T>),
+    {
+        let window_ids: Vec<_> = wt.windows.borrow().keys().copied().collect();
+        for window_id in window_ids {
+            callback(Event::WindowEvent {
+                window_id: crate::window::WindowId(window_id),
+                event: WindowEvent::ClipboardChanged(kind),
+            });
+        }
+    }
+
+    /// Reads the monitors again, and tells the windows whose scale factor changed.
+    fn monitors_changed<F>(wt: &super::EventLoopWindowTarget<T>, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        // In the future, it would be quite easy to emit monitor hotplug events.
+        let prev_list = monitor::invalidate_cached_monitor_list();
+        if let Some(prev_list) = prev_list {
+            let new_list = wt.xconn.available_monitors();
+            for new_monitor in new_list {
+                // Previous list may be empty, in case of disconnecting and
+                // reconnecting the only one monitor. We still need to emit events in
+                // this case.
+                let maybe_prev_scale_factor = prev_list
+                    .iter()
+                    .find(|prev_monitor| prev_monitor.name == new_monitor.name)
+                    .map(|prev_monitor| prev_monitor.scale_factor);
+                if Some(new_monitor.scale_factor) != maybe_prev_scale_factor {
+                    for (window_id, window) in wt.windows.borrow().iter() {
+                        if let Some(window) = window.upgrade() {
+                            // Check if the window is on this monitor
+                            let monitor = window.current_monitor();
+                            if monitor.name == new_monitor.name {
+                                let (width, height) = window.inner_size_physical();
+                                let (new_width, new_height) = window.adjust_for_dpi(
+                                    // If we couldn't determine the previous scale
+                                    // factor (e.g., because all monitors were closed
+                                    // before), just pick whatever the current monitor
+                                    // has set as a baseline.
+                                    maybe_prev_scale_factor.unwrap_or(monitor.scale_factor),
+                                    new_monitor.scale_factor,
+                                    width,
+                                    height,
+                                    &*window.shared_state.lock(),
+                                );
+
+                                {
+                                    // The monitor in the shared state still has the previous
+                                    // scale factor.
+                                    let mut shared_state = window.shared_state.lock();
+                                    shared_state.last_monitor = new_monitor.clone();
+                                    window.scale_factor_changed(&shared_state);
+                                }
+
+                                let window_id = crate::window::WindowId(*window_id);
+                                let old_inner_size = PhysicalSize::new(width, height);
+                                let mut new_inner_size = PhysicalSize::new(new_width, new_height);
+
+                                callback(Event::WindowEvent {
+                                    window_id,
+                                    event: WindowEvent::ScaleFactorChanged {
+                                        scale_factor: new_monitor.scale_factor,
+                                        new_inner_size: &mut new_inner_size,
+                                    },
+                                });
+
+                                if new_inner_size != old_inner_size {
+                                    let (new_width, new_height) = new_inner_size.into();
+                                    window.set_inner_size_physical(new_width, new_height);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Emits the files parsed from the `text/uri-list` of the drag being dropped.
+    fn emit_dropped_files<F>(&self, window_id: crate::window::WindowId, callback: &mut F)
+    where
+        F: FnMut(Event<'_, T>),
+    {
+        if let Some(Ok(ref path_list)) = self.dnd.result {
+            for path in path_list {
+                callback(Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::DroppedFile(path.clone()),
+                });
+            }
+        }
+    }
+
+    fn handle_pressed_keys<F>(
+        &mut self,
         window_id: crate::window::WindowId,
+        timestamp: Instant,
         state: ElementState,
-        mod_keymap: &ModifierKeymap,
-        device_mod_state: &mut ModifierKeyState,
         callback: &mut F,
     ) where
         F: FnMut(Event<'_, T>),
     {
+        let wt = get_xtarget(&self.target);
         let device_id = mkdid(util::VIRTUAL_CORE_KEYBOARD);
-        let modifiers = device_mod_state.modifiers();
+        let modifiers = self.device_mod_state.modifiers();
 
         // Update modifiers state and emit key events based on which keys are currently pressed.
         for keycode in wt
@@ -1325,11 +1836,12 @@ impl<T: 'static> EventProcessor<T> {
             .filter(|k| *k >= KEYCODE_OFFSET)
         {
             let scancode = (keycode - KEYCODE_OFFSET) as u32;
-            let keysym = wt.xconn.keycode_to_keysym(keycode);
-            let virtual_keycode = events::keysym_to_element(keysym as c_uint);
+            let keysym = self.keycode_to_keysym(keycode);
+            let virtual_keycode = keymap::keysym_to_vkey(keysym);
+            let key_event = keymap::key_event(scancode, keysym, None, state, false);
 
-            if let Some(modifier) = mod_keymap.get_modifier(keycode as ffi::KeyCode) {
-                device_mod_state.key_event(
+            if let Some(modifier) = self.mod_keymap.get_modifier(keycode as ffi::KeyCode) {
+                self.device_mod_state.key_event(
                     ElementState::Pressed,
                     keycode as ffi::KeyCode,
                     modifier,
@@ -1347,11 +1859,21 @@ impl<T: 'static> EventProcessor<T> {
                         virtual_keycode,
                         modifiers,
                     },
+                    event: key_event,
                     is_synthetic: true,
+                    timestamp,
                 },
             });
         }
     }
+
+    /// The keysym of a key outside of a key event, with the current modifiers if known.
+    fn keycode_to_keysym(&self, keycode: ffi::KeyCode) -> u32 {
+        match self.xkb {
+            Some(ref xkb) => xkb.keysym(keycode as u32),
+            None => get_xtarget(&self.target).xconn.keycode_to_keysym(keycode) as u32,
+        }
+    }
 }
 
 fn is_first_touch(first: &mut Option<u64>, num: &mut u32, id: u64, phase: TouchPhase) -> bool {
@@ -1,9 +1,183 @@
+use std::os::raw::{c_double, c_int, c_ulong};
+
 use x11_dl::xmd::CARD32;
 pub use x11_dl::{
     error::OpenError, keysym::*, xcursor::*, xinput::*, xinput2::*, xlib::*, xlib_xcb::*,
     xrandr::*, xrender::*,
 };
+// x11_dl names the XFixes library struct `Xlib`, which would clash with the actual Xlib.
+pub use x11_dl::xfixes::{XFixesSelectionNotifyEvent, Xlib as XFixes};
+pub use x11_dl::xss::Xss;
 
 // Isn't defined by x11_dl
 #[allow(non_upper_case_globals)]
 pub const IconicState: CARD32 = 3;
+#[allow(non_upper_case_globals)]
+pub const XFixesSelectionNotify: c_int = 0;
+#[allow(non_upper_case_globals)]
+pub const XFixesSetSelectionOwnerNotifyMask: c_ulong = 1;
+#[allow(non_upper_case_globals)]
+pub const ShapeBounding: c_int = 0;
+#[allow(non_upper_case_globals)]
+pub const ShapeClip: c_int = 1;
+#[allow(non_upper_case_globals)]
+pub const ShapeInput: c_int = 2;
+
+// XInput 2.4 touchpad gestures, which x11_dl doesn't define yet either. The masks don't fit in
+// 32 bits.
+#[allow(non_upper_case_globals)]
+pub const XI_2_4_Minor: c_int = 4;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchBegin: c_int = 27;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchUpdate: c_int = 28;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchEnd: c_int = 29;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeBegin: c_int = 30;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeUpdate: c_int = 31;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeEnd: c_int = 32;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchBeginMask: i64 = 1 << XI_GesturePinchBegin;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchUpdateMask: i64 = 1 << XI_GesturePinchUpdate;
+#[allow(non_upper_case_globals)]
+pub const XI_GesturePinchEndMask: i64 = 1 << XI_GesturePinchEnd;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeBeginMask: i64 = 1 << XI_GestureSwipeBegin;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeUpdateMask: i64 = 1 << XI_GestureSwipeUpdate;
+#[allow(non_upper_case_globals)]
+pub const XI_GestureSwipeEndMask: i64 = 1 << XI_GestureSwipeEnd;
+#[allow(non_upper_case_globals)]
+pub const XIGesturePinchEventCancelled: c_int = 1 << 0;
+#[allow(non_upper_case_globals)]
+pub const XIGestureSwipeEventCancelled: c_int = 1 << 0;
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct XIGesturePinchEvent {
+    pub _type: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub extension: c_int,
+    pub evtype: c_int,
+    pub time: Time,
+    pub deviceid: c_int,
+    pub sourceid: c_int,
+    pub detail: c_int,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: c_double,
+    pub root_y: c_double,
+    pub event_x: c_double,
+    pub event_y: c_double,
+    pub delta_x: c_double,
+    pub delta_y: c_double,
+    pub delta_unaccel_x: c_double,
+    pub delta_unaccel_y: c_double,
+    pub scale: c_double,
+    pub delta_angle: c_double,
+    pub flags: c_int,
+    pub mods: XIModifierState,
+    pub group: XIGroupState,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct XIGestureSwipeEvent {
+    pub _type: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub extension: c_int,
+    pub evtype: c_int,
+    pub time: Time,
+    pub deviceid: c_int,
+    pub sourceid: c_int,
+    pub detail: c_int,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: c_double,
+    pub root_y: c_double,
+    pub event_x: c_double,
+    pub event_y: c_double,
+    pub delta_x: c_double,
+    pub delta_y: c_double,
+    pub delta_unaccel_x: c_double,
+    pub delta_unaccel_y: c_double,
+    pub flags: c_int,
+    pub mods: XIModifierState,
+    pub group: XIGroupState,
+}
+
+// The offsets and sizes of `XIGesturePinchEvent` and `XIGestureSwipeEvent` in `XInput2.h` of
+// libXi 1.8, on LP64.
+#[cfg(all(test, target_pointer_width = "64"))]
+mod tests {
+    use std::mem::{self, MaybeUninit};
+    use std::ptr;
+
+    use super::*;
+
+    macro_rules! offset_of {
+        ($ty:ty, $field:ident) => {{
+            let value = MaybeUninit::<$ty>::uninit();
+            let base = value.as_ptr();
+            // Doesn't read the field, so it doesn't matter that it's uninitialized.
+            let field = unsafe { ptr::addr_of!((*base).$field) };
+            field as usize - base as usize
+        }};
+    }
+
+    macro_rules! assert_common_layout {
+        ($ty:ty) => {
+            assert_eq!(offset_of!($ty, _type), 0);
+            assert_eq!(offset_of!($ty, serial), 8);
+            assert_eq!(offset_of!($ty, send_event), 16);
+            assert_eq!(offset_of!($ty, display), 24);
+            assert_eq!(offset_of!($ty, extension), 32);
+            assert_eq!(offset_of!($ty, evtype), 36);
+            assert_eq!(offset_of!($ty, time), 40);
+            assert_eq!(offset_of!($ty, deviceid), 48);
+            assert_eq!(offset_of!($ty, sourceid), 52);
+            assert_eq!(offset_of!($ty, detail), 56);
+            assert_eq!(offset_of!($ty, root), 64);
+            assert_eq!(offset_of!($ty, event), 72);
+            assert_eq!(offset_of!($ty, child), 80);
+            assert_eq!(offset_of!($ty, root_x), 88);
+            assert_eq!(offset_of!($ty, root_y), 96);
+            assert_eq!(offset_of!($ty, event_x), 104);
+            assert_eq!(offset_of!($ty, event_y), 112);
+            assert_eq!(offset_of!($ty, delta_x), 120);
+            assert_eq!(offset_of!($ty, delta_y), 128);
+            assert_eq!(offset_of!($ty, delta_unaccel_x), 136);
+            assert_eq!(offset_of!($ty, delta_unaccel_y), 144);
+        };
+    }
+
+    #[test]
+    fn pinch_event_layout() {
+        assert_common_layout!(XIGesturePinchEvent);
+        assert_eq!(offset_of!(XIGesturePinchEvent, scale), 152);
+        assert_eq!(offset_of!(XIGesturePinchEvent, delta_angle), 160);
+        assert_eq!(offset_of!(XIGesturePinchEvent, flags), 168);
+        assert_eq!(offset_of!(XIGesturePinchEvent, mods), 172);
+        assert_eq!(offset_of!(XIGesturePinchEvent, group), 188);
+        assert_eq!(mem::size_of::<XIGesturePinchEvent>(), 208);
+    }
+
+    #[test]
+    fn swipe_event_layout() {
+        assert_common_layout!(XIGestureSwipeEvent);
+        assert_eq!(offset_of!(XIGestureSwipeEvent, flags), 152);
+        assert_eq!(offset_of!(XIGestureSwipeEvent, mods), 156);
+        assert_eq!(offset_of!(XIGestureSwipeEvent, group), 172);
+        assert_eq!(mem::size_of::<XIGestureSwipeEvent>(), 192);
+    }
+}
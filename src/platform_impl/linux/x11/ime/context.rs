@@ -1,14 +1,20 @@
 use std::ffi::CStr;
 use std::os::raw::c_short;
 use std::sync::Arc;
-use std::{mem, ptr};
+use std::{mem, ptr, slice};
 
 use x11_dl::xlib::{XIMCallback, XIMPreeditCaretCallbackStruct, XIMPreeditDrawCallbackStruct};
 
+use crate::event::{PreeditSpan, PreeditStyle};
 use crate::platform_impl::platform::x11::ime::{ImeEvent, ImeEventSender};
 
 use super::{ffi, util, XConnection, XError};
 
+// Preedit feedback flags, which `x11-dl` doesn't expose.
+const XIM_REVERSE: ffi::XIMFeedback = 1;
+const XIM_UNDERLINE: ffi::XIMFeedback = 1 << 1;
+const XIM_HIGHLIGHT: ffi::XIMFeedback = 1 << 2;
+
 /// IME creation error.
 #[derive(Debug)]
 pub enum ImeContextCreationError {
@@ -40,6 +46,7 @@ extern "C" fn preedit_start_callback(
     let client_data = unsafe { &mut *(client_data as *mut ImeContextClientData) };
 
     client_data.text.clear();
+    client_data.feedback.clear();
     client_data.cursor_pos = 0;
     client_data
         .event_sender
@@ -58,6 +65,7 @@ extern "C" fn preedit_done_callback(
 
     // Drop text buffer and reset cursor position on done.
     client_data.text = Vec::new();
+    client_data.feedback = Vec::new();
     client_data.cursor_pos = 0;
 
     client_data
@@ -72,6 +80,34 @@ fn calc_byte_position(text: &[char], pos: usize) -> usize {
         .fold(0, |byte_pos, text| byte_pos + text.len_utf8())
 }
 
+/// Merges the feedback of the characters of the preedit text into styled byte ranges.
+fn calc_spans(text: &[char], feedback: &[ffi::XIMFeedback]) -> Vec<PreeditSpan> {
+    let mut spans: Vec<PreeditSpan> = Vec::new();
+    let mut byte_pos = 0;
+    for (chr, &feedback) in text.iter().zip(feedback) {
+        let start = byte_pos;
+        byte_pos += chr.len_utf8();
+
+        let style = if feedback & (XIM_REVERSE | XIM_HIGHLIGHT) != 0 {
+            PreeditStyle::Highlight
+        } else if feedback & XIM_UNDERLINE != 0 {
+            PreeditStyle::Underline
+        } else {
+            continue;
+        };
+
+        match spans.last_mut() {
+            Some(span) if span.end == start && span.style == style => span.end = byte_pos,
+            _ => spans.push(PreeditSpan {
+                start,
+                end: byte_pos,
+                style,
+            }),
+        }
+    }
+    spans
+}
+
 /// Preedit text information to be drawn inline by the client.
 extern "C" fn preedit_draw_callback(
     _xim: ffi::XIM,
@@ -95,6 +131,7 @@ extern "C" fn preedit_draw_callback(
     }
 
     // NULL indicate text deletion
+    let mut new_feedback = Vec::new();
     let mut new_chars = if call_data.text.is_null() {
         Vec::new()
     } else {
@@ -111,21 +148,37 @@ extern "C" fn preedit_draw_callback(
 
         let new_text = unsafe { CStr::from_ptr(new_text) };
 
-        String::from(new_text.to_str().expect("Invalid UTF-8 String from IME"))
-            .chars()
-            .collect()
+        let new_chars: Vec<char> =
+            String::from(new_text.to_str().expect("Invalid UTF-8 String from IME"))
+                .chars()
+                .collect();
+
+        // The feedback has one entry per character, or is missing when the text is unstyled.
+        if !xim_text.feedback.is_null() {
+            let feedback =
+                unsafe { slice::from_raw_parts(xim_text.feedback, xim_text.length as usize) };
+            new_feedback.extend_from_slice(feedback);
+        }
+        new_feedback.resize(new_chars.len(), 0);
+
+        new_chars
     };
     let mut old_text_tail = client_data.text.split_off(chg_range.end);
     client_data.text.truncate(chg_range.start);
     client_data.text.append(&mut new_chars);
     client_data.text.append(&mut old_text_tail);
+    let mut old_feedback_tail = client_data.feedback.split_off(chg_range.end);
+    client_data.feedback.truncate(chg_range.start);
+    client_data.feedback.append(&mut new_feedback);
+    client_data.feedback.append(&mut old_feedback_tail);
     let cursor_byte_pos = calc_byte_position(&client_data.text, client_data.cursor_pos);
+    let spans = calc_spans(&client_data.text, &client_data.feedback);
 
     client_data
         .event_sender
         .send((
             client_data.window,
-            ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos),
+            ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos, spans),
         ))
         .expect("failed to send preedit update event");
 }
@@ -142,12 +195,13 @@ extern "C" fn preedit_caret_callback(
     if call_data.direction == ffi::XIMCaretDirection::XIMAbsolutePosition {
         client_data.cursor_pos = call_data.position as usize;
         let cursor_byte_pos = calc_byte_position(&client_data.text, client_data.cursor_pos);
+        let spans = calc_spans(&client_data.text, &client_data.feedback);
 
         client_data
             .event_sender
             .send((
                 client_data.window,
-                ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos),
+                ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos, spans),
             ))
             .expect("failed to send preedit update event");
     }
@@ -183,6 +237,8 @@ struct ImeContextClientData {
     window: ffi::Window,
     event_sender: ImeEventSender,
     text: Vec<char>,
+    /// The feedback of each character of `text`.
+    feedback: Vec<ffi::XIMFeedback>,
     cursor_pos: usize,
 }
 
@@ -212,6 +268,7 @@ impl ImeContext {
             window,
             event_sender,
             text: Vec::new(),
+            feedback: Vec::new(),
             cursor_pos: 0,
         }));
 
@@ -6,15 +6,17 @@
     target_os = "openbsd"
 ))]
 
+mod clipboard;
 mod dnd;
 mod event_processor;
-mod events;
 pub mod ffi;
 mod ime;
 mod monitor;
 pub mod util;
 mod window;
 mod xdisplay;
+mod xkb;
+mod xsettings;
 
 pub use self::{
     monitor::{MonitorHandle, VideoMode},
@@ -40,13 +42,17 @@ use std::{
 use libc::{self, setlocale, LC_CTYPE};
 
 use mio::{unix::SourceFd, Events, Interest, Poll, Token, Waker};
+use parking_lot::Mutex;
 use raw_window_handle::{RawDisplayHandle, XlibDisplayHandle};
 
 use self::{
-    dnd::{Dnd, DndState},
+    clipboard::Clipboard,
+    dnd::{Dnd, DragSource, DropAnswer, DropTarget, FinishedDrop},
     event_processor::EventProcessor,
     ime::{Ime, ImeCreationError, ImeReceiver, ImeRequest, ImeSender},
     util::modifiers::ModifierKeymap,
+    xkb::XkbKeyboard,
+    xsettings::XSettingsClient,
 };
 use crate::{
     error::OsError as RootOsError,
@@ -54,11 +60,15 @@ use crate::{
     event_loop::{
         ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW,
     },
+    platform::unix::XSettings,
     platform_impl::{
-        platform::{sticky_exit_callback, WindowId},
+        platform::{
+            common::{portal, xkb::SharedLayout},
+            sticky_exit_callback, WindowId,
+        },
         PlatformSpecificWindowBuilderAttributes,
     },
-    window::WindowAttributes,
+    window::{Theme, WindowAttributes},
 };
 
 const X_TOKEN: Token = Token(0);
@@ -103,6 +113,19 @@ impl<T> PeekableReceiver<T> {
     }
 }
 
+/// The theme of the desktop, from the settings portal or else from XSETTINGS.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemTheme {
+    portal: Option<Theme>,
+    xsettings: Option<Theme>,
+}
+
+impl SystemTheme {
+    pub fn theme(&self) -> Option<Theme> {
+        self.portal.or(self.xsettings)
+    }
+}
+
 pub struct EventLoopWindowTarget<T> {
     xconn: Arc<XConnection>,
     wm_delete_window: ffi::Atom,
@@ -112,7 +135,16 @@ pub struct EventLoopWindowTarget<T> {
     ime: RefCell<Ime>,
     windows: RefCell<HashMap<WindowId, Weak<UnownedWindow>>>,
     redraw_sender: WakeSender<WindowId>,
+    // Whether touchpad gestures can be selected, which requires XInput 2.4
+    xi2_gestures: bool,
+    shortcuts_inhibit_sender: WakeSender<WindowId>,
     device_event_filter: Cell<DeviceEventFilter>,
+    clipboard: Arc<Clipboard>,
+    drag_source: Arc<DragSource>,
+    drop_target: Arc<DropTarget>,
+    keyboard_layout: SharedLayout,
+    xsettings: XSettingsClient,
+    system_theme: Arc<Mutex<SystemTheme>>,
     _marker: ::std::marker::PhantomData<T>,
 }
 
@@ -121,6 +153,7 @@ pub struct EventLoop<T: 'static> {
     waker: Arc<Waker>,
     event_processor: EventProcessor<T>,
     redraw_receiver: PeekableReceiver<WindowId>,
+    theme_receiver: PeekableReceiver<Option<Theme>>,
     user_receiver: PeekableReceiver<T>, //waker.wake needs to be called whenever something gets sent
     user_sender: Sender<T>,
     target: Rc<RootELW<T>>,
@@ -151,6 +184,15 @@ impl<T: 'static> EventLoop<T> {
         let dnd = Dnd::new(Arc::clone(&xconn))
             .expect("Failed to call XInternAtoms when initializing drag and drop");
 
+        let clipboard = Clipboard::new(Arc::clone(&xconn), root)
+            .expect("Failed to create the clipboard window");
+
+        let drag_source = DragSource::new(Arc::clone(&xconn), root)
+            .expect("Failed to call XInternAtoms when initializing drag and drop");
+
+        let drop_target = DropTarget::new(Arc::clone(&xconn), root)
+            .expect("Failed to create the drop target window");
+
         let (ime_sender, ime_receiver) = mpsc::channel();
         let (ime_event_sender, ime_event_receiver) = mpsc::channel();
         // Input methods will open successfully without setting the locale, but it won't be
@@ -206,9 +248,10 @@ impl<T: 'static> EventLoop<T> {
             ext
         };
 
-        unsafe {
+        // The server replies with the highest version both support.
+        let xi2_version = unsafe {
             let mut xinput_major_ver = ffi::XI_2_Major;
-            let mut xinput_minor_ver = ffi::XI_2_Minor;
+            let mut xinput_minor_ver = ffi::XI_2_4_Minor;
             if (xconn.xinput2.XIQueryVersion)(
                 xconn.display,
                 &mut xinput_major_ver,
@@ -220,13 +263,31 @@ impl<T: 'static> EventLoop<T> {
                     xinput_major_ver, xinput_minor_ver,
                 );
             }
-        }
+
+            (xinput_major_ver, xinput_minor_ver)
+        };
 
         xconn.update_cached_wm_info(root);
 
+        // Make held keys repeat presses without releases in between, so that the repeats can be
+        // told apart from new presses.
+        unsafe {
+            let mut supported = 0;
+            (xconn.xlib.XkbSetDetectableAutoRepeat)(xconn.display, ffi::True, &mut supported);
+            if supported == ffi::False {
+                warn!("X server doesn't support detectable auto repeat");
+            }
+        }
+
         let mut mod_keymap = ModifierKeymap::new();
         mod_keymap.reset_from_x_connection(&xconn);
 
+        let keyboard_layout = SharedLayout::default();
+        let xkb = XkbKeyboard::new(&xconn, keyboard_layout.clone());
+        if xkb.is_none() {
+            warn!("Failed to load the keymap with xkbcommon, falling back to core X keysyms");
+        }
+
         let poll = Poll::new().unwrap();
         let waker = Arc::new(Waker::new(poll.registry(), USER_REDRAW_TOKEN).unwrap());
 
@@ -236,6 +297,22 @@ impl<T: 'static> EventLoop<T> {
 
         let (user_sender, user_channel) = std::sync::mpsc::channel();
         let (redraw_sender, redraw_channel) = std::sync::mpsc::channel();
+        let (shortcuts_inhibit_sender, shortcuts_inhibit_receiver) = std::sync::mpsc::channel();
+
+        // The portal is followed from its own thread.
+        let (theme_sender, theme_channel) = std::sync::mpsc::channel();
+        let theme_waker = waker.clone();
+        portal::watch_color_scheme(move |theme| {
+            if theme_sender.send(theme).is_ok() {
+                let _ = theme_waker.wake();
+            }
+        });
+        let screen = unsafe { (xconn.xlib.XDefaultScreen)(xconn.display) };
+        let xsettings = XSettingsClient::new(Arc::clone(&xconn), screen);
+        let system_theme = SystemTheme {
+            portal: None,
+            xsettings: xsettings::theme(&xsettings.values()),
+        };
 
         let window_target = EventLoopWindowTarget {
             ime,
@@ -250,7 +327,18 @@ impl<T: 'static> EventLoop<T> {
                 sender: redraw_sender, // not used again so no clone
                 waker: waker.clone(),
             },
+            xi2_gestures: xi2_version >= (2, ffi::XI_2_4_Minor),
+            shortcuts_inhibit_sender: WakeSender {
+                sender: shortcuts_inhibit_sender,
+                waker: waker.clone(),
+            },
             device_event_filter: Default::default(),
+            clipboard: Arc::new(clipboard),
+            drag_source: Arc::new(drag_source),
+            drop_target: Arc::new(drop_target),
+            keyboard_layout,
+            xsettings,
+            system_theme: Arc::new(Mutex::new(system_theme)),
         };
 
         // Set initial device event filter.
@@ -268,20 +356,28 @@ impl<T: 'static> EventLoop<T> {
             randr_event_offset,
             ime_receiver,
             ime_event_receiver,
+            shortcuts_inhibit_receiver,
             xi2ext,
             mod_keymap,
             device_mod_state: Default::default(),
             num_touch: 0,
             first_touch: None,
+            pinch_scale: 1.0,
             active_window: None,
             is_composing: false,
+            pressed_keys: Default::default(),
+            xkb,
         };
 
         // Register for device hotplug events
         // (The request buffer is flushed during `init_device`)
         get_xtarget(&target)
             .xconn
-            .select_xinput_events(root, ffi::XIAllDevices, ffi::XI_HierarchyChangedMask)
+            .select_xinput_events(
+                root,
+                ffi::XIAllDevices,
+                i64::from(ffi::XI_HierarchyChangedMask),
+            )
             .queue();
 
         event_processor.init_device(ffi::XIAllDevices);
@@ -291,6 +387,7 @@ impl<T: 'static> EventLoop<T> {
             waker,
             event_processor,
             redraw_receiver: PeekableReceiver::from_recv(redraw_channel),
+            theme_receiver: PeekableReceiver::from_recv(theme_channel),
             user_receiver: PeekableReceiver::from_recv(user_channel),
             user_sender,
             target,
@@ -451,12 +548,33 @@ impl<T: 'static> EventLoop<T> {
             if let ControlFlow::ExitWithCode(code) = control_flow {
                 break code;
             }
+            let wt = get_xtarget(&self.target);
+            let dnd_deadline = [wt.drag_source.deadline(), wt.drop_target.deadline()]
+                .iter()
+                .flatten()
+                .min()
+                .copied();
             let has_pending = self.event_processor.poll()
                 || self.user_receiver.has_incoming()
-                || self.redraw_receiver.has_incoming();
+                || self.redraw_receiver.has_incoming()
+                || self.theme_receiver.has_incoming()
+                || dnd_deadline.map_or(false, |deadline| deadline <= Instant::now());
             if !has_pending {
+                // Wake up in time to give up on a drag and drop peer which doesn't answer.
+                let timeout = match dnd_deadline {
+                    Some(deadline) => {
+                        let dnd_timeout = deadline.saturating_duration_since(Instant::now());
+                        Some(
+                            iter_result
+                                .timeout
+                                .map_or(dnd_timeout, |timeout| timeout.min(dnd_timeout)),
+                        )
+                    }
+                    None => iter_result.timeout,
+                };
+
                 // Wait until
-                if let Err(e) = self.poll.poll(&mut events, iter_result.timeout) {
+                if let Err(e) = self.poll.poll(&mut events, timeout) {
                     if e.raw_os_error() != Some(libc::EINTR) {
                         panic!("epoll returned an error: {:?}", e);
                     }
@@ -530,13 +648,28 @@ impl<T: 'static> EventLoop<T> {
                 );
             });
         }
+
+        self.event_processor
+            .process_shortcuts_inhibit_requests(|event| {
+                sticky_exit_callback(event, target, control_flow, callback)
+            });
+
+        self.event_processor.process_dnd_timeouts(|event| {
+            sticky_exit_callback(event, target, control_flow, callback)
+        });
+
+        while let Ok(theme) = self.theme_receiver.try_recv() {
+            self.event_processor.update_system_theme(
+                |system_theme| system_theme.portal = theme,
+                |event| sticky_exit_callback(event, target, control_flow, callback),
+            );
+        }
     }
 }
 
 pub(crate) fn get_xtarget<T>(target: &RootELW<T>) -> &EventLoopWindowTarget<T> {
     match target.p {
         super::EventLoopWindowTarget::X(ref target) => target,
-        #[cfg(feature = "wayland")]
         _ => unreachable!(),
     }
 }
@@ -548,6 +681,11 @@ impl<T> EventLoopWindowTarget<T> {
         &self.xconn
     }
 
+    /// Returns the settings of the XSETTINGS manager.
+    pub fn xsettings(&self) -> XSettings {
+        self.xsettings.values()
+    }
+
     pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
         self.device_event_filter.set(filter);
     }
@@ -567,7 +705,7 @@ impl<T> EventLoopWindowTarget<T> {
         }
 
         self.xconn
-            .select_xinput_events(self.root, ffi::XIAllMasterDevices, mask)
+            .select_xinput_events(self.root, ffi::XIAllMasterDevices, i64::from(mask))
             .queue();
     }
 
@@ -636,6 +774,16 @@ impl DeviceId {
     pub const unsafe fn dummy() -> Self {
         DeviceId(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        self.0 as u64
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_raw(raw: u64) -> Self {
+        DeviceId(raw as _)
+    }
 }
 
 pub struct Window(Arc<UnownedWindow>);
@@ -667,6 +815,8 @@ impl Drop for Window {
     fn drop(&mut self) {
         let window = self.deref();
         let xconn = &window.xconn;
+        // The screen saver would stay suspended until the connection is closed.
+        let _ = window.set_idle_inhibit(false);
         unsafe {
             (xconn.xlib.XDestroyWindow)(xconn.display, window.id().0 as ffi::Window);
             // If the window was somehow already destroyed, we'll get a `BadWindow` error, which we don't care about.
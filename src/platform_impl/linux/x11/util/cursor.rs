@@ -1,16 +1,110 @@
-use crate::window::CursorIcon;
+use std::sync::Arc;
+
+use crate::window::{CursorIcon, CustomCursor};
 
 use super::*;
 
+/// The cursor last requested for a window, shown whenever the cursor is visible.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectedCursor {
+    Named(CursorIcon),
+    Custom(CustomCursor),
+}
+
+impl Default for SelectedCursor {
+    fn default() -> Self {
+        SelectedCursor::Named(Default::default())
+    }
+}
+
 impl XConnection {
     pub fn set_cursor_icon(&self, window: ffi::Window, cursor: Option<CursorIcon>) {
-        let cursor = *self
+        let cursor = self.cached_cursor(cursor);
+        self.update_cursor(window, cursor);
+    }
+
+    pub fn set_selected_cursor(&self, window: ffi::Window, cursor: &SelectedCursor) {
+        match cursor {
+            SelectedCursor::Named(cursor) => self.set_cursor_icon(window, Some(*cursor)),
+            SelectedCursor::Custom(cursor) => match self.cached_custom_cursor(cursor) {
+                Ok(cursor) => self.update_cursor(window, cursor),
+                Err(err) => {
+                    warn!("Failed to create the custom cursor: {}", err);
+                    self.set_cursor_icon(window, Some(Default::default()));
+                }
+            },
+        }
+    }
+
+    /// Returns the X cursor for `cursor`, creating it the first time it's used.
+    ///
+    /// The cursors of dropped `CustomCursor`s are freed here as well, since we don't get a
+    /// chance to do so when they're dropped.
+    fn cached_custom_cursor(&self, cursor: &CustomCursor) -> Result<ffi::Cursor, &'static str> {
+        let mut cache = self.custom_cursor_cache.lock();
+        cache.retain(|(images, xcursor)| {
+            if images.strong_count() == 0 {
+                unsafe { (self.xlib.XFreeCursor)(self.display, *xcursor) };
+                false
+            } else {
+                true
+            }
+        });
+
+        if let Some((_, xcursor)) = cache
+            .iter()
+            .find(|(images, _)| images.as_ptr() == Arc::as_ptr(&cursor.images))
+        {
+            return Ok(*xcursor);
+        }
+
+        let xcursor = self.create_custom_cursor(cursor)?;
+        cache.push((Arc::downgrade(&cursor.images), xcursor));
+        Ok(xcursor)
+    }
+
+    fn create_custom_cursor(&self, cursor: &CustomCursor) -> Result<ffi::Cursor, &'static str> {
+        unsafe {
+            let images = (self.xcursor.XcursorImagesCreate)(cursor.images.len() as _);
+            if images.is_null() {
+                return Err("failed to allocate cursor images");
+            }
+
+            for (i, image) in cursor.images.iter().enumerate() {
+                let xcursor_image =
+                    (self.xcursor.XcursorImageCreate)(image.width as _, image.height as _);
+                if xcursor_image.is_null() {
+                    (self.xcursor.XcursorImagesDestroy)(images);
+                    return Err("failed to allocate cursor image");
+                }
+
+                (*xcursor_image).xhot = image.hotspot_x;
+                (*xcursor_image).yhot = image.hotspot_y;
+                (*xcursor_image).delay = (image.duration.as_millis() as u32).max(1);
+                let pixels =
+                    std::slice::from_raw_parts_mut((*xcursor_image).pixels, image.argb.len());
+                pixels.copy_from_slice(&image.argb);
+
+                *(*images).images.add(i) = xcursor_image;
+                (*images).nimage = i as i32 + 1;
+            }
+
+            let xcursor = (self.xcursor.XcursorImagesLoadCursor)(self.display, images);
+            // This also destroys the images it holds.
+            (self.xcursor.XcursorImagesDestroy)(images);
+            if xcursor == 0 {
+                return Err("failed to create the cursor");
+            }
+            Ok(xcursor)
+        }
+    }
+
+    pub fn cached_cursor(&self, cursor: Option<CursorIcon>) -> ffi::Cursor {
+        *self
             .cursor_cache
             .lock()
             .entry(cursor)
-            .or_insert_with(|| self.get_cursor(cursor));
-
-        self.update_cursor(window, cursor);
+            .or_insert_with(|| self.get_cursor(cursor))
     }
 
     fn create_empty_cursor(&self) -> ffi::Cursor {
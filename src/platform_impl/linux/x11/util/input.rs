@@ -63,7 +63,7 @@ impl XConnection {
         &self,
         window: c_ulong,
         device_id: c_int,
-        mask: i32,
+        mask: i64,
     ) -> Flusher<'_> {
         let mut event_mask = ffi::XIEventMask {
             deviceid: device_id,
@@ -36,8 +36,11 @@ pub fn calc_dpi_factor(
 }
 
 impl XConnection {
-    // Retrieve DPI from Xft.dpi property
+    // Retrieve DPI from the XSETTINGS manager, or else from the Xft.dpi property
     pub unsafe fn get_xft_dpi(&self) -> Option<f64> {
+        if let Some(dpi) = *self.xsettings_dpi.lock() {
+            return Some(dpi);
+        }
         (self.xlib.XrmInitialize)();
         let resource_manager_str = (self.xlib.XResourceManagerString)(self.display);
         if resource_manager_str.is_null() {
@@ -139,4 +139,11 @@ impl XConnection {
         );*/
         Flusher::new(self)
     }
+
+    pub fn delete_property(&self, window: c_ulong, property: ffi::Atom) -> Flusher<'_> {
+        unsafe {
+            (self.xlib.XDeleteProperty)(self.display, window, property);
+        }
+        Flusher::new(self)
+    }
 }
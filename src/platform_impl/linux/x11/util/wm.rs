@@ -70,7 +70,9 @@ impl XConnection {
         let child_window_wm_check = {
             let result = self.get_property(root_window_wm_check, check_atom, ffi::XA_WINDOW);
 
-            let wm_check = result.ok().and_then(|wm_check| wm_check.first().cloned());
+            let wm_check = result
+                .ok()
+                .and_then(|wm_check: Vec<ffi::Window>| wm_check.first().cloned());
 
             wm_check?
         };
@@ -1,7 +1,7 @@
 use std::{
     cmp, env,
     ffi::CString,
-    mem::{self, replace, MaybeUninit},
+    mem::{self, MaybeUninit},
     os::raw::*,
     path::Path,
     ptr, slice,
@@ -14,19 +14,30 @@ use raw_window_handle::{RawDisplayHandle, RawWindowHandle, XlibDisplayHandle, Xl
 use x11_dl::xlib::TrueColor;
 
 use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dnd::{DndAction, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
+    keyboard::{Key, KeyCode, KeyboardLayout},
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
     platform_impl::{
+        platform::common::{popup, xkb::SharedLayout},
         x11::{ime::ImeContextCreationError, MonitorHandle as X11MonitorHandle},
         MonitorHandle as PlatformMonitorHandle, OsError, PlatformSpecificWindowBuilderAttributes,
         VideoMode as PlatformVideoMode,
     },
-    window::{CursorGrabMode, CursorIcon, Fullscreen, Icon, UserAttentionType, WindowAttributes},
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, Icon, ImeHints, ImePurpose, Rect,
+        Region, Theme, UserAttentionType, WindowAttributes,
+    },
 };
 
 use super::{
-    ffi, util, EventLoopWindowTarget, ImeRequest, ImeSender, WakeSender, WindowId, XConnection,
+    clipboard::Clipboard,
+    dnd::{DragSource, DropAnswer, DropTarget},
+    ffi,
+    util::{self, SelectedCursor},
+    EventLoopWindowTarget, ImeRequest, ImeSender, SystemTheme, WakeSender, WindowId, XConnection,
     XError,
 };
 
@@ -54,6 +65,11 @@ pub struct SharedState {
     pub resize_increments: Option<Size>,
     pub base_size: Option<Size>,
     pub visibility: Visibility,
+    pub cursor_hittest: bool,
+    // `None` when the whole window receives input
+    pub input_region: Option<Vec<Rect>>,
+    // `None` when the window is rectangular
+    pub shape: Option<Region>,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -93,10 +109,22 @@ impl SharedState {
             max_inner_size: None,
             resize_increments: None,
             base_size: None,
+            cursor_hittest: true,
+            input_region: None,
+            shape: None,
         })
     }
 }
 
+/// The keyboard grab sending the system shortcuts to a window.
+#[derive(Debug, Default, Clone, Copy)]
+struct ShortcutsInhibit {
+    /// Whether the application asked for the system shortcuts.
+    requested: bool,
+    /// Whether the window currently holds the grab.
+    grabbed: bool,
+}
+
 unsafe impl Send for UnownedWindow {}
 unsafe impl Sync for UnownedWindow {}
 
@@ -105,12 +133,21 @@ pub struct UnownedWindow {
     xwindow: ffi::Window,        // never changes
     root: ffi::Window,           // never changes
     screen_id: i32,              // never changes
-    cursor: Mutex<CursorIcon>,
+    cursor: Mutex<SelectedCursor>,
     cursor_grabbed_mode: Mutex<CursorGrabMode>,
     cursor_visible: Mutex<bool>,
     ime_sender: Mutex<ImeSender>,
     pub shared_state: Mutex<SharedState>,
     redraw_sender: WakeSender<WindowId>,
+    clipboard: Arc<Clipboard>,
+    drag_source: Arc<DragSource>,
+    drop_target: Arc<DropTarget>,
+    keyboard_layout: SharedLayout,
+    popup_grab: bool, // never changes
+    idle_inhibited: Mutex<bool>,
+    shortcuts_inhibit: Mutex<ShortcutsInhibit>,
+    shortcuts_inhibit_sender: WakeSender<WindowId>,
+    system_theme: Arc<Mutex<SystemTheme>>,
 }
 
 impl UnownedWindow {
@@ -153,11 +190,16 @@ impl UnownedWindow {
             .min_inner_size
             .map(|size| size.to_physical::<u32>(scale_factor).into());
 
-        let position = window_attrs
+        let mut position = window_attrs
             .position
             .map(|position| position.to_physical::<i32>(scale_factor));
 
-        let dimensions = {
+        let parent = window_attrs
+            .parent
+            .map(|parent| u64::from(parent.0) as ffi::Window);
+        let popup = parent.and(pl_attribs.popup);
+
+        let mut dimensions = {
             // x11 only applies constraints when the window is actively resized
             // by the user, so we have to manually apply the initial constraints
             let mut dimensions: (u32, u32) = window_attrs
@@ -181,6 +223,36 @@ impl UnownedWindow {
             dimensions
         };
 
+        // Popups are placed against their parent, and kept on the monitor the anchor is on.
+        if let (Some(parent), Some(popup)) = (parent, popup) {
+            let origin = xconn
+                .translate_coords(parent, root)
+                .map_err(|err| os_error!(OsError::XError(err)))?;
+            let anchor_position = popup.anchor_position.to_physical::<i32>(scale_factor);
+            let anchor_size = popup.anchor_size.to_physical::<u32>(scale_factor);
+            let anchor_x = origin.x_rel_root + anchor_position.x;
+            let anchor_y = origin.y_rel_root + anchor_position.y;
+
+            let bounds = xconn
+                .available_monitors()
+                .into_iter()
+                .find(|monitor| {
+                    monitor
+                        .rect
+                        .contains_point(anchor_x as i64, anchor_y as i64)
+                })
+                .map(|monitor| (monitor.position().into(), monitor.size().into()));
+
+            let anchor_rect = ((anchor_x, anchor_y), anchor_size.into());
+            let (popup_position, popup_dimensions) =
+                popup::place(&popup, anchor_rect, dimensions, bounds);
+            position = Some(popup_position.into());
+            dimensions = popup_dimensions;
+        }
+
+        // Popups aren't managed by the window manager.
+        let override_redirect = pl_attribs.override_redirect || popup.is_some();
+
         let screen_id = match pl_attribs.screen_id {
             Some(id) => id,
             None => unsafe { (xconn.xlib.XDefaultScreen)(xconn.display) },
@@ -242,13 +314,13 @@ impl UnownedWindow {
                 | ffi::ButtonReleaseMask
                 | ffi::PointerMotionMask;
             swa.border_pixel = 0;
-            swa.override_redirect = pl_attribs.override_redirect as c_int;
+            swa.override_redirect = override_redirect as c_int;
             swa
         };
 
         let mut window_attributes = ffi::CWBorderPixel | ffi::CWColormap | ffi::CWEventMask;
 
-        if pl_attribs.override_redirect {
+        if override_redirect {
             window_attributes |= ffi::CWOverrideRedirect;
         }
 
@@ -284,6 +356,18 @@ impl UnownedWindow {
                 waker: event_loop.redraw_sender.waker.clone(),
                 sender: event_loop.redraw_sender.sender.clone(),
             },
+            clipboard: Arc::clone(&event_loop.clipboard),
+            drag_source: Arc::clone(&event_loop.drag_source),
+            drop_target: Arc::clone(&event_loop.drop_target),
+            keyboard_layout: event_loop.keyboard_layout.clone(),
+            popup_grab: popup.map_or(false, |popup| popup.grab),
+            idle_inhibited: Mutex::new(false),
+            shortcuts_inhibit: Default::default(),
+            shortcuts_inhibit_sender: WakeSender {
+                waker: event_loop.shortcuts_inhibit_sender.waker.clone(),
+                sender: event_loop.shortcuts_inhibit_sender.sender.clone(),
+            },
+            system_theme: Arc::clone(&event_loop.system_theme),
         };
 
         // Title must be set before mapping. Some tiling window managers (i.e. i3) use the window
@@ -350,7 +434,17 @@ impl UnownedWindow {
                 flusher.queue()
             }
 
-            window.set_window_types(pl_attribs.x11_window_types).queue();
+            let mut window_types = pl_attribs.x11_window_types;
+            if popup.is_some() && window_types == [util::WindowType::Normal] {
+                window_types = vec![util::WindowType::PopupMenu];
+            }
+            window.set_window_types(window_types).queue();
+
+            if let Some(parent) = parent {
+                unsafe {
+                    (xconn.xlib.XSetTransientForHint)(xconn.display, window.xwindow, parent);
+                } //.queue();
+            }
 
             if let Some(variant) = pl_attribs.gtk_theme_variant {
                 window.set_gtk_theme_variant(variant).queue();
@@ -419,6 +513,7 @@ impl UnownedWindow {
                 unsafe {
                     (xconn.xlib.XMapRaised)(xconn.display, window.xwindow);
                 } //.queue();
+                window.grab_popup_input();
             }
 
             // Attempt to make keyboard input repeat detectable
@@ -437,7 +532,8 @@ impl UnownedWindow {
             }
 
             // Select XInput2 events
-            let mask = ffi::XI_MotionMask
+            let mut mask = i64::from(
+                ffi::XI_MotionMask
                     | ffi::XI_ButtonPressMask
                     | ffi::XI_ButtonReleaseMask
                     //| ffi::XI_KeyPressMask
@@ -448,7 +544,17 @@ impl UnownedWindow {
                     | ffi::XI_FocusOutMask
                     | ffi::XI_TouchBeginMask
                     | ffi::XI_TouchUpdateMask
-                    | ffi::XI_TouchEndMask;
+                    | ffi::XI_TouchEndMask,
+            );
+            // The server refuses the gesture masks from clients which didn't ask for XInput 2.4.
+            if event_loop.xi2_gestures {
+                mask |= ffi::XI_GesturePinchBeginMask
+                    | ffi::XI_GesturePinchUpdateMask
+                    | ffi::XI_GesturePinchEndMask
+                    | ffi::XI_GestureSwipeBeginMask
+                    | ffi::XI_GestureSwipeUpdateMask
+                    | ffi::XI_GestureSwipeEndMask;
+            }
             xconn
                 .select_xinput_events(window.xwindow, ffi::XIAllMasterDevices, mask)
                 .queue();
@@ -681,7 +787,6 @@ impl UnownedWindow {
                         inner: PlatformMonitorHandle::X(monitor),
                     })) => (None, monitor),
                     Fullscreen::Borderless(None) => (None, self.current_monitor()),
-                    #[cfg(feature = "wayland")]
                     _ => unreachable!(),
                 };
 
@@ -982,6 +1087,7 @@ impl UnownedWindow {
                 .flush_requests()
                 .expect("Failed to call XMapRaised");
             shared_state.visibility = Visibility::YesWait;
+            self.grab_popup_input();
         } else {
             unsafe {
                 (self.xconn.xlib.XUnmapWindow)(self.xconn.display, self.xwindow);
@@ -993,6 +1099,49 @@ impl UnownedWindow {
         }
     }
 
+    /// Grabs the pointer and the keyboard for a popup which asked for it, after mapping it.
+    ///
+    /// The clicks outside of the application are then reported to the popup as core events,
+    /// while the grab is released by the server once the popup is unmapped.
+    fn grab_popup_input(&self) {
+        if !self.popup_grab {
+            return;
+        }
+
+        let pointer_grab = unsafe {
+            (self.xconn.xlib.XGrabPointer)(
+                self.xconn.display,
+                self.xwindow,
+                ffi::True,
+                ffi::ButtonPressMask as c_uint,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                0,
+                0,
+                ffi::CurrentTime,
+            )
+        };
+        let keyboard_grab = unsafe {
+            (self.xconn.xlib.XGrabKeyboard)(
+                self.xconn.display,
+                self.xwindow,
+                ffi::False,
+                ffi::GrabModeAsync,
+                ffi::GrabModeAsync,
+                ffi::CurrentTime,
+            )
+        };
+
+        if pointer_grab != ffi::GrabSuccess || keyboard_grab != ffi::GrabSuccess {
+            warn!("Failed to grab the input for the popup");
+        }
+    }
+
+    #[inline]
+    pub(crate) fn popup_grab(&self) -> bool {
+        self.popup_grab
+    }
+
     #[inline]
     pub fn is_visible(&self) -> Option<bool> {
         Some(self.shared_state.lock().visibility == Visibility::Yes)
@@ -1265,10 +1414,20 @@ impl UnownedWindow {
 
     #[inline]
     pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-        let old_cursor = replace(&mut *self.cursor.lock(), cursor);
-        if cursor != old_cursor && *self.cursor_visible.lock() {
-            self.xconn.set_cursor_icon(self.xwindow, Some(cursor));
+        self.set_selected_cursor(SelectedCursor::Named(cursor));
+    }
+
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) {
+        self.set_selected_cursor(SelectedCursor::Custom(cursor.clone()));
+    }
+
+    fn set_selected_cursor(&self, cursor: SelectedCursor) {
+        let mut cursor_lock = self.cursor.lock();
+        if cursor != *cursor_lock && *self.cursor_visible.lock() {
+            self.xconn.set_selected_cursor(self.xwindow, &cursor);
         }
+        *cursor_lock = cursor;
     }
 
     #[inline]
@@ -1350,14 +1509,14 @@ impl UnownedWindow {
         if visible == *visible_lock {
             return;
         }
-        let cursor = if visible {
-            Some(*self.cursor.lock())
-        } else {
-            None
-        };
         *visible_lock = visible;
         drop(visible_lock);
-        self.xconn.set_cursor_icon(self.xwindow, cursor);
+        if visible {
+            self.xconn
+                .set_selected_cursor(self.xwindow, &self.cursor.lock());
+        } else {
+            self.xconn.set_cursor_icon(self.xwindow, None);
+        }
     }
 
     #[inline]
@@ -1381,8 +1540,191 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn set_cursor_hittest(&self, _hittest: bool) -> Result<(), ExternalError> {
-        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+        let mut shared_state = self.shared_state.lock();
+        shared_state.cursor_hittest = hittest;
+        self.update_input_shape(&shared_state)
+    }
+
+    pub fn set_input_region(&self, region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        let mut shared_state = self.shared_state.lock();
+        shared_state.input_region = region.map(<[Rect]>::to_vec);
+        self.update_input_shape(&shared_state)
+    }
+
+    pub fn set_shape(&self, shape: Option<Region>) -> Result<(), ExternalError> {
+        let mut shared_state = self.shared_state.lock();
+        shared_state.shape = shape;
+        self.update_shape(&shared_state)
+    }
+
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        let opacity_atom = unsafe { self.xconn.get_atom_unchecked(b"_NET_WM_WINDOW_OPACITY\0") };
+        let opacity = opacity.max(0.);
+        let flusher = if opacity >= 1. {
+            self.xconn.delete_property(self.xwindow, opacity_atom)
+        } else {
+            // The opacity is a fraction of the largest 32-bit cardinal.
+            let opacity = (opacity as f64 * u32::MAX as f64).round() as u32 as util::Cardinal;
+            self.xconn.change_property(
+                self.xwindow,
+                opacity_atom,
+                ffi::XA_CARDINAL,
+                util::PropMode::Replace,
+                &[opacity],
+            )
+        };
+        flusher
+            .flush()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    pub fn set_blur(&self, blur: bool) -> Result<(), ExternalError> {
+        let blur_atom = unsafe {
+            self.xconn
+                .get_atom_unchecked(b"_KDE_NET_WM_BLUR_BEHIND_REGION\0")
+        };
+        let flusher = if blur {
+            // An empty region blurs behind the whole window.
+            let empty_region: [util::Cardinal; 0] = [];
+            self.xconn.change_property(
+                self.xwindow,
+                blur_atom,
+                ffi::XA_CARDINAL,
+                util::PropMode::Replace,
+                &empty_region,
+            )
+        } else {
+            self.xconn.delete_property(self.xwindow, blur_atom)
+        };
+        flusher
+            .flush()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    /// Sets the bounding and clip shapes of the window from its shape.
+    fn update_shape(&self, shared_state: &SharedState) -> Result<(), ExternalError> {
+        let xfixes = self.xfixes()?;
+        let rects = shared_state.shape.as_ref().map(|shape| shape.rects());
+        // The clip shape keeps the children of the window inside of the shape as well.
+        for &shape_kind in &[ffi::ShapeBounding, ffi::ShapeClip] {
+            self.set_shape_region(
+                xfixes,
+                shape_kind,
+                rects,
+                shared_state.last_monitor.scale_factor,
+            );
+        }
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    /// Sets the input shape of the window from the hittest and the input region.
+    fn update_input_shape(&self, shared_state: &SharedState) -> Result<(), ExternalError> {
+        let xfixes = self.xfixes()?;
+        let rects = if shared_state.cursor_hittest {
+            shared_state.input_region.as_deref()
+        } else {
+            // An empty shape lets all the input through.
+            Some(&[][..])
+        };
+        self.set_shape_region(
+            xfixes,
+            ffi::ShapeInput,
+            rects,
+            shared_state.last_monitor.scale_factor,
+        );
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    /// Sets the shapes again once the scale factor changed, since the server keeps them in
+    /// physical pixels.
+    pub(crate) fn scale_factor_changed(&self, shared_state: &SharedState) {
+        // Setting them failed already.
+        if self.xfixes().is_err() {
+            return;
+        }
+
+        if shared_state.shape.is_some() {
+            if let Err(err) = self.update_shape(shared_state) {
+                warn!("Failed to scale the window shape: {:?}", err);
+            }
+        }
+        if shared_state.input_region.is_some() {
+            if let Err(err) = self.update_input_shape(shared_state) {
+                warn!("Failed to scale the input region: {:?}", err);
+            }
+        }
+    }
+
+    /// The XFixes library, if the server supports the extension.
+    fn xfixes(&self) -> Result<&ffi::XFixes, ExternalError> {
+        let xfixes = match self.xconn.xfixes.as_ref() {
+            Some(xfixes) => xfixes,
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
+
+        let (mut event_base, mut error_base) = (0, 0);
+        let has_extension = unsafe {
+            (xfixes.XFixesQueryExtension)(self.xconn.display, &mut event_base, &mut error_base)
+        };
+        if has_extension == ffi::False {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        Ok(xfixes)
+    }
+
+    /// Sets one of the shapes of the window to the given rectangles, or to the whole window when
+    /// there are none.
+    fn set_shape_region(
+        &self,
+        xfixes: &ffi::XFixes,
+        shape_kind: c_int,
+        rects: Option<&[Rect]>,
+        scale_factor: f64,
+    ) {
+        let mut xrects = rects.map(|rects| {
+            rects
+                .iter()
+                .map(|rect| {
+                    let position = rect.position.to_physical::<i16>(scale_factor);
+                    let size = rect.size.to_physical::<u16>(scale_factor);
+                    ffi::XRectangle {
+                        x: position.x,
+                        y: position.y,
+                        width: size.width,
+                        height: size.height,
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+
+        unsafe {
+            // The default shape, the whole window, is restored with no region.
+            let region = match xrects.as_mut() {
+                Some(xrects) => (xfixes.XFixesCreateRegion)(
+                    self.xconn.display,
+                    xrects.as_mut_ptr(),
+                    xrects.len() as c_int,
+                ),
+                None => 0,
+            };
+            (xfixes.XFixesSetWindowShapeRegion)(
+                self.xconn.display,
+                self.xwindow,
+                shape_kind,
+                0,
+                0,
+                region,
+            );
+            if region != 0 {
+                (xfixes.XFixesDestroyRegion)(self.xconn.display, region);
+            }
+        }
     }
 
     pub fn drag_window(&self) -> Result<(), ExternalError> {
@@ -1425,6 +1767,61 @@ impl UnownedWindow {
             .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
     }
 
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        let selection = self.clipboard.selection_atom(kind);
+        self.clipboard
+            .store(selection, contents)
+            .map_err(|err| ExternalError::Os(os_error!(err)))
+    }
+
+    #[inline]
+    pub fn clipboard(&self, kind: ClipboardKind, mime_type: &str) -> Option<Vec<u8>> {
+        let selection = self.clipboard.selection_atom(kind);
+        self.clipboard.load(selection, mime_type)
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Vec<String> {
+        let selection = self.clipboard.selection_atom(kind);
+        self.clipboard.mime_types(selection)
+    }
+
+    #[inline]
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        self.keyboard_layout.layout()
+    }
+
+    #[inline]
+    pub fn key_label(&self, physical_key: KeyCode) -> Option<Key> {
+        self.keyboard_layout.key_label(physical_key)
+    }
+
+    #[inline]
+    pub fn start_drag(&self, data: DragData) -> Result<(), ExternalError> {
+        self.drag_source
+            .start(self.xwindow, data, &self.clipboard)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XMisc(err))))
+    }
+
+    #[inline]
+    pub fn accept_drag(&self, mime_type: &str, action: DndAction) -> Result<(), ExternalError> {
+        self.drop_target
+            .accept(self.xwindow, mime_type, action, &self.clipboard)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
+    #[inline]
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        self.drop_target
+            .answer(self.xwindow, DropAnswer::Rejected)
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))
+    }
+
     #[inline]
     pub fn set_ime_position(&self, spot: Position) {
         let (x, y) = spot.to_physical::<i32>(self.scale_factor()).into();
@@ -1434,6 +1831,35 @@ impl UnownedWindow {
             .send(ImeRequest::Position(self.xwindow, x, y));
     }
 
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+        // The XIM spot is on the baseline of the text, so the bottom of the area keeps the
+        // candidate box off the cursor.
+        let scale_factor = self.scale_factor();
+        let position = position.to_physical::<i32>(scale_factor);
+        let height = size.to_physical::<i32>(scale_factor).height;
+        self.set_ime_position(PhysicalPosition::new(position.x, position.y + height).into());
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(
+        &self,
+        _text: &str,
+        _cursor: usize,
+        _anchor: usize,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(
+        &self,
+        _purpose: ImePurpose,
+        _hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_ime_allowed(&self, allowed: bool) {
         let _ = self
@@ -1494,6 +1920,96 @@ impl UnownedWindow {
             .expect("Failed to set urgency hint");
     }
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, inhibit: bool) -> Result<(), ExternalError> {
+        let mut idle_inhibited = self.idle_inhibited.lock();
+        if *idle_inhibited == inhibit {
+            return Ok(());
+        }
+
+        let xss = match self.xconn.xss.as_ref() {
+            Some(xss) => xss,
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
+
+        let (mut event_base, mut error_base) = (0, 0);
+        let has_extension = unsafe {
+            (xss.XScreenSaverQueryExtension)(self.xconn.display, &mut event_base, &mut error_base)
+        };
+        if has_extension == ffi::False {
+            return Err(ExternalError::NotSupported(NotSupportedError::new()));
+        }
+
+        // Suspensions are counted per client, so each window only suspends the screen saver once,
+        // which also keeps DPMS from powering the monitors down.
+        unsafe {
+            (xss.XScreenSaverSuspend)(
+                self.xconn.display,
+                if inhibit { ffi::True } else { ffi::False },
+            );
+        }
+        self.xconn
+            .flush_requests()
+            .map_err(|err| ExternalError::Os(os_error!(OsError::XError(err))))?;
+
+        *idle_inhibited = inhibit;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        self.system_theme.lock().theme()
+    }
+
+    #[inline]
+    pub fn set_shortcuts_inhibited(&self, inhibit: bool) -> Result<(), ExternalError> {
+        self.shortcuts_inhibit.lock().requested = inhibit;
+
+        // The keyboard is only grabbed while the window is focused, which the event loop tracks.
+        self.shortcuts_inhibit_sender
+            .sender
+            .send(self.id())
+            .unwrap();
+        self.shortcuts_inhibit_sender.waker.wake().unwrap();
+
+        Ok(())
+    }
+
+    /// Grabs the keyboard when the system shortcuts were asked for and the window is focused, and
+    /// releases it otherwise.
+    ///
+    /// Returns whether the shortcuts are inhibited, when that changed or a grab was refused.
+    pub(crate) fn update_shortcuts_inhibit(&self, focused: bool) -> Option<bool> {
+        let mut shortcuts_inhibit = self.shortcuts_inhibit.lock();
+        let grab = shortcuts_inhibit.requested && focused;
+        if grab == shortcuts_inhibit.grabbed {
+            return None;
+        }
+
+        shortcuts_inhibit.grabbed = if grab {
+            let result = unsafe {
+                (self.xconn.xlib.XGrabKeyboard)(
+                    self.xconn.display,
+                    self.xwindow,
+                    ffi::True,
+                    ffi::GrabModeAsync,
+                    ffi::GrabModeAsync,
+                    ffi::CurrentTime,
+                )
+            };
+            result == ffi::GrabSuccess
+        } else {
+            unsafe { (self.xconn.xlib.XUngrabKeyboard)(self.xconn.display, ffi::CurrentTime) };
+            false
+        };
+
+        if let Err(err) = self.xconn.flush_requests() {
+            warn!("Failed to update the keyboard grab: {:?}", err);
+        }
+
+        Some(shortcuts_inhibit.grabbed)
+    }
+
     #[inline]
     pub fn id(&self) -> WindowId {
         WindowId(self.xwindow as u64)
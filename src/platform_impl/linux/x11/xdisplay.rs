@@ -1,9 +1,9 @@
-use std::{collections::HashMap, error::Error, fmt, os::raw::c_int, ptr};
+use std::{collections::HashMap, error::Error, fmt, os::raw::c_int, ptr, sync::Weak};
 
 use libc;
 use parking_lot::Mutex;
 
-use crate::window::CursorIcon;
+use crate::{cursor::CursorImage, window::CursorIcon};
 
 use super::ffi;
 
@@ -18,10 +18,15 @@ pub struct XConnection {
     pub xinput2: ffi::XInput2,
     pub xlib_xcb: ffi::Xlib_xcb,
     pub xrender: ffi::Xrender,
+    pub xfixes: Option<ffi::XFixes>,
+    pub xss: Option<ffi::Xss>,
     pub display: *mut ffi::Display,
     pub x11_fd: c_int,
     pub latest_error: Mutex<Option<XError>>,
     pub cursor_cache: Mutex<HashMap<Option<CursorIcon>, ffi::Cursor>>,
+    pub custom_cursor_cache: Mutex<Vec<(Weak<Vec<CursorImage>>, ffi::Cursor)>>,
+    /// The `Xft/DPI` of the XSETTINGS manager, which unlike the resource database follows changes.
+    pub xsettings_dpi: Mutex<Option<f64>>,
 }
 
 unsafe impl Send for XConnection {}
@@ -40,6 +45,8 @@ impl XConnection {
         let xinput2 = ffi::XInput2::open()?;
         let xlib_xcb = ffi::Xlib_xcb::open()?;
         let xrender = ffi::Xrender::open()?;
+        let xfixes = ffi::XFixes::open().ok();
+        let xss = ffi::Xss::open().ok();
 
         unsafe { (xlib.XInitThreads)() };
         unsafe { (xlib.XSetErrorHandler)(error_handler) };
@@ -64,10 +71,14 @@ impl XConnection {
             xinput2,
             xlib_xcb,
             xrender,
+            xfixes,
+            xss,
             display,
             x11_fd: fd,
             latest_error: Mutex::new(None),
             cursor_cache: Default::default(),
+            custom_cursor_cache: Default::default(),
+            xsettings_dpi: Mutex::new(None),
         })
     }
 
@@ -0,0 +1,293 @@
+//! Keyboard translation with xkbcommon, so that X11 goes through the same keymap path as Wayland.
+
+use std::{
+    collections::HashMap,
+    mem::MaybeUninit,
+    os::raw::{c_int, c_ulong},
+    ptr,
+    sync::Arc,
+};
+
+use xkbcommon_dl::{
+    xkb_keymap_compile_flags, xkb_x11_setup_xkb_extension_flags, XkbCommonX11,
+    XKB_X11_MIN_MAJOR_XKB_VERSION, XKB_X11_MIN_MINOR_XKB_VERSION,
+};
+
+use super::{ffi, XConnection};
+use crate::{
+    keyboard::KeyboardLayout,
+    platform_impl::platform::common::xkb::{SharedLayout, XkbKeymap},
+};
+
+/// `XkbUseCoreKbd`, which isn't exposed by `x11-dl`.
+const XKB_USE_CORE_KBD: u32 = 0x0100;
+
+/// The XKB events keeping the keymaps and states up to date.
+const EVENT_MASK: c_ulong =
+    ffi::XkbNewKeyboardNotifyMask | ffi::XkbMapNotifyMask | ffi::XkbStateNotifyMask;
+
+/// The keymaps and states of the keyboards, kept up to date with XKB events.
+///
+/// Every slave keyboard has its own keymap and state, used for the keys it sends. The core
+/// keyboard follows the last used one and is used when the source of a key isn't known, which is
+/// the case when raw key events are filtered out with [`DeviceEventFilter`]. Only its layout is
+/// reported to the windows.
+///
+/// [`DeviceEventFilter`]: crate::event_loop::DeviceEventFilter
+pub struct XkbKeyboard {
+    loader: KeymapLoader,
+    keymap: XkbKeymap,
+    /// The XKB id of the core keyboard.
+    core_device: c_int,
+    /// The keymaps of the slave keyboards, by device id.
+    devices: HashMap<c_int, XkbKeymap>,
+    /// The slave keyboard of the last raw key event.
+    source: Option<c_int>,
+    event_base: u8,
+}
+
+impl XkbKeyboard {
+    /// Loads the keymap of the core keyboard and subscribes to the XKB events updating it.
+    ///
+    /// Returns `None` if `libxkbcommon-x11` isn't available or the server doesn't support XKB.
+    pub fn new(xconn: &Arc<XConnection>, shared_layout: SharedLayout) -> Option<Self> {
+        let xkb_x11 = XkbCommonX11::open("libxkbcommon-x11.so.0")
+            .or_else(|_| XkbCommonX11::open("libxkbcommon-x11.so"))
+            .ok()?;
+        let keymap = XkbKeymap::new(shared_layout)?;
+
+        let connection = unsafe { (xconn.xlib_xcb.XGetXCBConnection)(xconn.display) };
+        if connection.is_null() {
+            return None;
+        }
+
+        let mut event_base = 0;
+        let success = unsafe {
+            (xkb_x11.xkb_x11_setup_xkb_extension)(
+                connection,
+                XKB_X11_MIN_MAJOR_XKB_VERSION,
+                XKB_X11_MIN_MINOR_XKB_VERSION,
+                xkb_x11_setup_xkb_extension_flags::XKB_X11_SETUP_XKB_EXTENSION_NO_FLAGS,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut event_base,
+                ptr::null_mut(),
+            )
+        };
+        if success == 0 {
+            return None;
+        }
+
+        let mut keyboard = XkbKeyboard {
+            loader: KeymapLoader {
+                xconn: Arc::clone(xconn),
+                xkb_x11,
+                connection,
+            },
+            keymap,
+            core_device: -1,
+            devices: HashMap::new(),
+            source: None,
+            event_base,
+        };
+        if !keyboard.reload_keymap() {
+            return None;
+        }
+
+        unsafe {
+            (xconn.xlib.XkbSelectEvents)(xconn.display, XKB_USE_CORE_KBD, EVENT_MASK, EVENT_MASK);
+        }
+        xconn.check_errors().ok()?;
+
+        Some(keyboard)
+    }
+
+    /// The type of the events of the XKB extension.
+    pub fn event_type(&self) -> i32 {
+        self.event_base as i32
+    }
+
+    /// Handles an event of the XKB extension, whose type is [`XkbKeyboard::event_type`].
+    ///
+    /// Returns whether the active layout of the core keyboard or the names of its layouts
+    /// changed.
+    pub fn process_event(&mut self, xev: &ffi::XEvent) -> bool {
+        // `XEvent` is as large as every XKB event.
+        let xev = xev as *const ffi::XEvent;
+        let any = unsafe { &*(xev as *const ffi::XkbAnyEvent) };
+        let device = any.device as c_int;
+        if device != self.core_device {
+            if self.devices.contains_key(&device) {
+                self.process_device_event(device, xev);
+            }
+            return false;
+        }
+
+        match any.xkb_type {
+            ffi::XkbStateNotify => {
+                let xev = unsafe { &*(xev as *const ffi::XkbStateNotifyEvent) };
+                update_mask(&mut self.keymap, xev)
+            }
+            // The core keyboard was switched to another device, or its keymap changed, which
+            // only matters when the layouts differ.
+            ffi::XkbNewKeyboardNotify | ffi::XkbMapNotify => {
+                let layouts = self.keymap.layouts();
+                self.reload_keymap() && self.keymap.layouts() != layouts
+            }
+            _ => false,
+        }
+    }
+
+    /// Handles an event of the XKB extension about the slave keyboard `device`.
+    fn process_device_event(&mut self, device: c_int, xev: *const ffi::XEvent) {
+        let any = unsafe { &*(xev as *const ffi::XkbAnyEvent) };
+        match any.xkb_type {
+            ffi::XkbStateNotify => {
+                let xev = unsafe { &*(xev as *const ffi::XkbStateNotifyEvent) };
+                if let Some(keymap) = self.devices.get_mut(&device) {
+                    update_mask(keymap, xev);
+                }
+            }
+            ffi::XkbNewKeyboardNotify | ffi::XkbMapNotify => {
+                let mut keymap = match self.devices.remove(&device) {
+                    Some(keymap) => keymap,
+                    None => return,
+                };
+                if self.loader.load(&mut keymap, device) {
+                    self.devices.insert(device, keymap);
+                } else if self.source == Some(device) {
+                    self.source = None;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Records that the slave keyboard `device` sent the next key events, loading its keymap
+    /// the first time it's seen.
+    ///
+    /// The raw key events carrying the device are received before the core key events.
+    pub fn set_source(&mut self, device: c_int) {
+        if !self.devices.contains_key(&device) {
+            let mut keymap = match XkbKeymap::new(SharedLayout::default()) {
+                Some(keymap) => keymap,
+                None => return,
+            };
+            if !self.loader.load(&mut keymap, device) {
+                self.source = None;
+                return;
+            }
+            let xconn = &self.loader.xconn;
+            unsafe {
+                (xconn.xlib.XkbSelectEvents)(xconn.display, device as _, EVENT_MASK, EVENT_MASK);
+            }
+            self.devices.insert(device, keymap);
+        }
+        self.source = Some(device);
+    }
+
+    /// Forgets the keymap of the removed keyboard `device`.
+    pub fn remove_device(&mut self, device: c_int) {
+        self.devices.remove(&device);
+        if self.source == Some(device) {
+            self.source = None;
+        }
+    }
+
+    /// The keysym produced by the key with the X `keycode`, with the current modifiers and group.
+    pub fn keysym(&self, keycode: u32) -> u32 {
+        self.source_keymap().keysym(keycode)
+    }
+
+    /// The text produced by the key with the X `keycode`, with the current modifiers and group.
+    pub fn text(&self, keycode: u32) -> Option<String> {
+        self.source_keymap().text(keycode)
+    }
+
+    /// The keymap of the keyboard which sent the last key, or of the core keyboard if unknown.
+    fn source_keymap(&self) -> &XkbKeymap {
+        self.source
+            .and_then(|device| self.devices.get(&device))
+            .unwrap_or(&self.keymap)
+    }
+
+    /// The active layout.
+    pub fn layout(&self) -> Option<KeyboardLayout> {
+        self.keymap.layout()
+    }
+
+    /// Fetches the keymap and state of the device currently backing the core keyboard, which
+    /// replace the old ones.
+    fn reload_keymap(&mut self) -> bool {
+        let loader = &self.loader;
+        let device =
+            unsafe { (loader.xkb_x11.xkb_x11_get_core_keyboard_device_id)(loader.connection) };
+        if device == -1 {
+            return false;
+        }
+        self.core_device = device;
+        loader.load(&mut self.keymap, device)
+    }
+}
+
+/// Fetches keymaps from the X server.
+struct KeymapLoader {
+    xconn: Arc<XConnection>,
+    xkb_x11: XkbCommonX11,
+    connection: *mut ffi::xcb_connection_t,
+}
+
+impl KeymapLoader {
+    /// Fetches the keymap and state of the keyboard `device` into `keymap`.
+    fn load(&self, keymap: &mut XkbKeymap, device: c_int) -> bool {
+        let loaded = unsafe {
+            let new_keymap = (self.xkb_x11.xkb_x11_keymap_new_from_device)(
+                keymap.context(),
+                self.connection,
+                device,
+                xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            );
+            let state = if new_keymap.is_null() {
+                ptr::null_mut()
+            } else {
+                (self.xkb_x11.xkb_x11_state_new_from_device)(new_keymap, self.connection, device)
+            };
+            keymap.set_keymap(new_keymap, state)
+        };
+        if !loaded {
+            return false;
+        }
+
+        // The state already has the right group, but the layout is only tracked through updates.
+        let mut state = MaybeUninit::<ffi::XkbStateRec>::uninit();
+        let status = unsafe {
+            (self.xconn.xlib.XkbGetState)(self.xconn.display, device as _, state.as_mut_ptr())
+        };
+        if status == ffi::Success as i32 {
+            let state = unsafe { state.assume_init() };
+            keymap.update_mask(
+                state.base_mods as u32,
+                state.latched_mods as u32,
+                state.locked_mods as u32,
+                state.base_group as u32,
+                state.latched_group as u32,
+                state.locked_group as u32,
+            );
+        }
+        true
+    }
+}
+
+/// Applies the state reported by an `XkbStateNotify` event to `keymap`.
+///
+/// Returns whether the active layout changed.
+fn update_mask(keymap: &mut XkbKeymap, xev: &ffi::XkbStateNotifyEvent) -> bool {
+    keymap.update_mask(
+        xev.base_mods,
+        xev.latched_mods,
+        xev.locked_mods,
+        xev.base_group as u32,
+        xev.latched_group as u32,
+        xev.locked_group as u32,
+    )
+}
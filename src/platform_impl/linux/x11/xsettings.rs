@@ -0,0 +1,407 @@
+//! Client of the XSETTINGS manager, through which desktops share their settings with X clients.
+//!
+//! See <https://specifications.freedesktop.org/xsettings-spec/0.5/>.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ffi::CString,
+    os::raw::c_int,
+    sync::Arc,
+    time::Duration,
+};
+
+use super::{ffi, XConnection};
+use crate::{platform::unix::XSettings, window::Theme};
+
+/// A value of the settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Setting {
+    Integer(i32),
+    String(String),
+    Color {
+        red: u16,
+        green: u16,
+        blue: u16,
+        alpha: u16,
+    },
+}
+
+/// Follows the settings of the manager of the screen, which may be replaced at any time.
+pub struct XSettingsClient {
+    xconn: Arc<XConnection>,
+    /// The `_XSETTINGS_S<screen>` selection owned by the manager.
+    selection: ffi::Atom,
+    /// The property holding the settings on the window of the manager.
+    settings: ffi::Atom,
+    /// The type of the messages announcing a new manager.
+    manager_message: ffi::Atom,
+    /// The window of the manager, or `0` when there's none.
+    manager: Cell<ffi::Window>,
+    values: RefCell<XSettings>,
+}
+
+impl XSettingsClient {
+    pub fn new(xconn: Arc<XConnection>, screen: c_int) -> Self {
+        let selection = xconn.get_atom(CString::new(format!("_XSETTINGS_S{}", screen)).unwrap());
+        let settings = unsafe { xconn.get_atom_unchecked(b"_XSETTINGS_SETTINGS\0") };
+        let manager_message = unsafe { xconn.get_atom_unchecked(b"MANAGER\0") };
+
+        // New managers announce themselves to the root window.
+        unsafe {
+            let root = (xconn.xlib.XRootWindow)(xconn.display, screen);
+            (xconn.xlib.XSelectInput)(xconn.display, root, ffi::StructureNotifyMask);
+        }
+
+        let client = XSettingsClient {
+            xconn,
+            selection,
+            settings,
+            manager_message,
+            manager: Cell::new(0),
+            values: Default::default(),
+        };
+        client.find_manager();
+        client.refresh();
+        client
+    }
+
+    /// Looks for the manager, and listens to the changes of its settings and to its exit.
+    pub fn find_manager(&self) {
+        let manager = unsafe {
+            let manager = (self.xconn.xlib.XGetSelectionOwner)(self.xconn.display, self.selection);
+            if manager != 0 {
+                (self.xconn.xlib.XSelectInput)(
+                    self.xconn.display,
+                    manager,
+                    ffi::PropertyChangeMask | ffi::StructureNotifyMask,
+                );
+            }
+            manager
+        };
+        // The manager may have exited in the meantime.
+        if self.xconn.check_errors().is_err() {
+            self.manager.set(0);
+        } else {
+            self.manager.set(manager);
+        }
+    }
+
+    /// Whether the message announces a new manager of our screen.
+    pub fn is_manager_announcement(&self, xev: &ffi::XClientMessageEvent) -> bool {
+        xev.message_type == self.manager_message
+            && xev.format == 32
+            && xev.data.get_long(1) as ffi::Atom == self.selection
+    }
+
+    /// Whether the destroyed window is the one of the manager.
+    pub fn is_manager_destroyed(&self, xev: &ffi::XDestroyWindowEvent) -> bool {
+        let manager = self.manager.get();
+        manager != 0 && xev.window == manager
+    }
+
+    /// Whether the event is about a change of the settings.
+    pub fn is_settings_change(&self, xev: &ffi::XPropertyEvent) -> bool {
+        let manager = self.manager.get();
+        manager != 0 && xev.window == manager && xev.atom == self.settings
+    }
+
+    /// The settings we know of, as of the last refresh.
+    pub fn values(&self) -> XSettings {
+        self.values.borrow().clone()
+    }
+
+    /// Reads the settings again, and shares their DPI with the monitors of the connection.
+    pub fn refresh(&self) {
+        let values = values(&self.read());
+        *self.xconn.xsettings_dpi.lock() = values.dpi;
+        *self.values.borrow_mut() = values;
+    }
+
+    /// Reads the settings, which are empty when there's no manager.
+    fn read(&self) -> HashMap<String, Setting> {
+        let manager = self.manager.get();
+        if manager == 0 {
+            return HashMap::new();
+        }
+
+        match self
+            .xconn
+            .get_property::<u8>(manager, self.settings, self.settings)
+        {
+            Ok(data) => parse(&data).unwrap_or_else(|| {
+                warn!("Failed to parse the XSETTINGS settings");
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        }
+    }
+}
+
+/// Whether the GTK theme of the settings is dark, since XSETTINGS has no color scheme.
+pub fn theme(values: &XSettings) -> Option<Theme> {
+    match values.theme_name {
+        Some(ref name) if name.to_lowercase().contains("dark") => Some(Theme::Dark),
+        Some(_) => Some(Theme::Light),
+        None => None,
+    }
+}
+
+/// Picks the settings we know of, ignoring those with the wrong type or an invalid value.
+fn values(settings: &HashMap<String, Setting>) -> XSettings {
+    let integer = |name: &str| match settings.get(name) {
+        Some(&Setting::Integer(value)) => Some(value),
+        _ => None,
+    };
+    let string = |name: &str| match settings.get(name) {
+        Some(Setting::String(value)) if !value.is_empty() => Some(value.clone()),
+        _ => None,
+    };
+    let millis = |name: &str| {
+        integer(name)
+            .filter(|&millis| millis > 0)
+            .map(|millis| Duration::from_millis(millis as u64))
+    };
+
+    XSettings {
+        // In 1024ths of a dot per inch, with -1 for the default.
+        dpi: integer("Xft/DPI")
+            .filter(|&dpi| dpi > 0)
+            .map(|dpi| dpi as f64 / 1024.),
+        cursor_theme_name: string("Gtk/CursorThemeName"),
+        cursor_theme_size: integer("Gtk/CursorThemeSize")
+            .filter(|&size| size > 0)
+            .map(|size| size as u32),
+        double_click_time: millis("Net/DoubleClickTime"),
+        cursor_blink_time: millis("Net/CursorBlinkTime"),
+        theme_name: string("Net/ThemeName"),
+    }
+}
+
+fn parse(data: &[u8]) -> Option<HashMap<String, Setting>> {
+    let big_endian = *data.first()? == 1;
+    let mut pos: usize = 4;
+    let mut take = |len: usize| {
+        let bytes = data.get(pos..pos.checked_add(len)?)?;
+        pos += len;
+        Some(bytes)
+    };
+    let card16 = |bytes: &[u8]| {
+        let bytes = bytes.try_into().unwrap();
+        if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    };
+    let card32 = |bytes: &[u8]| {
+        let bytes = bytes.try_into().unwrap();
+        if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+    // The lengths come from the data, so they may overflow on 32-bit targets.
+    let padded = |len: usize| len.checked_add(3).map(|len| len & !3);
+
+    // The serial of the settings.
+    take(4)?;
+    let count = card32(take(4)?);
+
+    let mut settings = HashMap::new();
+    for _ in 0..count {
+        let setting_type = take(2)?[0];
+        let name_len = card16(take(2)?) as usize;
+        let name = String::from_utf8_lossy(&take(padded(name_len)?)?[..name_len]).into_owned();
+        // The serial of the last change of the setting.
+        take(4)?;
+
+        let value = match setting_type {
+            0 => Setting::Integer(card32(take(4)?) as i32),
+            1 => {
+                let len = card32(take(4)?) as usize;
+                let value = &take(padded(len)?)?[..len];
+                Setting::String(String::from_utf8_lossy(value).into_owned())
+            }
+            // The components are in this unusual order.
+            2 => Setting::Color {
+                red: card16(take(2)?),
+                blue: card16(take(2)?),
+                green: card16(take(2)?),
+                alpha: card16(take(2)?),
+            },
+            _ => return None,
+        };
+        settings.insert(name, value);
+    }
+
+    Some(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An integer, a string and a color, in little endian.
+    #[rustfmt::skip]
+    const LITTLE_ENDIAN: &[u8] = &[
+        // The byte order, and the serial and the number of the settings.
+        0, 0, 0, 0,
+        7, 0, 0, 0,
+        3, 0, 0, 0,
+        // An integer named `Xft/DPI`, last changed at serial 1.
+        0, 0, 7, 0,
+        b'X', b'f', b't', b'/', b'D', b'P', b'I', 0,
+        1, 0, 0, 0,
+        0x00, 0x80, 0x01, 0x00,
+        // A string named `Net/ThemeName`.
+        1, 0, 13, 0,
+        b'N', b'e', b't', b'/', b'T', b'h', b'e', b'm', b'e', b'N', b'a', b'm', b'e', 0, 0, 0,
+        2, 0, 0, 0,
+        12, 0, 0, 0,
+        b'A', b'd', b'w', b'a', b'i', b't', b'a', b'-', b'd', b'a', b'r', b'k',
+        // A color named `Gtk/Color`, as red, blue, green and alpha.
+        2, 0, 9, 0,
+        b'G', b't', b'k', b'/', b'C', b'o', b'l', b'o', b'r', 0, 0, 0,
+        3, 0, 0, 0,
+        0x11, 0x11, 0x22, 0x22, 0x33, 0x33, 0xff, 0xff,
+    ];
+
+    /// The same settings in big endian.
+    #[rustfmt::skip]
+    const BIG_ENDIAN: &[u8] = &[
+        1, 0, 0, 0,
+        0, 0, 0, 7,
+        0, 0, 0, 3,
+        0, 0, 0, 7,
+        b'X', b'f', b't', b'/', b'D', b'P', b'I', 0,
+        0, 0, 0, 1,
+        0x00, 0x01, 0x80, 0x00,
+        1, 0, 0, 13,
+        b'N', b'e', b't', b'/', b'T', b'h', b'e', b'm', b'e', b'N', b'a', b'm', b'e', 0, 0, 0,
+        0, 0, 0, 2,
+        0, 0, 0, 12,
+        b'A', b'd', b'w', b'a', b'i', b't', b'a', b'-', b'd', b'a', b'r', b'k',
+        2, 0, 0, 9,
+        b'G', b't', b'k', b'/', b'C', b'o', b'l', b'o', b'r', 0, 0, 0,
+        0, 0, 0, 3,
+        0x11, 0x11, 0x22, 0x22, 0x33, 0x33, 0xff, 0xff,
+    ];
+
+    fn expected() -> HashMap<String, Setting> {
+        let mut settings = HashMap::new();
+        settings.insert("Xft/DPI".to_owned(), Setting::Integer(96 * 1024));
+        settings.insert(
+            "Net/ThemeName".to_owned(),
+            Setting::String("Adwaita-dark".to_owned()),
+        );
+        settings.insert(
+            "Gtk/Color".to_owned(),
+            Setting::Color {
+                red: 0x1111,
+                green: 0x3333,
+                blue: 0x2222,
+                alpha: 0xffff,
+            },
+        );
+        settings
+    }
+
+    #[test]
+    fn parse_little_endian() {
+        assert_eq!(parse(LITTLE_ENDIAN), Some(expected()));
+    }
+
+    #[test]
+    fn parse_big_endian() {
+        assert_eq!(parse(BIG_ENDIAN), Some(expected()));
+    }
+
+    #[test]
+    fn parse_no_settings() {
+        assert_eq!(parse(&LITTLE_ENDIAN[..8]), None);
+        assert_eq!(
+            parse(&[0, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0]),
+            Some(HashMap::new())
+        );
+        assert_eq!(parse(&[]), None);
+    }
+
+    #[test]
+    fn parse_truncated() {
+        for len in 0..LITTLE_ENDIAN.len() {
+            assert_eq!(parse(&LITTLE_ENDIAN[..len]), None, "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn parse_invalid() {
+        // An unknown type.
+        let mut data = LITTLE_ENDIAN.to_vec();
+        data[12] = 3;
+        assert_eq!(parse(&data), None);
+
+        // More settings than there are.
+        let mut data = LITTLE_ENDIAN.to_vec();
+        data[8] = 4;
+        assert_eq!(parse(&data), None);
+
+        // A string as long as a length can be, which doesn't fit.
+        let mut data = LITTLE_ENDIAN.to_vec();
+        data[56..60].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(parse(&data), None);
+    }
+
+    #[test]
+    fn known_values() {
+        let mut settings = expected();
+        settings.insert(
+            "Gtk/CursorThemeName".to_owned(),
+            Setting::String("Adwaita".to_owned()),
+        );
+        settings.insert("Gtk/CursorThemeSize".to_owned(), Setting::Integer(24));
+        settings.insert("Net/DoubleClickTime".to_owned(), Setting::Integer(400));
+        settings.insert("Net/CursorBlinkTime".to_owned(), Setting::Integer(1200));
+        let values = values(&settings);
+        assert_eq!(
+            values,
+            XSettings {
+                dpi: Some(96.),
+                cursor_theme_name: Some("Adwaita".to_owned()),
+                cursor_theme_size: Some(24),
+                double_click_time: Some(Duration::from_millis(400)),
+                cursor_blink_time: Some(Duration::from_millis(1200)),
+                theme_name: Some("Adwaita-dark".to_owned()),
+            }
+        );
+        assert_eq!(theme(&values), Some(Theme::Dark));
+    }
+
+    #[test]
+    fn invalid_values() {
+        let mut settings = HashMap::new();
+        // The default DPI, an empty name and values of the wrong type.
+        settings.insert("Xft/DPI".to_owned(), Setting::Integer(-1));
+        settings.insert("Net/ThemeName".to_owned(), Setting::String(String::new()));
+        settings.insert("Gtk/CursorThemeName".to_owned(), Setting::Integer(1));
+        settings.insert(
+            "Gtk/CursorThemeSize".to_owned(),
+            Setting::String("24".to_owned()),
+        );
+        settings.insert("Net/DoubleClickTime".to_owned(), Setting::Integer(0));
+        let values = values(&settings);
+        assert_eq!(values, XSettings::default());
+        assert_eq!(theme(&values), None);
+    }
+
+    #[test]
+    fn theme_from_name() {
+        let values = |name: &str| XSettings {
+            theme_name: Some(name.to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(theme(&values("Breeze-Dark")), Some(Theme::Dark));
+        assert_eq!(theme(&values("Adwaita")), Some(Theme::Light));
+    }
+}
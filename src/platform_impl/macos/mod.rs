@@ -41,6 +41,16 @@ impl DeviceId {
     pub const unsafe fn dummy() -> Self {
         DeviceId
     }
+
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        0
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_raw(_raw: u64) -> Self {
+        DeviceId
+    }
 }
 
 // Constant device ID; to be removed when if backend is updated to report real device IDs.
@@ -7,6 +7,7 @@ use std::{
         atomic::{compiler_fence, Ordering},
         Arc, Mutex, Weak,
     },
+    time::Instant,
 };
 
 use cocoa::{
@@ -26,11 +27,12 @@ use crate::{
         DeviceEvent, ElementState, Event, Ime, KeyboardInput, ModifiersState, MouseButton,
         MouseScrollDelta, TouchPhase, VirtualKeyCode, WindowEvent,
     },
+    keyboard::KeyEvent,
     platform_impl::platform::{
         app_state::AppState,
         event::{
             char_to_keycode, check_function_keys, event_mods, get_scancode, modifier_event,
-            scancode_to_keycode, EventWrapper,
+            scancode_to_keycode, scancode_to_physical_key, EventWrapper,
         },
         ffi::*,
         util::{self, id_to_string_lossy, IdRef},
@@ -552,7 +554,7 @@ extern "C" fn set_marked_text(
         // Send WindowEvent for updating marked text
         AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
             window_id: WindowId(get_window_id(state.ns_window)),
-            event: WindowEvent::Ime(Ime::Preedit(preedit_string, cursor_range)),
+            event: WindowEvent::Ime(Ime::Preedit(preedit_string, cursor_range, Vec::new())),
         }));
     }
 }
@@ -572,7 +574,7 @@ extern "C" fn unmark_text(this: &Object, _sel: Sel) {
         let state = &mut *(state_ptr as *mut ViewState);
         AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
             window_id: WindowId(get_window_id(state.ns_window)),
-            event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+            event: WindowEvent::Ime(Ime::Preedit(String::new(), None, Vec::new())),
         }));
         if state.is_ime_enabled() {
             // Leave the Preedit state
@@ -761,7 +763,10 @@ extern "C" fn key_down(this: &Object, _sel: Sel, event: id) {
         }
         let was_in_preedit = state.ime_state == ImeState::Preedit;
 
-        let characters = get_characters(event, false);
+        let characters: String = get_characters(event, false)
+            .chars()
+            .filter(|c| !is_corporate_character(*c))
+            .collect();
         state.forward_key_to_app = false;
 
         // The `interpretKeyEvents` function might call
@@ -791,7 +796,7 @@ extern "C" fn key_down(this: &Object, _sel: Sel, event: id) {
 
         let now_in_preedit = state.ime_state == ImeState::Preedit;
 
-        let scancode = get_scancode(event) as u32;
+        let scancode = get_scancode(event);
         let virtual_keycode = retrieve_keycode(event);
 
         update_potentially_stale_modifiers(state, event);
@@ -800,23 +805,31 @@ extern "C" fn key_down(this: &Object, _sel: Sel, event: id) {
 
         if !ime_related || state.forward_key_to_app || !state.ime_allowed {
             #[allow(deprecated)]
+            let input = KeyboardInput {
+                state: ElementState::Pressed,
+                scancode: scancode as _,
+                virtual_keycode,
+                modifiers: event_mods(event),
+            };
             let window_event = Event::WindowEvent {
                 window_id,
                 event: WindowEvent::KeyboardInput {
                     device_id: DEVICE_ID,
-                    input: KeyboardInput {
-                        state: ElementState::Pressed,
-                        scancode,
-                        virtual_keycode,
-                        modifiers: event_mods(event),
-                    },
+                    input,
+                    event: KeyEvent::from_native_key(
+                        &input,
+                        scancode_to_physical_key(scancode),
+                        Some(characters.clone()),
+                        event.isARepeat() == YES,
+                    ),
                     is_synthetic: false,
+                    timestamp: Instant::now(),
                 },
             };
 
             AppState::queue_event(EventWrapper::StaticEvent(window_event));
 
-            for character in characters.chars().filter(|c| !is_corporate_character(*c)) {
+            for character in characters.chars() {
                 AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
                     window_id,
                     event: WindowEvent::ReceivedCharacter(character),
@@ -832,7 +845,7 @@ extern "C" fn key_up(this: &Object, _sel: Sel, event: id) {
         let state_ptr: *mut c_void = *this.get_ivar("winitState");
         let state = &mut *(state_ptr as *mut ViewState);
 
-        let scancode = get_scancode(event) as u32;
+        let scancode = get_scancode(event);
         let virtual_keycode = retrieve_keycode(event);
 
         update_potentially_stale_modifiers(state, event);
@@ -840,17 +853,25 @@ extern "C" fn key_up(this: &Object, _sel: Sel, event: id) {
         // We want to send keyboard input when we are not currently in preedit
         if state.ime_state != ImeState::Preedit {
             #[allow(deprecated)]
+            let input = KeyboardInput {
+                state: ElementState::Released,
+                scancode: scancode as _,
+                virtual_keycode,
+                modifiers: event_mods(event),
+            };
             let window_event = Event::WindowEvent {
                 window_id: WindowId(get_window_id(state.ns_window)),
                 event: WindowEvent::KeyboardInput {
                     device_id: DEVICE_ID,
-                    input: KeyboardInput {
-                        state: ElementState::Released,
-                        scancode,
-                        virtual_keycode,
-                        modifiers: event_mods(event),
-                    },
+                    input,
+                    event: KeyEvent::from_native_key(
+                        &input,
+                        scancode_to_physical_key(scancode),
+                        None,
+                        false,
+                    ),
                     is_synthetic: false,
+                    timestamp: Instant::now(),
                 },
             };
 
@@ -960,17 +981,25 @@ extern "C" fn cancel_operation(this: &Object, _sel: Sel, _sender: id) {
         update_potentially_stale_modifiers(state, event);
 
         #[allow(deprecated)]
+        let input = KeyboardInput {
+            state: ElementState::Pressed,
+            scancode: scancode as _,
+            virtual_keycode,
+            modifiers: event_mods(event),
+        };
         let window_event = Event::WindowEvent {
             window_id: WindowId(get_window_id(state.ns_window)),
             event: WindowEvent::KeyboardInput {
                 device_id: DEVICE_ID,
-                input: KeyboardInput {
-                    state: ElementState::Pressed,
-                    scancode: scancode as _,
-                    virtual_keycode,
-                    modifiers: event_mods(event),
-                },
+                input,
+                event: KeyEvent::from_native_key(
+                    &input,
+                    scancode_to_physical_key(scancode),
+                    None,
+                    false,
+                ),
                 is_synthetic: false,
+                timestamp: Instant::now(),
             },
         };
 
@@ -992,6 +1021,7 @@ fn mouse_click(this: &Object, event: id, button: MouseButton, button_state: Elem
                 state: button_state,
                 button,
                 modifiers: event_mods(event),
+                timestamp: Instant::now(),
             },
         };
 
@@ -1071,6 +1101,7 @@ fn mouse_motion(this: &Object, event: id) {
                 device_id: DEVICE_ID,
                 position: logical_position.to_physical(state.get_scale_factor()),
                 modifiers: event_mods(event),
+                timestamp: Instant::now(),
             },
         };
 
@@ -1188,6 +1219,7 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
                 delta,
                 phase,
                 modifiers: event_mods(event),
+                timestamp: Instant::now(),
             },
         };
 
@@ -14,11 +14,14 @@ use raw_window_handle::{
 };
 
 use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dnd::{DndAction, DragData},
     dpi::{
         LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size, Size::Logical,
     },
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     icon::Icon,
+    keyboard::{Key, KeyCode, KeyboardLayout},
     monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
     platform::macos::WindowExtMacOS,
     platform_impl::platform::{
@@ -32,8 +35,8 @@ use crate::{
         OsError,
     },
     window::{
-        CursorGrabMode, CursorIcon, Fullscreen, UserAttentionType, WindowAttributes,
-        WindowId as RootWindowId,
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ImeHints, ImePurpose, Rect, Region,
+        Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
     },
 };
 use cocoa::{
@@ -677,6 +680,9 @@ impl UnownedWindow {
         unsafe { NSWindow::backingScaleFactor(*self.ns_window) as _ }
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) {}
+
     #[inline]
     pub fn set_cursor_position(&self, cursor_position: Position) -> Result<(), ExternalError> {
         let physical_window_position = self.inner_position().unwrap();
@@ -714,6 +720,70 @@ impl UnownedWindow {
         Ok(())
     }
 
+    #[inline]
+    pub fn set_input_region(&self, _region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_shape(&self, _shape: Option<Region>) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn clipboard(&self, _kind: ClipboardKind, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, _kind: ClipboardKind) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[inline]
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        None
+    }
+
+    #[inline]
+    pub fn key_label(&self, _physical_key: KeyCode) -> Option<Key> {
+        None
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _data: DragData) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn accept_drag(&self, _mime_type: &str, _action: DndAction) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     pub(crate) fn is_zoomed(&self) -> bool {
         // because `isZoomed` doesn't work if the window's borderless,
         // we make it resizable temporalily.
@@ -1079,6 +1149,30 @@ impl UnownedWindow {
         unsafe { view::set_ime_position(*self.ns_view, logical_spot) };
     }
 
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, _size: Size) {
+        self.set_ime_position(position);
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(
+        &self,
+        _text: &str,
+        _cursor: usize,
+        _anchor: usize,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(
+        &self,
+        _purpose: ImePurpose,
+        _hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_ime_allowed(&self, allowed: bool) {
         unsafe {
@@ -1114,6 +1208,21 @@ impl UnownedWindow {
         }
     }
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_shortcuts_inhibited(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        None
+    }
+
     #[inline]
     // Allow directly accessing the current monitor internally without unwrapping.
     pub(crate) fn current_monitor_inner(&self) -> RootMonitorHandle {
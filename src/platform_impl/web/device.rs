@@ -5,4 +5,14 @@ impl DeviceId {
     pub const unsafe fn dummy() -> Self {
         Self(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        self.0 as u64
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_raw(raw: u64) -> Self {
+        Self(raw as i32)
+    }
 }
@@ -3,6 +3,7 @@ use std::clone::Clone;
 use std::collections::{vec_deque::IntoIter as VecDequeIter, VecDeque};
 use std::rc::Rc;
 
+use instant::Instant;
 use raw_window_handle::{RawDisplayHandle, WebDisplayHandle};
 
 use super::{
@@ -15,6 +16,7 @@ use crate::event::{
     WindowEvent,
 };
 use crate::event_loop::ControlFlow;
+use crate::keyboard::KeyEvent;
 use crate::monitor::MonitorHandle as RootMH;
 use crate::window::{Theme, WindowId as RootWindowId};
 
@@ -81,19 +83,22 @@ impl<T> EventLoopWindowTarget<T> {
 
         let runner = self.runner.clone();
         canvas.on_keyboard_press(
-            move |scancode, virtual_keycode, modifiers| {
+            move |scancode, virtual_keycode, physical_key, text, modifiers| {
                 #[allow(deprecated)]
+                let input = KeyboardInput {
+                    scancode,
+                    state: ElementState::Pressed,
+                    virtual_keycode,
+                    modifiers,
+                };
                 runner.send_event(Event::WindowEvent {
                     window_id: RootWindowId(id),
                     event: WindowEvent::KeyboardInput {
                         device_id: RootDeviceId(unsafe { DeviceId::dummy() }),
-                        input: KeyboardInput {
-                            scancode,
-                            state: ElementState::Pressed,
-                            virtual_keycode,
-                            modifiers,
-                        },
+                        input,
+                        event: KeyEvent::from_native_key(&input, physical_key, text, false),
                         is_synthetic: false,
+                        timestamp: Instant::now(),
                     },
                 });
             },
@@ -102,19 +107,22 @@ impl<T> EventLoopWindowTarget<T> {
 
         let runner = self.runner.clone();
         canvas.on_keyboard_release(
-            move |scancode, virtual_keycode, modifiers| {
+            move |scancode, virtual_keycode, physical_key, modifiers| {
                 #[allow(deprecated)]
+                let input = KeyboardInput {
+                    scancode,
+                    state: ElementState::Released,
+                    virtual_keycode,
+                    modifiers,
+                };
                 runner.send_event(Event::WindowEvent {
                     window_id: RootWindowId(id),
                     event: WindowEvent::KeyboardInput {
                         device_id: RootDeviceId(unsafe { DeviceId::dummy() }),
-                        input: KeyboardInput {
-                            scancode,
-                            state: ElementState::Released,
-                            virtual_keycode,
-                            modifiers,
-                        },
+                        input,
+                        event: KeyEvent::from_native_key(&input, physical_key, None, false),
                         is_synthetic: false,
+                        timestamp: Instant::now(),
                     },
                 });
             },
@@ -160,6 +168,7 @@ impl<T> EventLoopWindowTarget<T> {
                     device_id: RootDeviceId(DeviceId(pointer_id)),
                     position,
                     modifiers,
+                    timestamp: Instant::now(),
                 },
             });
             runner.send_event(Event::DeviceEvent {
@@ -186,6 +195,7 @@ impl<T> EventLoopWindowTarget<T> {
                         device_id: RootDeviceId(DeviceId(pointer_id)),
                         position,
                         modifiers,
+                        timestamp: Instant::now(),
                     },
                 }))
                 .chain(std::iter::once(Event::WindowEvent {
@@ -195,6 +205,7 @@ impl<T> EventLoopWindowTarget<T> {
                         state: ElementState::Pressed,
                         button,
                         modifiers,
+                        timestamp: Instant::now(),
                     },
                 })),
             );
@@ -209,6 +220,7 @@ impl<T> EventLoopWindowTarget<T> {
                     state: ElementState::Released,
                     button,
                     modifiers,
+                    timestamp: Instant::now(),
                 },
             });
         });
@@ -223,6 +235,7 @@ impl<T> EventLoopWindowTarget<T> {
                         delta,
                         phase: TouchPhase::Moved,
                         modifiers,
+                        timestamp: Instant::now(),
                     },
                 });
             },
@@ -4,6 +4,7 @@ use super::media_query_handle::MediaQueryListHandle;
 use crate::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
 use crate::error::OsError as RootOE;
 use crate::event::{ModifiersState, MouseButton, MouseScrollDelta, ScanCode, VirtualKeyCode};
+use crate::keyboard::KeyCode;
 use crate::platform_impl::{OsError, PlatformSpecificWindowBuilderAttributes};
 
 use std::cell::RefCell;
@@ -152,7 +153,7 @@ impl Canvas {
 
     pub fn on_keyboard_release<F>(&mut self, mut handler: F, prevent_default: bool)
     where
-        F: 'static + FnMut(ScanCode, Option<VirtualKeyCode>, ModifiersState),
+        F: 'static + FnMut(ScanCode, Option<VirtualKeyCode>, KeyCode, ModifiersState),
     {
         self.on_keyboard_release = Some(self.common.add_user_event(
             "keyup",
@@ -164,6 +165,7 @@ impl Canvas {
                 handler(
                     event::scan_code(&event),
                     event::virtual_key_code(&event),
+                    event::physical_key(&event),
                     event::keyboard_modifiers(&event),
                 );
             },
@@ -172,7 +174,8 @@ impl Canvas {
 
     pub fn on_keyboard_press<F>(&mut self, mut handler: F, prevent_default: bool)
     where
-        F: 'static + FnMut(ScanCode, Option<VirtualKeyCode>, ModifiersState),
+        F: 'static
+            + FnMut(ScanCode, Option<VirtualKeyCode>, KeyCode, Option<String>, ModifiersState),
     {
         self.on_keyboard_press = Some(self.common.add_user_event(
             "keydown",
@@ -194,6 +197,8 @@ impl Canvas {
                 handler(
                     event::scan_code(&event),
                     event::virtual_key_code(&event),
+                    event::physical_key(&event),
+                    event::key_text(&event),
                     event::keyboard_modifiers(&event),
                 );
             },
@@ -1,5 +1,6 @@
 use crate::dpi::LogicalPosition;
 use crate::event::{ModifiersState, MouseButton, MouseScrollDelta, ScanCode, VirtualKeyCode};
+use crate::keyboard::KeyCode;
 
 use std::convert::TryInto;
 use web_sys::{HtmlCanvasElement, KeyboardEvent, MouseEvent, WheelEvent};
@@ -231,6 +232,191 @@ pub fn virtual_key_code(event: &KeyboardEvent) -> Option<VirtualKeyCode> {
     })
 }
 
+/// Converts `KeyboardEvent.code`, as defined by the UI Events specification, to a physical key.
+pub fn physical_key(event: &KeyboardEvent) -> KeyCode {
+    match &event.code()[..] {
+        "Backquote" => KeyCode::Backquote,
+        "Backslash" => KeyCode::Backslash,
+        "BracketLeft" => KeyCode::BracketLeft,
+        "BracketRight" => KeyCode::BracketRight,
+        "Comma" => KeyCode::Comma,
+        "Digit0" => KeyCode::Digit0,
+        "Digit1" => KeyCode::Digit1,
+        "Digit2" => KeyCode::Digit2,
+        "Digit3" => KeyCode::Digit3,
+        "Digit4" => KeyCode::Digit4,
+        "Digit5" => KeyCode::Digit5,
+        "Digit6" => KeyCode::Digit6,
+        "Digit7" => KeyCode::Digit7,
+        "Digit8" => KeyCode::Digit8,
+        "Digit9" => KeyCode::Digit9,
+        "Equal" => KeyCode::Equal,
+        "IntlBackslash" => KeyCode::IntlBackslash,
+        "IntlRo" => KeyCode::IntlRo,
+        "IntlYen" => KeyCode::IntlYen,
+        "KeyA" => KeyCode::KeyA,
+        "KeyB" => KeyCode::KeyB,
+        "KeyC" => KeyCode::KeyC,
+        "KeyD" => KeyCode::KeyD,
+        "KeyE" => KeyCode::KeyE,
+        "KeyF" => KeyCode::KeyF,
+        "KeyG" => KeyCode::KeyG,
+        "KeyH" => KeyCode::KeyH,
+        "KeyI" => KeyCode::KeyI,
+        "KeyJ" => KeyCode::KeyJ,
+        "KeyK" => KeyCode::KeyK,
+        "KeyL" => KeyCode::KeyL,
+        "KeyM" => KeyCode::KeyM,
+        "KeyN" => KeyCode::KeyN,
+        "KeyO" => KeyCode::KeyO,
+        "KeyP" => KeyCode::KeyP,
+        "KeyQ" => KeyCode::KeyQ,
+        "KeyR" => KeyCode::KeyR,
+        "KeyS" => KeyCode::KeyS,
+        "KeyT" => KeyCode::KeyT,
+        "KeyU" => KeyCode::KeyU,
+        "KeyV" => KeyCode::KeyV,
+        "KeyW" => KeyCode::KeyW,
+        "KeyX" => KeyCode::KeyX,
+        "KeyY" => KeyCode::KeyY,
+        "KeyZ" => KeyCode::KeyZ,
+        "Minus" => KeyCode::Minus,
+        "Period" => KeyCode::Period,
+        "Quote" => KeyCode::Quote,
+        "Semicolon" => KeyCode::Semicolon,
+        "Slash" => KeyCode::Slash,
+        "AltLeft" => KeyCode::AltLeft,
+        "AltRight" => KeyCode::AltRight,
+        "Backspace" => KeyCode::Backspace,
+        "CapsLock" => KeyCode::CapsLock,
+        "ContextMenu" => KeyCode::ContextMenu,
+        "ControlLeft" => KeyCode::ControlLeft,
+        "ControlRight" => KeyCode::ControlRight,
+        "Enter" => KeyCode::Enter,
+        // Older browsers use the `OS` names.
+        "MetaLeft" | "OSLeft" => KeyCode::SuperLeft,
+        "MetaRight" | "OSRight" => KeyCode::SuperRight,
+        "ShiftLeft" => KeyCode::ShiftLeft,
+        "ShiftRight" => KeyCode::ShiftRight,
+        "Space" => KeyCode::Space,
+        "Tab" => KeyCode::Tab,
+        "Convert" => KeyCode::Convert,
+        "KanaMode" => KeyCode::KanaMode,
+        "Lang1" => KeyCode::Lang1,
+        "Lang2" => KeyCode::Lang2,
+        "Lang3" => KeyCode::Lang3,
+        "Lang4" => KeyCode::Lang4,
+        "Lang5" => KeyCode::Lang5,
+        "NonConvert" => KeyCode::NonConvert,
+        "Delete" => KeyCode::Delete,
+        "End" => KeyCode::End,
+        "Help" => KeyCode::Help,
+        "Home" => KeyCode::Home,
+        "Insert" => KeyCode::Insert,
+        "PageDown" => KeyCode::PageDown,
+        "PageUp" => KeyCode::PageUp,
+        "ArrowDown" => KeyCode::ArrowDown,
+        "ArrowLeft" => KeyCode::ArrowLeft,
+        "ArrowRight" => KeyCode::ArrowRight,
+        "ArrowUp" => KeyCode::ArrowUp,
+        "NumLock" => KeyCode::NumLock,
+        "Numpad0" => KeyCode::Numpad0,
+        "Numpad1" => KeyCode::Numpad1,
+        "Numpad2" => KeyCode::Numpad2,
+        "Numpad3" => KeyCode::Numpad3,
+        "Numpad4" => KeyCode::Numpad4,
+        "Numpad5" => KeyCode::Numpad5,
+        "Numpad6" => KeyCode::Numpad6,
+        "Numpad7" => KeyCode::Numpad7,
+        "Numpad8" => KeyCode::Numpad8,
+        "Numpad9" => KeyCode::Numpad9,
+        "NumpadAdd" => KeyCode::NumpadAdd,
+        "NumpadComma" => KeyCode::NumpadComma,
+        "NumpadDecimal" => KeyCode::NumpadDecimal,
+        "NumpadDivide" => KeyCode::NumpadDivide,
+        "NumpadEnter" => KeyCode::NumpadEnter,
+        "NumpadEqual" => KeyCode::NumpadEqual,
+        "NumpadMultiply" => KeyCode::NumpadMultiply,
+        "NumpadParenLeft" => KeyCode::NumpadParenLeft,
+        "NumpadParenRight" => KeyCode::NumpadParenRight,
+        "NumpadSubtract" => KeyCode::NumpadSubtract,
+        "Escape" => KeyCode::Escape,
+        "F1" => KeyCode::F1,
+        "F2" => KeyCode::F2,
+        "F3" => KeyCode::F3,
+        "F4" => KeyCode::F4,
+        "F5" => KeyCode::F5,
+        "F6" => KeyCode::F6,
+        "F7" => KeyCode::F7,
+        "F8" => KeyCode::F8,
+        "F9" => KeyCode::F9,
+        "F10" => KeyCode::F10,
+        "F11" => KeyCode::F11,
+        "F12" => KeyCode::F12,
+        "F13" => KeyCode::F13,
+        "F14" => KeyCode::F14,
+        "F15" => KeyCode::F15,
+        "F16" => KeyCode::F16,
+        "F17" => KeyCode::F17,
+        "F18" => KeyCode::F18,
+        "F19" => KeyCode::F19,
+        "F20" => KeyCode::F20,
+        "F21" => KeyCode::F21,
+        "F22" => KeyCode::F22,
+        "F23" => KeyCode::F23,
+        "F24" => KeyCode::F24,
+        "Fn" => KeyCode::Fn,
+        "PrintScreen" => KeyCode::PrintScreen,
+        "ScrollLock" => KeyCode::ScrollLock,
+        "Pause" => KeyCode::Pause,
+        "BrowserBack" => KeyCode::BrowserBack,
+        "BrowserFavorites" => KeyCode::BrowserFavorites,
+        "BrowserForward" => KeyCode::BrowserForward,
+        "BrowserHome" => KeyCode::BrowserHome,
+        "BrowserRefresh" => KeyCode::BrowserRefresh,
+        "BrowserSearch" => KeyCode::BrowserSearch,
+        "BrowserStop" => KeyCode::BrowserStop,
+        "Eject" => KeyCode::Eject,
+        "LaunchApp1" => KeyCode::LaunchApp1,
+        "LaunchApp2" => KeyCode::LaunchApp2,
+        "LaunchMail" => KeyCode::LaunchMail,
+        "MediaPlayPause" => KeyCode::MediaPlayPause,
+        "MediaSelect" | "LaunchMediaPlayer" => KeyCode::MediaSelect,
+        "MediaStop" => KeyCode::MediaStop,
+        "MediaTrackNext" => KeyCode::MediaTrackNext,
+        "MediaTrackPrevious" => KeyCode::MediaTrackPrevious,
+        "Power" => KeyCode::Power,
+        "Sleep" => KeyCode::Sleep,
+        "AudioVolumeDown" | "VolumeDown" => KeyCode::AudioVolumeDown,
+        "AudioVolumeMute" | "VolumeMute" => KeyCode::AudioVolumeMute,
+        "AudioVolumeUp" | "VolumeUp" => KeyCode::AudioVolumeUp,
+        "WakeUp" => KeyCode::WakeUp,
+        "Again" => KeyCode::Again,
+        "Copy" => KeyCode::Copy,
+        "Cut" => KeyCode::Cut,
+        "Find" => KeyCode::Find,
+        "Open" => KeyCode::Open,
+        "Paste" => KeyCode::Paste,
+        "Props" => KeyCode::Props,
+        "Select" => KeyCode::Select,
+        "Undo" => KeyCode::Undo,
+        _ => KeyCode::Unidentified(scan_code(event)),
+    }
+}
+
+/// The text typed by a key, from `KeyboardEvent.key`.
+///
+/// The key values of keys that don't type anything, like `Enter` or `Dead`, are ASCII names
+/// longer than a character, while typed text is a single character or isn't ASCII.
+pub fn key_text(event: &KeyboardEvent) -> Option<String> {
+    let key = event.key();
+    if key.chars().count() == 1 || !key.is_ascii() {
+        Some(key)
+    } else {
+        None
+    }
+}
+
 pub fn keyboard_modifiers(event: &KeyboardEvent) -> ModifiersState {
     let mut m = ModifiersState::empty();
     m.set(ModifiersState::SHIFT, event.shift_key());
@@ -1,10 +1,14 @@
+use crate::clipboard::{ClipboardContents, ClipboardKind};
+use crate::dnd::{DndAction, DragData};
 use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOE};
 use crate::event;
 use crate::icon::Icon;
+use crate::keyboard::{Key, KeyCode, KeyboardLayout};
 use crate::monitor::MonitorHandle as RootMH;
 use crate::window::{
-    CursorGrabMode, CursorIcon, Fullscreen, UserAttentionType, WindowAttributes, WindowId as RootWI,
+    CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ImeHints, ImePurpose, Rect, Region,
+    Theme, UserAttentionType, WindowAttributes, WindowId as RootWI,
 };
 
 use raw_window_handle::{RawDisplayHandle, RawWindowHandle, WebDisplayHandle, WebWindowHandle};
@@ -212,6 +216,9 @@ impl Window {
         backend::set_canvas_style_property(self.canvas.borrow().raw(), "cursor", text);
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) {}
+
     #[inline]
     pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
@@ -254,6 +261,70 @@ impl Window {
         Err(ExternalError::NotSupported(NotSupportedError::new()))
     }
 
+    #[inline]
+    pub fn set_input_region(&self, _region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_shape(&self, _shape: Option<Region>) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn clipboard(&self, _kind: ClipboardKind, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, _kind: ClipboardKind) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[inline]
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        None
+    }
+
+    #[inline]
+    pub fn key_label(&self, _physical_key: KeyCode) -> Option<Key> {
+        None
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _data: DragData) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn accept_drag(&self, _mime_type: &str, _action: DndAction) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_minimized(&self, _minimized: bool) {
         // Intentionally a no-op, as canvases cannot be 'minimized'
@@ -312,6 +383,30 @@ impl Window {
         // Currently a no-op as it does not seem there is good support for this on web
     }
 
+    #[inline]
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {
+        // Currently a no-op, like `set_ime_position`
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(
+        &self,
+        _text: &str,
+        _cursor: usize,
+        _anchor: usize,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(
+        &self,
+        _purpose: ImePurpose,
+        _hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_ime_allowed(&self, _allowed: bool) {
         // Currently not implemented
@@ -327,6 +422,21 @@ impl Window {
         // Currently an intentional no-op
     }
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_shortcuts_inhibited(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        None
+    }
+
     #[inline]
     // Allow directly accessing the current monitor internally without unwrapping.
     fn current_monitor_inner(&self) -> RootMH {
@@ -54,7 +54,7 @@ use windows_sys::Win32::{
             GetMessageW, LoadCursorW, MsgWaitForMultipleObjectsEx, PeekMessageW, PostMessageW,
             PostThreadMessageW, RegisterClassExW, RegisterWindowMessageA, SetCursor, SetWindowPos,
             TranslateMessage, CREATESTRUCTW, GIDC_ARRIVAL, GIDC_REMOVAL, GWL_STYLE, GWL_USERDATA,
-            HTCAPTION, HTCLIENT, MAPVK_VK_TO_VSC, MINMAXINFO, MSG, MWMO_INPUTAVAILABLE,
+            HTCAPTION, HTCLIENT, MAPVK_VK_TO_VSC_EX, MINMAXINFO, MSG, MWMO_INPUTAVAILABLE,
             NCCALCSIZE_PARAMS, PM_NOREMOVE, PM_QS_PAINT, PM_REMOVE, PT_PEN, PT_TOUCH, QS_ALLEVENTS,
             RI_KEY_E0, RI_KEY_E1, RI_MOUSE_WHEEL, SC_MINIMIZE, SC_RESTORE, SIZE_MAXIMIZED,
             SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, WHEEL_DELTA, WINDOWPOS,
@@ -80,6 +80,7 @@ use crate::{
     event_loop::{
         ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW,
     },
+    keyboard::KeyEvent,
     monitor::MonitorHandle as RootMonitorHandle,
     platform_impl::platform::{
         dark_mode::try_theme,
@@ -821,23 +822,31 @@ fn update_modifiers<T>(window: HWND, userdata: &WindowData<T>) {
 unsafe fn gain_active_focus<T>(window: HWND, userdata: &WindowData<T>) {
     use crate::event::{ElementState::Released, WindowEvent::Focused};
     for windows_keycode in event::get_pressed_keys() {
-        let scancode = MapVirtualKeyA(windows_keycode as u32, MAPVK_VK_TO_VSC);
+        let scancode = MapVirtualKeyA(windows_keycode as u32, MAPVK_VK_TO_VSC_EX);
         let virtual_keycode = event::vkey_to_winit_vkey(windows_keycode);
 
         update_modifiers(window, userdata);
 
         #[allow(deprecated)]
+        let input = KeyboardInput {
+            scancode,
+            virtual_keycode,
+            state: Released,
+            modifiers: event::get_key_mods(),
+        };
         userdata.send_event(Event::WindowEvent {
             window_id: RootWindowId(WindowId(window)),
             event: WindowEvent::KeyboardInput {
                 device_id: DEVICE_ID,
-                input: KeyboardInput {
-                    scancode,
-                    virtual_keycode,
-                    state: Released,
-                    modifiers: event::get_key_mods(),
-                },
+                input,
+                event: KeyEvent::from_native_key(
+                    &input,
+                    event::scancode_to_physical_key(scancode),
+                    None,
+                    false,
+                ),
                 is_synthetic: true,
+                timestamp: Instant::now(),
             },
         })
     }
@@ -855,21 +864,29 @@ unsafe fn lose_active_focus<T>(window: HWND, userdata: &WindowData<T>) {
         WindowEvent::{Focused, ModifiersChanged},
     };
     for windows_keycode in event::get_pressed_keys() {
-        let scancode = MapVirtualKeyA(windows_keycode as u32, MAPVK_VK_TO_VSC);
+        let scancode = MapVirtualKeyA(windows_keycode as u32, MAPVK_VK_TO_VSC_EX);
         let virtual_keycode = event::vkey_to_winit_vkey(windows_keycode);
 
         #[allow(deprecated)]
+        let input = KeyboardInput {
+            scancode,
+            virtual_keycode,
+            state: Released,
+            modifiers: event::get_key_mods(),
+        };
         userdata.send_event(Event::WindowEvent {
             window_id: RootWindowId(WindowId(window)),
             event: WindowEvent::KeyboardInput {
                 device_id: DEVICE_ID,
-                input: KeyboardInput {
-                    scancode,
-                    virtual_keycode,
-                    state: Released,
-                    modifiers: event::get_key_mods(),
-                },
+                input,
+                event: KeyEvent::from_native_key(
+                    &input,
+                    event::scancode_to_physical_key(scancode),
+                    None,
+                    false,
+                ),
                 is_synthetic: true,
+                timestamp: Instant::now(),
             },
         })
     }
@@ -886,6 +903,23 @@ unsafe fn lose_active_focus<T>(window: HWND, userdata: &WindowData<T>) {
     });
 }
 
+/// Removes the `WM_CHAR` and `WM_SYSCHAR` messages that `TranslateMessage` queued for the key
+/// being pressed, and returns their text, so that it can be delivered with the key event.
+unsafe fn take_key_text(window: HWND) -> String {
+    let mut units = Vec::new();
+    let mut msg = mem::zeroed();
+    // The characters are posted right after the key is translated, so only the messages at the
+    // front of the queue belong to it.
+    while PeekMessageW(&mut msg, window, 0, 0, PM_NOREMOVE) != false.into()
+        && (msg.message == WM_CHAR || msg.message == WM_SYSCHAR)
+    {
+        let message = msg.message;
+        PeekMessageW(&mut msg, window, message, message, PM_REMOVE);
+        units.push(msg.wParam as u16);
+    }
+    char::decode_utf16(units).filter_map(Result::ok).collect()
+}
+
 /// Any window whose callback is configured to this function will have its events propagated
 /// through the events loop of the thread the window was created in.
 //
@@ -1248,7 +1282,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 if lparam == 0 {
                     userdata.send_event(Event::WindowEvent {
                         window_id: RootWindowId(WindowId(window)),
-                        event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                        event: WindowEvent::Ime(Ime::Preedit(String::new(), None, Vec::new())),
                     });
                 }
 
@@ -1267,13 +1301,15 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
                 // Next, receive preedit range for next composing if exist.
                 if (lparam as u32 & GCS_COMPSTR) != 0 {
-                    if let Some((text, first, last)) = ime_context.get_composing_text_and_cursor() {
+                    if let Some((text, first, last, spans)) =
+                        ime_context.get_composing_text_and_cursor()
+                    {
                         userdata.window_state.lock().ime_state = ImeState::Preedit;
                         let cursor_range = first.map(|f| (f, last.unwrap_or(f)));
 
                         userdata.send_event(Event::WindowEvent {
                             window_id: RootWindowId(WindowId(window)),
-                            event: WindowEvent::Ime(Ime::Preedit(text, cursor_range)),
+                            event: WindowEvent::Ime(Ime::Preedit(text, cursor_range, spans)),
                         });
                     }
                 }
@@ -1391,6 +1427,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                         device_id: DEVICE_ID,
                         position,
                         modifiers: event::get_key_mods(),
+                        timestamp: Instant::now(),
                     },
                 });
             }
@@ -1433,6 +1470,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     delta: LineDelta(0.0, value),
                     phase: TouchPhase::Moved,
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
 
@@ -1455,6 +1493,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     delta: LineDelta(value, 0.0),
                     phase: TouchPhase::Moved,
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
 
@@ -1469,26 +1508,39 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 if let Some((scancode, vkey)) = process_key_params(wparam, lparam) {
                     update_modifiers(window, userdata);
 
+                    let mut text = take_key_text(window);
+                    // Windows doesn't emit a delete character by default, but in order to make it
+                    // consistent with the other platforms we'll emit a delete character here.
+                    if vkey == Some(VirtualKeyCode::Delete) {
+                        text.push('\u{7F}');
+                    }
+
                     #[allow(deprecated)]
+                    let input = KeyboardInput {
+                        state: Pressed,
+                        scancode,
+                        virtual_keycode: vkey,
+                        modifiers: event::get_key_mods(),
+                    };
                     userdata.send_event(Event::WindowEvent {
                         window_id: RootWindowId(WindowId(window)),
                         event: WindowEvent::KeyboardInput {
                             device_id: DEVICE_ID,
-                            input: KeyboardInput {
-                                state: Pressed,
-                                scancode,
-                                virtual_keycode: vkey,
-                                modifiers: event::get_key_mods(),
-                            },
+                            input,
+                            event: KeyEvent::from_native_key(
+                                &input,
+                                event::scancode_to_physical_key(scancode),
+                                Some(text.clone()),
+                                (lparam >> 30) & 1 == 1,
+                            ),
                             is_synthetic: false,
+                            timestamp: Instant::now(),
                         },
                     });
-                    // Windows doesn't emit a delete character by default, but in order to make it
-                    // consistent with the other platforms we'll emit a delete character here.
-                    if vkey == Some(VirtualKeyCode::Delete) {
+                    for chr in text.chars() {
                         userdata.send_event(Event::WindowEvent {
                             window_id: RootWindowId(WindowId(window)),
-                            event: WindowEvent::ReceivedCharacter('\u{7F}'),
+                            event: WindowEvent::ReceivedCharacter(chr),
                         });
                     }
                 }
@@ -1502,17 +1554,25 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 update_modifiers(window, userdata);
 
                 #[allow(deprecated)]
+                let input = KeyboardInput {
+                    state: Released,
+                    scancode,
+                    virtual_keycode: vkey,
+                    modifiers: event::get_key_mods(),
+                };
                 userdata.send_event(Event::WindowEvent {
                     window_id: RootWindowId(WindowId(window)),
                     event: WindowEvent::KeyboardInput {
                         device_id: DEVICE_ID,
-                        input: KeyboardInput {
-                            state: Released,
-                            scancode,
-                            virtual_keycode: vkey,
-                            modifiers: event::get_key_mods(),
-                        },
+                        input,
+                        event: KeyEvent::from_native_key(
+                            &input,
+                            event::scancode_to_physical_key(scancode),
+                            None,
+                            false,
+                        ),
                         is_synthetic: false,
+                        timestamp: Instant::now(),
                     },
                 });
             }
@@ -1533,6 +1593,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     state: Pressed,
                     button: Left,
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
             0
@@ -1554,6 +1615,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     state: Released,
                     button: Left,
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
             0
@@ -1575,6 +1637,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     state: Pressed,
                     button: Right,
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
             0
@@ -1596,6 +1659,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     state: Released,
                     button: Right,
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
             0
@@ -1617,6 +1681,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     state: Pressed,
                     button: Middle,
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
             0
@@ -1638,6 +1703,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     state: Released,
                     button: Middle,
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
             0
@@ -1660,6 +1726,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     state: Pressed,
                     button: Other(xbutton),
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
             0
@@ -1682,6 +1749,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                     state: Released,
                     button: Other(xbutton),
                     modifiers: event::get_key_mods(),
+                    timestamp: Instant::now(),
                 },
             });
             0
@@ -1739,6 +1807,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             force: None, // WM_TOUCH doesn't support pressure information
                             id: input.dwID as u64,
                             device_id: DEVICE_ID,
+                            timestamp: Instant::now(),
                         }),
                     });
                 }
@@ -1878,6 +1947,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
                             force,
                             id: pointer_info.pointerId as u64,
                             device_id: DEVICE_ID,
+                            timestamp: Instant::now(),
                         }),
                     });
                 }
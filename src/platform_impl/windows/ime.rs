@@ -19,7 +19,11 @@ use windows_sys::Win32::{
     },
 };
 
-use crate::{dpi::Position, platform::windows::HWND};
+use crate::{
+    dpi::Position,
+    event::{PreeditSpan, PreeditStyle},
+    platform::windows::HWND,
+};
 
 pub struct ImeContext {
     hwnd: HWND,
@@ -34,12 +38,13 @@ impl ImeContext {
 
     pub unsafe fn get_composing_text_and_cursor(
         &self,
-    ) -> Option<(String, Option<usize>, Option<usize>)> {
+    ) -> Option<(String, Option<usize>, Option<usize>, Vec<PreeditSpan>)> {
         let text = self.get_composition_string(GCS_COMPSTR)?;
         let attrs = self.get_composition_data(GCS_COMPATTR).unwrap_or_default();
 
         let mut first = None;
         let mut last = None;
+        let mut spans: Vec<PreeditSpan> = Vec::new();
         let mut boundary_before_char = 0;
 
         for (attr, chr) in attrs.into_iter().zip(text.chars()) {
@@ -52,7 +57,23 @@ impl ImeContext {
                 last = Some(boundary_before_char);
             }
 
-            boundary_before_char += chr.len_utf8();
+            // The targetted clause is highlighted, the rest of the composition underlined.
+            let style = if char_is_targetted {
+                PreeditStyle::Highlight
+            } else {
+                PreeditStyle::Underline
+            };
+            let boundary_after_char = boundary_before_char + chr.len_utf8();
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.end = boundary_after_char,
+                _ => spans.push(PreeditSpan {
+                    start: boundary_before_char,
+                    end: boundary_after_char,
+                    style,
+                }),
+            }
+
+            boundary_before_char = boundary_after_char;
         }
 
         if first.is_some() && last.is_none() {
@@ -64,7 +85,7 @@ impl ImeContext {
             last = cursor;
         }
 
-        Some((text, first, last))
+        Some((text, first, last, spans))
     }
 
     pub unsafe fn get_composed_text(&self) -> Option<String> {
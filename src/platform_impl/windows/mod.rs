@@ -70,6 +70,16 @@ impl DeviceId {
     pub const unsafe fn dummy() -> Self {
         DeviceId(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub fn into_raw(self) -> u64 {
+        self.0 as u64
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_raw(raw: u64) -> Self {
+        DeviceId(raw as u32)
+    }
 }
 
 impl DeviceId {
@@ -53,9 +53,12 @@ use windows_sys::Win32::{
 };
 
 use crate::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dnd::{DndAction, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     icon::Icon,
+    keyboard::{Key, KeyCode, KeyboardLayout},
     monitor::MonitorHandle as RootMonitorHandle,
     platform_impl::platform::{
         dark_mode::try_theme,
@@ -71,7 +74,10 @@ use crate::{
         window_state::{CursorFlags, SavedWindow, WindowFlags, WindowState},
         Parent, PlatformSpecificWindowBuilderAttributes, WindowId,
     },
-    window::{CursorGrabMode, CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes},
+    window::{
+        CursorGrabMode, CursorIcon, CustomCursor, Fullscreen, ImeHints, ImePurpose, Rect, Region,
+        Theme, UserAttentionType, WindowAttributes,
+    },
 };
 
 /// The Win32 implementation of the main `Window` object.
@@ -333,6 +339,9 @@ impl Window {
         self.window_state.lock().scale_factor
     }
 
+    #[inline]
+    pub fn set_custom_cursor(&self, _cursor: &CustomCursor) {}
+
     #[inline]
     pub fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
         let scale_factor = self.scale_factor();
@@ -387,6 +396,70 @@ impl Window {
         Ok(())
     }
 
+    #[inline]
+    pub fn set_input_region(&self, _region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_shape(&self, _shape: Option<Region>) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_opacity(&self, _opacity: f32) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        _kind: ClipboardKind,
+        _contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn clipboard(&self, _kind: ClipboardKind, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    #[inline]
+    pub fn clipboard_mime_types(&self, _kind: ClipboardKind) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[inline]
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        None
+    }
+
+    #[inline]
+    pub fn key_label(&self, _physical_key: KeyCode) -> Option<Key> {
+        None
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _data: DragData) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn accept_drag(&self, _mime_type: &str, _action: DndAction) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn id(&self) -> WindowId {
         WindowId(self.hwnd())
@@ -648,6 +721,30 @@ impl Window {
         }
     }
 
+    #[inline]
+    pub fn set_ime_cursor_area(&self, position: Position, _size: Size) {
+        self.set_ime_position(position);
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(
+        &self,
+        _text: &str,
+        _cursor: usize,
+        _anchor: usize,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(
+        &self,
+        _purpose: ImePurpose,
+        _hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
     #[inline]
     pub fn set_ime_allowed(&self, allowed: bool) {
         self.window_state.lock().ime_allowed = allowed;
@@ -685,8 +782,18 @@ impl Window {
     }
 
     #[inline]
-    pub fn theme(&self) -> Theme {
-        self.window_state.lock().current_theme
+    pub fn set_idle_inhibit(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_shortcuts_inhibited(&self, _inhibit: bool) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        Some(self.window_state.lock().current_theme)
     }
 
     #[inline]
@@ -0,0 +1,316 @@
+//! Recording of the events received by an application, to replay them later.
+//!
+//! This is meant to reproduce input bugs: a user records a session with an [`EventRecorder`]
+//! and sends the file, which is then fed to the application's event handler by an
+//! [`EventReplayer`].
+//!
+//! ```no_run
+//! use winit::{event_loop::EventLoop, record::EventRecorder};
+//!
+//! let event_loop = EventLoop::new();
+//! let recorder = EventRecorder::create("session.jsonl").unwrap();
+//!
+//! event_loop.run(recorder.wrap(move |event, _, control_flow| {
+//!     control_flow.set_wait();
+//!     // Handle the event as usual.
+//! }));
+//! ```
+//!
+//! Recordings are [JSON Lines](https://jsonlines.org) files, holding one event per line along
+//! with the time it was received since the start of the recording. Window ids and device ids are
+//! stored as numbers and don't refer to anything outside of the recording, and
+//! [`Event::UserEvent`]s are stored with their own [`Serialize`] implementation.
+//!
+//! This module requires the `record` feature.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use instant::Instant;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    dpi::PhysicalSize,
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoopWindowTarget},
+    window::WindowId,
+};
+
+/// Writes the events received by an application to a recording.
+#[derive(Debug)]
+pub struct EventRecorder<W: Write = BufWriter<File>> {
+    writer: W,
+    start: Instant,
+}
+
+impl EventRecorder {
+    /// Creates a recorder writing to the file at `path`, which is replaced if it already exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        File::create(path).map(|file| Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> EventRecorder<W> {
+    /// Creates a recorder writing to `writer`.
+    ///
+    /// Event times are measured from the creation of the recorder.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            start: Instant::now(),
+        }
+    }
+
+    /// Appends `event` to the recording.
+    ///
+    /// The event is written to the underlying writer right away, but may be buffered by it; use
+    /// [`EventRecorder::flush`] to make sure it is written out.
+    pub fn record<T: Serialize>(&mut self, event: &Event<'_, T>) -> io::Result<()> {
+        let entry = Entry {
+            time: self.start.elapsed(),
+            event,
+        };
+        serde_json::to_writer(&mut self.writer, &entry)?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Wraps an event handler so that every event passed to it is recorded first.
+    ///
+    /// The recording is flushed after every [`Event::RedrawEventsCleared`] and on
+    /// [`Event::LoopDestroyed`], so it is complete up to the last iteration of the event loop
+    /// even if the application crashes. If writing fails, a warning is logged and recording
+    /// stops, but the handler keeps receiving the events.
+    pub fn wrap<T, F>(
+        mut self,
+        mut handler: F,
+    ) -> impl FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow)
+    where
+        T: Serialize,
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let mut failed = false;
+        move |event: Event<'_, T>,
+              target: &EventLoopWindowTarget<T>,
+              control_flow: &mut ControlFlow| {
+            if !failed {
+                let flush = matches!(event, Event::RedrawEventsCleared | Event::LoopDestroyed);
+                let result =
+                    self.record(&event)
+                        .and_then(|()| if flush { self.flush() } else { Ok(()) });
+                if let Err(err) = result {
+                    warn!("Failed to record event, recording stopped: {}", err);
+                    failed = true;
+                }
+            }
+
+            handler(event, target, control_flow);
+        }
+    }
+}
+
+/// How fast an [`EventReplayer`] delivers the events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayTiming {
+    /// Events are delivered at the same pace as they were recorded.
+    Original,
+    /// Events are delivered faster than they were recorded, by the given factor. A factor that
+    /// isn't a positive finite number is treated like [`ReplayTiming::Original`].
+    Accelerated(f64),
+    /// Events are delivered one after the other without waiting.
+    Immediate,
+}
+
+/// Feeds a recording made with an [`EventRecorder`] back to an event handler.
+pub struct EventReplayer<T: 'static> {
+    events: Vec<(Duration, ReplayedEvent<T>)>,
+}
+
+impl<T: DeserializeOwned> EventReplayer<T> {
+    /// Loads the recording from the file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        File::open(path).and_then(|file| Self::from_reader(BufReader::new(file)))
+    }
+
+    /// Loads the recording from `reader`.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if a line isn't a valid event.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut events = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // `ScaleFactorChanged` can't be deserialized as an `Event` since it borrows the new
+            // inner size, so it's tried separately first.
+            let event = match serde_json::from_str::<Entry<ScaleFactorChangedEvent>>(&line) {
+                Ok(entry) => (entry.time, ReplayedEvent::ScaleFactorChanged(entry.event)),
+                Err(_) => {
+                    let entry =
+                        serde_json::from_str::<Entry<Event<'static, T>>>(&line).map_err(|err| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("invalid event on line {}: {}", index + 1, err),
+                            )
+                        })?;
+                    (entry.time, ReplayedEvent::Event(entry.event))
+                }
+            };
+            events.push(event);
+        }
+
+        Ok(Self { events })
+    }
+}
+
+impl<T: 'static> EventReplayer<T> {
+    /// The number of events in the recording.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether the recording holds no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Delivers the recorded events to `handler`, in order and with the given timing.
+    ///
+    /// `target` is passed to the handler along with the events, for instance to create windows.
+    /// It can belong to any event loop, but an event loop using the headless backend on Linux
+    /// avoids mixing the recording with real input. Like in an event loop, exiting with
+    /// [`ControlFlow::ExitWithCode`] is sticky, but the rest of the recording is still delivered
+    /// since it normally ends with [`Event::LoopDestroyed`].
+    ///
+    /// Returns the exit code set by the handler, or `0`.
+    pub fn replay<F>(
+        self,
+        target: &EventLoopWindowTarget<T>,
+        timing: ReplayTiming,
+        mut handler: F,
+    ) -> i32
+    where
+        F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+    {
+        let start = Instant::now();
+        let mut control_flow = ControlFlow::default();
+
+        for (time, event) in self.events {
+            let time = match timing {
+                ReplayTiming::Original => Some(time),
+                ReplayTiming::Accelerated(factor) => Some(accelerate(time, factor)),
+                ReplayTiming::Immediate => None,
+            };
+            if let Some(time) = time {
+                let elapsed = start.elapsed();
+                if time > elapsed {
+                    thread::sleep(time - elapsed);
+                }
+            }
+
+            let mut event_control_flow = control_flow;
+            match event {
+                ReplayedEvent::Event(event) => handler(event, target, &mut event_control_flow),
+                ReplayedEvent::ScaleFactorChanged(ScaleFactorChangedEvent::WindowEvent {
+                    window_id,
+                    event:
+                        ScaleFactorChanged::ScaleFactorChanged {
+                            scale_factor,
+                            mut new_inner_size,
+                        },
+                }) => handler(
+                    Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size: &mut new_inner_size,
+                        },
+                    },
+                    target,
+                    &mut event_control_flow,
+                ),
+            }
+            if !matches!(control_flow, ControlFlow::ExitWithCode(_)) {
+                control_flow = event_control_flow;
+            }
+        }
+
+        match control_flow {
+            ControlFlow::ExitWithCode(code) => code,
+            _ => 0,
+        }
+    }
+}
+
+/// A line of a recording.
+#[derive(Serialize, Deserialize)]
+struct Entry<E> {
+    time: Duration,
+    event: E,
+}
+
+enum ReplayedEvent<T: 'static> {
+    Event(Event<'static, T>),
+    ScaleFactorChanged(ScaleFactorChangedEvent),
+}
+
+/// The parts of [`Event`] leading to [`WindowEvent::ScaleFactorChanged`], with an owned new
+/// inner size.
+#[derive(Deserialize)]
+enum ScaleFactorChangedEvent {
+    WindowEvent {
+        window_id: WindowId,
+        event: ScaleFactorChanged,
+    },
+}
+
+#[derive(Deserialize)]
+enum ScaleFactorChanged {
+    ScaleFactorChanged {
+        scale_factor: f64,
+        new_inner_size: PhysicalSize<u32>,
+    },
+}
+
+/// Scales the time of an event for [`ReplayTiming::Accelerated`].
+fn accelerate(time: Duration, factor: f64) -> Duration {
+    if !(factor.is_finite() && factor > 0.0) {
+        return time;
+    }
+    // `Duration::from_secs_f64` panics on overflow, which small factors can cause.
+    let secs = time.as_secs_f64() / factor;
+    if secs < Duration::MAX.as_secs_f64() {
+        Duration::from_secs_f64(secs)
+    } else {
+        Duration::MAX
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accelerate_time() {
+        let time = Duration::from_secs(3);
+        assert_eq!(accelerate(time, 2.0), Duration::from_millis(1500));
+        assert_eq!(accelerate(time, 0.5), Duration::from_secs(6));
+        assert_eq!(accelerate(time, f64::INFINITY), time);
+        assert_eq!(accelerate(time, f64::NAN), time);
+        assert_eq!(accelerate(time, 0.0), time);
+        assert_eq!(accelerate(time, -1.0), time);
+        assert_eq!(accelerate(time, 1e-300), Duration::MAX);
+        assert_eq!(accelerate(Duration::MAX, 1.0), Duration::MAX);
+    }
+}
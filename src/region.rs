@@ -0,0 +1,182 @@
+use crate::{
+    cursor::BadImage,
+    dpi::{PhysicalPosition, PhysicalSize},
+    icon::PIXEL_SIZE,
+    window::Rect,
+};
+
+/// The shape of a window, which can be set with [`Window::set_shape`].
+///
+/// The shape is the union of rectangles of the client area of the window.
+///
+/// [`Window::set_shape`]: crate::window::Window::set_shape
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Region {
+    rects: Vec<Rect>,
+}
+
+impl Region {
+    /// Creates a region covering the given rectangles, which may overlap.
+    pub fn from_rects(rects: Vec<Rect>) -> Self {
+        Region { rects }
+    }
+
+    /// Creates a region from a 1-bit mask of 32bpp RGBA data, where the pixels which aren't fully
+    /// transparent are inside the region.
+    ///
+    /// The mask is in physical pixels, from the top-left corner of the client area. The length of
+    /// `rgba` must be divisible by 4, and `width * height` must equal `rgba.len() / 4`. Otherwise,
+    /// this will return a `BadImage` error.
+    pub fn from_rgba(rgba: &[u8], width: u32, height: u32) -> Result<Self, BadImage> {
+        if rgba.len() % PIXEL_SIZE != 0 {
+            return Err(BadImage::ByteCountNotDivisibleBy4 {
+                byte_count: rgba.len(),
+            });
+        }
+        let pixel_count = rgba.len() / PIXEL_SIZE;
+        let width_x_height = width as usize * height as usize;
+        if pixel_count != width_x_height {
+            return Err(BadImage::DimensionsVsPixelCount {
+                width,
+                height,
+                width_x_height,
+                pixel_count,
+            });
+        }
+
+        // Consecutive rows with the same spans of opaque pixels are merged into a single band of
+        // rectangles, which keeps the region small for the usual shapes.
+        let mut rects = Vec::new();
+        let mut band: Vec<(u32, u32)> = Vec::new();
+        let mut band_start = 0;
+        let row_length = width as usize * PIXEL_SIZE;
+        for y in 0..=height {
+            let spans = if y < height {
+                let row = &rgba[y as usize * row_length..][..row_length];
+                opaque_spans(row)
+            } else {
+                Vec::new()
+            };
+
+            if spans != band {
+                for &(x, span_width) in &band {
+                    rects.push(Rect::new(
+                        PhysicalPosition::new(x as i32, band_start as i32),
+                        PhysicalSize::new(span_width, y - band_start),
+                    ));
+                }
+                band = spans;
+                band_start = y;
+            }
+        }
+
+        Ok(Region { rects })
+    }
+
+    /// The rectangles covered by the region.
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+}
+
+/// The starts and the widths of the runs of opaque pixels of a row.
+fn opaque_spans(row: &[u8]) -> Vec<(u32, u32)> {
+    let mut spans = Vec::new();
+    let mut span_start = None;
+    for (x, pixel) in row.chunks_exact(PIXEL_SIZE).enumerate() {
+        let x = x as u32;
+        match (span_start, pixel[3] != 0) {
+            (None, true) => span_start = Some(x),
+            (Some(start), false) => {
+                spans.push((start, x - start));
+                span_start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some(start) = span_start {
+        let width = (row.len() / PIXEL_SIZE) as u32;
+        spans.push((start, width - start));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A mask with the given alpha values, row by row.
+    fn mask(alphas: &[u8]) -> Vec<u8> {
+        alphas.iter().flat_map(|&alpha| [0, 0, 0, alpha]).collect()
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect::new(
+            PhysicalPosition::new(x, y),
+            PhysicalSize::new(width, height),
+        )
+    }
+
+    #[test]
+    fn spans_of_a_row() {
+        let region = Region::from_rgba(&mask(&[0, 255, 1, 0, 255]), 5, 1).unwrap();
+        assert_eq!(region.rects(), &[rect(1, 0, 2, 1), rect(4, 0, 1, 1)]);
+
+        let region = Region::from_rgba(&mask(&[255, 255, 255]), 3, 1).unwrap();
+        assert_eq!(region.rects(), &[rect(0, 0, 3, 1)]);
+
+        let region = Region::from_rgba(&mask(&[0, 0, 0]), 3, 1).unwrap();
+        assert_eq!(region.rects(), &[]);
+    }
+
+    #[test]
+    fn rows_with_the_same_spans_form_bands() {
+        #[rustfmt::skip]
+        let alphas = [
+            0,   255, 255, 0,
+            0,   255, 255, 0,
+            255, 0,   0,   255,
+            0,   0,   0,   0,
+            0,   255, 255, 0,
+        ];
+        let region = Region::from_rgba(&mask(&alphas), 4, 5).unwrap();
+        assert_eq!(
+            region.rects(),
+            &[
+                rect(1, 0, 2, 2),
+                rect(0, 2, 1, 1),
+                rect(3, 2, 1, 1),
+                rect(1, 4, 2, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn band_reaching_the_last_row() {
+        let region = Region::from_rgba(&mask(&[0, 255, 0, 255]), 2, 2).unwrap();
+        assert_eq!(region.rects(), &[rect(1, 0, 1, 2)]);
+    }
+
+    #[test]
+    fn bad_masks() {
+        assert!(matches!(
+            Region::from_rgba(&[0; 7], 1, 2),
+            Err(BadImage::ByteCountNotDivisibleBy4 { byte_count: 7 })
+        ));
+        assert!(matches!(
+            Region::from_rgba(&mask(&[0; 6]), 2, 2),
+            Err(BadImage::DimensionsVsPixelCount {
+                width_x_height: 4,
+                pixel_count: 6,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn empty_mask() {
+        let region = Region::from_rgba(&[], 0, 0).unwrap();
+        assert_eq!(region.rects(), &[]);
+    }
+}
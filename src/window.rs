@@ -6,14 +6,19 @@ use raw_window_handle::{
 };
 
 use crate::{
+    clipboard::{ClipboardContents, ClipboardKind, TEXT_MIME_TYPE},
+    dnd::{DndAction, DragData},
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     error::{ExternalError, NotSupportedError, OsError},
     event_loop::EventLoopWindowTarget,
+    keyboard::{Key, KeyCode, KeyboardLayout},
     monitor::{MonitorHandle, VideoMode},
     platform_impl,
 };
 
+pub use crate::cursor::{BadImage, CustomCursor, MAX_CURSOR_SIZE};
 pub use crate::icon::{BadIcon, Icon};
+pub use crate::region::Region;
 
 /// Represents a window.
 ///
@@ -99,6 +104,27 @@ impl From<u64> for WindowId {
     }
 }
 
+/// Serialized as the number returned by the `u64` conversion.
+#[cfg(feature = "serde")]
+impl serde::Serialize for WindowId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(&u64::from(*self), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for WindowId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        <u64 as serde::Deserialize>::deserialize(deserializer).map(WindowId::from)
+    }
+}
+
 /// Object that allows building windows.
 #[derive(Clone, Default)]
 #[must_use]
@@ -134,6 +160,7 @@ pub(crate) struct WindowAttributes {
     pub decorations: bool,
     pub always_on_top: bool,
     pub window_icon: Option<Icon>,
+    pub parent: Option<WindowId>,
 }
 
 impl Default for WindowAttributes {
@@ -153,6 +180,7 @@ impl Default for WindowAttributes {
             decorations: true,
             always_on_top: false,
             window_icon: None,
+            parent: None,
         }
     }
 }
@@ -333,6 +361,26 @@ impl WindowBuilder {
         self
     }
 
+    /// Sets the window the window is a child of.
+    ///
+    /// The default is `None`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The window is transient for its parent, and stays above it.
+    /// - **Wayland:** The window is a subsurface of its parent, without decorations, and is
+    ///   positioned relative to it with [`Window::set_outer_position`].
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported. On Windows, see
+    ///   `WindowBuilderExtWindows::with_parent_window` instead.
+    ///
+    /// On X11 and Wayland, the window can be made a popup, like a context menu or a tooltip, with
+    /// `WindowBuilderExtUnix::with_popup`.
+    #[inline]
+    pub fn with_parent(mut self, parent: &Window) -> Self {
+        self.window.parent = Some(parent.id());
+        self
+    }
+
     /// Builds the window.
     ///
     /// Possible causes of error include denied permission, incompatible system, and lack of memory.
@@ -392,7 +440,7 @@ impl Window {
     ///
     /// ## Platform-specific
     ///
-    /// - **X11:** This respects Xft.dpi, and can be overridden using the `WINIT_X11_SCALE_FACTOR` environment variable.
+    /// - **X11:** This respects the `Xft/DPI` of XSETTINGS and Xft.dpi, and can be overridden using the `WINIT_X11_SCALE_FACTOR` environment variable.
     /// - **Android:** Always returns 1.0.
     /// - **iOS:** Can only be called on the main thread. Returns the underlying `UIView`'s
     ///   [`contentScaleFactor`].
@@ -442,7 +490,9 @@ impl Window {
     ///   window's [safe area] in the screen space coordinate system.
     /// - **Web:** Returns the top-left coordinates relative to the viewport. _Note: this returns the
     ///    same value as [`Window::outer_position`]._
-    /// - **Android / Wayland:** Always returns [`NotSupportedError`].
+    /// - **Wayland:** Returns the position relative to the parent for child windows, and
+    ///   [`NotSupportedError`] otherwise.
+    /// - **Android:** Always returns [`NotSupportedError`].
     ///
     /// [safe area]: https://developer.apple.com/documentation/uikit/uiview/2891103-safeareainsets?language=objc
     #[inline]
@@ -465,7 +515,9 @@ impl Window {
     /// - **iOS:** Can only be called on the main thread. Returns the top left coordinates of the
     ///   window in the screen space coordinate system.
     /// - **Web:** Returns the top-left coordinates relative to the viewport.
-    /// - **Android / Wayland:** Always returns [`NotSupportedError`].
+    /// - **Wayland:** Returns the position relative to the parent for child windows, and
+    ///   [`NotSupportedError`] otherwise.
+    /// - **Android:** Always returns [`NotSupportedError`].
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         self.window.outer_position()
@@ -494,7 +546,10 @@ impl Window {
     /// - **iOS:** Can only be called on the main thread. Sets the top left coordinates of the
     ///   window in the screen space coordinate system.
     /// - **Web:** Sets the top-left coordinates relative to the viewport.
-    /// - **Android / Wayland:** Unsupported.
+    /// - **Wayland:** Only supported by child windows, which are positioned relative to their
+    ///   parent. The new position is applied with the next redraw of the parent. Popups are
+    ///   placed by the compositor and can't be moved.
+    /// - **Android:** Unsupported.
     #[inline]
     pub fn set_outer_position<P: Into<Position>>(&self, position: P) {
         self.window.set_outer_position(position.into())
@@ -825,6 +880,79 @@ impl Window {
         self.window.set_ime_position(position.into())
     }
 
+    /// Sets the area of the text cursor in client area coordinates relative to the top left,
+    /// which the IME avoids covering with its candidate box.
+    ///
+    /// This is [`Window::set_ime_position`] with the size of the cursor, or of the text being
+    /// composed, so that the candidate box can be placed next to it rather than at a single
+    /// point.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The spot is placed at the bottom left corner of the area.
+    /// - **Windows / macOS:** Only the position is used.
+    /// - **iOS / Android / Web:** Unsupported.
+    #[inline]
+    pub fn set_ime_cursor_area<P: Into<Position>, S: Into<Size>>(&self, position: P, size: S) {
+        self.window
+            .set_ime_cursor_area(position.into(), size.into())
+    }
+
+    /// Tells the IME the text around the cursor, so that it can take it into account for its
+    /// suggestions and delete parts of it with [`Ime::DeleteSurrounding`].
+    ///
+    /// `text` should be the current paragraph, or the line for single-line fields, without the
+    /// preedit. `cursor` and `anchor` are the byte offsets of the cursor and of the other end of
+    /// the selection in `text`, and are equal without selection. Offsets inside a character are
+    /// moved back to its start, and offsets past the end of `text` to its end.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** The text is trimmed around the cursor to the 4000 bytes the protocol allows.
+    ///   Returns [`ExternalError::NotSupported`] if the compositor doesn't support text input.
+    /// - **X11:** Returns [`ExternalError::NotSupported`], XIM has no way to share the text with
+    ///   the input method.
+    /// - **Windows / macOS / iOS / Android / Web:** Returns [`ExternalError::NotSupported`].
+    ///
+    /// [`Ime::DeleteSurrounding`]: crate::event::Ime::DeleteSurrounding
+    #[inline]
+    pub fn set_ime_surrounding_text(
+        &self,
+        text: &str,
+        cursor: usize,
+        anchor: usize,
+    ) -> Result<(), ExternalError> {
+        let clamp = |offset: usize| {
+            let mut offset = offset.min(text.len());
+            while !text.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            offset
+        };
+        self.window
+            .set_ime_surrounding_text(text, clamp(cursor), clamp(anchor))
+    }
+
+    /// Tells the IME what kind of text the focused field expects, so that it can adapt its
+    /// input, for example by showing a numeric keypad or not learning passwords.
+    ///
+    /// The purpose is [`ImePurpose::Normal`] with no hints by default.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Returns [`ExternalError::NotSupported`] if the compositor doesn't support
+    ///   text input.
+    /// - **X11:** Returns [`ExternalError::NotSupported`], XIM has no content types.
+    /// - **Windows / macOS / iOS / Android / Web:** Returns [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_ime_purpose(
+        &self,
+        purpose: ImePurpose,
+        hints: ImeHints,
+    ) -> Result<(), ExternalError> {
+        self.window.set_ime_purpose(purpose, hints)
+    }
+
     /// Sets whether the window should get IME events
     ///
     /// When IME is allowed, the window will receive [`Ime`] events, and during the
@@ -884,6 +1012,63 @@ impl Window {
     pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
         self.window.request_user_attention(request_type)
     }
+
+    /// Keeps the screen from blanking or locking because of the inactivity of the user, like
+    /// while playing a video or giving a presentation.
+    ///
+    /// The inhibition is lifted by passing `false`, or when the window is dropped.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the `zwp_idle_inhibit_manager_v1` protocol, and only applies while
+    ///   the window is visible.
+    /// - **X11:** Requires the XScreenSaver extension, which also keeps DPMS from turning the
+    ///   monitors off. Applies whether the window is visible or not.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_idle_inhibit(&self, inhibit: bool) -> Result<(), ExternalError> {
+        self.window.set_idle_inhibit(inhibit)
+    }
+
+    /// Asks for the system shortcuts, like <kbd>Alt</kbd>+<kbd>Tab</kbd> or the
+    /// <kbd>Super</kbd> key, to be sent to the window instead while it has keyboard focus, as
+    /// remote desktop clients and virtual machine viewers need.
+    ///
+    /// The system may refuse, so whether the shortcuts are actually inhibited is reported with
+    /// [`WindowEvent::ShortcutsInhibited`], whenever it changes.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Requires the `zwp_keyboard_shortcuts_inhibit_manager_v1` protocol.
+    /// - **X11:** Actively grabs the keyboard while the window is focused, which fails when
+    ///   another client holds a grab.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::ShortcutsInhibited`]: crate::event::WindowEvent::ShortcutsInhibited
+    #[inline]
+    pub fn set_shortcuts_inhibited(&self, inhibit: bool) -> Result<(), ExternalError> {
+        self.window.set_shortcuts_inhibited(inhibit)
+    }
+
+    /// Returns the theme of the system, which [`WindowEvent::ThemeChanged`] reports the changes
+    /// of, or `None` when it's unknown.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** From the `color-scheme` setting of the XDG desktop portal, or else from whether
+    ///   the GTK theme of XSETTINGS is dark.
+    /// - **Wayland:** From the `color-scheme` setting of the XDG desktop portal.
+    /// - **X11 / Wayland:** The portal is read in the background once the event loop is created,
+    ///   and [`WindowEvent::ThemeChanged`] is sent when its answer changes the theme.
+    /// - **macOS / iOS / Android / Web:** Unsupported, always returns `None`.
+    ///
+    /// [`WindowEvent::ThemeChanged`]: crate::event::WindowEvent::ThemeChanged
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        self.window.theme()
+    }
 }
 
 /// Cursor functions.
@@ -898,6 +1083,21 @@ impl Window {
         self.window.set_cursor_icon(cursor);
     }
 
+    /// Shows a custom cursor image over the window, replacing the cursor icon.
+    ///
+    /// Animated cursors play in a loop for as long as they're shown. Calling
+    /// [`Window::set_cursor_icon`] switches back to a cursor from the system theme.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11 / Wayland:** The images are shown at their size in physical pixels. When they can't
+    ///   be uploaded, a warning is logged and the default cursor is shown on X11.
+    /// - **iOS / Android / Web / Windows / macOS:** Unsupported.
+    #[inline]
+    pub fn set_custom_cursor(&self, cursor: &CustomCursor) {
+        self.window.set_custom_cursor(cursor);
+    }
+
     /// Changes the position of the cursor in window coordinates.
     ///
     /// ```no_run
@@ -981,11 +1181,250 @@ impl Window {
     ///
     /// ## Platform-specific
     ///
-    /// - **iOS / Android / Web / X11:** Always returns an [`ExternalError::NotSupported`].
+    /// - **X11:** Requires the XFixes extension.
+    /// - **iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
     #[inline]
     pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
         self.window.set_cursor_hittest(hittest)
     }
+
+    /// Restricts the parts of the window which catch cursor events to the given rectangles,
+    /// so that clicks on the rest of a transparent window reach the windows behind it.
+    ///
+    /// `None` makes the whole window catch cursor events again, which is the default. The region
+    /// has no effect while hittest is disabled with [`Window::set_cursor_hittest`].
+    ///
+    /// Rectangles in logical pixels follow the scale factor of the window when it changes.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the XFixes extension.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_input_region(&self, region: Option<&[Rect]>) -> Result<(), ExternalError> {
+        self.window.set_input_region(region)
+    }
+
+    /// Cuts the client area of the window to the given shape, for splash screens and desktop
+    /// widgets which aren't rectangular. The window isn't drawn outside of the shape, and the
+    /// cursor events there reach the windows behind it. Decorations aren't cut, so this is meant
+    /// for windows without them.
+    ///
+    /// `None` makes the window rectangular again, which is the default.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires the XFixes extension. The frame the window manager puts around the
+    ///   window isn't shaped.
+    /// - **Wayland:** Always returns an [`ExternalError::NotSupported`], since surfaces can't be
+    ///   cut. Draw transparent pixels outside of the shape, and pass [`Region::rects`] to
+    ///   [`Window::set_input_region`] so that the cursor events there reach the windows behind.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_shape(&self, shape: Option<Region>) -> Result<(), ExternalError> {
+        self.window.set_shape(shape)
+    }
+
+    /// Sets the opacity of the whole window, decorations included, from `0.0` for invisible to
+    /// `1.0` for opaque, which is the default. Values outside of this range are clamped.
+    ///
+    /// Unlike a [transparent][WindowBuilder::with_transparent] window, this doesn't need an alpha
+    /// channel and fades every pixel alike, which suits translucent terminals.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sets `_NET_WM_WINDOW_OPACITY`, which is only honored by compositing managers.
+    /// - **Wayland:** Returns an [`ExternalError::NotSupported`] for any opacity below `1.0`,
+    ///   since there's no protocol for it. Draw with a transparent window instead.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_opacity(&self, opacity: f32) -> Result<(), ExternalError> {
+        self.window.set_opacity(opacity)
+    }
+
+    /// Asks the compositor to blur what's behind the window, which shows through its transparent
+    /// or translucent parts. Disabled by default.
+    ///
+    /// The compositor may ignore the request, for example when it's configured not to blur.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Sets `_KDE_NET_WM_BLUR_BEHIND_REGION`, which KWin and a few other compositing
+    ///   managers follow.
+    /// - **Wayland:** Returns an [`ExternalError::NotSupported`] when enabling the blur, since
+    ///   KWin's `org_kde_kwin_blur_manager` protocol isn't supported.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an
+    ///   [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_blur(&self, blur: bool) -> Result<(), ExternalError> {
+        self.window.set_blur(blur)
+    }
+}
+
+/// Clipboard functions.
+impl Window {
+    /// Replaces the contents of the given clipboard with `contents`.
+    ///
+    /// The data is served by the application for as long as it owns the clipboard, so it is lost
+    /// once the application exits unless a clipboard manager is running.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Fails when another client claimed the clipboard at the same time.
+    /// - **Wayland:** Fails unless one of the application's windows has keyboard focus, and
+    ///   returns an [`ExternalError::NotSupported`] when the compositor lacks the data device or
+    ///   primary selection protocol. The contents are set with the next iteration of the event
+    ///   loop, before which [`Window::clipboard`] still returns the previous ones.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    #[inline]
+    pub fn set_clipboard(
+        &self,
+        kind: ClipboardKind,
+        contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        self.window.set_clipboard(kind, contents)
+    }
+
+    /// Reads the contents of the given clipboard in the representation identified by `mime_type`.
+    ///
+    /// Returns `None` if the clipboard is empty, doesn't offer `mime_type`, or the transfer failed.
+    /// This blocks until the owner of the clipboard sent the data, so avoid calling it from the
+    /// event loop thread for large transfers.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11 / Wayland:** Gives up and returns `None` when the owner didn't send anything for
+    ///   2 seconds.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported, always returns `None`.
+    #[inline]
+    pub fn clipboard(&self, kind: ClipboardKind, mime_type: &str) -> Option<Vec<u8>> {
+        self.window.clipboard(kind, mime_type)
+    }
+
+    /// Returns the MIME types the given clipboard currently offers.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported, always returns an empty list.
+    #[inline]
+    pub fn clipboard_mime_types(&self, kind: ClipboardKind) -> Vec<String> {
+        self.window.clipboard_mime_types(kind)
+    }
+
+    /// Replaces the contents of the given clipboard with `text`.
+    ///
+    /// This is a shorthand for [`Window::set_clipboard`] with [`ClipboardContents::from_text`].
+    #[inline]
+    pub fn set_clipboard_text(&self, kind: ClipboardKind, text: &str) -> Result<(), ExternalError> {
+        self.set_clipboard(kind, ClipboardContents::from_text(text))
+    }
+
+    /// Reads the given clipboard as UTF-8 text.
+    ///
+    /// Returns `None` if the clipboard doesn't hold text. See [`Window::clipboard`].
+    #[inline]
+    pub fn clipboard_text(&self, kind: ClipboardKind) -> Option<String> {
+        self.clipboard(kind, TEXT_MIME_TYPE)
+            .and_then(|data| String::from_utf8(data).ok())
+    }
+}
+
+/// Drag and drop functions.
+impl Window {
+    /// Starts dragging `data` out of the window, until the mouse button is released.
+    ///
+    /// This must be called while a mouse button is held down over the window, usually on the
+    /// first [`WindowEvent::CursorMoved`] after a press. The outcome is reported with
+    /// [`WindowEvent::DragFinished`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Fails if another drag from the application is in progress. The drag is reported
+    ///   as cancelled when the target doesn't confirm a drop within 5 seconds, or is destroyed.
+    /// - **Wayland:** Fails unless a mouse button is held over the window, and returns an
+    ///   [`ExternalError::NotSupported`] when the compositor lacks the data device protocol.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::CursorMoved`]: crate::event::WindowEvent::CursorMoved
+    /// [`WindowEvent::DragFinished`]: crate::event::WindowEvent::DragFinished
+    #[inline]
+    pub fn start_drag(&self, data: DragData) -> Result<(), ExternalError> {
+        self.window.start_drag(data)
+    }
+
+    /// Accepts the drag over the window, asking for its data as `mime_type`.
+    ///
+    /// `mime_type` should be one of those reported by [`WindowEvent::DragEnter`]. If the drag is
+    /// dropped, the data is delivered with [`WindowEvent::DragDrop`] and the source is told that
+    /// `action` was performed. This can be called again while handling
+    /// [`WindowEvent::DragOver`], for instance to only accept drops on some parts of the window.
+    ///
+    /// Does nothing if no drag is over the window, and rejects the drag if it doesn't offer
+    /// `mime_type`.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** [`DndAction::Link`] is unsupported and treated as [`DndAction::Copy`].
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::DragEnter`]: crate::event::WindowEvent::DragEnter
+    /// [`WindowEvent::DragDrop`]: crate::event::WindowEvent::DragDrop
+    /// [`WindowEvent::DragOver`]: crate::event::WindowEvent::DragOver
+    #[inline]
+    pub fn accept_drag(&self, mime_type: &str, action: DndAction) -> Result<(), ExternalError> {
+        self.window.accept_drag(mime_type, action)
+    }
+
+    /// Rejects the drag over the window, which is the default until [`Window::accept_drag`] is
+    /// called.
+    ///
+    /// Does nothing if no drag is over the window.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Without an answer, drags of files are still accepted to report them with
+    ///   [`WindowEvent::DroppedFile`]. Rejecting the drag prevents that as well.
+    /// - **Windows / macOS / iOS / Android / Web:** Always returns an [`ExternalError::NotSupported`].
+    ///
+    /// [`WindowEvent::DroppedFile`]: crate::event::WindowEvent::DroppedFile
+    #[inline]
+    pub fn reject_drag(&self) -> Result<(), ExternalError> {
+        self.window.reject_drag()
+    }
+}
+
+/// Keyboard layout functions.
+impl Window {
+    /// Returns the active keyboard layout.
+    ///
+    /// Returns `None` if it isn't known yet, for instance before the keymap was received.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires `libxkbcommon-x11`.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported, always returns `None`.
+    #[inline]
+    pub fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        self.window.keyboard_layout()
+    }
+
+    /// Returns the label of a physical key in the active keyboard layout.
+    ///
+    /// The label is what the key means without any modifier, like `Key::Character("q")` for
+    /// [`KeyCode::KeyA`] on a French AZERTY layout, which suits displaying shortcuts. Returns
+    /// `None` if the layout isn't known or the key doesn't exist in the keymap.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Requires `libxkbcommon-x11`.
+    /// - **Windows / macOS / iOS / Android / Web:** Unsupported, always returns `None`.
+    #[inline]
+    pub fn key_label(&self, physical_key: KeyCode) -> Option<Key> {
+        self.window.key_label(physical_key)
+    }
 }
 
 /// Monitor info functions.
@@ -1276,12 +1715,62 @@ pub enum Fullscreen {
     Borderless(Option<MonitorHandle>),
 }
 
+/// A rectangle of a window, relative to the top-left corner of its client area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rect {
+    pub position: Position,
+    pub size: Size,
+}
+
+impl Rect {
+    pub fn new<P: Into<Position>, S: Into<Size>>(position: P, size: S) -> Self {
+        Self {
+            position: position.into(),
+            size: size.into(),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Theme {
     Light,
     Dark,
 }
 
+/// Who draws the decorations of a window.
+///
+/// The mode picked by the compositor is always [`Client`] or [`Server`], while the `Prefer*`
+/// modes can only be asked for.
+///
+/// ## Platform-specific
+///
+/// Only negotiated on Wayland, where the compositor may draw the decorations itself.
+///
+/// [`Client`]: Self::Client
+/// [`Server`]: Self::Server
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DecorationMode {
+    /// The decorations are drawn by the application, or by winit on its behalf.
+    ///
+    /// When asked for, the compositor isn't asked to draw them.
+    Client,
+
+    /// The decorations are drawn by the compositor.
+    ///
+    /// When asked for and the compositor refuses, the window is left undecorated.
+    Server,
+
+    /// Asks the compositor to let the client draw the decorations, which it may refuse.
+    PreferClient,
+
+    /// Asks the compositor to draw the decorations, drawing them on the client side when it
+    /// refuses.
+    PreferServer,
+}
+
 /// ## Platform-specific
 ///
 /// - **X11:** Sets the WM's `XUrgencyHint`. No distinction between [`Critical`] and [`Informational`].
@@ -1307,3 +1796,72 @@ impl Default for UserAttentionType {
         UserAttentionType::Informational
     }
 }
+
+/// The kind of text that an input field expects, given to the IME with
+/// [`Window::set_ime_purpose`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImePurpose {
+    /// Any text.
+    Normal,
+    /// Only letters.
+    Alpha,
+    /// Only digits.
+    Digits,
+    /// A number, possibly with a sign and a decimal separator.
+    Number,
+    /// A phone number.
+    Phone,
+    /// A URL.
+    Url,
+    /// An email address.
+    Email,
+    /// The name of a person.
+    Name,
+    /// A password, which the IME shouldn't show or remember.
+    Password,
+    /// A PIN, which the IME shouldn't show or remember.
+    Pin,
+    /// A date.
+    Date,
+    /// A time.
+    Time,
+    /// A date and a time.
+    DateTime,
+    /// The input of a terminal.
+    Terminal,
+}
+
+impl Default for ImePurpose {
+    fn default() -> Self {
+        ImePurpose::Normal
+    }
+}
+
+bitflags! {
+    /// Hints about how the IME should handle the text of an input field, given with
+    /// [`Window::set_ime_purpose`].
+    #[derive(Default)]
+    pub struct ImeHints: u32 {
+        /// Suggest completions of the text.
+        const COMPLETION = 1 << 0;
+        /// Suggest corrections of the spelling.
+        const SPELLCHECK = 1 << 1;
+        /// Capitalize the first letter of sentences.
+        const AUTO_CAPITALIZATION = 1 << 2;
+        /// Prefer lowercase letters.
+        const LOWERCASE = 1 << 3;
+        /// Prefer uppercase letters.
+        const UPPERCASE = 1 << 4;
+        /// Prefer the casing of titles.
+        const TITLECASE = 1 << 5;
+        /// The characters shouldn't be shown.
+        const HIDDEN_TEXT = 1 << 6;
+        /// The text shouldn't be remembered, for example to learn words.
+        const SENSITIVE_DATA = 1 << 7;
+        /// Only Latin characters are expected.
+        const LATIN = 1 << 8;
+        /// The field accepts several lines of text.
+        const MULTILINE = 1 << 9;
+    }
+}
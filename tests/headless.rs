@@ -0,0 +1,114 @@
+#![cfg(target_os = "linux")]
+
+use std::time::Duration;
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{Event, StartCause, WindowEvent},
+    event_loop::EventLoopBuilder,
+    platform::{
+        run_return::EventLoopExtRunReturn,
+        unix::{EventLoopBuilderExtUnix, EventLoopWindowTargetExtUnix},
+    },
+    window::WindowBuilder,
+};
+
+// Only one event loop can be created per process, so everything is tested with the same one.
+#[test]
+fn headless_event_loop() {
+    let mut event_loop = EventLoopBuilder::new()
+        .with_headless()
+        .with_any_thread(true)
+        .build();
+    assert!(event_loop.is_headless());
+    let injector = event_loop.headless_injector().unwrap();
+
+    let window = WindowBuilder::new()
+        .with_inner_size(PhysicalSize::new(640, 480))
+        .build(&event_loop)
+        .unwrap();
+    let window_id = window.id();
+    assert_eq!(window.inner_size(), PhysicalSize::new(640, 480));
+
+    // Injected events are delivered in order, and new windows get redrawn.
+    injector.inject_window_event(window_id, WindowEvent::Focused(true));
+    injector.inject_window_event(window_id, WindowEvent::Resized(PhysicalSize::new(320, 240)));
+    let mut events = Vec::new();
+    let mut redrawn = false;
+    event_loop.run_return(|event, _, control_flow| {
+        control_flow.set_wait();
+        match event {
+            Event::WindowEvent {
+                window_id: id,
+                event,
+            } if id == window_id => {
+                events.push(event.to_static().unwrap());
+            }
+            Event::RedrawRequested(id) if id == window_id => redrawn = true,
+            Event::RedrawEventsCleared => control_flow.set_exit(),
+            _ => (),
+        }
+    });
+    assert_eq!(
+        events,
+        [
+            WindowEvent::Focused(true),
+            WindowEvent::Resized(PhysicalSize::new(320, 240)),
+        ]
+    );
+    assert!(redrawn);
+    assert_eq!(window.inner_size(), PhysicalSize::new(320, 240));
+
+    // Changing the scale factor of the monitor resizes the windows on it.
+    let monitor = window.current_monitor().unwrap();
+    injector.set_scale_factor(&monitor, 2.0);
+    let mut scale_factor = None;
+    let mut resized = None;
+    event_loop.run_return(|event, _, control_flow| {
+        control_flow.set_wait();
+        match event {
+            Event::WindowEvent {
+                event:
+                    WindowEvent::ScaleFactorChanged {
+                        scale_factor: new_scale_factor,
+                        new_inner_size,
+                    },
+                ..
+            } => {
+                scale_factor = Some(new_scale_factor);
+                assert_eq!(*new_inner_size, PhysicalSize::new(640, 480));
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            } => resized = Some(size),
+            Event::RedrawEventsCleared => control_flow.set_exit(),
+            _ => (),
+        }
+    });
+    assert_eq!(scale_factor, Some(2.0));
+    assert_eq!(resized, Some(PhysicalSize::new(640, 480)));
+    assert_eq!(window.scale_factor(), 2.0);
+    assert_eq!(window.current_monitor().unwrap().scale_factor(), 2.0);
+
+    // Waiting doesn't sleep, the virtual clock jumps to the deadline instead.
+    let start = injector.now();
+    let deadline = start + Duration::from_secs(3600);
+    let mut resumed = false;
+    event_loop.run_return(|event, _, control_flow| match event {
+        Event::NewEvents(StartCause::Init) => control_flow.set_wait_until(deadline),
+        Event::NewEvents(StartCause::ResumeTimeReached {
+            requested_resume, ..
+        }) => {
+            assert_eq!(requested_resume, deadline);
+            resumed = true;
+            control_flow.set_exit();
+        }
+        _ => (),
+    });
+    assert!(resumed);
+    assert_eq!(injector.now(), deadline);
+
+    injector.advance_time(Duration::from_secs(1));
+    assert_eq!(injector.now(), deadline + Duration::from_secs(1));
+}
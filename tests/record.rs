@@ -0,0 +1,133 @@
+#![cfg(all(feature = "record", target_os = "linux"))]
+
+use std::time::{Duration, Instant};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{DeviceId, ElementState, Event, KeyboardInput, StartCause, WindowEvent},
+    event_loop::EventLoopBuilder,
+    keyboard::{Key, KeyCode, KeyEvent, KeyLocation},
+    platform::unix::EventLoopBuilderExtUnix,
+    record::{EventRecorder, EventReplayer, ReplayTiming},
+    window::WindowId,
+};
+
+#[test]
+fn record_and_replay() {
+    let window_id = WindowId::from(7);
+    let device_id = unsafe { DeviceId::dummy() };
+    // The deprecated field still has to be set, and is recorded like the others.
+    #[allow(deprecated)]
+    let input = KeyboardInput {
+        scancode: 30,
+        state: ElementState::Pressed,
+        virtual_keycode: None,
+        modifiers: Default::default(),
+    };
+    let key_event = KeyEvent {
+        physical_key: KeyCode::KeyA,
+        logical_key: Key::Character("a".to_owned()),
+        text: Some("a".to_owned()),
+        location: KeyLocation::Standard,
+        state: ElementState::Pressed,
+        repeat: false,
+    };
+    let timestamp = Instant::now() - Duration::from_millis(250);
+    let mut new_inner_size = PhysicalSize::new(200, 100);
+
+    let events: Vec<Event<'_, String>> = vec![
+        Event::NewEvents(StartCause::Init),
+        Event::WindowEvent {
+            window_id,
+            event: WindowEvent::KeyboardInput {
+                device_id,
+                input,
+                event: key_event.clone(),
+                is_synthetic: false,
+                timestamp,
+            },
+        },
+        Event::WindowEvent {
+            window_id,
+            event: WindowEvent::ScaleFactorChanged {
+                scale_factor: 2.0,
+                new_inner_size: &mut new_inner_size,
+            },
+        },
+        Event::UserEvent("hello".to_owned()),
+        Event::LoopDestroyed,
+    ];
+
+    let mut buffer = Vec::new();
+    let mut recorder = EventRecorder::new(&mut buffer);
+    for event in &events {
+        recorder.record(event).unwrap();
+    }
+    recorder.flush().unwrap();
+
+    let replayer = EventReplayer::<String>::from_reader(&buffer[..]).unwrap();
+    assert_eq!(replayer.len(), events.len());
+
+    let event_loop = EventLoopBuilder::<String>::with_user_event()
+        .with_headless()
+        .with_any_thread(true)
+        .build();
+    let mut replayed = Vec::new();
+    let exit_code = replayer.replay(
+        &event_loop,
+        ReplayTiming::Immediate,
+        |event, _, control_flow| {
+            if let Event::WindowEvent {
+                event: WindowEvent::ScaleFactorChanged { new_inner_size, .. },
+                ..
+            } = &event
+            {
+                assert_eq!(**new_inner_size, PhysicalSize::new(200, 100));
+            }
+            if let Event::UserEvent(_) = event {
+                control_flow.set_exit_with_code(3);
+            }
+            // Device ids aren't restored as they were, only the rest of the event is compared.
+            if let Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input: replayed_input,
+                        event: replayed_key_event,
+                        timestamp: replayed_timestamp,
+                        ..
+                    },
+                ..
+            } = event
+            {
+                assert_eq!(replayed_input, input);
+                assert_eq!(replayed_key_event, key_event);
+                assert_eq!(replayed_timestamp, timestamp);
+                replayed.push("KeyboardInput".to_owned());
+            } else {
+                replayed.push(format!("{:?}", event));
+            }
+        },
+    );
+
+    assert_eq!(exit_code, 3);
+    assert_eq!(
+        replayed,
+        [
+            "NewEvents(Init)".to_owned(),
+            "KeyboardInput".to_owned(),
+            format!("{:?}", events[2]),
+            "UserEvent(\"hello\")".to_owned(),
+            "LoopDestroyed".to_owned(),
+        ]
+    );
+
+    // Factors that aren't positive and finite fall back to the original timing.
+    for factor in [0.0, -2.0, f64::NAN, f64::INFINITY] {
+        let replayer = EventReplayer::<String>::from_reader(&buffer[..]).unwrap();
+        let mut count = 0;
+        replayer.replay(&event_loop, ReplayTiming::Accelerated(factor), |_, _, _| {
+            count += 1
+        });
+        assert_eq!(count, events.len());
+    }
+}
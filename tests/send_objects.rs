@@ -25,3 +25,10 @@ fn ids_send() {
     needs_send::<winit::event::DeviceId>();
     needs_send::<winit::monitor::MonitorHandle>();
 }
+
+#[test]
+fn custom_cursor_send() {
+    // ensures that `winit::window::CustomCursor` implements `Send`, so it can be
+    // created once and set on windows from any thread
+    needs_send::<winit::window::CustomCursor>();
+}
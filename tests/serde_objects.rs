@@ -2,12 +2,15 @@
 
 use serde::{Deserialize, Serialize};
 use winit::{
+    clipboard::{ClipboardContents, ClipboardKind},
+    dnd::DndAction,
     dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
     event::{
-        ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, TouchPhase,
-        VirtualKeyCode,
+        DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, ModifiersState, MouseButton,
+        MouseScrollDelta, PreeditSpan, StartCause, Touch, TouchPhase, VirtualKeyCode, WindowEvent,
     },
-    window::CursorIcon,
+    keyboard::{Key, KeyCode, KeyEvent, KeyLocation, KeyboardLayout, NamedKey},
+    window::{CursorIcon, DecorationMode, ImePurpose, Rect, Region, Theme, WindowId},
 };
 
 #[allow(dead_code)]
@@ -16,6 +19,12 @@ fn needs_serde<S: Serialize + Deserialize<'static>>() {}
 #[test]
 fn window_serde() {
     needs_serde::<CursorIcon>();
+    needs_serde::<DecorationMode>();
+    needs_serde::<ImePurpose>();
+    needs_serde::<Rect>();
+    needs_serde::<Region>();
+    needs_serde::<Theme>();
+    needs_serde::<WindowId>();
 }
 
 #[test]
@@ -27,6 +36,34 @@ fn events_serde() {
     needs_serde::<MouseScrollDelta>();
     needs_serde::<VirtualKeyCode>();
     needs_serde::<ModifiersState>();
+    needs_serde::<Touch>();
+    needs_serde::<PreeditSpan>();
+    needs_serde::<DeviceId>();
+    needs_serde::<StartCause>();
+    needs_serde::<WindowEvent<'static>>();
+    needs_serde::<DeviceEvent>();
+    needs_serde::<Event<'static, ()>>();
+}
+
+#[test]
+fn keyboard_serde() {
+    needs_serde::<KeyEvent>();
+    needs_serde::<KeyCode>();
+    needs_serde::<Key>();
+    needs_serde::<NamedKey>();
+    needs_serde::<KeyLocation>();
+    needs_serde::<KeyboardLayout>();
+}
+
+#[test]
+fn clipboard_serde() {
+    needs_serde::<ClipboardKind>();
+    needs_serde::<ClipboardContents>();
+}
+
+#[test]
+fn dnd_serde() {
+    needs_serde::<DndAction>();
 }
 
 #[test]